@@ -1122,7 +1122,9 @@ impl EurocontrolResolver {
                     }
                     _ => {}
                 },
-                Field15Element::Modifier(_) => {}
+                Field15Element::Modifier(_)
+                | Field15Element::MissingInitialGroup { .. }
+                | Field15Element::Overfly { .. } => {}
             }
         }
 
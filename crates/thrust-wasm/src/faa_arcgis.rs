@@ -515,7 +515,9 @@ impl FaaArcgisResolver {
                     }
                     _ => {}
                 },
-                Field15Element::Modifier(_) => {}
+                Field15Element::Modifier(_)
+                | Field15Element::MissingInitialGroup { .. }
+                | Field15Element::Overfly { .. } => {}
             }
         }
 
@@ -321,7 +321,9 @@ impl NasrResolver {
                     }
                     _ => {}
                 },
-                Field15Element::Modifier(_) => {}
+                Field15Element::Modifier(_)
+                | Field15Element::MissingInitialGroup { .. }
+                | Field15Element::Overfly { .. } => {}
             }
         }
 
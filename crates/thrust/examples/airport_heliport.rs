@@ -22,7 +22,8 @@ fn main() {
                 "longitude" => airports.values().map(|airport| airport.longitude).collect::<Vec<_>>(),
                 "altitude" => airports.values().map(|airport| airport.altitude).collect::<Vec<_>>(),
                 "city" => airports.values().map(|airport| airport.city.clone()).collect::<Vec<_>>(),
-                "type" => airports.values().map(|airport| airport.r#type.clone()).collect::<Vec<_>>(),
+                "type" => airports.values().map(|airport| airport.r#type.to_string()).collect::<Vec<_>>(),
+                "control_type" => airports.values().map(|airport| airport.control_type.clone()).collect::<Vec<_>>(),
             ) {
                 println!("{df:?}");
             }
@@ -12,15 +12,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let db = AirwayDatabase::new(path)?;
 
-    let candidates = ResolvedPoint::lookup(name, &db);
-    for candidate in candidates {
+    let points = ResolvedPoint::lookup(name, &db);
+    for candidate in &points {
         println!("Found point: {:?}", candidate);
     }
 
-    let candidates = ResolvedRoute::lookup(name, &db);
-    for candidate in candidates {
+    let routes = ResolvedRoute::lookup(name, &db);
+    for candidate in &routes {
         println!("Found route: {:?}", candidate);
     }
 
+    if points.is_empty() && routes.is_empty() {
+        let suggestions = db.suggest(name, None, 5);
+        if !suggestions.is_empty() {
+            println!("Did you mean: {}?", suggestions.join(", "));
+        }
+    }
+
     Ok(())
 }
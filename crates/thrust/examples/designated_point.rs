@@ -19,7 +19,7 @@ fn main() {
                 "name" => points.values().map(|point| point.name.clone()).collect::<Vec<_>>(),
                 "latitude" => points.values().map(|point| point.latitude).collect::<Vec<_>>(),
                 "longitude" => points.values().map(|point| point.longitude).collect::<Vec<_>>(),
-                "type" => points.values().map(|point| point.r#type.clone()).collect::<Vec<_>>(),
+                "type" => points.values().map(|point| point.r#type.to_string()).collect::<Vec<_>>(),
             ) {
                 println!("{df:?}");
             }
@@ -1,55 +1,371 @@
 use std::env;
 use std::io;
 use std::io::BufRead;
-use std::path::Path;
-use thrust::data::eurocontrol::database::AirwayDatabase;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+#[cfg(feature = "toml")]
+use thrust::config::Config;
+use thrust::data::eurocontrol::database::{AirwayDatabase, EnrichmentReport, ResolvedRouteSegment};
+use thrust::data::eurocontrol::events::NdjsonSink;
+use thrust::data::eurocontrol::geometry::compare_geometry;
+use thrust::data::eurocontrol::response::{Export, OutputOptions, RouteFootprint, RouteRequest, RouteResponse};
 use thrust::data::field15::Field15Parser;
+use thrust::distance::NAUTICAL_MILE;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: {} <path_to_aixm_folder>", args[0]);
+    let mut args: Vec<String> = env::args().collect();
+    // With --jsonl, each line is a {"id": ..., "route": ...} object and the id is echoed in
+    // the output. Lines starting with '{' are also read as JSON without the flag.
+    let jsonl = args.iter().any(|arg| arg == "--jsonl");
+    args.retain(|arg| arg != "--jsonl");
+    // With --trace, the candidates remaining after each enrichment stage are printed on stderr.
+    let trace = args.iter().any(|arg| arg == "--trace");
+    args.retain(|arg| arg != "--trace");
+    // With --compare, the deviation of each enriched route from a reference
+    // GeoJSON LineString (e.g. a radar track) is reported on stderr.
+    let reference = match args.iter().position(|arg| arg == "--compare") {
+        Some(i) if i + 1 < args.len() => {
+            let path = args.remove(i + 1);
+            args.remove(i);
+            let geojson: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+            match line_string(&geojson) {
+                Some(reference) => Some(reference),
+                None => {
+                    eprintln!("Error: no LineString found in {}", path);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(_) => {
+            eprintln!("Error: --compare expects a GeoJSON file");
+            std::process::exit(1);
+        }
+        None => None,
+    };
+    // With --precision and --units, coordinates are written with that many
+    // decimals and distances in meters (m) or nautical miles (nm).
+    let precision = flag_value(&mut args, "--precision");
+    let units = flag_value(&mut args, "--units");
+    let options = match OutputOptions::parse(precision.as_deref(), units.as_deref()) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // With --footprint, responses summarize the airways, fixes, distances and
+    // horizontal efficiency of each route, and with --csv this summary is written
+    // as one CSV row per route instead of JSON. Regions crossed (e.g. FIRs) are
+    // listed when polygon features are given with --regions, named after their
+    // `name` property.
+    let footprint = args.iter().any(|arg| arg == "--footprint");
+    args.retain(|arg| arg != "--footprint");
+    let csv = args.iter().any(|arg| arg == "--csv");
+    args.retain(|arg| arg != "--csv");
+    let regions = match flag_value(&mut args, "--regions") {
+        Some(path) => {
+            let geojson: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+            polygons(&geojson)
+        }
+        None => Vec::new(),
+    };
+    let mut csv_writer = csv.then(|| csv::Writer::from_writer(io::stdout()));
+    // With --events, the decisions taken while enriching each route are written
+    // to a file as JSON lines, see `thrust::data::eurocontrol::events`.
+    let events = flag_value(&mut args, "--events");
+    // The AIXM folder and enrichment options may be set in a configuration file
+    // (--config or THRUST_CONFIG) or THRUST_* variables, see `thrust::config`,
+    // when built with the `toml` feature.
+    let config_path = flag_value(&mut args, "--config");
+    #[cfg(feature = "toml")]
+    let (mut dataset, snapshot, mut enrichment) = {
+        let (config, warnings) = Config::load(config_path.as_deref().map(Path::new))?;
+        for warning in warnings {
+            eprintln!("Warning: {}", warning);
+        }
+        let enrichment = config.enrichment_options();
+        (config.dataset, config.snapshot, enrichment)
+    };
+    #[cfg(not(feature = "toml"))]
+    let (mut dataset, snapshot, mut enrichment) = {
+        if config_path.is_some() {
+            eprintln!("Error: build with `--features toml` to read a configuration file");
+            std::process::exit(1);
+        }
+        let enrichment = thrust::data::eurocontrol::database::EnrichmentOptions::default();
+        (None::<PathBuf>, None::<PathBuf>, enrichment)
+    };
+    // `export fixes` writes the fix table of the database, with records merged
+    // within one nautical mile, as JSON lines or to a Parquet file with --parquet.
+    let parquet = flag_value(&mut args, "--parquet");
+    let export = args.get(1).map(String::as_str) == Some("export");
+    if export {
+        args.remove(1);
+        if args.len() < 2 || args.remove(1) != "fixes" {
+            eprintln!("Error: the only table to export is `fixes`");
+            std::process::exit(1);
+        }
+    }
+    if let Some(path) = args.get(1) {
+        dataset = Some(PathBuf::from(path));
+    }
+    if dataset.is_none() {
+        eprintln!(
+            "Usage: {} [--jsonl] [--trace] [--compare <geojson>] [--precision <decimals>] [--units m|nm] [--footprint] [--csv] [--regions <geojson>] [--events <file>] [--config <file>] <path_to_aixm_folder>",
+            args[0]
+        );
+        eprintln!("       {} schema", args[0]);
+        eprintln!(
+            "       {} export fixes [--parquet <file>] [--config <file>] <path_to_aixm_folder>",
+            args[0]
+        );
         std::process::exit(1);
     }
 
-    let aixm_path = Path::new(&args[1]);
+    // Print the JSON Schema of the JSON-lines responses
+    if args.get(1).map(String::as_str) == Some("schema") {
+        #[cfg(feature = "schema")]
+        {
+            let schema = thrust::data::eurocontrol::response::response_schema();
+            println!("{}", serde_json::to_string_pretty(&schema)?);
+            return Ok(());
+        }
+        #[cfg(not(feature = "schema"))]
+        {
+            eprintln!("Error: build with `--features schema` to print the JSON Schema");
+            std::process::exit(1);
+        }
+    }
+
+    let aixm_path = dataset.as_deref().unwrap();
     if !aixm_path.exists() {
         eprintln!("Error: Path does not exist: {}", aixm_path.display());
         std::process::exit(1);
     }
 
     eprintln!("Loading AIXM data from: {}", aixm_path.display());
-    let db = AirwayDatabase::new(aixm_path)?;
-
-    let stdin = io::stdin();
-    for line in stdin.lock().lines() {
-        let line = match line {
-            Ok(l) => {
-                let trimmed = l.trim();
-                let trimmed = trimmed
-                    .strip_prefix('"')
-                    .or_else(|| trimmed.strip_prefix('\''))
-                    .unwrap_or(trimmed);
-                let trimmed = trimmed
-                    .strip_suffix('"')
-                    .or_else(|| trimmed.strip_suffix('\''))
-                    .unwrap_or(trimmed);
-                trimmed.to_string()
-            }
-            Err(_) => continue,
+    let mut db = match &snapshot {
+        Some(snapshot) => AirwayDatabase::new_with_point_index(aixm_path, snapshot)?,
+        None => AirwayDatabase::new(aixm_path)?,
+    };
+    if let Some(path) = &events {
+        enrichment.events = Some(Arc::new(NdjsonSink::create(Path::new(path))?));
+    }
+    db.set_enrichment_options(enrichment);
+
+    if export {
+        return export_fixes(&db, parquet.as_deref());
+    }
+
+    // Routes are enriched with `enrich_batch`, in parallel, and a route which fails
+    // (e.g. a panic in the resolver) is reported without stopping the others. With
+    // --jsonl or --csv, the whole input is read before enriching; otherwise each
+    // line is answered as soon as it is read.
+    let batch = jsonl || csv;
+    let mut lines = io::stdin().lock().lines().map_while(Result::ok).map(|line| {
+        let trimmed = line.trim();
+        let trimmed = trimmed
+            .strip_prefix('"')
+            .or_else(|| trimmed.strip_prefix('\''))
+            .unwrap_or(trimmed);
+        let trimmed = trimmed
+            .strip_suffix('"')
+            .or_else(|| trimmed.strip_suffix('\''))
+            .unwrap_or(trimmed);
+        trimmed.to_string()
+    });
+    loop {
+        let chunk: Vec<String> = if batch {
+            lines.by_ref().collect()
+        } else {
+            lines.next().into_iter().collect()
         };
-        if line.is_empty() {
-            continue;
+        if chunk.is_empty() {
+            break;
+        }
+        let mut routes = Vec::new();
+        for line in chunk.into_iter().filter(|line| !line.is_empty()) {
+            let input = if jsonl || line.starts_with('{') {
+                match serde_json::from_str::<RouteRequest>(&line) {
+                    Ok(request) => Input {
+                        route: request.route,
+                        id: request.id,
+                        json: true,
+                    },
+                    Err(e) => {
+                        eprintln!("Invalid JSON line: {}", e);
+                        continue;
+                    }
+                }
+            } else {
+                Input {
+                    route: line,
+                    id: None,
+                    json: false,
+                }
+            };
+            if trace {
+                eprint!("{}", db.enrich_route_trace(Field15Parser::parse(&input.route)));
+            }
+            let route = input.route.clone();
+            routes.push((input, route));
         }
 
-        let elements = Field15Parser::parse(&line);
-        let enriched = db.enrich_route(elements);
+        for (input, result) in db.enrich_batch(routes) {
+            let (enriched, report) = match result {
+                Ok(enriched) => enriched,
+                Err(e) => {
+                    eprintln!("Error: {}: {}", input.route, e);
+                    // JSON requests are answered with the error, keyed by their id
+                    if input.json && csv_writer.is_none() {
+                        let mut error = serde_json::json!({"route": input.route, "error": e.to_string()});
+                        if let Some(id) = input.id {
+                            error["id"] = id;
+                        }
+                        println!("{}", error);
+                        continue;
+                    }
+                    (Vec::new(), EnrichmentReport::default())
+                }
+            };
+            if let Some(reference) = &reference {
+                report_deviation(&enriched, reference);
+            }
+            if let Some(writer) = &mut csv_writer {
+                let row = RouteFootprint::from_segments_and_regions(&enriched, &regions).to_row(&input.route);
+                writer.serialize(row.to_output(&options))?;
+                writer.flush()?;
+                continue;
+            }
+            // With --footprint, plain routes are answered with a full response object
+            if input.json || footprint {
+                let mut response = RouteResponse::new(&input.route, &enriched);
+                if input.json {
+                    response = response.with_report(&report).with_id(input.id);
+                }
+                if footprint {
+                    response = response.with_footprint(RouteFootprint::from_segments_and_regions(&enriched, &regions));
+                }
+                match serde_json::to_string(&options.export(&response)) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => eprintln!("JSON serialization error: {}", e),
+                }
+                continue;
+            }
 
-        match serde_json::to_string(&enriched) {
-            Ok(json) => println!("{}", json),
-            Err(e) => eprintln!("JSON serialization error: {}", e),
+            match serde_json::to_string(&options.export(&enriched)) {
+                Ok(json) => println!("{}", json),
+                Err(e) => eprintln!("JSON serialization error: {}", e),
+            }
         }
     }
 
     Ok(())
 }
+
+/// A route read from standard input.
+struct Input {
+    route: String,
+    /// Echoed in the response to a JSON line
+    id: Option<serde_json::Value>,
+    /// Whether the route was read from a JSON line
+    json: bool,
+}
+
+/// Write the fix table of the database as JSON lines, or to a Parquet file.
+fn export_fixes(db: &AirwayDatabase, parquet: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let fixes = db.fix_table(NAUTICAL_MILE);
+    match parquet {
+        None => {
+            for fix in &fixes {
+                println!("{}", serde_json::to_string(fix)?);
+            }
+        }
+        #[cfg(feature = "polars")]
+        Some(path) => {
+            let mut df = thrust::data::eurocontrol::response::fix_table_dataframe(&fixes)?;
+            polars::prelude::ParquetWriter::new(std::fs::File::create(path)?).finish(&mut df)?;
+        }
+        #[cfg(not(feature = "polars"))]
+        Some(_) => {
+            eprintln!("Error: build with `--features polars` to write Parquet files");
+            std::process::exit(1);
+        }
+    }
+    eprintln!("Exported {} fixes", fixes.len());
+    Ok(())
+}
+
+/// Remove a `--flag value` pair from the arguments and return the value.
+fn flag_value(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let i = args.iter().position(|arg| arg == flag)?;
+    if i + 1 >= args.len() {
+        eprintln!("Error: {} expects a value", flag);
+        std::process::exit(1);
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    Some(value)
+}
+
+/// The (latitude, longitude) pairs of the first LineString in a GeoJSON geometry,
+/// feature or feature collection.
+fn line_string(geojson: &serde_json::Value) -> Option<Vec<(f64, f64)>> {
+    match geojson["type"].as_str()? {
+        "LineString" => geojson["coordinates"]
+            .as_array()?
+            .iter()
+            .map(|position| Some((position[1].as_f64()?, position[0].as_f64()?)))
+            .collect(),
+        "Feature" => line_string(&geojson["geometry"]),
+        "FeatureCollection" => geojson["features"].as_array()?.iter().find_map(line_string),
+        _ => None,
+    }
+}
+
+/// The named polygons of a GeoJSON feature collection, as (name, vertices) with
+/// (latitude, longitude) vertices. Only the outer ring of each polygon is kept,
+/// and features without a `name` property are skipped.
+fn polygons(geojson: &serde_json::Value) -> Vec<(String, Vec<(f64, f64)>)> {
+    let ring = |ring: &serde_json::Value| -> Option<Vec<(f64, f64)>> {
+        ring.as_array()?
+            .iter()
+            .map(|position| Some((position[1].as_f64()?, position[0].as_f64()?)))
+            .collect()
+    };
+    let features = match geojson["type"].as_str() {
+        Some("FeatureCollection") => geojson["features"].as_array().cloned().unwrap_or_default(),
+        Some("Feature") => vec![geojson.clone()],
+        _ => Vec::new(),
+    };
+    let mut polygons = Vec::new();
+    for feature in features {
+        let Some(name) = feature["properties"]["name"].as_str() else {
+            continue;
+        };
+        let geometry = &feature["geometry"];
+        let outer_rings = match geometry["type"].as_str() {
+            Some("Polygon") => vec![&geometry["coordinates"][0]],
+            Some("MultiPolygon") => geometry["coordinates"]
+                .as_array()
+                .map(|parts| parts.iter().map(|part| &part[0]).collect())
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        polygons.extend(
+            outer_rings
+                .into_iter()
+                .filter_map(ring)
+                .map(|vertices| (name.to_string(), vertices)),
+        );
+    }
+    polygons
+}
+
+fn report_deviation(enriched: &[ResolvedRouteSegment], reference: &[(f64, f64)]) {
+    let deviation = compare_geometry(enriched, reference);
+    eprintln!(
+        "Deviation from reference: max {:.0} m (reference point {:?}), mean {:.0} m, hausdorff {:.0} m",
+        deviation.max_reference, deviation.worst_reference, deviation.mean_reference, deviation.hausdorff
+    );
+}
@@ -1,80 +1,101 @@
-use axum::extract::{Json, State};
-use axum::http::StatusCode;
-use axum::response::IntoResponse;
-use axum::routing::post;
-use axum::Router;
-use serde::{Deserialize, Serialize};
 use std::env;
-use std::path::Path;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use thrust::config::Config;
+use thrust::data::eurocontrol::cache::CachedEnricher;
 use thrust::data::eurocontrol::database::AirwayDatabase;
-use thrust::data::field15::Field15Parser;
-use tower_http::cors::{Any, CorsLayer};
-
-#[derive(Debug, Deserialize)]
-struct RouteRequest {
-    route: String,
-}
-
-#[derive(Debug, Serialize)]
-struct RouteResponse {
-    route: String,
-    segments: Vec<thrust::data::eurocontrol::database::ResolvedRouteSegment>,
-}
-
-struct AppState {
-    database: AirwayDatabase,
-}
-
-async fn resolve_route(State(_state): State<Arc<AppState>>, Json(payload): Json<RouteRequest>) -> impl IntoResponse {
-    eprintln!("Received route to resolve: {}", payload.route);
-    let elements = Field15Parser::parse(&payload.route);
-    let enriched = _state.database.enrich_route(elements);
-
-    (
-        StatusCode::OK,
-        Json(RouteResponse {
-            route: payload.route.clone(),
-            segments: enriched,
-        }),
-    )
-        .into_response()
-}
+use thrust::serve::ServeState;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Usage: {} <path_to_directory>", args[0]);
-        std::process::exit(1);
-    }
-    let path = Path::new(&args[1]);
+    let mut args: Vec<String> = env::args().collect();
 
     // Initialize tracing
     tracing_subscriber::fmt::init();
 
+    // Options from the configuration file and THRUST_* variables, overridden by flags
+    let config_path = flag_value(&mut args, "--config");
+    let (mut config, warnings) = Config::load(config_path.as_deref().map(Path::new))?;
+    for warning in warnings {
+        tracing::warn!("{}", warning);
+    }
+    if let Some(bind) = flag_value(&mut args, "--bind") {
+        config.bind = bind.parse()?;
+    }
+    if let Some(path) = args.get(1) {
+        config.dataset = Some(PathBuf::from(path));
+    }
+    // The directory may also be set in the configuration file
+    let path = match &config.dataset {
+        Some(path) if args.len() <= 2 => path.clone(),
+        _ => {
+            eprintln!(
+                "Usage: {} [--config <file>] [--bind <address>] [<path_to_directory>]",
+                args[0]
+            );
+            std::process::exit(1);
+        }
+    };
+
     // Load the database once at startup
     println!("Loading database...");
-    let database = AirwayDatabase::new(path)?;
+    let mut database = match &config.snapshot {
+        Some(snapshot) => AirwayDatabase::new_with_point_index(&path, snapshot)?,
+        None => AirwayDatabase::new(&path)?,
+    };
+    database.set_enrichment_options(config.enrichment_options());
     println!("Database loaded successfully!");
 
-    // Create shared state
-    let state = Arc::new(AppState { database });
+    // Create shared state, caching repeated routes
+    let database = Arc::new(RwLock::new(database));
+    let enricher = CachedEnricher::with_capacity(database, config.cache_max_entries, config.cache_max_bytes);
+    let state = ServeState::new(enricher).with_dataset(path);
 
     // Configure CORS
-    let cors = CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any);
+    let origins = if config.cors_origins.is_empty() {
+        AllowOrigin::from(Any)
+    } else {
+        let origins = config
+            .cors_origins
+            .iter()
+            .map(|origin| origin.parse())
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+    let cors = CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(Any)
+        .allow_headers(Any);
 
-    // Build the route
-    let app = Router::new()
-        .route("/resolve", post(resolve_route))
-        .with_state(state)
-        .layer(cors);
+    let app = thrust::serve::routes(state).layer(cors);
 
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await?;
-    println!("Server listening on http://127.0.0.1:3000");
+    let listener = tokio::net::TcpListener::bind(config.bind).await?;
+    println!("Server listening on http://{}", config.bind);
     println!("POST to /resolve with JSON: {{\"route\": \"YOUR_ROUTE_STRING\"}}");
+    println!("POST to /resolve?debug=trace to list the candidates of each enrichment stage");
+    println!("POST to /resolve?footprint=true to summarize the airways and fixes of the route");
+    println!("POST to /resolve_stream for the segments as application/x-ndjson, one per line");
+    println!("GET /stats for database and cache statistics");
+    println!("POST /reload to reload the database and clear the cache");
+    println!("GET /airway/{{name}}/segments?format=csv|json for the segments of an airway");
+    println!("GET /complete?q=NA&limit=10 for designators starting with a prefix");
+    #[cfg(feature = "schema")]
+    println!("GET /schema for the JSON Schema of /resolve responses");
 
     axum::serve(listener, app).await?;
 
     Ok(())
 }
+
+/// Remove a `--flag value` pair from the arguments and return the value.
+fn flag_value(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let i = args.iter().position(|arg| arg == flag)?;
+    if i + 1 >= args.len() {
+        eprintln!("Error: {} expects a value", flag);
+        std::process::exit(1);
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    Some(value)
+}
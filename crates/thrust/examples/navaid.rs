@@ -20,6 +20,7 @@ fn main() {
                 "latitude" => navaids.values().map(|navaid| navaid.latitude).collect::<Vec<_>>(),
                 "longitude" => navaids.values().map(|navaid| navaid.longitude).collect::<Vec<_>>(),
                 "type" => navaids.values().map(|navaid| navaid.r#type.clone()).collect::<Vec<_>>(),
+                "frequency" => navaids.values().map(|navaid| navaid.frequency).collect::<Vec<_>>(),
             ) {
                 println!("{df:?}");
             }
@@ -0,0 +1,39 @@
+//! Compare the time to open a point archive with the time to read its file.
+//!
+//! Opening an archive only validates it, so both times should be of the same order.
+use std::time::Instant;
+use std::{env, path::Path};
+use thrust::data::eurocontrol::archive::PointArchive;
+use thrust::data::eurocontrol::database::{AirwayDatabase, ResolvedPoint};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("Usage: {} <path_to_directory> <archive_file>", args[0]);
+        std::process::exit(1);
+    }
+    let path = Path::new(&args[1]);
+    let archive = Path::new(&args[2]);
+
+    let start = Instant::now();
+    let db = AirwayDatabase::new_lazy(path)?;
+    println!("Parsed AIXM datasets in {:?}", start.elapsed());
+    db.save_archive(archive)?;
+
+    let start = Instant::now();
+    let bytes = std::fs::read(archive)?;
+    println!("Read {} bytes in {:?}", bytes.len(), start.elapsed());
+
+    let start = Instant::now();
+    PointArchive::from_vec(bytes)?;
+    println!("Validated the archive in {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let archived = AirwayDatabase::load_archive(path, archive)?;
+    println!("Loaded the database with archived points in {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let found = ResolvedPoint::lookup("NARAK", &archived).len();
+    println!("Found {found} point(s) named NARAK in {:?}", start.elapsed());
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use std::env;
+use std::io;
+use std::path::Path;
+use thrust::data::eurocontrol::database::AirwayDatabase;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args: Vec<String> = env::args().collect();
+    // With --csv, the report is written as CSV instead of JSON lines
+    let csv = args.iter().any(|arg| arg == "--csv");
+    args.retain(|arg| arg != "--csv");
+    if args.len() != 2 {
+        eprintln!("Usage: {} [--csv] <path_to_aixm_folder>", args[0]);
+        std::process::exit(1);
+    }
+
+    let aixm_path = Path::new(&args[1]);
+    eprintln!("Loading AIXM data from: {}", aixm_path.display());
+    let db = AirwayDatabase::new(aixm_path)?;
+
+    if csv {
+        let mut writer = csv::Writer::from_writer(io::stdout().lock());
+        for entry in db.route_report() {
+            writer.serialize(entry)?;
+        }
+        writer.flush()?;
+    } else {
+        for entry in db.route_report() {
+            println!("{}", serde_json::to_string(&entry)?);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,324 @@
+//! Defaults of the route resolution server and command line tools.
+//!
+//! Options are read from a TOML file, then overridden by `THRUST_*` environment
+//! variables, and finally by command line flags. The precedence order is thus:
+//!
+//! 1. command line flags, handled by each tool;
+//! 2. environment variables, named after the keys in uppercase, e.g.
+//!    `THRUST_CACHE_MAX_BYTES`; lists are comma-separated;
+//! 3. the configuration file, e.g. given with `--config` or `THRUST_CONFIG`;
+//! 4. the defaults of [`Config::default`].
+//!
+//! ```toml
+//! dataset = "/data/aixm/2604"
+//! snapshot = "/data/aixm/2604/points.idx"
+//! bind = "0.0.0.0:3000"
+//! cors_origins = ["https://example.org"]
+//! cache_max_bytes = 134217728
+//! airway_substitution = true
+//! airway_selection = "nearest"
+//! ```
+//!
+//! Unknown keys are reported as warnings, so that a file may be shared by
+//! several versions of the tools; invalid values are errors naming the key.
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::eurocontrol::cache::{DEFAULT_MAX_BYTES, DEFAULT_MAX_ENTRIES};
+use crate::data::eurocontrol::database::{AirwaySelection, EnrichmentOptions};
+use crate::error::ThrustError;
+
+/// Prefix of the environment variables overriding configuration keys.
+pub const ENV_PREFIX: &str = "THRUST_";
+
+/// Environment variable holding the path of the configuration file.
+pub const CONFIG_ENV: &str = "THRUST_CONFIG";
+
+/// Other `THRUST_*` variables of the repository, e.g. the data directories of
+/// the tests, which are not configuration keys but are not reported either.
+const OTHER_ENV: [&str; 3] = ["THRUST_AIXM_PATH", "THRUST_DDR_PATH", "THRUST_PYTHON"];
+
+/// Options shared by the server and command line tools.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Directory of the AIXM dataset
+    pub dataset: Option<PathBuf>,
+    /// Point index memory-mapped instead of parsing points, see
+    /// [`AirwayDatabase::write_point_index`](crate::data::eurocontrol::database::AirwayDatabase::write_point_index)
+    pub snapshot: Option<PathBuf>,
+    /// Address the server listens on
+    pub bind: SocketAddr,
+    /// Origins allowed to query the server; any origin when empty
+    pub cors_origins: Vec<String>,
+    /// Maximum number of routes kept in the cache of the server
+    pub cache_max_entries: usize,
+    /// Maximum estimated size of the cache of the server, in bytes
+    pub cache_max_bytes: usize,
+    /// See [`EnrichmentOptions::airway_substitution`]
+    pub airway_substitution: bool,
+    /// See [`EnrichmentOptions::merge_split_airways`]
+    pub merge_split_airways: bool,
+    /// See [`EnrichmentOptions::airway_selection`]
+    pub airway_selection: AirwaySelection,
+    /// See [`EnrichmentOptions::keep_partial_airways`]
+    pub keep_partial_airways: bool,
+    /// See [`EnrichmentOptions::navaid_priority`]
+    pub navaid_priority: bool,
+    /// See [`EnrichmentOptions::exclude_vfr_reporting_points`]
+    pub exclude_vfr_reporting_points: bool,
+    /// See [`EnrichmentOptions::emit_gaps`]
+    pub emit_gaps: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            dataset: None,
+            snapshot: None,
+            bind: SocketAddr::from(([127, 0, 0, 1], 3000)),
+            cors_origins: Vec::new(),
+            cache_max_entries: DEFAULT_MAX_ENTRIES,
+            cache_max_bytes: DEFAULT_MAX_BYTES,
+            airway_substitution: false,
+            merge_split_airways: false,
+            airway_selection: AirwaySelection::default(),
+            keep_partial_airways: false,
+            navaid_priority: false,
+            exclude_vfr_reporting_points: false,
+            emit_gaps: false,
+        }
+    }
+}
+
+/// Keys of the configuration, as written in files: the fields of [`Config`].
+const KEYS: [&str; 13] = [
+    "dataset",
+    "snapshot",
+    "bind",
+    "cors_origins",
+    "cache_max_entries",
+    "cache_max_bytes",
+    "airway_substitution",
+    "merge_split_airways",
+    "airway_selection",
+    "keep_partial_airways",
+    "navaid_priority",
+    "exclude_vfr_reporting_points",
+    "emit_gaps",
+];
+
+impl Config {
+    /// Read the configuration file at `path` (or named by `THRUST_CONFIG`, if any),
+    /// then apply the overrides of `THRUST_*` environment variables.
+    ///
+    /// Warnings about unknown keys and variables are returned for the tool to report.
+    pub fn load(path: Option<&Path>) -> Result<(Self, Vec<String>), ThrustError> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os(CONFIG_ENV).map(PathBuf::from));
+        let (config, mut warnings) = match path {
+            Some(path) => {
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|e| ThrustError::FileNotFound(format!("{}: {}", path.display(), e)))?;
+                Config::from_toml(&text, &path.display().to_string())?
+            }
+            None => (Config::default(), Vec::new()),
+        };
+        let (config, env_warnings) = config.with_env(std::env::vars())?;
+        warnings.extend(env_warnings);
+        Ok((config, warnings))
+    }
+
+    /// Parse a TOML configuration, with the warnings about unknown keys.
+    ///
+    /// `origin` (e.g. the path of the file) prefixes error and warning messages.
+    pub fn from_toml(text: &str, origin: &str) -> Result<(Self, Vec<String>), ThrustError> {
+        let table: toml::Table = toml::from_str(text).map_err(|e| invalid(origin, e))?;
+        let config = toml::from_str(text).map_err(|e| invalid(origin, e))?;
+        let warnings = table
+            .keys()
+            .filter(|key| !KEYS.contains(&key.as_str()))
+            .map(|key| format!("{origin}: unknown key `{key}` ignored"))
+            .collect();
+        Ok((config, warnings))
+    }
+
+    /// Override keys with `THRUST_*` variables among `vars`, e.g. [`std::env::vars`],
+    /// with the warnings about unknown variables.
+    pub fn with_env<I>(self, vars: I) -> Result<(Self, Vec<String>), ThrustError>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut table = toml::Table::try_from(&self).map_err(|e| ThrustError::Other(e.to_string()))?;
+        let mut warnings = Vec::new();
+        for (variable, value) in vars {
+            let Some(key) = variable.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            if variable == CONFIG_ENV || OTHER_ENV.contains(&variable.as_str()) {
+                continue;
+            }
+            let key = key.to_ascii_lowercase();
+            if !KEYS.contains(&key.as_str()) {
+                warnings.push(format!("unknown environment variable {variable} ignored"));
+                continue;
+            }
+            let value = env_value(&key, &value);
+            // Check the value alone, so that errors name the variable
+            let mut single = toml::Table::new();
+            single.insert(key.clone(), value.clone());
+            Config::deserialize(single).map_err(|e| invalid(&variable, e))?;
+            table.insert(key, value);
+        }
+        let config = Config::deserialize(table).map_err(|e| ThrustError::Other(e.to_string()))?;
+        Ok((config, warnings))
+    }
+
+    /// The enrichment options to set on a database.
+    pub fn enrichment_options(&self) -> EnrichmentOptions {
+        EnrichmentOptions {
+            airway_substitution: self.airway_substitution,
+            merge_split_airways: self.merge_split_airways,
+            airway_selection: self.airway_selection,
+            keep_partial_airways: self.keep_partial_airways,
+            navaid_priority: self.navaid_priority,
+            exclude_vfr_reporting_points: self.exclude_vfr_reporting_points,
+            emit_gaps: self.emit_gaps,
+            events: None,
+        }
+    }
+}
+
+fn invalid(origin: &str, error: impl std::fmt::Display) -> ThrustError {
+    ThrustError::ParseError(format!("{origin}: {error}"))
+}
+
+/// The TOML value of an environment variable, after the type of its key: lists
+/// are comma-separated, booleans and integers are parsed, paths, addresses and
+/// names are strings even if they look like numbers.
+fn env_value(key: &str, value: &str) -> toml::Value {
+    let value = value.trim();
+    match key {
+        "cors_origins" => {
+            let origins = value.split(',').map(str::trim).filter(|origin| !origin.is_empty());
+            return toml::Value::Array(origins.map(|origin| toml::Value::String(origin.to_string())).collect());
+        }
+        "dataset" | "snapshot" | "bind" | "airway_selection" => return toml::Value::String(value.to_string()),
+        _ => {}
+    }
+    if let Ok(boolean) = value.parse::<bool>() {
+        return toml::Value::Boolean(boolean);
+    }
+    if let Ok(integer) = value.parse::<i64>() {
+        return toml::Value::Integer(integer);
+    }
+    toml::Value::String(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+dataset = "/data/aixm"
+bind = "0.0.0.0:8080"
+cache_max_bytes = 1024
+airway_selection = "nearest"
+"#;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_precedence() {
+        let (config, warnings) = Config::from_toml(FILE, "thrust.toml").unwrap();
+        assert!(warnings.is_empty());
+        // The file overrides the defaults
+        assert_eq!(config.dataset, Some(PathBuf::from("/data/aixm")));
+        assert_eq!(config.bind.port(), 8080);
+        assert_eq!(config.cache_max_bytes, 1024);
+        assert_eq!(config.cache_max_entries, DEFAULT_MAX_ENTRIES);
+        assert_eq!(config.airway_selection, AirwaySelection::Nearest);
+
+        // The environment overrides the file, for keys set in both
+        let vars = env(&[
+            ("THRUST_DATASET", "/data/aixm-next"),
+            ("THRUST_AIRWAY_SELECTION", "most_segments"),
+            ("THRUST_CACHE_MAX_BYTES", "2048"),
+            ("THRUST_CORS_ORIGINS", "https://a.org, https://b.org"),
+            ("THRUST_AIRWAY_SUBSTITUTION", "true"),
+            ("THRUST_AIXM_PATH", "/data/aixm"),
+            ("HOME", "/root"),
+        ]);
+        let (mut config, warnings) = config.with_env(vars).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(config.dataset, Some(PathBuf::from("/data/aixm-next")));
+        assert_eq!(config.airway_selection, AirwaySelection::MostSegments);
+        assert_eq!(config.cache_max_bytes, 2048);
+        assert_eq!(config.cors_origins, ["https://a.org", "https://b.org"]);
+        assert!(config.enrichment_options().airway_substitution);
+        assert_eq!(config.bind.port(), 8080);
+
+        // Flags override the environment, as set by each tool
+        config.bind = "127.0.0.1:9000".parse().unwrap();
+        assert_eq!(config.bind.port(), 9000);
+    }
+
+    #[test]
+    fn test_env_values_follow_the_keys() {
+        // Paths may look like numbers or booleans
+        let vars = env(&[("THRUST_DATASET", "2604"), ("THRUST_SNAPSHOT", "1")]);
+        let (config, _) = Config::default().with_env(vars).unwrap();
+        assert_eq!(config.dataset, Some(PathBuf::from("2604")));
+        assert_eq!(config.snapshot, Some(PathBuf::from("1")));
+    }
+
+    #[test]
+    fn test_keys_are_the_fields() {
+        let config = Config {
+            dataset: Some(PathBuf::from("/data/aixm")),
+            snapshot: Some(PathBuf::from("/data/aixm/points.idx")),
+            ..Config::default()
+        };
+        // Optional keys are only written when set
+        let table = toml::Table::try_from(&config).unwrap();
+        let mut fields = table.keys().map(String::as_str).collect::<Vec<_>>();
+        let mut keys = KEYS.to_vec();
+        fields.sort_unstable();
+        keys.sort_unstable();
+        assert_eq!(fields, keys);
+    }
+
+    #[test]
+    fn test_unknown_key_is_a_warning() {
+        let text = format!("{FILE}cache_size = 12\n");
+        let (config, warnings) = Config::from_toml(&text, "thrust.toml").unwrap();
+        assert_eq!(config.cache_max_bytes, 1024);
+        assert_eq!(warnings, ["thrust.toml: unknown key `cache_size` ignored"]);
+
+        let (_, warnings) = Config::default().with_env(env(&[("THRUST_CACHE", "1")])).unwrap();
+        assert_eq!(warnings, ["unknown environment variable THRUST_CACHE ignored"]);
+    }
+
+    #[test]
+    fn test_invalid_value_names_key() {
+        let text = FILE.replace("1024", "\"large\"");
+        let error = Config::from_toml(&text, "thrust.toml").unwrap_err().to_string();
+        assert!(error.contains("thrust.toml"), "{error}");
+        assert!(error.contains("line 4"), "{error}");
+        assert!(error.contains("cache_max_bytes"), "{error}");
+
+        let vars = env(&[("THRUST_CACHE_MAX_BYTES", "large")]);
+        let error = Config::default().with_env(vars).unwrap_err().to_string();
+        assert!(error.contains("THRUST_CACHE_MAX_BYTES"), "{error}");
+
+        let vars = env(&[("THRUST_BIND", "localhost")]);
+        let error = Config::default().with_env(vars).unwrap_err().to_string();
+        assert!(error.contains("THRUST_BIND"), "{error}");
+    }
+}
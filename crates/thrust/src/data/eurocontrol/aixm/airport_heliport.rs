@@ -1,15 +1,19 @@
 use quick_xml::name::QName;
 use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
-use std::io::BufReader;
 use std::path::Path;
 use std::{collections::HashMap, fs::File};
 use zip::read::ZipArchive;
 
+use crate::data::eurocontrol::aixm::identifier::GmlId;
 use crate::data::eurocontrol::aixm::Node;
+use crate::data::eurocontrol::geometry::magnetic_to_true;
 use crate::error::ThrustError;
 
-use super::{find_node, read_text};
+use super::{
+    baseline_reader, find_node, identifier_fallback, log_parsed, parse_number, parse_pos_srs, read_text, AixmEntries,
+    DEFAULT_ENTRY_SUFFIX,
+};
 
 /// An airport or heliport as defined in AIXM (Aeronautical Information Exchange Model).
 ///
@@ -22,10 +26,14 @@ use super::{find_node, read_text};
 /// - `latitude`/`longitude`: Location in WGS84 decimal degrees
 /// - `altitude`: Field elevation in feet above mean sea level
 /// - `iata`: IATA code (e.g., "JFK"), if assigned
-/// - `icao`: ICAO code (e.g., "KJFK")
+/// - `icao`: ICAO code (e.g., "KJFK"), empty for many heliports
+/// - `designator`: AIXM designator, e.g. a national code for a heliport without ICAO code
 /// - `name`: Official facility name
 /// - `city`: Serving city/municipality
-/// - `r#type`: Facility type (e.g., "Airport", "Heliport")
+/// - `r#type`: Facility type (aerodrome, heliport, landing site)
+/// - `control_type`: Civil and/or military control of the facility
+/// - `time_zone`/`utc_offset`: Time reference of the published schedules, if any
+/// - `magnetic_variation`: Magnetic variation in degrees, positive East
 ///
 /// # Example
 /// ```ignore
@@ -40,6 +48,7 @@ use super::{find_node, read_text};
 /// };
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AirportHeliport {
     /// Unique identifier
     #[serde(skip)]
@@ -52,34 +61,134 @@ pub struct AirportHeliport {
     pub altitude: f64,
     /// IATA code, if available
     pub iata: Option<String>,
-    /// ICAO code
+    /// ICAO code, empty if not assigned
     pub icao: String,
+    /// AIXM designator, if published (e.g., a national code for a heliport)
+    pub designator: Option<String>,
     /// Name of the airport/heliport
     pub name: String,
     /// City served by the airport/heliport
     pub city: Option<String>,
     /// Type of airport/heliport
-    pub r#type: String,
+    pub r#type: FacilityType,
+    /// Type of control (e.g., "CIVIL", "MIL", "JOINT")
+    pub control_type: Option<String>,
+    /// Time reference of the availability schedules (e.g., "UTC", "UTC+1")
+    pub time_zone: Option<String>,
+    /// Offset of the time reference from UTC, in hours
+    pub utc_offset: Option<f64>,
+    /// Magnetic variation in degrees, positive East and negative West
+    pub magnetic_variation: Option<f64>,
+}
+
+impl AirportHeliport {
+    /// The code of the facility as filed in routes: its ICAO code, or for
+    /// facilities without one (e.g. many heliports), its designator or its name.
+    pub fn code(&self) -> &str {
+        [self.icao.trim(), self.designator.as_deref().unwrap_or("").trim()]
+            .into_iter()
+            .find(|code| !code.is_empty())
+            .unwrap_or(self.name.trim())
+    }
+
+    /// Convert a magnetic course into a true course, in degrees within [0, 360).
+    ///
+    /// Returns `None` if the magnetic variation of the airport is not published.
+    pub fn true_course(&self, magnetic_course: f64) -> Option<f64> {
+        self.magnetic_variation
+            .map(|variation| magnetic_to_true(magnetic_course, variation))
+    }
+}
+
+/// The AIXM type of an airport/heliport (`CodeAirportHeliportType`).
+///
+/// # Variants
+/// - `Aerodrome`: Aerodrome only (AD)
+/// - `AerodromeHeliport`: Aerodrome with a heliport (AH)
+/// - `Heliport`: Heliport only (HP)
+/// - `LandingSite`: Landing site (LS)
+/// - `Other`: Any other or missing value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum FacilityType {
+    #[serde(rename = "AD")]
+    Aerodrome,
+    #[serde(rename = "AH")]
+    AerodromeHeliport,
+    #[serde(rename = "HP")]
+    Heliport,
+    #[serde(rename = "LS")]
+    LandingSite,
+    #[default]
+    #[serde(rename = "OTHER")]
+    Other,
+}
+
+impl FacilityType {
+    /// Parse an AIXM airport/heliport type code.
+    pub fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "AD" => FacilityType::Aerodrome,
+            "AH" => FacilityType::AerodromeHeliport,
+            "HP" => FacilityType::Heliport,
+            "LS" => FacilityType::LandingSite,
+            _ => FacilityType::Other,
+        }
+    }
+
+    /// Whether fixed-wing aircraft may use the facility.
+    pub fn is_aerodrome(&self) -> bool {
+        matches!(self, FacilityType::Aerodrome | FacilityType::AerodromeHeliport)
+    }
+
+    /// Whether the facility is a heliport only.
+    pub fn is_heliport(&self) -> bool {
+        matches!(self, FacilityType::Heliport)
+    }
+}
+
+impl std::fmt::Display for FacilityType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let code = match self {
+            FacilityType::Aerodrome => "AD",
+            FacilityType::AerodromeHeliport => "AH",
+            FacilityType::Heliport => "HP",
+            FacilityType::LandingSite => "LS",
+            FacilityType::Other => "OTHER",
+        };
+        write!(f, "{code}")
+    }
 }
 
 /// Parse airport/heliport data from a ZIP file containing AIXM data.
 pub fn parse_airport_heliport_zip_file<P: AsRef<Path>>(
     path: P,
-) -> Result<HashMap<String, AirportHeliport>, ThrustError> {
+) -> Result<HashMap<GmlId, AirportHeliport>, ThrustError> {
+    parse_airport_heliport_zip_file_with_suffix(path, DEFAULT_ENTRY_SUFFIX)
+}
+
+/// Like [`parse_airport_heliport_zip_file`], reading the archive entries whose name ends with
+/// `entry_suffix` (e.g. `.xml`) instead of [`DEFAULT_ENTRY_SUFFIX`].
+pub fn parse_airport_heliport_zip_file_with_suffix<P: AsRef<Path>>(
+    path: P,
+    entry_suffix: &str,
+) -> Result<HashMap<GmlId, AirportHeliport>, ThrustError> {
+    let path = path.as_ref();
     let file = File::open(path)?;
     let mut archive = ZipArchive::new(file)?;
+    let entries = AixmEntries::new(&archive, entry_suffix);
     let mut airports = HashMap::new();
 
     for i in 0..archive.len() {
-        let file = archive.by_index(i)?;
-        if file.name().ends_with(".BASELINE") {
-            let mut reader = Reader::from_reader(BufReader::new(file));
-            while let Ok(_node) = find_node(&mut reader, vec![QName(b"aixm:AirportHeliport")], None) {
-                let airport = parse_airport_heliport(&mut reader)?;
-                airports.insert(airport.identifier.clone(), airport);
+        if let Some(mut reader) = baseline_reader(&mut archive, i, &entries)? {
+            while let Ok(node) = find_node(&mut reader, vec![QName(b"aixm:AirportHeliport")], None) {
+                let mut airport = parse_airport_heliport(&mut reader)?;
+                identifier_fallback(&mut airport.identifier, &node);
+                airports.insert(airport.identifier.clone().into(), airport);
             }
         }
     }
+    log_parsed("airports/heliports", path, airports.len());
     Ok(airports)
 }
 
@@ -92,14 +201,18 @@ fn parse_airport_heliport<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result
             QName(b"gml:identifier"),
             QName(b"aixm:locationIndicatorICAO"),
             QName(b"aixm:designatorIATA"),
+            QName(b"aixm:designator"),
             QName(b"aixm:name"),
             QName(b"aixm:servedCity"),
+            QName(b"aixm:type"),
             QName(b"aixm:controlType"),
             QName(b"aixm:ElevatedPoint"),
+            QName(b"aixm:availability"),
+            QName(b"aixm:magneticVariation"),
         ],
         Some(QName(b"aixm:AirportHeliport")),
     ) {
-        let Node { name, .. } = node;
+        let Node { name, attributes, .. } = node;
         match name {
             QName(b"gml:identifier") => {
                 airport.identifier = read_text(reader, name)?;
@@ -110,6 +223,9 @@ fn parse_airport_heliport<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result
             QName(b"aixm:designatorIATA") => {
                 airport.iata = Some(read_text(reader, name)?);
             }
+            QName(b"aixm:designator") => {
+                airport.designator = Some(read_text(reader, name)?);
+            }
             QName(b"aixm:name") => {
                 airport.name = read_text(reader, name)?;
             }
@@ -119,23 +235,62 @@ fn parse_airport_heliport<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result
                 find_node(reader, vec![QName(b"aixm:name")], Some(QName(b"aixm:City")))?;
                 airport.city = Some(read_text(reader, QName(b"aixm:name"))?);
             }
+            QName(b"aixm:type") => {
+                airport.r#type = FacilityType::parse(&read_text(reader, name)?);
+            }
             QName(b"aixm:controlType") => {
-                airport.r#type = read_text(reader, name)?;
+                airport.control_type = Some(read_text(reader, name)?);
+            }
+            QName(b"aixm:magneticVariation") => {
+                let text = read_text(reader, name)?;
+                match parse_number(&text) {
+                    Ok(variation) => airport.magnetic_variation = Some(variation),
+                    Err(e) => {
+                        tracing::warn!(
+                            target: "thrust::aixm",
+                            "Ignoring magnetic variation of {}: {}",
+                            airport.identifier,
+                            e
+                        )
+                    }
+                }
+            }
+            QName(b"aixm:availability") => {
+                // Only look for the time reference of timesheets: usages have their own aixm:type
+                while let Ok(node) = find_node(reader, vec![QName(b"aixm:timeReference")], Some(name)) {
+                    let time_zone = read_text(reader, node.name)?;
+                    if airport.time_zone.is_none() {
+                        airport.utc_offset = parse_utc_offset(&time_zone);
+                        airport.time_zone = Some(time_zone);
+                    }
+                }
             }
             QName(b"aixm:ElevatedPoint") => {
+                // The reference system may be declared on the point or on the position
+                let point_srs = attributes.get("srsName");
                 while let Ok(node) = find_node(reader, vec![QName(b"gml:pos"), QName(b"aixm:elevation")], Some(name)) {
-                    let Node { name, .. } = node;
+                    let Node { name, attributes, .. } = node;
                     match name {
                         QName(b"gml:pos") => {
-                            let coords: Vec<f64> = read_text(reader, name)?
-                                .split_whitespace()
-                                .map(|s| s.parse().unwrap())
-                                .collect();
-                            airport.latitude = coords[0];
-                            airport.longitude = coords[1];
+                            let srs_name = attributes.get("srsName").or(point_srs).map(String::as_str);
+                            if let Some((latitude, longitude)) = parse_pos_srs(&read_text(reader, name)?, srs_name) {
+                                airport.latitude = latitude;
+                                airport.longitude = longitude;
+                            }
                         }
                         QName(b"aixm:elevation") => {
-                            airport.altitude = read_text(reader, name)?.parse()?;
+                            let text = read_text(reader, name)?;
+                            match parse_number(&text) {
+                                Ok(altitude) => airport.altitude = altitude,
+                                Err(e) => {
+                                    tracing::warn!(
+                                        target: "thrust::aixm",
+                                        "Ignoring elevation of {}: {}",
+                                        airport.identifier,
+                                        e
+                                    )
+                                }
+                            }
                         }
                         _ => (),
                     }
@@ -147,3 +302,186 @@ fn parse_airport_heliport<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result
 
     Ok(airport)
 }
+
+/// Parse the offset in hours of an AIXM time reference, e.g. "UTC" or "UTC-05:30".
+fn parse_utc_offset(time_reference: &str) -> Option<f64> {
+    let offset = time_reference.trim().strip_prefix("UTC")?;
+    if offset.is_empty() {
+        return Some(0.);
+    }
+    let (sign, offset) = match (offset.strip_prefix('+'), offset.strip_prefix('-')) {
+        (Some(rest), _) => (1., rest),
+        (_, Some(rest)) => (-1., rest),
+        _ => return None,
+    };
+    let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+    Some(sign * (hours.parse::<f64>().ok()? + minutes.parse::<f64>().ok()? / 60.))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AIRPORT_HELIPORT: &str = r#"<message:AIXMBasicMessage>
+  <message:hasMember>
+    <aixm:AirportHeliport gml:id="ah-lfbo">
+      <gml:identifier codeSpace="urn:uuid:">ah-lfbo</gml:identifier>
+      <aixm:timeSlice>
+        <aixm:AirportHeliportTimeSlice>
+          <aixm:name>TOULOUSE BLAGNAC</aixm:name>
+          <aixm:locationIndicatorICAO>LFBO</aixm:locationIndicatorICAO>
+          <aixm:type>AD</aixm:type>
+          <aixm:controlType>CIVIL</aixm:controlType>
+          <aixm:magneticVariation>2</aixm:magneticVariation>
+          <aixm:magneticVariationAccuracy>1</aixm:magneticVariationAccuracy>
+          <aixm:ARP>
+            <aixm:ElevatedPoint>
+              <gml:pos>43.635 1.368</gml:pos>
+              <aixm:elevation uom="FT">499</aixm:elevation>
+            </aixm:ElevatedPoint>
+          </aixm:ARP>
+          <aixm:availability>
+            <aixm:AirportHeliportAvailability>
+              <aixm:usage>
+                <aixm:AirportHeliportUsage>
+                  <aixm:type>PERMIT</aixm:type>
+                </aixm:AirportHeliportUsage>
+              </aixm:usage>
+              <aixm:timeInterval>
+                <aixm:Timesheet>
+                  <aixm:timeReference>UTC+1</aixm:timeReference>
+                  <aixm:day>ANY</aixm:day>
+                </aixm:Timesheet>
+              </aixm:timeInterval>
+            </aixm:AirportHeliportAvailability>
+          </aixm:availability>
+        </aixm:AirportHeliportTimeSlice>
+      </aixm:timeSlice>
+    </aixm:AirportHeliport>
+  </message:hasMember>
+</message:AIXMBasicMessage>"#;
+
+    fn parse(xml: &str) -> AirportHeliport {
+        let mut reader = Reader::from_str(xml);
+        find_node(&mut reader, vec![QName(b"aixm:AirportHeliport")], None).unwrap();
+        parse_airport_heliport(&mut reader).unwrap()
+    }
+
+    #[test]
+    fn test_parse_facility_type() {
+        let airport = parse(AIRPORT_HELIPORT);
+        assert_eq!(airport.icao, "LFBO");
+        assert_eq!(airport.r#type, FacilityType::Aerodrome);
+        assert_eq!(airport.control_type.as_deref(), Some("CIVIL"));
+        assert_eq!(airport.altitude, 499.);
+        assert!(airport.r#type.is_aerodrome());
+
+        let heliport = parse(&AIRPORT_HELIPORT.replace("<aixm:type>AD</aixm:type>", "<aixm:type>HP</aixm:type>"));
+        assert_eq!(heliport.r#type, FacilityType::Heliport);
+        assert!(heliport.r#type.is_heliport() && !heliport.r#type.is_aerodrome());
+        assert!(serde_json::to_string(&heliport).unwrap().contains(r#""type":"HP""#));
+    }
+
+    #[test]
+    fn test_parse_heliport_without_icao() {
+        let heliport = parse(
+            &AIRPORT_HELIPORT
+                .replace("<aixm:type>AD</aixm:type>", "<aixm:type>HP</aixm:type>")
+                .replace(
+                    "<aixm:locationIndicatorICAO>LFBO</aixm:locationIndicatorICAO>",
+                    "<aixm:designator>LF3152</aixm:designator>",
+                ),
+        );
+        assert!(heliport.r#type.is_heliport());
+        assert_eq!(heliport.icao, "");
+        assert_eq!(heliport.designator.as_deref(), Some("LF3152"));
+        assert_eq!(heliport.code(), "LF3152");
+
+        let heliport = AirportHeliport {
+            name: " PURPAN ".to_string(),
+            ..heliport
+        };
+        assert_eq!(heliport.code(), "LF3152");
+        assert_eq!(
+            AirportHeliport {
+                designator: None,
+                ..heliport
+            }
+            .code(),
+            "PURPAN"
+        );
+        assert_eq!(parse(AIRPORT_HELIPORT).code(), "LFBO");
+    }
+
+    #[test]
+    fn test_parse_time_reference() {
+        let airport = parse(AIRPORT_HELIPORT);
+        assert_eq!(airport.time_zone.as_deref(), Some("UTC+1"));
+        assert_eq!(airport.utc_offset, Some(1.));
+
+        let airport = parse(&AIRPORT_HELIPORT.replace("UTC+1", "UTC-05:30"));
+        assert_eq!(airport.utc_offset, Some(-5.5));
+
+        let start = AIRPORT_HELIPORT.find("<aixm:timeInterval>").unwrap();
+        let end = AIRPORT_HELIPORT.find("</aixm:timeInterval>").unwrap() + "</aixm:timeInterval>".len();
+        let airport = parse(&format!("{}{}", &AIRPORT_HELIPORT[..start], &AIRPORT_HELIPORT[end..]));
+        assert_eq!((airport.time_zone, airport.utc_offset), (None, None));
+        assert_eq!(parse_utc_offset("UTC"), Some(0.));
+        assert_eq!(parse_utc_offset("local"), None);
+        assert_eq!(parse_utc_offset("UTC−1"), None);
+    }
+
+    #[test]
+    fn test_parse_magnetic_variation() {
+        let airport = parse(AIRPORT_HELIPORT);
+        assert_eq!(airport.magnetic_variation, Some(2.));
+        // 2°E: a magnetic course of 090 is 092 true
+        assert_eq!(airport.true_course(90.), Some(92.));
+        assert_eq!(airport.true_course(359.), Some(1.));
+
+        let airport = parse(&AIRPORT_HELIPORT.replace(">2</aixm:magneticVariation>", ">-1.5</aixm:magneticVariation>"));
+        assert_eq!(airport.magnetic_variation, Some(-1.5));
+        assert_eq!(airport.true_course(1.), Some(359.5));
+
+        let airport = parse(&AIRPORT_HELIPORT.replace("<aixm:magneticVariation>2</aixm:magneticVariation>", ""));
+        assert_eq!((airport.magnetic_variation, airport.true_course(90.)), (None, None));
+    }
+
+    #[test]
+    fn test_parse_escaped_names() {
+        let airport = parse(&AIRPORT_HELIPORT.replace(
+            "<aixm:name>TOULOUSE BLAGNAC</aixm:name>",
+            "<aixm:name>Toulon &amp; Hy&#232;res</aixm:name>
+          <aixm:servedCity>
+            <aixm:City>
+              <aixm:name><![CDATA[Toulon & Hyères]]></aixm:name>
+            </aixm:City>
+          </aixm:servedCity>",
+        ));
+        assert_eq!(airport.name, "Toulon & Hyères");
+        assert_eq!(airport.city.as_deref(), Some("Toulon & Hyères"));
+        assert!(serde_json::to_string(&airport)
+            .unwrap()
+            .contains(r#""name":"Toulon & Hyères""#));
+    }
+
+    #[test]
+    fn test_parse_lon_first_position() {
+        let airport = parse(AIRPORT_HELIPORT);
+        assert_eq!((airport.latitude, airport.longitude), (43.635, 1.368));
+
+        let airport = parse(&AIRPORT_HELIPORT.replace(
+            "<aixm:ElevatedPoint>
+              <gml:pos>43.635 1.368</gml:pos>",
+            r#"<aixm:ElevatedPoint srsName="urn:ogc:def:crs:OGC:1.3:CRS84">
+              <gml:pos>1.368 43.635</gml:pos>"#,
+        ));
+        assert_eq!((airport.latitude, airport.longitude), (43.635, 1.368));
+
+        let airport = parse(&AIRPORT_HELIPORT.replace(
+            "<gml:pos>43.635 1.368</gml:pos>",
+            r#"<gml:pos srsName="urn:ogc:def:crs:EPSG::4326">43.635 1.368</gml:pos>"#,
+        ));
+        assert_eq!((airport.latitude, airport.longitude), (43.635, 1.368));
+    }
+}
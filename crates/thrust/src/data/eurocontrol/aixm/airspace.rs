@@ -4,13 +4,16 @@ use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::BufReader;
 use std::path::Path;
 use zip::read::ZipArchive;
 
+use crate::data::eurocontrol::aixm::identifier::GmlId;
 use crate::data::eurocontrol::aixm::Node;
 
-use super::{find_node, read_text};
+use super::{
+    baseline_reader, find_node, identifier_fallback, log_parsed, parse_pos, read_text, AixmEntries,
+    DEFAULT_ENTRY_SUFFIX,
+};
 
 /// A vertically bounded portion of airspace with horizontal and vertical extents.
 ///
@@ -69,23 +72,33 @@ pub struct Airspace {
     pub volumes: Vec<AirspaceVolume>,
 }
 
-pub fn parse_airspace_zip_file<P: AsRef<Path>>(path: P) -> Result<HashMap<String, Airspace>, ThrustError> {
+pub fn parse_airspace_zip_file<P: AsRef<Path>>(path: P) -> Result<HashMap<GmlId, Airspace>, ThrustError> {
+    parse_airspace_zip_file_with_suffix(path, DEFAULT_ENTRY_SUFFIX)
+}
+
+/// Like [`parse_airspace_zip_file`], reading the archive entries whose name ends with
+/// `entry_suffix` (e.g. `.xml`) instead of [`DEFAULT_ENTRY_SUFFIX`].
+pub fn parse_airspace_zip_file_with_suffix<P: AsRef<Path>>(
+    path: P,
+    entry_suffix: &str,
+) -> Result<HashMap<GmlId, Airspace>, ThrustError> {
+    let path = path.as_ref();
     let file = File::open(path)?;
     let mut archive = ZipArchive::new(file)?;
+    let entries = AixmEntries::new(&archive, entry_suffix);
     let mut airspaces = HashMap::new();
 
     for i in 0..archive.len() {
-        let file = archive.by_index(i)?;
-        if file.name().ends_with(".BASELINE") {
-            let mut reader = Reader::from_reader(BufReader::new(file));
-
-            while let Ok(_node) = find_node(&mut reader, vec![QName(b"aixm:Airspace")], None) {
-                let airspace = parse_airspace(&mut reader)?;
-                airspaces.insert(airspace.identifier.clone(), airspace);
+        if let Some(mut reader) = baseline_reader(&mut archive, i, &entries)? {
+            while let Ok(node) = find_node(&mut reader, vec![QName(b"aixm:Airspace")], None) {
+                let mut airspace = parse_airspace(&mut reader)?;
+                identifier_fallback(&mut airspace.identifier, &node);
+                airspaces.insert(airspace.identifier.clone().into(), airspace);
             }
         }
     }
 
+    log_parsed("airspaces", path, airspaces.len());
     Ok(airspaces)
 }
 
@@ -144,7 +157,7 @@ fn parse_airspace_volume<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<
         ],
         Some(QName(b"aixm:AirspaceVolume")),
     ) {
-        let Node { name, attributes } = node;
+        let Node { name, attributes, .. } = node;
         match name {
             QName(b"aixm:upperLimit") => {
                 volume.upper_limit = Some(read_text(reader, name)?);
@@ -159,10 +172,8 @@ fn parse_airspace_volume<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<
                 volume.lower_limit_reference = Some(read_text(reader, name)?);
             }
             QName(b"gml:pos") => {
-                let text = read_text(reader, name)?;
-                let mut numbers = text.split_whitespace().filter_map(|x| x.parse::<f64>().ok());
-                if let (Some(lat), Some(lon)) = (numbers.next(), numbers.next()) {
-                    volume.polygon.push((lat, lon));
+                if let Some(pos) = parse_pos(&read_text(reader, name)?) {
+                    volume.polygon.push(pos);
                 }
             }
             QName(b"gml:pointProperty") => {
@@ -4,14 +4,18 @@ use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::BufReader;
 use std::path::Path;
 use zip::read::ZipArchive;
 
+use crate::data::eurocontrol::aixm::designated_point::ProcedureFixRole;
+use crate::data::eurocontrol::aixm::identifier::GmlId;
 use crate::data::eurocontrol::aixm::route_segment::PointReference;
 use crate::data::eurocontrol::aixm::Node;
 
-use super::{find_node, read_text};
+use super::{
+    baseline_reader, find_node, identifier_fallback, log_parsed, parse_number, parse_terminal_segment_point, read_text,
+    AixmEntries, DEFAULT_ENTRY_SUFFIX,
+};
 
 /// A single segment of a Standard Arrival Route (STAR) procedure.
 ///
@@ -23,14 +27,19 @@ use super::{find_node, read_text};
 /// - `arrival`: Associated STAR identifier (e.g., "KSEA01")
 /// - `start`: Entry point (navaid, waypoint, or airport)
 /// - `end`: Exit point or subsequent waypoint
+/// - `leg_type`: ARINC 424 path terminator (e.g., "TF", "RF", "CF")
+/// - `course`: Published course in degrees, if any
+/// - `turn_direction`: Turn direction ("LEFT" or "RIGHT") for arc legs
+/// - `arc_centre`: Centre of the arc for radius-to-fix and arc legs
+/// - `start_role`, `end_role`: Procedural role of the start and end points (e.g. IAF, FAF), if published
 ///
 /// # Example
 /// ```ignore
 /// let leg = ArrivalLeg {
 ///     identifier: "LEG001".to_string(),
 ///     arrival: Some("KSEA01".to_string()),
-///     start: PointReference::Navaid("SEA".to_string()),
-///     end: PointReference::DesignatedPoint("APTIN".to_string()),
+///     start: PointReference::Navaid("SEA".into()),
+///     end: PointReference::DesignatedPoint("APTIN".into()),
 /// };
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -39,25 +48,43 @@ pub struct ArrivalLeg {
     pub arrival: Option<String>,
     pub start: PointReference,
     pub end: PointReference,
+    pub leg_type: Option<String>,
+    pub course: Option<f64>,
+    pub turn_direction: Option<String>,
+    pub arc_centre: PointReference,
+    #[serde(default)]
+    pub start_role: Option<ProcedureFixRole>,
+    #[serde(default)]
+    pub end_role: Option<ProcedureFixRole>,
 }
 
-pub fn parse_arrival_leg_zip_file<P: AsRef<Path>>(path: P) -> Result<HashMap<String, ArrivalLeg>, ThrustError> {
+pub fn parse_arrival_leg_zip_file<P: AsRef<Path>>(path: P) -> Result<HashMap<GmlId, ArrivalLeg>, ThrustError> {
+    parse_arrival_leg_zip_file_with_suffix(path, DEFAULT_ENTRY_SUFFIX)
+}
+
+/// Like [`parse_arrival_leg_zip_file`], reading the archive entries whose name ends with
+/// `entry_suffix` (e.g. `.xml`) instead of [`DEFAULT_ENTRY_SUFFIX`].
+pub fn parse_arrival_leg_zip_file_with_suffix<P: AsRef<Path>>(
+    path: P,
+    entry_suffix: &str,
+) -> Result<HashMap<GmlId, ArrivalLeg>, ThrustError> {
+    let path = path.as_ref();
     let file = File::open(path)?;
     let mut archive = ZipArchive::new(file)?;
+    let entries = AixmEntries::new(&archive, entry_suffix);
     let mut legs = HashMap::new();
 
     for i in 0..archive.len() {
-        let file = archive.by_index(i)?;
-        if file.name().ends_with(".BASELINE") {
-            let mut reader = Reader::from_reader(BufReader::new(file));
-
-            while let Ok(_node) = find_node(&mut reader, vec![QName(b"aixm:ArrivalLeg")], None) {
-                let leg = parse_arrival_leg(&mut reader)?;
-                legs.insert(leg.identifier.clone(), leg);
+        if let Some(mut reader) = baseline_reader(&mut archive, i, &entries)? {
+            while let Ok(node) = find_node(&mut reader, vec![QName(b"aixm:ArrivalLeg")], None) {
+                let mut leg = parse_arrival_leg(&mut reader)?;
+                identifier_fallback(&mut leg.identifier, &node);
+                legs.insert(leg.identifier.clone().into(), leg);
             }
         }
     }
 
+    log_parsed("arrival legs", path, legs.len());
     Ok(legs)
 }
 
@@ -71,25 +98,41 @@ fn parse_arrival_leg<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<Arri
             QName(b"aixm:startPoint"),
             QName(b"aixm:endPoint"),
             QName(b"aixm:arrival"),
+            QName(b"aixm:legTypeARINC"),
+            QName(b"aixm:course"),
+            QName(b"aixm:turnDirection"),
+            QName(b"aixm:arcCentre"),
         ],
         Some(QName(b"aixm:ArrivalLeg")),
     ) {
-        let Node { name, attributes } = node;
+        let Node { name, attributes, .. } = node;
         match name {
             QName(b"gml:identifier") => {
                 leg.identifier = read_text(reader, name)?;
             }
             QName(b"aixm:startPoint") => {
-                leg.start = parse_terminal_segment_point(reader, name)?;
+                (leg.start, leg.start_role) = parse_terminal_segment_point(reader, name)?;
             }
             QName(b"aixm:endPoint") => {
-                leg.end = parse_terminal_segment_point(reader, name)?;
+                (leg.end, leg.end_role) = parse_terminal_segment_point(reader, name)?;
             }
             QName(b"aixm:arrival") => {
                 leg.arrival = attributes
                     .get("xlink:href")
                     .map(|s| s.strip_prefix("urn:uuid:").unwrap_or(s).to_string());
             }
+            QName(b"aixm:legTypeARINC") => {
+                leg.leg_type = Some(read_text(reader, name)?);
+            }
+            QName(b"aixm:course") => {
+                leg.course = parse_number(&read_text(reader, name)?).ok();
+            }
+            QName(b"aixm:turnDirection") => {
+                leg.turn_direction = Some(read_text(reader, name)?);
+            }
+            QName(b"aixm:arcCentre") => {
+                (leg.arc_centre, _) = parse_terminal_segment_point(reader, name)?;
+            }
             _ => (),
         }
     }
@@ -97,33 +140,53 @@ fn parse_arrival_leg<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<Arri
     Ok(leg)
 }
 
-fn parse_terminal_segment_point<R: std::io::BufRead>(
-    reader: &mut Reader<R>,
-    end: QName,
-) -> Result<PointReference, ThrustError> {
-    while let Ok(node) = find_node(reader, vec![QName(b"aixm:TerminalSegmentPoint")], Some(end)) {
-        while let Ok(node) = find_node(
-            reader,
-            vec![
-                QName(b"aixm:pointChoice_fixDesignatedPoint"),
-                QName(b"aixm:pointChoice_navaidSystem"),
-                QName(b"aixm:pointChoice_airportReferencePoint"),
-            ],
-            Some(node.name),
-        ) {
-            let Node { name, attributes } = node;
-            if let Some(id) = attributes
-                .get("xlink:href")
-                .map(|s| s.strip_prefix("urn:uuid:").unwrap_or(s).to_string())
-            {
-                return Ok(match name {
-                    QName(b"aixm:pointChoice_fixDesignatedPoint") => PointReference::DesignatedPoint(id),
-                    QName(b"aixm:pointChoice_navaidSystem") => PointReference::Navaid(id),
-                    QName(b"aixm:pointChoice_airportReferencePoint") => PointReference::AirportHeliport(id),
-                    _ => PointReference::None,
-                });
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARRIVAL_LEG: &str = r#"<aixm:ArrivalLeg gml:id="leg-1">
+  <gml:identifier codeSpace="urn:uuid:">leg-1</gml:identifier>
+  <aixm:timeSlice>
+    <aixm:ArrivalLegTimeSlice>
+      <aixm:legTypeARINC>TF</aixm:legTypeARINC>
+      <aixm:startPoint>
+        <aixm:TerminalSegmentPoint>
+          <aixm:role>IF</aixm:role>
+          <aixm:flyOver>false</aixm:flyOver>
+          <aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-abc"/>
+        </aixm:TerminalSegmentPoint>
+      </aixm:startPoint>
+      <aixm:endPoint>
+        <aixm:TerminalSegmentPoint>
+          <aixm:role>FAF</aixm:role>
+          <aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-def"/>
+        </aixm:TerminalSegmentPoint>
+      </aixm:endPoint>
+      <aixm:arcCentre>
+        <aixm:TerminalSegmentPoint>
+          <aixm:role xsi:nil="true"/>
+          <aixm:pointChoice_navaidSystem xlink:href="urn:uuid:nav-tou"/>
+        </aixm:TerminalSegmentPoint>
+      </aixm:arcCentre>
+      <aixm:arrival xlink:href="urn:uuid:star-abc1a"/>
+    </aixm:ArrivalLegTimeSlice>
+  </aixm:timeSlice>
+</aixm:ArrivalLeg>"#;
+
+    #[test]
+    fn test_parse_procedure_fix_role() {
+        let mut reader = Reader::from_str(ARRIVAL_LEG);
+        find_node(&mut reader, vec![QName(b"aixm:ArrivalLeg")], None).unwrap();
+        let leg = parse_arrival_leg(&mut reader).unwrap();
+        assert_eq!(leg.identifier, "leg-1");
+        assert_eq!(leg.arrival.as_deref(), Some("star-abc1a"));
+        assert_eq!(leg.start, PointReference::DesignatedPoint("dp-abc".into()));
+        assert_eq!(leg.start_role, Some(ProcedureFixRole::If));
+        assert_eq!(leg.end, PointReference::DesignatedPoint("dp-def".into()));
+        assert_eq!(leg.end_role, Some(ProcedureFixRole::Faf));
+        assert_eq!(leg.arc_centre, PointReference::Navaid("nav-tou".into()));
+
+        assert_eq!(ProcedureFixRole::from("if_iaf"), ProcedureFixRole::IfIaf);
+        assert_eq!(ProcedureFixRole::from("OTHER:ENRT").to_string(), "OTHER:ENRT");
     }
-    Ok(PointReference::None)
 }
@@ -1,15 +1,17 @@
 use std::collections::HashMap;
-use std::io::{BufReader, Cursor, Read};
+use std::io::Cursor;
 use std::path::Path;
 
 use quick_xml::name::QName;
-use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
 use zip::read::ZipArchive;
 
 use crate::error::ThrustError;
 
-use super::{find_node, read_text};
+use super::navaid::{parse_navaids, Navaid};
+use super::{
+    baseline_reader, find_node, identifier_fallback, parse_number, read_text, AixmEntries, DEFAULT_ENTRY_SUFFIX,
+};
 
 const AIXM_REQUIRED_FILES: [&str; 5] = [
     "AirportHeliport.BASELINE.zip",
@@ -133,30 +135,17 @@ pub fn parse_aixm_folder_bytes(files: &HashMap<String, Vec<u8>>) -> Result<AixmD
     })
 }
 
-fn read_baseline_xml_documents(zip_bytes: &[u8]) -> Result<Vec<String>, ThrustError> {
-    let cursor = Cursor::new(zip_bytes);
-    let mut archive = ZipArchive::new(cursor)?;
-    let mut xmls = Vec::new();
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
-        if !file.name().ends_with(".BASELINE") {
-            continue;
-        }
-        let mut xml = String::new();
-        file.read_to_string(&mut xml)?;
-        if !xml.is_empty() {
-            xmls.push(xml);
-        }
-    }
-    Ok(xmls)
-}
-
 fn parse_aixm_airports(zip_bytes: &[u8]) -> Result<Vec<AirportRecord>, ThrustError> {
     let mut out = Vec::new();
-    for xml in read_baseline_xml_documents(zip_bytes)? {
-        let mut reader = Reader::from_reader(BufReader::new(Cursor::new(xml.into_bytes())));
+    let mut archive = ZipArchive::new(Cursor::new(zip_bytes))?;
+    let entries = AixmEntries::new(&archive, DEFAULT_ENTRY_SUFFIX);
+    for i in 0..archive.len() {
+        let Some(mut reader) = baseline_reader(&mut archive, i, &entries)? else {
+            continue;
+        };
         while find_node(&mut reader, vec![QName(b"aixm:AirportHeliport")], None).is_ok() {
             let mut icao = String::new();
+            let mut designator = String::new();
             let mut iata = None;
             let mut name = String::new();
             let mut latitude = 0.0_f64;
@@ -166,6 +155,7 @@ fn parse_aixm_airports(zip_bytes: &[u8]) -> Result<Vec<AirportRecord>, ThrustErr
                 vec![
                     QName(b"aixm:locationIndicatorICAO"),
                     QName(b"aixm:designatorIATA"),
+                    QName(b"aixm:designator"),
                     QName(b"aixm:name"),
                     QName(b"aixm:ElevatedPoint"),
                 ],
@@ -174,6 +164,7 @@ fn parse_aixm_airports(zip_bytes: &[u8]) -> Result<Vec<AirportRecord>, ThrustErr
                 match node.name {
                     QName(b"aixm:locationIndicatorICAO") => icao = read_text(&mut reader, node.name)?,
                     QName(b"aixm:designatorIATA") => iata = Some(read_text(&mut reader, node.name)?),
+                    QName(b"aixm:designator") => designator = read_text(&mut reader, node.name)?,
                     QName(b"aixm:name") => name = read_text(&mut reader, node.name)?,
                     QName(b"aixm:ElevatedPoint") => {
                         while let Ok(pos) =
@@ -181,7 +172,7 @@ fn parse_aixm_airports(zip_bytes: &[u8]) -> Result<Vec<AirportRecord>, ThrustErr
                         {
                             let coords: Vec<f64> = read_text(&mut reader, pos.name)?
                                 .split_whitespace()
-                                .filter_map(|s| s.parse::<f64>().ok())
+                                .filter_map(|s| parse_number(s).ok())
                                 .collect();
                             if coords.len() == 2 {
                                 latitude = coords[0];
@@ -193,7 +184,19 @@ fn parse_aixm_airports(zip_bytes: &[u8]) -> Result<Vec<AirportRecord>, ThrustErr
                 }
             }
 
-            if !icao.is_empty() {
+            // Heliports often have no ICAO code, only a national designator
+            if icao.is_empty() && !designator.is_empty() {
+                out.push(AirportRecord {
+                    code: designator.to_uppercase(),
+                    iata: None,
+                    icao: None,
+                    name: (!name.is_empty()).then_some(name),
+                    latitude,
+                    longitude,
+                    region: None,
+                    source: "eurocontrol_aixm".to_string(),
+                });
+            } else if !icao.is_empty() {
                 let name_value = if name.is_empty() { None } else { Some(name.clone()) };
                 out.push(AirportRecord {
                     code: icao.to_uppercase(),
@@ -226,9 +229,13 @@ fn parse_aixm_airports(zip_bytes: &[u8]) -> Result<Vec<AirportRecord>, ThrustErr
 fn parse_aixm_designated_points(zip_bytes: &[u8]) -> Result<NavpointsWithRefIndex, ThrustError> {
     let mut out = Vec::new();
     let mut by_id: HashMap<String, AirwayPointRecord> = HashMap::new();
-    for xml in read_baseline_xml_documents(zip_bytes)? {
-        let mut reader = Reader::from_reader(BufReader::new(Cursor::new(xml.into_bytes())));
-        while find_node(&mut reader, vec![QName(b"aixm:DesignatedPoint")], None).is_ok() {
+    let mut archive = ZipArchive::new(Cursor::new(zip_bytes))?;
+    let entries = AixmEntries::new(&archive, DEFAULT_ENTRY_SUFFIX);
+    for i in 0..archive.len() {
+        let Some(mut reader) = baseline_reader(&mut archive, i, &entries)? else {
+            continue;
+        };
+        while let Ok(feature) = find_node(&mut reader, vec![QName(b"aixm:DesignatedPoint")], None) {
             let mut identifier = String::new();
             let mut designator = String::new();
             let mut name = None;
@@ -255,7 +262,7 @@ fn parse_aixm_designated_points(zip_bytes: &[u8]) -> Result<NavpointsWithRefInde
                         while let Ok(pos) = find_node(&mut reader, vec![QName(b"gml:pos")], Some(node.name)) {
                             let coords: Vec<f64> = read_text(&mut reader, pos.name)?
                                 .split_whitespace()
-                                .filter_map(|s| s.parse::<f64>().ok())
+                                .filter_map(|s| parse_number(s).ok())
                                 .collect();
                             if coords.len() == 2 {
                                 latitude = coords[0];
@@ -266,6 +273,7 @@ fn parse_aixm_designated_points(zip_bytes: &[u8]) -> Result<NavpointsWithRefInde
                     _ => {}
                 }
             }
+            identifier_fallback(&mut identifier, &feature);
 
             if !designator.is_empty() {
                 let code = designator.to_uppercase();
@@ -303,47 +311,23 @@ fn parse_aixm_designated_points(zip_bytes: &[u8]) -> Result<NavpointsWithRefInde
 fn parse_aixm_navaids(zip_bytes: &[u8]) -> Result<NavpointsWithRefIndex, ThrustError> {
     let mut out = Vec::new();
     let mut by_id: HashMap<String, AirwayPointRecord> = HashMap::new();
-    for xml in read_baseline_xml_documents(zip_bytes)? {
-        let mut reader = Reader::from_reader(BufReader::new(Cursor::new(xml.into_bytes())));
-        while find_node(&mut reader, vec![QName(b"aixm:Navaid")], None).is_ok() {
-            let mut identifier = String::new();
-            let mut designator = None;
-            let mut description = None;
-            let mut point_type = None;
-            let mut latitude = 0.0_f64;
-            let mut longitude = 0.0_f64;
-            while let Ok(node) = find_node(
-                &mut reader,
-                vec![
-                    QName(b"gml:identifier"),
-                    QName(b"aixm:designator"),
-                    QName(b"aixm:type"),
-                    QName(b"aixm:name"),
-                    QName(b"aixm:ElevatedPoint"),
-                ],
-                Some(QName(b"aixm:Navaid")),
-            ) {
-                match node.name {
-                    QName(b"gml:identifier") => identifier = read_text(&mut reader, node.name)?,
-                    QName(b"aixm:designator") => designator = Some(read_text(&mut reader, node.name)?),
-                    QName(b"aixm:type") => point_type = Some(read_text(&mut reader, node.name)?),
-                    QName(b"aixm:name") => description = Some(read_text(&mut reader, node.name)?),
-                    QName(b"aixm:ElevatedPoint") => {
-                        while let Ok(pos) = find_node(&mut reader, vec![QName(b"gml:pos")], Some(node.name)) {
-                            let coords: Vec<f64> = read_text(&mut reader, pos.name)?
-                                .split_whitespace()
-                                .filter_map(|s| s.parse::<f64>().ok())
-                                .collect();
-                            if coords.len() == 2 {
-                                latitude = coords[0];
-                                longitude = coords[1];
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
-
+    let mut archive = ZipArchive::new(Cursor::new(zip_bytes))?;
+    let entries = AixmEntries::new(&archive, DEFAULT_ENTRY_SUFFIX);
+    for i in 0..archive.len() {
+        let Some(mut reader) = baseline_reader(&mut archive, i, &entries)? else {
+            continue;
+        };
+        for navaid in parse_navaids(&mut reader)? {
+            let Navaid {
+                identifier,
+                latitude,
+                longitude,
+                name: designator,
+                r#type,
+                description,
+                ..
+            } = navaid;
+            let point_type = (!r#type.is_empty()).then_some(r#type);
             if let Some(code) = designator {
                 let upper = code.to_uppercase();
                 out.push(NavpointRecord {
@@ -383,9 +367,13 @@ fn parse_aixm_airways(
     points_by_id: &HashMap<String, AirwayPointRecord>,
 ) -> Result<Vec<AirwayRecord>, ThrustError> {
     let mut route_name_by_id: HashMap<String, String> = HashMap::new();
-    for xml in read_baseline_xml_documents(route_zip_bytes)? {
-        let mut reader = Reader::from_reader(BufReader::new(Cursor::new(xml.into_bytes())));
-        while find_node(&mut reader, vec![QName(b"aixm:Route")], None).is_ok() {
+    let mut archive = ZipArchive::new(Cursor::new(route_zip_bytes))?;
+    let entries = AixmEntries::new(&archive, DEFAULT_ENTRY_SUFFIX);
+    for i in 0..archive.len() {
+        let Some(mut reader) = baseline_reader(&mut archive, i, &entries)? else {
+            continue;
+        };
+        while let Ok(feature) = find_node(&mut reader, vec![QName(b"aixm:Route")], None) {
             let mut identifier = String::new();
             let mut prefix = String::new();
             let mut second = String::new();
@@ -411,6 +399,7 @@ fn parse_aixm_airways(
                     _ => {}
                 }
             }
+            identifier_fallback(&mut identifier, &feature);
             if !identifier.is_empty() {
                 route_name_by_id.insert(identifier, format!("{prefix}{second}{number}{multiple}").to_uppercase());
             }
@@ -418,8 +407,12 @@ fn parse_aixm_airways(
     }
 
     let mut grouped: HashMap<String, Vec<AirwayPointRecord>> = HashMap::new();
-    for xml in read_baseline_xml_documents(route_segment_zip_bytes)? {
-        let mut reader = Reader::from_reader(BufReader::new(Cursor::new(xml.into_bytes())));
+    let mut archive = ZipArchive::new(Cursor::new(route_segment_zip_bytes))?;
+    let entries = AixmEntries::new(&archive, DEFAULT_ENTRY_SUFFIX);
+    for i in 0..archive.len() {
+        let Some(mut reader) = baseline_reader(&mut archive, i, &entries)? else {
+            continue;
+        };
         while find_node(&mut reader, vec![QName(b"aixm:RouteSegment")], None).is_ok() {
             let mut route_id: Option<String> = None;
             let mut start_id: Option<String> = None;
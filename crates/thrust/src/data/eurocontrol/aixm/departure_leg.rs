@@ -4,14 +4,18 @@ use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::BufReader;
 use std::path::Path;
 use zip::read::ZipArchive;
 
+use crate::data::eurocontrol::aixm::designated_point::ProcedureFixRole;
+use crate::data::eurocontrol::aixm::identifier::GmlId;
 use crate::data::eurocontrol::aixm::route_segment::PointReference;
 use crate::data::eurocontrol::aixm::Node;
 
-use super::{find_node, read_text};
+use super::{
+    baseline_reader, find_node, identifier_fallback, log_parsed, parse_number, parse_terminal_segment_point, read_text,
+    AixmEntries, DEFAULT_ENTRY_SUFFIX,
+};
 
 /// A single segment of a Standard Instrument Departure (SID) procedure.
 ///
@@ -24,6 +28,11 @@ use super::{find_node, read_text};
 /// - `departure`: Associated SID identifier (e.g., "KSEA01")
 /// - `start`: Entry point (airport or initial waypoint)
 /// - `end`: Exit point to the next leg or navaid
+/// - `leg_type`: ARINC 424 path terminator (e.g., "TF", "RF", "CF")
+/// - `course`: Published course in degrees, if any
+/// - `turn_direction`: Turn direction ("LEFT" or "RIGHT") for arc legs
+/// - `arc_centre`: Centre of the arc for radius-to-fix and arc legs
+/// - `start_role`, `end_role`: Procedural role of the start and end points (e.g. IAF, FAF), if published
 ///
 /// # Example
 /// ```ignore
@@ -31,7 +40,7 @@ use super::{find_node, read_text};
 ///     identifier: "LEG001".to_string(),
 ///     departure: Some("KSEA01".to_string()),
 ///     start: PointReference::Airport("KSEA".to_string()),
-///     end: PointReference::DesignatedPoint("KENRY".to_string()),
+///     end: PointReference::DesignatedPoint("KENRY".into()),
 /// };
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -40,25 +49,43 @@ pub struct DepartureLeg {
     pub departure: Option<String>,
     pub start: PointReference,
     pub end: PointReference,
+    pub leg_type: Option<String>,
+    pub course: Option<f64>,
+    pub turn_direction: Option<String>,
+    pub arc_centre: PointReference,
+    #[serde(default)]
+    pub start_role: Option<ProcedureFixRole>,
+    #[serde(default)]
+    pub end_role: Option<ProcedureFixRole>,
 }
 
-pub fn parse_departure_leg_zip_file<P: AsRef<Path>>(path: P) -> Result<HashMap<String, DepartureLeg>, ThrustError> {
+pub fn parse_departure_leg_zip_file<P: AsRef<Path>>(path: P) -> Result<HashMap<GmlId, DepartureLeg>, ThrustError> {
+    parse_departure_leg_zip_file_with_suffix(path, DEFAULT_ENTRY_SUFFIX)
+}
+
+/// Like [`parse_departure_leg_zip_file`], reading the archive entries whose name ends with
+/// `entry_suffix` (e.g. `.xml`) instead of [`DEFAULT_ENTRY_SUFFIX`].
+pub fn parse_departure_leg_zip_file_with_suffix<P: AsRef<Path>>(
+    path: P,
+    entry_suffix: &str,
+) -> Result<HashMap<GmlId, DepartureLeg>, ThrustError> {
+    let path = path.as_ref();
     let file = File::open(path)?;
     let mut archive = ZipArchive::new(file)?;
+    let entries = AixmEntries::new(&archive, entry_suffix);
     let mut legs = HashMap::new();
 
     for i in 0..archive.len() {
-        let file = archive.by_index(i)?;
-        if file.name().ends_with(".BASELINE") {
-            let mut reader = Reader::from_reader(BufReader::new(file));
-
-            while let Ok(_node) = find_node(&mut reader, vec![QName(b"aixm:DepartureLeg")], None) {
-                let leg = parse_departure_leg(&mut reader)?;
-                legs.insert(leg.identifier.clone(), leg);
+        if let Some(mut reader) = baseline_reader(&mut archive, i, &entries)? {
+            while let Ok(node) = find_node(&mut reader, vec![QName(b"aixm:DepartureLeg")], None) {
+                let mut leg = parse_departure_leg(&mut reader)?;
+                identifier_fallback(&mut leg.identifier, &node);
+                legs.insert(leg.identifier.clone().into(), leg);
             }
         }
     }
 
+    log_parsed("departure legs", path, legs.len());
     Ok(legs)
 }
 
@@ -72,58 +99,86 @@ fn parse_departure_leg<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<De
             QName(b"aixm:startPoint"),
             QName(b"aixm:endPoint"),
             QName(b"aixm:departure"),
+            QName(b"aixm:legTypeARINC"),
+            QName(b"aixm:course"),
+            QName(b"aixm:turnDirection"),
+            QName(b"aixm:arcCentre"),
         ],
         Some(QName(b"aixm:DepartureLeg")),
     ) {
-        let Node { name, attributes } = node;
+        let Node { name, attributes, .. } = node;
         match name {
             QName(b"gml:identifier") => {
                 leg.identifier = read_text(reader, name)?;
             }
             QName(b"aixm:startPoint") => {
-                leg.start = parse_terminal_segment_point(reader, name)?;
+                (leg.start, leg.start_role) = parse_terminal_segment_point(reader, name)?;
             }
             QName(b"aixm:endPoint") => {
-                leg.end = parse_terminal_segment_point(reader, name)?;
+                (leg.end, leg.end_role) = parse_terminal_segment_point(reader, name)?;
             }
             QName(b"aixm:departure") => {
                 leg.departure = attributes
                     .get("xlink:href")
                     .map(|s| s.strip_prefix("urn:uuid:").unwrap_or(s).to_string());
             }
+            QName(b"aixm:legTypeARINC") => {
+                leg.leg_type = Some(read_text(reader, name)?);
+            }
+            QName(b"aixm:course") => {
+                leg.course = parse_number(&read_text(reader, name)?).ok();
+            }
+            QName(b"aixm:turnDirection") => {
+                leg.turn_direction = Some(read_text(reader, name)?);
+            }
+            QName(b"aixm:arcCentre") => {
+                (leg.arc_centre, _) = parse_terminal_segment_point(reader, name)?;
+            }
             _ => (),
         }
     }
     Ok(leg)
 }
 
-fn parse_terminal_segment_point<R: std::io::BufRead>(
-    reader: &mut Reader<R>,
-    end: QName,
-) -> Result<PointReference, ThrustError> {
-    while let Ok(node) = find_node(reader, vec![QName(b"aixm:TerminalSegmentPoint")], Some(end)) {
-        while let Ok(node) = find_node(
-            reader,
-            vec![
-                QName(b"aixm:pointChoice_fixDesignatedPoint"),
-                QName(b"aixm:pointChoice_navaidSystem"),
-                QName(b"aixm:pointChoice_airportReferencePoint"),
-            ],
-            Some(node.name),
-        ) {
-            let Node { name, attributes } = node;
-            if let Some(id) = attributes
-                .get("xlink:href")
-                .map(|s| s.strip_prefix("urn:uuid:").unwrap_or(s).to_string())
-            {
-                return Ok(match name {
-                    QName(b"aixm:pointChoice_fixDesignatedPoint") => PointReference::DesignatedPoint(id),
-                    QName(b"aixm:pointChoice_navaidSystem") => PointReference::Navaid(id),
-                    QName(b"aixm:pointChoice_airportReferencePoint") => PointReference::AirportHeliport(id),
-                    _ => PointReference::None,
-                });
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Terminal segment points in AIXM 5.1 schema order: the role after the point choice
+    const DEPARTURE_LEG: &str = r#"<aixm:DepartureLeg gml:id="leg-2">
+  <gml:identifier codeSpace="urn:uuid:">leg-2</gml:identifier>
+  <aixm:timeSlice>
+    <aixm:DepartureLegTimeSlice>
+      <aixm:legTypeARINC>CF</aixm:legTypeARINC>
+      <aixm:startPoint>
+        <aixm:TerminalSegmentPoint>
+          <aixm:flyOver>true</aixm:flyOver>
+          <aixm:pointChoice_airportReferencePoint xlink:href="urn:uuid:ad-lfbo"/>
+        </aixm:TerminalSegmentPoint>
+      </aixm:startPoint>
+      <aixm:endPoint>
+        <aixm:TerminalSegmentPoint>
+          <aixm:flyOver>false</aixm:flyOver>
+          <aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-abc"/>
+          <aixm:role>OTHER:SID_ENRT</aixm:role>
+          <aixm:indicatorFACF>NO</aixm:indicatorFACF>
+        </aixm:TerminalSegmentPoint>
+      </aixm:endPoint>
+      <aixm:departure xlink:href="urn:uuid:sid-abc1d"/>
+    </aixm:DepartureLegTimeSlice>
+  </aixm:timeSlice>
+</aixm:DepartureLeg>"#;
+
+    #[test]
+    fn test_role_after_point_choice() {
+        let mut reader = Reader::from_str(DEPARTURE_LEG);
+        find_node(&mut reader, vec![QName(b"aixm:DepartureLeg")], None).unwrap();
+        let leg = parse_departure_leg(&mut reader).unwrap();
+        assert_eq!(leg.departure.as_deref(), Some("sid-abc1d"));
+        assert_eq!(leg.start, PointReference::AirportHeliport("ad-lfbo".into()));
+        assert_eq!(leg.start_role, None);
+        assert_eq!(leg.end, PointReference::DesignatedPoint("dp-abc".into()));
+        assert_eq!(leg.end_role, Some(ProcedureFixRole::from("OTHER:SID_ENRT")));
+        assert_eq!(leg.leg_type.as_deref(), Some("CF"));
     }
-    Ok(PointReference::None)
 }
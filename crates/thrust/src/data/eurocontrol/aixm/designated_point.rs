@@ -4,13 +4,16 @@ use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::BufReader;
 use std::path::Path;
 use zip::read::ZipArchive;
 
+use crate::data::eurocontrol::aixm::identifier::GmlId;
 use crate::data::eurocontrol::aixm::Node;
 
-use super::{find_node, read_text};
+use super::{
+    baseline_reader, find_node, identifier_fallback, log_parsed, parse_pos, read_text, AixmEntries,
+    DEFAULT_ENTRY_SUFFIX,
+};
 
 /// A published waypoint or fix that is not a navigation aid (navaid).
 ///
@@ -34,6 +37,8 @@ use super::{find_node, read_text};
 /// };
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct DesignatedPoint {
     #[serde(skip)]
     pub identifier: String,
@@ -47,29 +52,194 @@ pub struct DesignatedPoint {
     #[serde(skip)]
     pub name: Option<String>,
     #[serde(skip)]
-    /// Type of designated point (TODO: enum?)
-    pub r#type: String,
+    /// Type of designated point
+    pub r#type: DesignatedPointType,
+}
+
+/// The AIXM type of a designated point (`CodeDesignatedPointType`).
+///
+/// # Variants
+/// - `Icao`: Published ICAO five-letter name code
+/// - `Coord`: Point named after its geographical coordinates
+/// - `Cnf`: Computer navigation fix
+/// - `Designed`: Point designed for an instrument procedure
+/// - `Mtr`: Military training route point
+/// - `Terminal`: Terminal area point
+/// - `BrgDist`: Point defined by a bearing and distance from a navaid
+/// - `Other(String)`: Any other value, as published
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+pub enum DesignatedPointType {
+    Icao,
+    Coord,
+    Cnf,
+    Designed,
+    Mtr,
+    Terminal,
+    BrgDist,
+    Other(String),
+}
+
+impl DesignatedPointType {
+    /// Whether the point is a VFR reporting point.
+    ///
+    /// AIXM 5.1 has no code for them, so they are published with a free text type,
+    /// e.g. "VFR-RP" or "OTHER:VFR_REP".
+    pub fn is_vfr_reporting_point(&self) -> bool {
+        match self {
+            DesignatedPointType::Other(value) => {
+                let value = value.trim().to_ascii_uppercase();
+                value.strip_prefix("OTHER:").unwrap_or(&value).starts_with("VFR")
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Default for DesignatedPointType {
+    fn default() -> Self {
+        DesignatedPointType::Other(String::new())
+    }
+}
+
+impl From<&str> for DesignatedPointType {
+    fn from(value: &str) -> Self {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "ICAO" => DesignatedPointType::Icao,
+            "COORD" => DesignatedPointType::Coord,
+            "CNF" => DesignatedPointType::Cnf,
+            "DESIGNED" => DesignatedPointType::Designed,
+            "MTR" => DesignatedPointType::Mtr,
+            "TERMINAL" => DesignatedPointType::Terminal,
+            "BRG_DIST" => DesignatedPointType::BrgDist,
+            _ => DesignatedPointType::Other(value.trim().to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for DesignatedPointType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DesignatedPointType::Icao => write!(f, "ICAO"),
+            DesignatedPointType::Coord => write!(f, "COORD"),
+            DesignatedPointType::Cnf => write!(f, "CNF"),
+            DesignatedPointType::Designed => write!(f, "DESIGNED"),
+            DesignatedPointType::Mtr => write!(f, "MTR"),
+            DesignatedPointType::Terminal => write!(f, "TERMINAL"),
+            DesignatedPointType::BrgDist => write!(f, "BRG_DIST"),
+            DesignatedPointType::Other(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// Role of a fix in a terminal procedure (AIXM CodeProcedureFixRoleType).
+///
+/// The role is published on the terminal segment point of a procedure leg,
+/// not on the designated point itself, as the same fix may play different
+/// roles in different procedures.
+///
+/// # Variants
+/// - `Iaf`: Initial approach fix
+/// - `If`: Intermediate fix
+/// - `IfIaf`: Intermediate fix, also used as initial approach fix
+/// - `Faf`: Final approach fix
+/// - `Vdp`: Visual descent point
+/// - `Sdf`: Step down fix
+/// - `Fpap`: Flight path alignment point
+/// - `Ftp`: Fictitious threshold point
+/// - `Frop`: Final roll-out point
+/// - `Tp`: Turning point
+/// - `Mapt`: Missed approach point
+/// - `Mahf`: Missed approach holding fix
+/// - `Other(String)`: Any other value, as published
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ProcedureFixRole {
+    Iaf,
+    If,
+    IfIaf,
+    Faf,
+    Vdp,
+    Sdf,
+    Fpap,
+    Ftp,
+    Frop,
+    Tp,
+    Mapt,
+    Mahf,
+    Other(String),
+}
+
+impl From<&str> for ProcedureFixRole {
+    fn from(value: &str) -> Self {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "IAF" => ProcedureFixRole::Iaf,
+            "IF" => ProcedureFixRole::If,
+            "IF_IAF" => ProcedureFixRole::IfIaf,
+            "FAF" => ProcedureFixRole::Faf,
+            "VDP" => ProcedureFixRole::Vdp,
+            "SDF" => ProcedureFixRole::Sdf,
+            "FPAP" => ProcedureFixRole::Fpap,
+            "FTP" => ProcedureFixRole::Ftp,
+            "FROP" => ProcedureFixRole::Frop,
+            "TP" => ProcedureFixRole::Tp,
+            "MAPT" => ProcedureFixRole::Mapt,
+            "MAHF" => ProcedureFixRole::Mahf,
+            _ => ProcedureFixRole::Other(value.trim().to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for ProcedureFixRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcedureFixRole::Iaf => write!(f, "IAF"),
+            ProcedureFixRole::If => write!(f, "IF"),
+            ProcedureFixRole::IfIaf => write!(f, "IF_IAF"),
+            ProcedureFixRole::Faf => write!(f, "FAF"),
+            ProcedureFixRole::Vdp => write!(f, "VDP"),
+            ProcedureFixRole::Sdf => write!(f, "SDF"),
+            ProcedureFixRole::Fpap => write!(f, "FPAP"),
+            ProcedureFixRole::Ftp => write!(f, "FTP"),
+            ProcedureFixRole::Frop => write!(f, "FROP"),
+            ProcedureFixRole::Tp => write!(f, "TP"),
+            ProcedureFixRole::Mapt => write!(f, "MAPT"),
+            ProcedureFixRole::Mahf => write!(f, "MAHF"),
+            ProcedureFixRole::Other(value) => write!(f, "{value}"),
+        }
+    }
 }
 
 pub fn parse_designated_point_zip_file<P: AsRef<Path>>(
     path: P,
-) -> Result<HashMap<String, DesignatedPoint>, ThrustError> {
+) -> Result<HashMap<GmlId, DesignatedPoint>, ThrustError> {
+    parse_designated_point_zip_file_with_suffix(path, DEFAULT_ENTRY_SUFFIX)
+}
+
+/// Like [`parse_designated_point_zip_file`], reading the archive entries whose name ends with
+/// `entry_suffix` (e.g. `.xml`) instead of [`DEFAULT_ENTRY_SUFFIX`].
+pub fn parse_designated_point_zip_file_with_suffix<P: AsRef<Path>>(
+    path: P,
+    entry_suffix: &str,
+) -> Result<HashMap<GmlId, DesignatedPoint>, ThrustError> {
+    let path = path.as_ref();
     let file = File::open(path)?;
     let mut archive = ZipArchive::new(file)?;
+    let entries = AixmEntries::new(&archive, entry_suffix);
     let mut points = HashMap::new();
 
     for i in 0..archive.len() {
-        let file = archive.by_index(i)?;
-        if file.name().ends_with(".BASELINE") {
-            let mut reader = Reader::from_reader(BufReader::new(file));
-
-            while let Ok(_nome) = find_node(&mut reader, vec![QName(b"aixm:DesignatedPoint")], None) {
-                let point = parse_designated_point(&mut reader)?;
-                points.insert(point.identifier.clone(), point);
+        if let Some(mut reader) = baseline_reader(&mut archive, i, &entries)? {
+            while let Ok(node) = find_node(&mut reader, vec![QName(b"aixm:DesignatedPoint")], None) {
+                let mut point = parse_designated_point(&mut reader)?;
+                identifier_fallback(&mut point.identifier, &node);
+                points.insert(point.identifier.clone().into(), point);
             }
         }
     }
 
+    log_parsed("designated points", path, points.len());
     Ok(points)
 }
 
@@ -99,17 +269,15 @@ fn parse_designated_point<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result
                 point.designator = read_text(reader, name)?;
             }
             QName(b"aixm:type") => {
-                point.r#type = read_text(reader, name)?;
+                point.r#type = DesignatedPointType::from(read_text(reader, name)?.as_str());
             }
             QName(b"aixm:Point") => {
                 while let Ok(node) = find_node(reader, vec![QName(b"gml:pos")], Some(name)) {
                     let Node { name, .. } = node;
-                    let coords: Vec<f64> = read_text(reader, name)?
-                        .split_whitespace()
-                        .map(|s| s.parse().unwrap())
-                        .collect();
-                    point.latitude = coords[0];
-                    point.longitude = coords[1];
+                    if let Some((latitude, longitude)) = parse_pos(&read_text(reader, name)?) {
+                        point.latitude = latitude;
+                        point.longitude = longitude;
+                    }
                 }
             }
             _ => (),
@@ -118,3 +286,150 @@ fn parse_designated_point<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result
 
     Ok(point)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    const DESIGNATED_POINT: &str = r#"<message:AIXMBasicMessage>
+  <message:hasMember>
+    <aixm:DesignatedPoint gml:id="dp-abc">
+      <gml:identifier codeSpace="urn:uuid:">dp-abc</gml:identifier>
+      <aixm:timeSlice>
+        <aixm:DesignatedPointTimeSlice>
+          <aixm:designator>ABC</aixm:designator>
+          <aixm:type>ICAO</aixm:type>
+          <aixm:location>
+            <aixm:Point>
+              <gml:pos>45.0 1.5</gml:pos>
+            </aixm:Point>
+          </aixm:location>
+        </aixm:DesignatedPointTimeSlice>
+      </aixm:timeSlice>
+    </aixm:DesignatedPoint>
+  </message:hasMember>
+</message:AIXMBasicMessage>"#;
+
+    #[test]
+    fn test_identifier_from_gml_id_attribute() {
+        let xml = DESIGNATED_POINT.replace(r#"<gml:identifier codeSpace="urn:uuid:">dp-abc</gml:identifier>"#, "");
+        let path = std::env::temp_dir().join(format!("thrust-gml-id-{}.zip", std::process::id()));
+        let mut writer = ZipWriter::new(File::create(&path).unwrap());
+        writer
+            .start_file("DesignatedPoint.BASELINE", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(xml.as_bytes()).unwrap();
+        writer
+            .write_all(xml.replace("dp-abc", "dp-def").replace("ABC", "DEF").as_bytes())
+            .unwrap();
+        writer.finish().unwrap();
+
+        let points = parse_designated_point_zip_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        let points = points.unwrap();
+        // Both records are kept, keyed by their gml:id
+        assert_eq!(points.len(), 2);
+        assert_eq!(points["dp-abc"].designator, "ABC");
+        assert_eq!(points["dp-def"].designator, "DEF");
+    }
+
+    #[test]
+    fn test_parse_xml_entry_archive() {
+        let path = std::env::temp_dir().join(format!("thrust-xml-entry-{}.zip", std::process::id()));
+        let mut writer = ZipWriter::new(File::create(&path).unwrap());
+        writer.add_directory("export/", SimpleFileOptions::default()).unwrap();
+        writer
+            .start_file("export/README.txt", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(b"Repackaged AIRAC data").unwrap();
+        writer
+            .start_file("export/DesignatedPoint.xml", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(b"\xEF\xBB\xBF\n").unwrap();
+        writer.write_all(DESIGNATED_POINT.as_bytes()).unwrap();
+        writer
+            .start_file("export/DesignatedPoint.gml", SimpleFileOptions::default())
+            .unwrap();
+        writer
+            .write_all(
+                DESIGNATED_POINT
+                    .replace("dp-abc", "dp-def")
+                    .replace("ABC", "DEF")
+                    .as_bytes(),
+            )
+            .unwrap();
+        writer.finish().unwrap();
+
+        // No entry ends with .BASELINE: the XML entries are read, the others are skipped
+        let points = parse_designated_point_zip_file(&path);
+        let xml_points = parse_designated_point_zip_file_with_suffix(&path, ".xml");
+        std::fs::remove_file(&path).unwrap();
+        let points = points.unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points["dp-abc"].designator, "ABC");
+        // Only the entries with the given suffix are read when there are some
+        let xml_points = xml_points.unwrap();
+        assert_eq!(xml_points.len(), 1);
+        assert_eq!(xml_points["dp-abc"].designator, "ABC");
+    }
+
+    #[test]
+    fn test_vfr_reporting_point_type() {
+        let xml = DESIGNATED_POINT.replace("<aixm:type>ICAO</aixm:type>", "<aixm:type>OTHER:VFR_REP</aixm:type>");
+        let mut reader = Reader::from_str(&xml);
+        find_node(&mut reader, vec![QName(b"aixm:DesignatedPoint")], None).unwrap();
+        let point = parse_designated_point(&mut reader).unwrap();
+        assert!(point.r#type.is_vfr_reporting_point());
+        assert!(DesignatedPointType::from("vfr-rp").is_vfr_reporting_point());
+        assert!(!DesignatedPointType::Icao.is_vfr_reporting_point());
+        assert!(!DesignatedPointType::from("OTHER").is_vfr_reporting_point());
+    }
+
+    #[test]
+    fn test_parse_comma_decimal_coordinates() {
+        let xml = DESIGNATED_POINT.replace("45.0 1.5", " 48,8566  2,3522 ");
+        let mut reader = Reader::from_str(&xml);
+        find_node(&mut reader, vec![QName(b"aixm:DesignatedPoint")], None).unwrap();
+        let point = parse_designated_point(&mut reader).unwrap();
+        assert_eq!((point.latitude, point.longitude), (48.8566, 2.3522));
+        // Serialization never depends on the locale
+        let json = serde_json::to_string(&point).unwrap();
+        assert!(json.contains("\"latitude\":48.8566"), "{json}");
+
+        // Malformed coordinates are skipped instead of failing the whole file
+        let xml = DESIGNATED_POINT.replace("45.0 1.5", "48°51' 2°21'");
+        let mut reader = Reader::from_str(&xml);
+        find_node(&mut reader, vec![QName(b"aixm:DesignatedPoint")], None).unwrap();
+        let point = parse_designated_point(&mut reader).unwrap();
+        assert_eq!(point.designator, "ABC");
+        assert_eq!((point.latitude, point.longitude), (0., 0.));
+    }
+
+    #[test]
+    fn test_parse_zip64_archive() {
+        // Writing a >4 GB entry is not reasonable in a test: force zip64 headers
+        // on a small entry instead, so the archive requires zip64 support to open.
+        let path = std::env::temp_dir().join(format!("thrust-zip64-{}.zip", std::process::id()));
+        {
+            let mut writer = ZipWriter::new(File::create(&path).unwrap());
+            writer
+                .start_file(
+                    "DesignatedPoint.BASELINE",
+                    SimpleFileOptions::default().large_file(true),
+                )
+                .unwrap();
+            writer.write_all(DESIGNATED_POINT.as_bytes()).unwrap();
+            writer.finish().unwrap();
+        }
+
+        let points = parse_designated_point_zip_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        let points = points.unwrap();
+        let point = points.get("dp-abc").unwrap();
+        assert_eq!(point.designator, "ABC");
+        assert_eq!((point.latitude, point.longitude), (45.0, 1.5));
+    }
+}
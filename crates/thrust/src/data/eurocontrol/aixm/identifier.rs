@@ -0,0 +1,146 @@
+//! Distinct types for the identifiers of AIXM features and their designators.
+//!
+//! AIXM features are referenced by their `gml:identifier` (a UUID), while
+//! flight plans refer to points and routes by their published designator.
+//! Both are strings, and looking up one where the other is expected fails
+//! silently. [`GmlId`] and [`Designator`] keep them apart at the boundaries
+//! where they are most often confused: the keys of feature maps and
+//! [`PointReference`]. Both serialize as plain strings.
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use thrust::data::eurocontrol::aixm::identifier::GmlId;
+//! use thrust::data::eurocontrol::aixm::route_segment::PointReference;
+//!
+//! let id = GmlId::from("dp-tou");
+//! let mut names = HashMap::new();
+//! names.insert(id.clone(), "TOU");
+//! assert_eq!(names.get("dp-tou"), Some(&"TOU"));
+//! assert_eq!(serde_json::to_string(&id).unwrap(), r#""dp-tou""#);
+//! assert_eq!(PointReference::Navaid(id).name(), "dp-tou");
+//! ```
+//!
+//! A designator is not accepted where a gml identifier is expected:
+//!
+//! ```compile_fail
+//! use thrust::data::eurocontrol::aixm::identifier::Designator;
+//! use thrust::data::eurocontrol::aixm::route_segment::PointReference;
+//!
+//! let designator = Designator::from("TOU");
+//! let reference = PointReference::Navaid(designator);
+//! ```
+//!
+//! ```compile_fail
+//! use std::collections::HashMap;
+//! use thrust::data::eurocontrol::aixm::identifier::{Designator, GmlId};
+//!
+//! let mut names: HashMap<GmlId, &str> = HashMap::new();
+//! names.insert(Designator::from("TOU"), "TOU");
+//! ```
+//!
+//! [`PointReference`]: crate::data::eurocontrol::aixm::route_segment::PointReference
+
+use std::borrow::Borrow;
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+
+macro_rules! string_newtype {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            pub fn into_string(self) -> String {
+                self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        /// Look up maps keyed by this type with a `&str`.
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                $name(value)
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                $name(value.to_string())
+            }
+        }
+
+        impl From<&String> for $name {
+            fn from(value: &String) -> Self {
+                $name(value.clone())
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                &self.0 == other
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+}
+
+string_newtype!(
+    /// The `gml:identifier` of an AIXM feature, e.g. a UUID such as
+    /// "f1a3b6c2-...", used to reference features from one another.
+    GmlId
+);
+
+string_newtype!(
+    /// A published designator, e.g. "TOU" for a navaid, "LACOU" for a
+    /// designated point or "UN869" for a route, as filed in flight plans.
+    Designator
+);
@@ -0,0 +1,294 @@
+//! Best-effort reading of the free text instructions of SID and STAR procedures.
+//!
+//! The `instruction` of a procedure (e.g. `"Climb to 5000ft, turn right heading
+//! 120°, max 250kt"`) is sometimes the only source of its constraints. The parser
+//! recognises altitude, heading and speed directives with regular expressions and
+//! keeps everything else as raw text. It is lossy by design: qualifiers it does not
+//! know about are dropped, and the result should not be relied upon for anything
+//! more than indicative constraints.
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+use crate::data::field15::{Altitude, Speed};
+
+/// Kind of altitude or speed restriction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Restriction {
+    #[serde(rename = "at")]
+    At,
+    #[serde(rename = "at_or_above")]
+    AtOrAbove,
+    #[serde(rename = "at_or_below")]
+    AtOrBelow,
+}
+
+/// Direction of a turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Turn {
+    #[serde(rename = "left")]
+    Left,
+    #[serde(rename = "right")]
+    Right,
+}
+
+/// One directive of a procedure instruction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InstructionStep {
+    /// An altitude to climb or descend to, or to cross a fix at.
+    ///
+    /// Altitudes in feet are rounded to hundreds of feet, and in meters to tens
+    /// of meters.
+    #[serde(rename = "altitude")]
+    Altitude {
+        fix: Option<String>,
+        altitude: Altitude,
+        restriction: Restriction,
+    },
+    /// A heading to fly, or a track (course, radial, QDR or QDM) to follow.
+    #[serde(rename = "heading")]
+    Heading {
+        degrees: u16,
+        track: bool,
+        turn: Option<Turn>,
+    },
+    /// A speed limit, in knots.
+    #[serde(rename = "speed")]
+    Speed { speed: Speed, restriction: Restriction },
+    /// A part of the instruction which was not recognised.
+    #[serde(rename = "raw")]
+    Raw(String),
+}
+
+static CLAUSE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)[,;]|\.(?:\s|$)|\bthen\b").unwrap());
+
+static ALTITUDE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?ix)
+        (?:\b(?:climb|descend|maintain|expect)(?:\s+to)?\s+)?
+        (?:\b(?:at|cross|over|overhead)\s+(?P<fix>[a-z][a-z0-9]{1,4})\s+)?
+        (?:\b(?P<prefix>at\s+or\s+above|at\s+or\s+below|not\s+below|not\s+above|above|below|max(?:imum)?|min(?:imum)?|at)\s*)?
+        (?:\bFL\s*(?P<fl>\d{2,3})|\b(?P<ft>\d{3,5})\s*(?:ft|feet)|\b(?P<m>\d{3,4})\s*(?:m|meters|metres))\b
+        (?:\s+(?P<suffix>or\s+above|or\s+higher|or\s+below|or\s+lower))?",
+    )
+    .unwrap()
+});
+
+static HEADING: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?ix)
+        (?:\b(?:turn\s+)?(?P<turn>left|right)\s+)?
+        (?:\b(?:to|on|onto|intercept)\s+)?
+        \b(?P<kind>heading|hdg|track|trk|course|crs|radial|qdr|qdm)\s*(?P<degrees>\d{1,3})\b\s*(?:°|deg(?:rees)?\b)?",
+    )
+    .unwrap()
+});
+
+static SPEED: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?ix)
+        (?:\b(?P<prefix>max(?:imum)?|min(?:imum)?|not\s+above|not\s+below)\s*)?
+        (?:\bIAS\s*)?
+        \b(?P<knots>\d{2,3})\s*(?:kts?|knots|kias)\b
+        (?:\s+(?P<suffix>or\s+less|or\s+below|or\s+more|or\s+above|max(?:imum)?|min(?:imum)?))?",
+    )
+    .unwrap()
+});
+
+/// Words caught as a fix name by [`ALTITUDE`] in phrases like "at or above".
+const NOT_A_FIX: [&str; 6] = ["OR", "TO", "AND", "LEAST", "MOST", "THE"];
+
+/// Read altitude, heading and speed directives from a SID or STAR instruction.
+///
+/// The text is split into clauses at commas, semicolons, full stops and "then";
+/// each clause yields its directives in order, and the text left between them
+/// as [`InstructionStep::Raw`]. Connecting words like "climb to" or "turn" are
+/// consumed with the directive they introduce.
+///
+/// # Example
+/// ```
+/// use thrust::data::eurocontrol::aixm::instruction::{parse_instruction, InstructionStep, Restriction, Turn};
+/// use thrust::data::field15::Altitude;
+///
+/// let steps = parse_instruction("Climb to 5000ft, turn right heading 120°");
+/// assert_eq!(
+///     steps,
+///     [
+///         InstructionStep::Altitude { fix: None, altitude: Altitude::Altitude(50), restriction: Restriction::At },
+///         InstructionStep::Heading { degrees: 120, track: false, turn: Some(Turn::Right) },
+///     ]
+/// );
+/// ```
+pub fn parse_instruction(text: &str) -> Vec<InstructionStep> {
+    CLAUSE
+        .split(text)
+        .flat_map(|clause| {
+            let mut matches = [
+                (&*ALTITUDE, altitude_step as fn(&Captures) -> Option<InstructionStep>),
+                (&*HEADING, heading_step),
+                (&*SPEED, speed_step),
+            ]
+            .into_iter()
+            .flat_map(|(regex, step)| {
+                regex
+                    .captures_iter(clause)
+                    .filter_map(move |captures| Some((captures.get(0)?.range(), step(&captures)?)))
+            })
+            .collect::<Vec<_>>();
+            matches.sort_by_key(|(range, _)| (range.start, std::cmp::Reverse(range.end)));
+
+            let mut steps = Vec::new();
+            let mut end = 0;
+            for (range, step) in matches {
+                if range.start < end {
+                    continue; // overlaps a directive already read
+                }
+                push_raw(&mut steps, &clause[end..range.start]);
+                steps.push(step);
+                end = range.end;
+            }
+            push_raw(&mut steps, &clause[end..]);
+            steps
+        })
+        .collect()
+}
+
+fn push_raw(steps: &mut Vec<InstructionStep>, text: &str) {
+    let text = text.trim_matches(|c: char| !c.is_alphanumeric());
+    if !text.is_empty() {
+        steps.push(InstructionStep::Raw(text.to_string()));
+    }
+}
+
+fn restriction(prefix: Option<&str>, suffix: Option<&str>) -> Restriction {
+    let words = [prefix, suffix]
+        .into_iter()
+        .flatten()
+        .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" ").to_uppercase())
+        .collect::<Vec<_>>();
+    match words.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        [.., "AT OR ABOVE" | "NOT BELOW" | "ABOVE" | "OR ABOVE" | "OR HIGHER" | "OR MORE"] => Restriction::AtOrAbove,
+        [.., "AT OR BELOW" | "NOT ABOVE" | "BELOW" | "OR BELOW" | "OR LOWER" | "OR LESS"] => Restriction::AtOrBelow,
+        [.., word] if word.starts_with("MIN") => Restriction::AtOrAbove,
+        [.., word] if word.starts_with("MAX") => Restriction::AtOrBelow,
+        _ => Restriction::At,
+    }
+}
+
+fn altitude_step(captures: &Captures) -> Option<InstructionStep> {
+    let number = |name| captures.name(name)?.as_str().parse::<u32>().ok();
+    let altitude = if let Some(fl) = number("fl") {
+        Altitude::FlightLevel(fl as u16)
+    } else if let Some(feet) = number("ft") {
+        Altitude::Altitude(((feet + 50) / 100) as u16)
+    } else {
+        Altitude::MetricAltitude(((number("m")? + 5) / 10) as u16)
+    };
+    let fix = captures
+        .name("fix")
+        .map(|fix| fix.as_str().to_uppercase())
+        .filter(|fix| !NOT_A_FIX.contains(&fix.as_str()));
+    Some(InstructionStep::Altitude {
+        fix,
+        altitude,
+        restriction: restriction(
+            captures.name("prefix").map(|m| m.as_str()),
+            captures.name("suffix").map(|m| m.as_str()),
+        ),
+    })
+}
+
+fn heading_step(captures: &Captures) -> Option<InstructionStep> {
+    let degrees = captures["degrees"].parse::<u16>().ok().filter(|&d| d <= 360)?;
+    let track = !matches!(captures["kind"].to_uppercase().as_str(), "HEADING" | "HDG");
+    let turn = captures
+        .name("turn")
+        .map(|turn| match turn.as_str().to_uppercase().as_str() {
+            "LEFT" => Turn::Left,
+            _ => Turn::Right,
+        });
+    Some(InstructionStep::Heading { degrees, track, turn })
+}
+
+fn speed_step(captures: &Captures) -> Option<InstructionStep> {
+    let knots = captures["knots"].parse::<u16>().ok()?;
+    Some(InstructionStep::Speed {
+        speed: Speed::Knots(knots),
+        restriction: restriction(
+            captures.name("prefix").map(|m| m.as_str()),
+            captures.name("suffix").map(|m| m.as_str()),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_departure_instruction() {
+        let steps = parse_instruction(
+            "Climb to 5000ft, turn right heading 120°. Cross ABC at or above FL100; max 250 kt. Contact Paris Control",
+        );
+        assert_eq!(
+            steps,
+            [
+                InstructionStep::Altitude {
+                    fix: None,
+                    altitude: Altitude::Altitude(50),
+                    restriction: Restriction::At,
+                },
+                InstructionStep::Heading {
+                    degrees: 120,
+                    track: false,
+                    turn: Some(Turn::Right),
+                },
+                InstructionStep::Altitude {
+                    fix: Some("ABC".to_string()),
+                    altitude: Altitude::FlightLevel(100),
+                    restriction: Restriction::AtOrAbove,
+                },
+                InstructionStep::Speed {
+                    speed: Speed::Knots(250),
+                    restriction: Restriction::AtOrBelow,
+                },
+                InstructionStep::Raw("Contact Paris Control".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arrival_instruction() {
+        let steps = parse_instruction("AT OR BELOW 1500 M THEN LEFT TRACK 275 DEG, 220KT OR LESS UNTIL LENTO");
+        assert_eq!(
+            steps,
+            [
+                InstructionStep::Altitude {
+                    fix: None,
+                    altitude: Altitude::MetricAltitude(150),
+                    restriction: Restriction::AtOrBelow,
+                },
+                InstructionStep::Heading {
+                    degrees: 275,
+                    track: true,
+                    turn: Some(Turn::Left),
+                },
+                InstructionStep::Speed {
+                    speed: Speed::Knots(220),
+                    restriction: Restriction::AtOrBelow,
+                },
+                InstructionStep::Raw("UNTIL LENTO".to_string()),
+            ]
+        );
+
+        // Nothing recognised: the instruction is kept as is
+        assert_eq!(
+            parse_instruction("Only for aircraft with RNAV 1 approval"),
+            [InstructionStep::Raw(
+                "Only for aircraft with RNAV 1 approval".to_string()
+            )]
+        );
+        assert!(parse_instruction("").is_empty());
+    }
+}
@@ -6,11 +6,26 @@
 //! The parsers are provided under an open source license and can be used to read
 //! and process AIXM XML data files provided by EUROCONTROL B2B services under
 //! a specific license agreement.
+//!
+//! Parsers log under the `thrust::aixm` target: the number of records read from
+//! each file at info level, entries read without the expected suffix at debug
+//! level, and ignored malformed values at warn level. Use e.g.
+//! `RUST_LOG=thrust::aixm=warn` to only see data issues.
 
 use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Seek};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use quick_xml::{events::Event, name::QName, Reader, XmlVersion};
+use quick_xml::{
+    events::{BytesStart, Event},
+    name::QName,
+    Reader, XmlVersion,
+};
+use zip::read::{ZipArchive, ZipFile};
 
+use crate::data::eurocontrol::aixm::designated_point::ProcedureFixRole;
+use crate::data::eurocontrol::aixm::route_segment::PointReference;
 use crate::error::ThrustError;
 
 pub mod airport_heliport;
@@ -19,15 +34,199 @@ pub mod arrival_leg;
 pub mod dataset;
 pub mod departure_leg;
 pub mod designated_point;
+pub mod identifier;
+pub mod instruction;
 pub mod navaid;
 pub mod route;
 pub mod route_segment;
 pub mod standard_instrument_arrival;
 pub mod standard_instrument_departure;
 
+/// Default uncompressed size (in bytes) above which reading an archive entry emits a warning.
+pub const DEFAULT_LARGE_ENTRY_THRESHOLD: u64 = 1 << 30;
+
+static LARGE_ENTRY_THRESHOLD: AtomicU64 = AtomicU64::new(DEFAULT_LARGE_ENTRY_THRESHOLD);
+
+/// Set the uncompressed size (in bytes) above which reading an AIXM archive entry emits a warning.
+///
+/// Entries are always streamed, so large (zip64) entries can be parsed; the
+/// warning is a hint that parsing will take a while and produce large maps.
+pub fn set_large_entry_threshold(bytes: u64) {
+    LARGE_ENTRY_THRESHOLD.store(bytes, Ordering::Relaxed);
+}
+
+/// The uncompressed size (in bytes) above which reading an AIXM archive entry emits a warning.
+pub fn large_entry_threshold() -> u64 {
+    LARGE_ENTRY_THRESHOLD.load(Ordering::Relaxed)
+}
+
+/// Default suffix of the names of the archive entries read by the AIXM parsers.
+///
+/// The `parse_*_zip_file_with_suffix` functions take another suffix, e.g. `.xml`
+/// for archives repackaged by other tools. In an archive without any entry with
+/// this suffix, entries whose content looks like XML are read instead, so that a
+/// different suffix does not silently produce zero records.
+pub const DEFAULT_ENTRY_SUFFIX: &str = ".BASELINE";
+
+/// The entries of an archive read by the AIXM parsers, see [`baseline_reader`].
+#[derive(Clone, Copy)]
+struct AixmEntries<'a> {
+    suffix: &'a str,
+    /// No entry has the suffix, so entries are read if they look like XML
+    sniff: bool,
+}
+
+impl<'a> AixmEntries<'a> {
+    fn new<R: Read + Seek>(archive: &ZipArchive<R>, suffix: &'a str) -> Self {
+        let sniff = !archive.file_names().any(|name| name.ends_with(suffix));
+        AixmEntries { suffix, sniff }
+    }
+}
+
+/// Whether the beginning of a file looks like XML: a `<` after an optional
+/// byte order mark and whitespace.
+fn looks_like_xml(bytes: &[u8]) -> bool {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    bytes.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'<')
+}
+
+/// Report the number of records parsed from an AIXM file, as a progress event.
+fn log_parsed(kind: &str, path: &Path, count: usize) {
+    tracing::info!(target: "thrust::aixm", "Parsed {} {} from {}", count, kind, path.display());
+}
+
+/// Open the `index`-th entry of an archive as a streaming XML reader if it is
+/// an AIXM file: its name ends with the suffix of `entries` or, if no entry of
+/// the archive does, its content looks like XML.
+fn baseline_reader<'a, R: Read + Seek>(
+    archive: &'a mut ZipArchive<R>,
+    index: usize,
+    entries: &AixmEntries,
+) -> Result<Option<Reader<BufReader<ZipFile<'a, R>>>>, ThrustError> {
+    let AixmEntries { suffix, sniff } = *entries;
+    let file = archive.by_index(index)?;
+    if file.is_dir() || !(sniff || file.name().ends_with(suffix)) {
+        return Ok(None);
+    }
+    let name = file.name().to_string();
+    let (size, compressed_size) = (file.size(), file.compressed_size());
+    let mut buffer = BufReader::new(file);
+    if sniff {
+        if !looks_like_xml(buffer.fill_buf()?) {
+            return Ok(None);
+        }
+        tracing::debug!(
+            target: "thrust::aixm",
+            "Reading archive entry '{}' without the '{}' suffix as XML",
+            name,
+            suffix
+        );
+    }
+    if size > large_entry_threshold() {
+        tracing::warn!(
+            target: "thrust::aixm",
+            "Large archive entry '{}': {} bytes uncompressed ({} compressed)",
+            name,
+            size,
+            compressed_size
+        );
+    }
+    Ok(Some(Reader::from_reader(buffer)))
+}
+
+/// Parse a decimal number, tolerating the formats produced by localized tools.
+///
+/// Surrounding whitespace is trimmed and comma decimals ("48,8566") are accepted.
+/// When both separators are present, the last one is the decimal separator and
+/// the other one groups thousands ("1,234.5" or "1.234,5"). A single comma is
+/// always read as a decimal separator. Non-finite values are rejected.
+pub fn parse_number(text: &str) -> Result<f64, ThrustError> {
+    let text = text.trim();
+    let malformed = || ThrustError::ParseError(format!("Malformed number '{}'", text));
+    let normalized = match (text.rfind(','), text.rfind('.')) {
+        (None, _) => text.to_string(),
+        (Some(comma), Some(dot)) if comma > dot => {
+            ungroup(&text[..comma], '.').ok_or_else(malformed)? + "." + &text[comma + 1..]
+        }
+        (Some(_), Some(dot)) => ungroup(&text[..dot], ',').ok_or_else(malformed)? + &text[dot..],
+        (Some(comma), None) if text.matches(',').count() == 1 => format!("{}.{}", &text[..comma], &text[comma + 1..]),
+        (Some(_), None) => ungroup(text, ',').ok_or_else(malformed)?,
+    };
+    match normalized.parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(value),
+        _ => Err(malformed()),
+    }
+}
+
+/// Remove thousands separators from the integer part of a number, checking the grouping.
+fn ungroup(integer: &str, separator: char) -> Option<String> {
+    let mut groups = integer.split(separator);
+    let first = groups.next()?;
+    let mut result = first.to_string();
+    for group in groups {
+        if group.len() != 3 || !group.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        result.push_str(group);
+    }
+    Some(result)
+}
+
+/// Parse a `gml:pos` element content into a (latitude, longitude) pair.
+///
+/// Malformed positions are reported with a warning and skipped.
+fn parse_pos(text: &str) -> Option<(f64, f64)> {
+    let mut numbers = text.split_whitespace().map(parse_number);
+    match (numbers.next(), numbers.next()) {
+        (Some(Ok(latitude)), Some(Ok(longitude))) => Some((latitude, longitude)),
+        _ => {
+            tracing::warn!(target: "thrust::aixm", "Ignoring malformed position '{}'", text.trim());
+            None
+        }
+    }
+}
+
+/// Parse a `gml:pos` element content in the axis order of its `srsName`.
+///
+/// Positions are latitude first (EPSG:4326, the AIXM default) unless the
+/// reference system is CRS84, which is longitude first.
+fn parse_pos_srs(text: &str, srs_name: Option<&str>) -> Option<(f64, f64)> {
+    let (first, second) = parse_pos(text)?;
+    match srs_name {
+        Some(srs_name) if is_lon_first(srs_name) => Some((second, first)),
+        _ => Some((first, second)),
+    }
+}
+
+/// Whether a `srsName` orders coordinates longitude first, e.g.
+/// `urn:ogc:def:crs:OGC:1.3:CRS84` or `CRS:84`.
+fn is_lon_first(srs_name: &str) -> bool {
+    let srs_name = srs_name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_uppercase();
+    srs_name.ends_with("CRS84")
+}
+
 struct Node<'a> {
     name: QName<'a>,
     attributes: HashMap<String, String>,
+    /// Self-closing element, without any content nor end tag
+    empty: bool,
+}
+
+fn read_attributes(e: &BytesStart) -> Result<HashMap<String, String>, ThrustError> {
+    let mut attributes = HashMap::new();
+    for attr in e.attributes().with_checks(false) {
+        let attr = attr?;
+        let key = std::str::from_utf8(attr.key.0)?;
+        attributes.insert(
+            key.to_string(),
+            attr.normalized_value(XmlVersion::Implicit1_0)?.to_string(),
+        );
+    }
+    Ok(attributes)
 }
 
 fn find_node<'a, R: std::io::BufRead>(
@@ -39,39 +238,23 @@ fn find_node<'a, R: std::io::BufRead>(
     loop {
         match reader.read_event_into(&mut buf) {
             Ok(Event::Start(ref e)) => {
-                for elt in lookup.iter() {
-                    if e.name() == *elt {
-                        let mut attributes = HashMap::new();
-
-                        for attr in e.attributes().with_checks(false) {
-                            let attr = attr?;
-                            let key = std::str::from_utf8(attr.key.0)?;
-                            attributes.insert(
-                                key.to_string(),
-                                attr.normalized_value(XmlVersion::Implicit1_0)?.to_string(),
-                            );
-                        }
-
-                        return Ok(Node { name: *elt, attributes });
-                    }
+                if let Some(elt) = lookup.iter().find(|elt| e.name() == **elt) {
+                    let attributes = read_attributes(e)?;
+                    return Ok(Node {
+                        name: *elt,
+                        attributes,
+                        empty: false,
+                    });
                 }
             }
             Ok(Event::Empty(ref e)) => {
-                for elt in lookup.iter() {
-                    if e.name() == *elt {
-                        let mut attributes = HashMap::new();
-
-                        for attr in e.attributes().with_checks(false) {
-                            let attr = attr?;
-                            let key = std::str::from_utf8(attr.key.0)?;
-                            attributes.insert(
-                                key.to_string(),
-                                attr.normalized_value(XmlVersion::Implicit1_0)?.to_string(),
-                            );
-                        }
-
-                        return Ok(Node { name: *elt, attributes });
-                    }
+                if let Some(elt) = lookup.iter().find(|elt| e.name() == **elt) {
+                    let attributes = read_attributes(e)?;
+                    return Ok(Node {
+                        name: *elt,
+                        attributes,
+                        empty: true,
+                    });
                 }
             }
             Ok(Event::End(ref e)) => {
@@ -90,17 +273,43 @@ fn find_node<'a, R: std::io::BufRead>(
     Err(ThrustError::ParseError("Node not found".to_string()))
 }
 
+/// Fall back to the `gml:id` attribute of a feature element when the feature has
+/// no `gml:identifier` child element, as in some AIXM variants.
+fn identifier_fallback(identifier: &mut String, feature: &Node) {
+    if identifier.is_empty() {
+        if let Some(id) = feature.attributes.get("gml:id") {
+            *identifier = id.clone();
+        }
+    }
+}
+
+/// Read the text content of the current element, up to its end tag.
+///
+/// Entity and character references are resolved, and CDATA sections are kept
+/// verbatim, so that the returned string is unescaped exactly once.
 fn read_text<R: std::io::BufRead>(reader: &mut Reader<R>, end: QName) -> Result<String, ThrustError> {
+    let invalid_encoding = |_| ThrustError::ParseError("Invalid XML encoding".to_string());
     let mut buf = Vec::new();
     let mut text = String::new();
     loop {
         match reader.read_event_into(&mut buf) {
             Ok(Event::Text(e)) => {
-                let decoded = e
-                    .decode()
-                    .map_err(|_| ThrustError::ParseError("Invalid XML encoding".to_string()))?;
-                text.push_str(&decoded);
+                text.push_str(&e.decode().map_err(invalid_encoding)?);
+            }
+            Ok(Event::CData(e)) => {
+                text.push_str(&e.decode().map_err(invalid_encoding)?);
             }
+            Ok(Event::GeneralRef(e)) => match e.resolve_char_ref()? {
+                Some(c) => text.push(c),
+                None => {
+                    let entity = e.decode().map_err(invalid_encoding)?;
+                    match quick_xml::escape::resolve_predefined_entity(&entity) {
+                        Some(resolved) => text.push_str(resolved),
+                        // Undeclared entities are kept as they are written
+                        None => text.push_str(&format!("&{entity};")),
+                    }
+                }
+            },
             Ok(Event::End(e)) if e.name() == end => break,
             Ok(Event::Eof) => break,
             Err(e) => return Err(ThrustError::from(e)),
@@ -111,14 +320,22 @@ fn read_text<R: std::io::BufRead>(reader: &mut Reader<R>, end: QName) -> Result<
     Ok(text)
 }
 
+/// Read the value of the first `attr_name` attribute found on an element ahead of the reader.
+///
+/// Start and empty elements are inspected in document order, from the current
+/// position of the reader. The search stops with `Ok(None)` at the first end tag
+/// named `end`, which is consumed, or at the end of the document when `end` is
+/// `None`. Pass the name of the enclosing element as `end` so that a missing
+/// attribute never consumes the rest of the document.
 pub fn read_attribute<R: std::io::BufRead>(
     reader: &mut Reader<R>,
     attr_name: QName,
+    end: Option<QName>,
 ) -> Result<Option<String>, ThrustError> {
     let mut buf = Vec::new();
     loop {
         match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) => {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
                 for attr in e.attributes().with_checks(false) {
                     let attr = attr?;
                     if attr.key == attr_name {
@@ -126,6 +343,7 @@ pub fn read_attribute<R: std::io::BufRead>(
                     }
                 }
             }
+            Ok(Event::End(ref e)) if Some(e.name()) == end => break,
             Ok(Event::Eof) => break,
             Err(e) => return Err(ThrustError::from(e)),
             _ => (),
@@ -134,3 +352,234 @@ pub fn read_attribute<R: std::io::BufRead>(
     }
     Ok(None)
 }
+
+/// The target of an `adrext:connectingPoint` in SID/STAR extensions.
+#[derive(Debug, Clone, PartialEq)]
+enum ConnectingPoint {
+    /// A single fix or navaid
+    Point(PointReference),
+    /// A reference to a route (identifier of the `aixm:Route`)
+    Route(String),
+}
+
+fn extract_href(attributes: &HashMap<String, String>) -> Option<String> {
+    attributes
+        .get("xlink:href")
+        .map(|s| s.strip_prefix("urn:uuid:").unwrap_or(s).to_string())
+}
+
+/// Parse an `adrext:connectingPoint` element, which references either a point or a route.
+///
+/// The start tag of the element (`node`) has just been read. The whole element is
+/// consumed, tracking the nesting depth, so that malformed or empty connecting
+/// points never read past their own end tag; the first reference found wins.
+fn parse_connecting_point<R: std::io::BufRead>(
+    reader: &mut Reader<R>,
+    node: &Node,
+) -> Result<Option<ConnectingPoint>, ThrustError> {
+    let mut connecting_point = None;
+    if !node.empty {
+        let mut buf = Vec::new();
+        let mut depth = 0usize;
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) => {
+                    depth += 1;
+                    if connecting_point.is_none() {
+                        connecting_point = connecting_reference(e)?;
+                    }
+                }
+                Ok(Event::Empty(ref e)) if connecting_point.is_none() => {
+                    connecting_point = connecting_reference(e)?;
+                }
+                // The end tag of the connecting point itself
+                Ok(Event::End(_)) if depth == 0 => break,
+                Ok(Event::End(_)) => depth -= 1,
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(ThrustError::from(e)),
+                _ => (),
+            }
+            buf.clear();
+        }
+    }
+
+    if connecting_point.is_none() {
+        tracing::warn!(target: "thrust::aixm", "Ignoring connecting point without any point or route reference");
+    }
+    Ok(connecting_point)
+}
+
+/// The point or route referenced by an element found in a connecting point, if any.
+fn connecting_reference(e: &BytesStart) -> Result<Option<ConnectingPoint>, ThrustError> {
+    let reference = |id| match e.name() {
+        QName(b"adrext:route") | QName(b"aixm:route") => Some(ConnectingPoint::Route(id)),
+        QName(b"aixm:pointChoice_fixDesignatedPoint") => {
+            Some(ConnectingPoint::Point(PointReference::DesignatedPoint(id.into())))
+        }
+        QName(b"aixm:pointChoice_navaidSystem") => Some(ConnectingPoint::Point(PointReference::Navaid(id.into()))),
+        QName(b"aixm:pointChoice_airportReferencePoint") => {
+            Some(ConnectingPoint::Point(PointReference::AirportHeliport(id.into())))
+        }
+        _ => None,
+    };
+    Ok(extract_href(&read_attributes(e)?).and_then(reference))
+}
+
+/// Parse the `aixm:TerminalSegmentPoint` of a procedure leg element (e.g.
+/// `aixm:startPoint`, whose start tag `end` has just been read), with its
+/// procedural role if any.
+///
+/// The whole point is read, since the role comes after the point choice in AIXM 5.1.
+fn parse_terminal_segment_point<R: BufRead>(
+    reader: &mut Reader<R>,
+    end: QName,
+) -> Result<(PointReference, Option<ProcedureFixRole>), ThrustError> {
+    let mut point = PointReference::None;
+    let mut role = None;
+    while let Ok(node) = find_node(reader, vec![QName(b"aixm:TerminalSegmentPoint")], Some(end)) {
+        while let Ok(Node {
+            name,
+            attributes,
+            empty,
+        }) = find_node(
+            reader,
+            vec![
+                QName(b"aixm:role"),
+                QName(b"aixm:pointChoice_fixDesignatedPoint"),
+                QName(b"aixm:pointChoice_navaidSystem"),
+                QName(b"aixm:pointChoice_airportReferencePoint"),
+            ],
+            Some(node.name),
+        ) {
+            if name == QName(b"aixm:role") {
+                if !empty {
+                    role = Some(ProcedureFixRole::from(read_text(reader, name)?.as_str()));
+                }
+                continue;
+            }
+            if let Some(id) = extract_href(&attributes) {
+                point = match name {
+                    QName(b"aixm:pointChoice_fixDesignatedPoint") => PointReference::DesignatedPoint(id.into()),
+                    QName(b"aixm:pointChoice_navaidSystem") => PointReference::Navaid(id.into()),
+                    QName(b"aixm:pointChoice_airportReferencePoint") => PointReference::AirportHeliport(id.into()),
+                    _ => PointReference::None,
+                };
+            }
+        }
+    }
+    Ok((point, role))
+}
+
+/// Split a SID or STAR designator into its basic indicator, validity indicator
+/// and route indicator (ICAO Annex 11, Appendix 3), e.g. `GODOS1N` into `GODOS`,
+/// `1` and `N`.
+///
+/// The designator is trimmed and uppercased. A trailing letter is only a route
+/// indicator after a validity indicator of one or two digits; designators without
+/// such a suffix are returned whole, e.g. `GODOS` or `LFPG`.
+pub fn parse_procedure_designator(s: &str) -> (String, Option<u8>, Option<char>) {
+    let designator = s.trim().to_ascii_uppercase();
+    let (rest, route) = match designator.chars().last() {
+        Some(c)
+            if c.is_ascii_alphabetic()
+                && designator[..designator.len() - 1].ends_with(|c: char| c.is_ascii_digit()) =>
+        {
+            (&designator[..designator.len() - 1], Some(c))
+        }
+        _ => (designator.as_str(), None),
+    };
+    let digits = rest.len() - rest.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let (basic, validity) = rest.split_at(rest.len() - digits);
+    match validity.parse::<u8>() {
+        Ok(validity) if (1..=2).contains(&digits) && !basic.is_empty() => (basic.to_string(), Some(validity), route),
+        _ => (designator.clone(), None, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_procedure_designator() {
+        assert_eq!(
+            parse_procedure_designator("GODOS1N"),
+            ("GODOS".to_string(), Some(1), Some('N'))
+        );
+        assert_eq!(
+            parse_procedure_designator(" denut2a "),
+            ("DENUT".to_string(), Some(2), Some('A'))
+        );
+        assert_eq!(
+            parse_procedure_designator("TOU12B"),
+            ("TOU".to_string(), Some(12), Some('B'))
+        );
+        assert_eq!(parse_procedure_designator("RCKT2"), ("RCKT".to_string(), Some(2), None));
+        // No suffix
+        assert_eq!(parse_procedure_designator("GODOS"), ("GODOS".to_string(), None, None));
+        assert_eq!(
+            parse_procedure_designator("ABC123D"),
+            ("ABC123D".to_string(), None, None)
+        );
+        assert_eq!(parse_procedure_designator("1A"), ("1A".to_string(), None, None));
+        assert_eq!(parse_procedure_designator(""), (String::new(), None, None));
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number(" 48.8566 ").unwrap(), 48.8566);
+        assert_eq!(parse_number("48,8566").unwrap(), 48.8566);
+        assert_eq!(parse_number("-2,3522").unwrap(), -2.3522);
+        assert_eq!(parse_number("1,234.5").unwrap(), 1234.5);
+        assert_eq!(parse_number("1.234,5").unwrap(), 1234.5);
+        assert_eq!(parse_number("1,234,567").unwrap(), 1234567.);
+        assert_eq!(parse_number("12").unwrap(), 12.);
+        for malformed in ["", "abc", "1.2.3", "1,23,4", "12,34.5.6", "NaN", "inf"] {
+            assert!(parse_number(malformed).is_err(), "{malformed}");
+        }
+        assert_eq!(parse_pos("48,8566 2,3522"), Some((48.8566, 2.3522)));
+        assert_eq!(parse_pos("48.8566"), None);
+        assert_eq!(parse_pos_srs("2.35 48.85", Some("CRS:84")), Some((48.85, 2.35)));
+        assert_eq!(
+            parse_pos_srs("2.35 48.85", Some("urn:ogc:def:crs:OGC:1.3:CRS84")),
+            Some((48.85, 2.35))
+        );
+        assert_eq!(
+            parse_pos_srs("48.85 2.35", Some("urn:ogc:def:crs:EPSG::4326")),
+            Some((48.85, 2.35))
+        );
+        assert_eq!(parse_pos_srs("48.85 2.35", None), Some((48.85, 2.35)));
+    }
+
+    const FEATURE: &str = r#"<aixm:Feature>
+        <aixm:timeSlice><aixm:point xlink:href="urn:uuid:abc"/></aixm:timeSlice>
+        <aixm:other gml:id="x"/>
+    </aixm:Feature>
+    <aixm:Next xlink:href="urn:uuid:next"/>"#;
+
+    #[test]
+    fn test_read_attribute_present() {
+        let mut reader = Reader::from_str(FEATURE);
+        let href = read_attribute(&mut reader, QName(b"xlink:href"), Some(QName(b"aixm:Feature"))).unwrap();
+        assert_eq!(href.as_deref(), Some("urn:uuid:abc"));
+    }
+
+    #[test]
+    fn test_read_attribute_absent_within_bounds() {
+        let mut reader = Reader::from_str(FEATURE);
+        let end = Some(QName(b"aixm:timeSlice"));
+        assert_eq!(read_attribute(&mut reader, QName(b"gml:id"), end).unwrap(), None);
+        // The reader stops right after the bounding element
+        let id = read_attribute(&mut reader, QName(b"gml:id"), Some(QName(b"aixm:Feature"))).unwrap();
+        assert_eq!(id.as_deref(), Some("x"));
+        let next = read_attribute(&mut reader, QName(b"xlink:href"), None).unwrap();
+        assert_eq!(next.as_deref(), Some("urn:uuid:next"));
+    }
+
+    #[test]
+    fn test_read_attribute_absent_until_eof() {
+        let mut reader = Reader::from_str(FEATURE);
+        assert_eq!(read_attribute(&mut reader, QName(b"gml:missing"), None).unwrap(), None);
+        assert!(matches!(reader.read_event(), Ok(Event::Eof)));
+    }
+}
@@ -2,14 +2,18 @@ use crate::error::ThrustError;
 use quick_xml::name::QName;
 use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
-use std::io::BufReader;
 use std::path::Path;
 use std::{collections::HashMap, fs::File};
 use zip::read::ZipArchive;
 
+use crate::data::eurocontrol::aixm::identifier::GmlId;
 use crate::data::eurocontrol::aixm::Node;
+use crate::data::eurocontrol::geometry::magnetic_to_true;
 
-use super::{find_node, read_text};
+use super::{
+    baseline_reader, extract_href, find_node, identifier_fallback, log_parsed, parse_number, parse_pos, read_text,
+    AixmEntries, DEFAULT_ENTRY_SUFFIX,
+};
 
 /// A radio navigation aid (VOR, NDB, DME, etc.) as defined in AIXM.
 ///
@@ -21,6 +25,9 @@ use super::{find_node, read_text};
 /// - `latitude`/`longitude`: Location in WGS84 decimal degrees
 /// - `name`: Published identifier/designator (e.g., "SEA" for Seattle-Tacoma VOR)
 /// - `r#type`: Navaid classification (e.g., "VOR", "NDB", "DME", "TACAN")
+/// - `frequency`: Radio frequency in MHz, when published with the navaid
+/// - `call_sign`: Audio identification of the navaid equipment, which may differ from `name`
+/// - `magnetic_variation`: Magnetic variation at the navaid equipment in degrees, positive East
 ///
 /// # Example
 /// ```ignore
@@ -34,6 +41,8 @@ use super::{find_node, read_text};
 /// };
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct Navaid {
     #[serde(skip)]
     pub identifier: String,
@@ -49,30 +58,115 @@ pub struct Navaid {
     #[serde(skip)]
     /// Textual description of the navaid
     pub description: Option<String>,
+    #[serde(skip)]
+    /// Radio frequency in MHz
+    pub frequency: Option<f64>,
+    #[serde(skip)]
+    /// Audio identification (morse ident) transmitted by the navaid equipment
+    pub call_sign: Option<String>,
+    #[serde(skip)]
+    /// Magnetic variation in degrees, positive East
+    pub magnetic_variation: Option<f64>,
+}
+
+impl Navaid {
+    /// Convert a magnetic course into a true course, in degrees within [0, 360).
+    ///
+    /// Returns `None` if the magnetic variation of the navaid is not published.
+    pub fn true_course(&self, magnetic_course: f64) -> Option<f64> {
+        self.magnetic_variation
+            .map(|variation| magnetic_to_true(magnetic_course, variation))
+    }
+}
+
+pub fn parse_navaid_zip_file<P: AsRef<Path>>(path: P) -> Result<HashMap<GmlId, Navaid>, ThrustError> {
+    parse_navaid_zip_file_with_suffix(path, DEFAULT_ENTRY_SUFFIX)
 }
 
-pub fn parse_navaid_zip_file<P: AsRef<Path>>(path: P) -> Result<HashMap<String, Navaid>, ThrustError> {
+/// Like [`parse_navaid_zip_file`], reading the archive entries whose name ends with
+/// `entry_suffix` (e.g. `.xml`) instead of [`DEFAULT_ENTRY_SUFFIX`].
+pub fn parse_navaid_zip_file_with_suffix<P: AsRef<Path>>(
+    path: P,
+    entry_suffix: &str,
+) -> Result<HashMap<GmlId, Navaid>, ThrustError> {
+    let path = path.as_ref();
     let file = File::open(path)?;
     let mut archive = ZipArchive::new(file)?;
+    let entries = AixmEntries::new(&archive, entry_suffix);
     let mut navaids = HashMap::new();
 
     for i in 0..archive.len() {
-        let file = archive.by_index(i)?;
-        if file.name().ends_with(".BASELINE") {
-            let mut reader = Reader::from_reader(BufReader::new(file));
-
-            while let Ok(_node) = find_node(&mut reader, vec![QName(b"aixm:Navaid")], None) {
-                let navaid = parse_navaid(&mut reader)?;
-                navaids.insert(navaid.identifier.clone(), navaid);
+        if let Some(mut reader) = baseline_reader(&mut archive, i, &entries)? {
+            for navaid in parse_navaids(&mut reader)? {
+                navaids.insert(navaid.identifier.clone().into(), navaid);
             }
         }
     }
 
+    log_parsed("navaids", path, navaids.len());
     Ok(navaids)
 }
 
-fn parse_navaid<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<Navaid, ThrustError> {
+/// Equipment of a navaid (VOR, DME, etc.), either inline in its `aixm:NavaidComponent`
+/// or as a separate feature referenced by `aixm:theNavaidEquipment`.
+const EQUIPMENT: [QName<'static>; 5] = [
+    QName(b"aixm:NavaidEquipment"),
+    QName(b"aixm:VOR"),
+    QName(b"aixm:DME"),
+    QName(b"aixm:NDB"),
+    QName(b"aixm:TACAN"),
+];
+
+/// Parse all navaids of a baseline file.
+///
+/// Navaids without a position of their own are located at their equipment:
+/// the first inline equipment with a position, else the first referenced
+/// equipment feature of the same file with one. The magnetic variation is
+/// likewise read from the first equipment publishing one.
+pub(super) fn parse_navaids<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<Vec<Navaid>, ThrustError> {
+    let mut navaids = Vec::new();
+    let mut equipment = HashMap::new();
+    let features = [&[QName(b"aixm:Navaid")][..], &EQUIPMENT].concat();
+    while let Ok(node) = find_node(reader, features.clone(), None) {
+        if node.name == QName(b"aixm:Navaid") {
+            let (mut navaid, located, references) = parse_navaid(reader)?;
+            identifier_fallback(&mut navaid.identifier, &node);
+            navaids.push((navaid, located, references));
+        } else {
+            let mut feature = parse_equipment(reader, node.name)?;
+            identifier_fallback(&mut feature.identifier, &node);
+            equipment.insert(feature.identifier.clone(), feature);
+        }
+    }
+
+    Ok(navaids
+        .into_iter()
+        .map(|(mut navaid, located, references)| {
+            let referenced = || references.iter().filter_map(|id| equipment.get(id));
+            if !located {
+                match referenced().find_map(|feature| feature.position) {
+                    Some((latitude, longitude)) => {
+                        navaid.latitude = latitude;
+                        navaid.longitude = longitude;
+                    }
+                    None => tracing::warn!(target: "thrust::aixm", "No position for navaid {}", navaid.identifier),
+                }
+            }
+            if navaid.magnetic_variation.is_none() {
+                navaid.magnetic_variation = referenced().find_map(|feature| feature.magnetic_variation);
+            }
+            navaid
+        })
+        .collect())
+}
+
+/// Parse a navaid, with whether it was located (by its own position or that of
+/// its inline equipment) and the identifiers of the equipment features it references.
+fn parse_navaid<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<(Navaid, bool, Vec<String>), ThrustError> {
     let mut navaid = Navaid::default();
+    let mut position = None;
+    let mut equipment_position = None;
+    let mut references = Vec::new();
 
     while let Ok(node) = find_node(
         reader,
@@ -81,11 +175,18 @@ fn parse_navaid<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<Navaid, T
             QName(b"aixm:designator"),
             QName(b"aixm:type"),
             QName(b"aixm:name"),
+            QName(b"aixm:frequency"),
             QName(b"aixm:ElevatedPoint"),
+            QName(b"aixm:theNavaidEquipment"),
+            EQUIPMENT[0],
+            EQUIPMENT[1],
+            EQUIPMENT[2],
+            EQUIPMENT[3],
+            EQUIPMENT[4],
         ],
         Some(QName(b"aixm:Navaid")),
     ) {
-        let Node { name, .. } = node;
+        let Node { name, attributes, .. } = node;
         match name {
             QName(b"gml:identifier") => {
                 navaid.identifier = read_text(reader, name)?;
@@ -99,20 +200,215 @@ fn parse_navaid<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<Navaid, T
             QName(b"aixm:name") => {
                 navaid.description = Some(read_text(reader, name)?);
             }
+            QName(b"aixm:frequency") => {
+                let text = read_text(reader, name)?;
+                parse_frequency(&mut navaid, &text, attributes.get("uom"));
+            }
+            QName(b"aixm:theNavaidEquipment") => references.extend(extract_href(&attributes)),
+            _ if EQUIPMENT.contains(&name) => {
+                let equipment = parse_equipment(reader, name)?;
+                // The designator of the equipment is its call sign, not the name of the navaid
+                if equipment.call_sign.is_some() {
+                    navaid.call_sign = equipment.call_sign;
+                }
+                if let Some((value, uom)) = equipment.frequency {
+                    parse_frequency(&mut navaid, &value, uom.as_ref());
+                }
+                equipment_position = equipment_position.or(equipment.position);
+                navaid.magnetic_variation = navaid.magnetic_variation.or(equipment.magnetic_variation);
+            }
             QName(b"aixm:ElevatedPoint") => {
                 while let Ok(node) = find_node(reader, vec![QName(b"gml:pos")], Some(name)) {
                     let Node { name, .. } = node;
-                    let coords: Vec<f64> = read_text(reader, name)?
-                        .split_whitespace()
-                        .map(|s| s.parse().unwrap())
-                        .collect();
-                    navaid.latitude = coords[0];
-                    navaid.longitude = coords[1];
+                    if let Some(pos) = parse_pos(&read_text(reader, name)?) {
+                        position = Some(pos);
+                    }
                 }
             }
             _ => (),
         }
     }
 
-    Ok(navaid)
+    let position = position.or(equipment_position);
+    if let Some((latitude, longitude)) = position {
+        navaid.latitude = latitude;
+        navaid.longitude = longitude;
+    }
+    Ok((navaid, position.is_some(), references))
+}
+
+/// What is read from a navaid equipment, inline or as a separate feature.
+#[derive(Debug, Default)]
+struct Equipment {
+    identifier: String,
+    call_sign: Option<String>,
+    /// Text and unit of measurement of the frequency
+    frequency: Option<(String, Option<String>)>,
+    position: Option<(f64, f64)>,
+    magnetic_variation: Option<f64>,
+}
+
+fn parse_equipment<R: std::io::BufRead>(reader: &mut Reader<R>, end: QName) -> Result<Equipment, ThrustError> {
+    let mut equipment = Equipment::default();
+    while let Ok(node) = find_node(
+        reader,
+        vec![
+            QName(b"gml:identifier"),
+            QName(b"aixm:ident"),
+            QName(b"aixm:designator"),
+            QName(b"aixm:frequency"),
+            QName(b"aixm:magneticVariation"),
+            QName(b"gml:pos"),
+        ],
+        Some(end),
+    ) {
+        let Node {
+            name, mut attributes, ..
+        } = node;
+        let text = read_text(reader, name)?;
+        match name {
+            QName(b"gml:identifier") => equipment.identifier = text,
+            QName(b"aixm:frequency") => equipment.frequency = Some((text, attributes.remove("uom"))),
+            QName(b"gml:pos") => equipment.position = equipment.position.or(parse_pos(&text)),
+            QName(b"aixm:magneticVariation") => match parse_number(&text) {
+                Ok(variation) => equipment.magnetic_variation = Some(variation),
+                Err(e) => tracing::warn!(
+                    target: "thrust::aixm",
+                    "Ignoring magnetic variation of {}: {}",
+                    equipment.identifier,
+                    e
+                ),
+            },
+            _ if !text.trim().is_empty() => equipment.call_sign = Some(text.trim().to_string()),
+            _ => (),
+        }
+    }
+    Ok(equipment)
+}
+
+fn parse_frequency(navaid: &mut Navaid, text: &str, uom: Option<&String>) {
+    match parse_number(text) {
+        // NDB frequencies are published in kHz
+        Ok(value) => match uom.map(|uom| uom.to_ascii_uppercase()).as_deref() {
+            Some("KHZ") => navaid.frequency = Some(value / 1000.),
+            _ => navaid.frequency = Some(value),
+        },
+        Err(e) => tracing::warn!(target: "thrust::aixm", "Ignoring frequency of {}: {}", navaid.identifier, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VOR: &str = r#"<message:AIXMBasicMessage>
+  <message:hasMember>
+    <aixm:Navaid gml:id="nav-tou">
+      <gml:identifier codeSpace="urn:uuid:">nav-tou</gml:identifier>
+      <aixm:timeSlice>
+        <aixm:NavaidTimeSlice>
+          <aixm:designator>TOU</aixm:designator>
+          <aixm:name>TOULOUSE BLAGNAC</aixm:name>
+          <aixm:type>VOR_DME</aixm:type>
+          <aixm:navaidEquipment>
+            <aixm:NavaidComponent>
+              <aixm:NavaidEquipment>
+                <aixm:ident>TBN</aixm:ident>
+                <aixm:frequency uom="MHZ">117.7</aixm:frequency>
+              </aixm:NavaidEquipment>
+            </aixm:NavaidComponent>
+          </aixm:navaidEquipment>
+          <aixm:location>
+            <aixm:ElevatedPoint>
+              <gml:pos>43.6 1.4</gml:pos>
+            </aixm:ElevatedPoint>
+          </aixm:location>
+        </aixm:NavaidTimeSlice>
+      </aixm:timeSlice>
+    </aixm:Navaid>
+  </message:hasMember>
+</message:AIXMBasicMessage>"#;
+
+    #[test]
+    fn test_parse_call_sign() {
+        let mut reader = Reader::from_str(VOR);
+        find_node(&mut reader, vec![QName(b"aixm:Navaid")], None).unwrap();
+        let (navaid, ..) = parse_navaid(&mut reader).unwrap();
+        assert_eq!(navaid.name.as_deref(), Some("TOU"));
+        assert_eq!(navaid.call_sign.as_deref(), Some("TBN"));
+        assert_eq!(navaid.frequency, Some(117.7));
+        assert_eq!(navaid.description.as_deref(), Some("TOULOUSE BLAGNAC"));
+        assert_eq!((navaid.latitude, navaid.longitude), (43.6, 1.4));
+
+        // Without equipment, no call sign
+        let xml = VOR.replace("<aixm:ident>TBN</aixm:ident>", "");
+        let mut reader = Reader::from_str(&xml);
+        find_node(&mut reader, vec![QName(b"aixm:Navaid")], None).unwrap();
+        assert_eq!(parse_navaid(&mut reader).unwrap().0.call_sign, None);
+    }
+
+    #[test]
+    fn test_parse_magnetic_variation() {
+        let xml = VOR.replace(
+            "<aixm:ident>TBN</aixm:ident>",
+            "<aixm:ident>TBN</aixm:ident><aixm:magneticVariation>-1.5</aixm:magneticVariation>",
+        );
+        let navaids = parse_navaids(&mut Reader::from_str(&xml)).unwrap();
+        assert_eq!(navaids[0].magnetic_variation, Some(-1.5));
+        assert_eq!(navaids[0].true_course(0.), Some(358.5));
+
+        let navaids = parse_navaids(&mut Reader::from_str(VOR)).unwrap();
+        assert_eq!(
+            (navaids[0].magnetic_variation, navaids[0].true_course(90.)),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn test_position_from_equipment() {
+        // Position on the inline DME equipment only
+        let xml = VOR
+            .replace("<aixm:NavaidEquipment>", "<aixm:DME>")
+            .replace("</aixm:NavaidEquipment>", "<aixm:location><aixm:ElevatedPoint><gml:pos>43.7 1.5</gml:pos></aixm:ElevatedPoint></aixm:location></aixm:DME>")
+            .replace("<gml:pos>43.6 1.4</gml:pos>", "");
+        let navaids = parse_navaids(&mut Reader::from_str(&xml)).unwrap();
+        assert_eq!((navaids[0].latitude, navaids[0].longitude), (43.7, 1.5));
+        assert_eq!(navaids[0].call_sign.as_deref(), Some("TBN"));
+        assert_eq!(navaids[0].frequency, Some(117.7));
+
+        // The position of the navaid itself is preferred
+        let xml = VOR.replace(
+            "</aixm:NavaidEquipment>",
+            "<aixm:location><aixm:ElevatedPoint><gml:pos>43.7 1.5</gml:pos></aixm:ElevatedPoint></aixm:location></aixm:NavaidEquipment>",
+        );
+        let navaids = parse_navaids(&mut Reader::from_str(&xml)).unwrap();
+        assert_eq!((navaids[0].latitude, navaids[0].longitude), (43.6, 1.4));
+
+        // Position on a separate DME feature, referenced by the navaid
+        let component =
+            r#"<aixm:NavaidComponent><aixm:theNavaidEquipment xlink:href="urn:uuid:dme-tou"/></aixm:NavaidComponent>"#;
+        let dme = r#"<message:hasMember>
+    <aixm:DME gml:id="dme-tou">
+      <gml:identifier codeSpace="urn:uuid:">dme-tou</gml:identifier>
+      <aixm:timeSlice>
+        <aixm:DMETimeSlice>
+          <aixm:designator>TBN</aixm:designator>
+          <aixm:magneticVariation>1</aixm:magneticVariation>
+          <aixm:location><aixm:ElevatedPoint><gml:pos>43.62 1.37</gml:pos></aixm:ElevatedPoint></aixm:location>
+        </aixm:DMETimeSlice>
+      </aixm:timeSlice>
+    </aixm:DME>
+  </message:hasMember>
+</message:AIXMBasicMessage>"#;
+        let start = VOR.find("<aixm:NavaidComponent>").unwrap();
+        let end = VOR.find("</aixm:NavaidComponent>").unwrap() + "</aixm:NavaidComponent>".len();
+        let xml = format!("{}{}{}", &VOR[..start], component, &VOR[end..])
+            .replace("<gml:pos>43.6 1.4</gml:pos>", "")
+            .replace("</message:AIXMBasicMessage>", dme);
+        let navaids = parse_navaids(&mut Reader::from_str(&xml)).unwrap();
+        assert_eq!(navaids.len(), 1);
+        assert_eq!(navaids[0].name.as_deref(), Some("TOU"));
+        assert_eq!((navaids[0].latitude, navaids[0].longitude), (43.62, 1.37));
+        assert_eq!(navaids[0].magnetic_variation, Some(1.));
+    }
 }
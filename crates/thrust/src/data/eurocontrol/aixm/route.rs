@@ -4,13 +4,15 @@ use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::BufReader;
 use std::path::Path;
 use zip::read::ZipArchive;
 
+use crate::data::eurocontrol::aixm::identifier::GmlId;
 use crate::data::eurocontrol::aixm::Node;
 
-use super::{find_node, read_text};
+use super::{
+    baseline_reader, find_node, identifier_fallback, log_parsed, read_text, AixmEntries, DEFAULT_ENTRY_SUFFIX,
+};
 
 /// An Airway Traffic Service (ATS) route connecting navigation points.
 ///
@@ -29,6 +31,7 @@ use super::{find_node, read_text};
 /// - `second_letter`: Route category designator (e.g., "N" for North Atlantic)
 /// - `number`: Numeric designator (1-999)
 /// - `multiple_identifier`: Optional letter for alternative routes
+/// - `segments`: Identifiers of the route segments forming the route
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Route {
     #[serde(skip)]
@@ -41,28 +44,192 @@ pub struct Route {
     pub number: Option<String>,
     /// The multiple identifier of the route, if any
     pub multiple_identifier: Option<String>,
+    /// Identifiers of the route segments forming the route (populated by the database)
+    #[serde(skip)]
+    pub segments: Vec<String>,
+}
+
+/// The components of an ATS route designator, following the ICAO grammar:
+/// `[prefix]second_letter number[multiple_identifier]`
+///
+/// - `prefix`: optional `U` (upper route)
+/// - `second_letter`: a single letter
+/// - `number`: one to three digits
+/// - `multiple_identifier`: optional single letter
+///
+/// # Example
+/// ```
+/// use thrust::data::eurocontrol::aixm::route::RouteDesignator;
+///
+/// let designator = RouteDesignator::parse("UN871").unwrap();
+/// assert_eq!(designator.prefix.as_deref(), Some("U"));
+/// assert_eq!(designator.second_letter, "N");
+/// assert_eq!(designator.number, "871");
+/// assert_eq!(designator.multiple_identifier, None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteDesignator {
+    pub prefix: Option<String>,
+    pub second_letter: String,
+    pub number: String,
+    pub multiple_identifier: Option<String>,
+}
+
+impl RouteDesignator {
+    /// Decompose a full route designator (e.g. "UN871", "L9", "N756C") into its components.
+    ///
+    /// Returns None if the designator does not follow the ICAO grammar.
+    pub fn parse(designator: &str) -> Option<Self> {
+        let bytes = designator.trim().as_bytes();
+        let (prefix, rest) = match bytes {
+            [b'U', second, ..] if second.is_ascii_uppercase() => (Some("U".to_string()), &bytes[1..]),
+            _ => (None, bytes),
+        };
+        let (second_letter, rest) = match rest {
+            [letter, rest @ ..] if letter.is_ascii_uppercase() => (*letter as char, rest),
+            _ => return None,
+        };
+        let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+        if !(1..=3).contains(&digits) {
+            return None;
+        }
+        let (number, rest) = rest.split_at(digits);
+        let multiple_identifier = match rest {
+            [] => None,
+            [letter] if letter.is_ascii_uppercase() => Some((*letter as char).to_string()),
+            _ => return None,
+        };
+        Some(RouteDesignator {
+            prefix,
+            second_letter: second_letter.to_string(),
+            // only ASCII digits at this point
+            number: String::from_utf8_lossy(number).to_string(),
+            multiple_identifier,
+        })
+    }
+}
+
+impl Route {
+    /// The full designator of the route, e.g. "UN871"
+    pub fn designator(&self) -> String {
+        format!(
+            "{}{}{}{}",
+            self.prefix.as_deref().unwrap_or(""),
+            self.second_letter.as_deref().unwrap_or(""),
+            self.number.as_deref().unwrap_or(""),
+            self.multiple_identifier.as_deref().unwrap_or("")
+        )
+    }
+
+    /// Check whether the designator components follow the ICAO grammar.
+    pub fn is_well_formed(&self) -> bool {
+        let is_letter = |s: &str| s.len() == 1 && s.bytes().all(|b| b.is_ascii_uppercase());
+        self.prefix.as_deref().is_none_or(|p| p == "U")
+            && self.second_letter.as_deref().is_some_and(is_letter)
+            && self
+                .number
+                .as_deref()
+                .is_some_and(|n| (1..=3).contains(&n.len()) && n.bytes().all(|b| b.is_ascii_digit()))
+            && self.multiple_identifier.as_deref().is_none_or(is_letter)
+    }
+
+    /// Re-derive the designator components from the full designator when they look malformed.
+    ///
+    /// Some AIXM exports put the full designator (e.g. "UN871") in the
+    /// `designatorSecondLetter` field and leave the other components empty.
+    /// Returns true if the route has been modified.
+    pub fn normalize(&mut self) -> bool {
+        if self.is_well_formed() {
+            return false;
+        }
+        let designator = self.designator();
+        match RouteDesignator::parse(&designator) {
+            Some(parsed) => {
+                tracing::warn!(
+                    target: "thrust::aixm",
+                    "Malformed designator components for route {}, normalized to '{}'",
+                    self.identifier,
+                    designator
+                );
+                self.prefix = parsed.prefix;
+                self.second_letter = Some(parsed.second_letter);
+                self.number = Some(parsed.number);
+                self.multiple_identifier = parsed.multiple_identifier;
+                true
+            }
+            None => {
+                tracing::warn!(
+                    target: "thrust::aixm",
+                    "Invalid designator '{}' for route {}, excluded from name lookups",
+                    designator,
+                    self.identifier
+                );
+                false
+            }
+        }
+    }
 }
 
 /**
  * Parse route data from a ZIP file containing AIXM data.
  */
-pub fn parse_route_zip_file<P: AsRef<Path>>(path: P) -> Result<HashMap<String, Route>, ThrustError> {
+pub fn parse_route_zip_file<P: AsRef<Path>>(path: P) -> Result<HashMap<GmlId, Route>, ThrustError> {
+    parse_route_zip_file_with_suffix(path, DEFAULT_ENTRY_SUFFIX)
+}
+
+/// Like [`parse_route_zip_file`], reading the archive entries whose name ends with
+/// `entry_suffix` (e.g. `.xml`) instead of [`DEFAULT_ENTRY_SUFFIX`].
+pub fn parse_route_zip_file_with_suffix<P: AsRef<Path>>(
+    path: P,
+    entry_suffix: &str,
+) -> Result<HashMap<GmlId, Route>, ThrustError> {
+    parse_route_zip_file_filtered(path, entry_suffix, |_| true)
+}
+
+/// Parse the routes of a ZIP file containing AIXM data, only keeping those for
+/// which `keep` returns true, e.g. `|route| route.prefix.as_deref() == Some("U")`.
+///
+/// Routes are filtered one at a time as they are parsed, with their designator
+/// already normalized, so that discarded routes are never stored.
+pub fn parse_route_zip_file_filtered<P: AsRef<Path>>(
+    path: P,
+    entry_suffix: &str,
+    mut keep: impl FnMut(&Route) -> bool,
+) -> Result<HashMap<GmlId, Route>, ThrustError> {
+    let path = path.as_ref();
     let file = File::open(path)?;
     let mut archive = ZipArchive::new(file)?;
+    let entries = AixmEntries::new(&archive, entry_suffix);
     let mut routes = HashMap::new();
 
     for i in 0..archive.len() {
-        let file = archive.by_index(i)?;
-        if file.name().ends_with(".BASELINE") {
-            let mut reader = Reader::from_reader(BufReader::new(file));
-
-            while let Ok(_node) = find_node(&mut reader, vec![QName(b"aixm:Route")], None) {
-                let route = parse_route(&mut reader)?;
-                routes.insert(route.identifier.clone(), route);
-            }
+        if let Some(mut reader) = baseline_reader(&mut archive, i, &entries)? {
+            routes.extend(parse_routes(&mut reader, &mut keep)?);
         }
     }
 
+    log_parsed("routes", path, routes.len());
+    Ok(routes)
+}
+
+/// Parse the routes of an AIXM XML document accepted by `keep`, normalizing
+/// malformed designators.
+///
+/// Routes whose designator cannot be normalized (e.g. without a number) are kept,
+/// as their segments still belong to the network, but they are never found by name.
+fn parse_routes<R: std::io::BufRead>(
+    reader: &mut Reader<R>,
+    keep: &mut impl FnMut(&Route) -> bool,
+) -> Result<HashMap<GmlId, Route>, ThrustError> {
+    let mut routes = HashMap::new();
+    while let Ok(node) = find_node(reader, vec![QName(b"aixm:Route")], None) {
+        let mut route = parse_route(reader)?;
+        identifier_fallback(&mut route.identifier, &node);
+        route.normalize();
+        if keep(&route) {
+            routes.insert(route.identifier.clone().into(), route);
+        }
+    }
     Ok(routes)
 }
 
@@ -102,3 +269,169 @@ fn parse_route<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<Route, Thr
     }
     Ok(route)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::eurocontrol::aixm::DEFAULT_ENTRY_SUFFIX;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    const WELL_FORMED: &str = r#"<message:AIXMBasicMessage>
+  <message:hasMember>
+    <aixm:Route gml:id="r1">
+      <gml:identifier codeSpace="urn:uuid:">rte-un871</gml:identifier>
+      <aixm:timeSlice>
+        <aixm:RouteTimeSlice gml:id="r1-ts">
+          <aixm:designatorPrefix>U</aixm:designatorPrefix>
+          <aixm:designatorSecondLetter>N</aixm:designatorSecondLetter>
+          <aixm:designatorNumber>871</aixm:designatorNumber>
+        </aixm:RouteTimeSlice>
+      </aixm:timeSlice>
+    </aixm:Route>
+  </message:hasMember>
+</message:AIXMBasicMessage>"#;
+
+    const SLOPPY: &str = r#"<message:AIXMBasicMessage>
+  <message:hasMember>
+    <aixm:Route gml:id="r1">
+      <gml:identifier codeSpace="urn:uuid:">rte-un871</gml:identifier>
+      <aixm:timeSlice>
+        <aixm:RouteTimeSlice gml:id="r1-ts">
+          <aixm:designatorSecondLetter>UN871</aixm:designatorSecondLetter>
+        </aixm:RouteTimeSlice>
+      </aixm:timeSlice>
+    </aixm:Route>
+  </message:hasMember>
+</message:AIXMBasicMessage>"#;
+
+    #[test]
+    fn test_route_designator_parse() {
+        let parsed = RouteDesignator::parse("N756C").unwrap();
+        assert_eq!(parsed.prefix, None);
+        assert_eq!(parsed.second_letter, "N");
+        assert_eq!(parsed.number, "756");
+        assert_eq!(parsed.multiple_identifier.as_deref(), Some("C"));
+
+        assert!(RouteDesignator::parse("L9").is_some());
+        assert!(RouteDesignator::parse("UN").is_none());
+        assert!(RouteDesignator::parse("UN1234").is_none());
+        assert!(RouteDesignator::parse("UN12AB").is_none());
+    }
+
+    /// The decomposition formerly done by `ResolvedRoute::lookup`, slicing bytes:
+    /// only valid on ASCII designators, and panicking when nothing is left
+    /// after the multiple identifier (None here).
+    fn sliced_decomposition(name: &str) -> Option<RouteDesignator> {
+        let last = name.chars().last()?;
+        let (name, multiple_identifier) = if last.is_alphabetic() {
+            (&name[..name.len() - 1], Some(last.to_string()))
+        } else {
+            (name, None)
+        };
+        if name.is_empty() {
+            return None;
+        }
+        let (prefix, second_letter, number) = if name.starts_with('U') && name.len() >= 3 {
+            (Some("U".to_string()), name[1..2].to_string(), name[2..].to_string())
+        } else {
+            (None, name[..1].to_string(), name[1..].to_string())
+        };
+        Some(RouteDesignator {
+            prefix,
+            second_letter,
+            number,
+            multiple_identifier,
+        })
+    }
+
+    #[test]
+    fn test_route_designator_parse_short_strings() {
+        // All strings of up to four characters over an alphabet mixing ASCII and
+        // multi-byte characters, as pasted by users
+        let alphabet = ["U", "N", "L", "1", "9", "Ü", "é", "Ｕ", "🛫", " "];
+        let mut names = vec![String::new()];
+        let mut previous = names.clone();
+        for _ in 0..4 {
+            previous = previous
+                .iter()
+                .flat_map(|name| alphabet.iter().map(move |c| format!("{name}{c}")))
+                .collect();
+            names.extend(previous.iter().cloned());
+        }
+        for name in &names {
+            let parsed = std::panic::catch_unwind(|| RouteDesignator::parse(name))
+                .unwrap_or_else(|_| panic!("parsing {name:?} panicked"));
+            let trimmed = name.trim();
+            if !trimmed.is_ascii() {
+                assert_eq!(parsed, None, "{name:?}");
+                continue;
+            }
+            // Where slicing gave well-formed components, e.g. not for "U11", they agree
+            let well_formed = sliced_decomposition(trimmed).filter(|sliced| {
+                Route {
+                    prefix: sliced.prefix.clone(),
+                    second_letter: Some(sliced.second_letter.clone()),
+                    number: Some(sliced.number.clone()),
+                    multiple_identifier: sliced.multiple_identifier.clone(),
+                    ..Default::default()
+                }
+                .is_well_formed()
+            });
+            if well_formed.is_some() {
+                assert_eq!(parsed, well_formed, "{name:?}");
+            }
+        }
+        assert_eq!(RouteDesignator::parse("Ü1"), None);
+        assert_eq!(RouteDesignator::parse("U"), None);
+        assert_eq!(RouteDesignator::parse(""), None);
+        assert!(RouteDesignator::parse("UN1").is_some());
+    }
+
+    #[test]
+    fn test_normalize_sloppy_designators() {
+        let well_formed = parse_routes(&mut Reader::from_str(WELL_FORMED), &mut |_| true).unwrap();
+        let sloppy = parse_routes(&mut Reader::from_str(SLOPPY), &mut |_| true).unwrap();
+        for routes in [well_formed, sloppy] {
+            let route = routes.get("rte-un871").unwrap();
+            assert!(route.is_well_formed());
+            assert_eq!(route.prefix.as_deref(), Some("U"));
+            assert_eq!(route.second_letter.as_deref(), Some("N"));
+            assert_eq!(route.number.as_deref(), Some("871"));
+            assert_eq!(route.designator(), "UN871");
+        }
+
+        // A record without designatorNumber cannot be normalized, and is kept as is
+        let incomplete = WELL_FORMED.replace("<aixm:designatorNumber>871</aixm:designatorNumber>", "");
+        let routes = parse_routes(&mut Reader::from_str(&incomplete), &mut |_| true).unwrap();
+        let route = routes.get("rte-un871").unwrap();
+        assert!(!route.is_well_formed());
+        assert_eq!(route.designator(), "UN");
+    }
+
+    #[test]
+    fn test_parse_filtered_routes() {
+        let path = std::env::temp_dir().join(format!("thrust-routes-{}.zip", std::process::id()));
+        let mut writer = ZipWriter::new(File::create(&path).unwrap());
+        writer
+            .start_file("Route.BASELINE", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(WELL_FORMED.as_bytes()).unwrap();
+        let lower = WELL_FORMED
+            .replace("rte-un871", "rte-n871")
+            .replace("<aixm:designatorPrefix>U</aixm:designatorPrefix>", "");
+        writer.write_all(lower.as_bytes()).unwrap();
+        writer.finish().unwrap();
+
+        let all = parse_route_zip_file(&path);
+        let upper = parse_route_zip_file_filtered(&path, DEFAULT_ENTRY_SUFFIX, |route| {
+            route.prefix.as_deref() == Some("U")
+        });
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(all.unwrap().len(), 2);
+        let upper = upper.unwrap();
+        assert_eq!(upper.len(), 1);
+        assert_eq!(upper["rte-un871"].designator(), "UN871");
+    }
+}
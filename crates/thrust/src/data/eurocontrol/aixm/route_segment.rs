@@ -4,13 +4,16 @@ use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::BufReader;
 use std::path::Path;
 use zip::read::ZipArchive;
 
+use crate::data::eurocontrol::aixm::identifier::GmlId;
 use crate::data::eurocontrol::aixm::Node;
 
-use super::{find_node, read_text};
+use super::{
+    baseline_reader, find_node, identifier_fallback, log_parsed, parse_number, parse_pos, read_text, AixmEntries,
+    DEFAULT_ENTRY_SUFFIX,
+};
 
 /// A single segment of an ATS route connecting two sequential navigation points.
 ///
@@ -23,14 +26,15 @@ use super::{find_node, read_text};
 /// - `route_formed`: The parent route designator this segment belongs to
 /// - `start`: Departure point (navaid, waypoint, or airport)
 /// - `end`: Arrival point (navaid, waypoint, or airport)
+/// - `availability`: Direction, vertical limits and cruising levels of the segment
 ///
 /// # Example
 /// ```ignore
 /// let segment = RouteSegment {
 ///     identifier: "SEG001".to_string(),
 ///     route_formed: Some("N100".to_string()),
-///     start: PointReference::Navaid("SEA".to_string()),
-///     end: PointReference::DesignatedPoint("APTIN".to_string()),
+///     start: PointReference::Navaid("SEA".into()),
+///     end: PointReference::DesignatedPoint("APTIN".into()),
 ///     ..Default::default()
 /// };
 /// ```
@@ -44,10 +48,247 @@ pub struct RouteSegment {
     pub start: PointReference,
     /// Ending point of the segment
     pub end: PointReference,
-    // the following fields are related to availabilities, which are not properly modelled yet
-    // pub lower_limit: Option<String>,
-    // pub upper_limit: Option<String>,
-    // pub direction: Option<String>,
+    /// Availabilities of the segment, e.g. one for each direction of flight
+    #[serde(default)]
+    pub availability: Vec<RouteAvailability>,
+}
+
+impl RouteSegment {
+    /// The direction of flight of the segment: the one of all its availabilities if
+    /// they agree, `Both` otherwise, and `None` without any availability.
+    pub fn direction(&self) -> Option<RouteDirection> {
+        let first = self.availability.first()?.direction;
+        match self.availability.iter().all(|a| a.direction == first) {
+            true => Some(first),
+            false => Some(RouteDirection::Both),
+        }
+    }
+
+    /// The lowest lower limit of the availabilities, if they all have one.
+    pub fn lower_limit(&self) -> Option<VerticalLimit> {
+        let limits = self.availability.iter().map(|a| a.lower_limit);
+        limits
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .min_by(|a, b| a.flight_level().total_cmp(&b.flight_level()))
+    }
+
+    /// The highest upper limit of the availabilities, if they all have one.
+    pub fn upper_limit(&self) -> Option<VerticalLimit> {
+        let limits = self.availability.iter().map(|a| a.upper_limit);
+        limits
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .max_by(|a, b| a.flight_level().total_cmp(&b.flight_level()))
+    }
+}
+
+/// The direction of flight along a route segment, relative to its start and end points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RouteDirection {
+    /// From the start point to the end point
+    Forward,
+    /// From the end point to the start point
+    Backward,
+    #[default]
+    Both,
+}
+
+/// The series of cruising levels assigned to a direction of flight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LevelSeries {
+    /// Odd flight levels (FL 310, 330, ..., 410, then 450, 490, ...)
+    Odd,
+    /// Even flight levels (FL 300, 320, ..., 400, then 430, 470, ...)
+    Even,
+}
+
+impl LevelSeries {
+    /// Parse a series code, e.g. "ODD", "EVEN" or "ODD_IFR".
+    pub fn parse(code: &str) -> Option<Self> {
+        let code = code.trim().to_ascii_uppercase();
+        if code.starts_with("ODD") {
+            Some(LevelSeries::Odd)
+        } else if code.starts_with("EVEN") {
+            Some(LevelSeries::Even)
+        } else {
+            None
+        }
+    }
+
+    /// The series of an IFR cruising level, in hundreds of feet (e.g. 350 is odd).
+    ///
+    /// Levels follow the table of cruising levels of ICAO Annex 2, Appendix 3 in
+    /// RVSM airspace: every 2000 ft up to FL 410 in each series, then every 4000 ft
+    /// (FL 450, 490, ... are odd; FL 430, 470, ... even). Other levels, e.g. VFR
+    /// levels such as FL 355, belong to no series.
+    pub fn of_flight_level(flight_level: u16) -> Option<Self> {
+        match flight_level {
+            10..=410 if flight_level.is_multiple_of(20) => Some(LevelSeries::Even),
+            10..=410 if flight_level.is_multiple_of(10) => Some(LevelSeries::Odd),
+            411.. => match (flight_level - 410) % 40 {
+                0 => Some(LevelSeries::Odd),
+                20 => Some(LevelSeries::Even),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// The datum a vertical limit is measured from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LimitReference {
+    /// Standard pressure (1013.25 hPa), for flight levels
+    #[default]
+    Std,
+    /// Mean sea level, for altitudes
+    Msl,
+    /// The surface, for heights
+    Sfc,
+    /// The WGS84 ellipsoid
+    W84,
+    Other,
+}
+
+impl LimitReference {
+    /// Parse an AIXM vertical reference code (STD, MSL, SFC, W84 or OTHER).
+    pub fn parse(code: &str) -> Self {
+        match code.trim() {
+            "STD" => LimitReference::Std,
+            "MSL" => LimitReference::Msl,
+            "SFC" => LimitReference::Sfc,
+            "W84" => LimitReference::W84,
+            _ => LimitReference::Other,
+        }
+    }
+}
+
+/// The unit of measurement of a vertical limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LimitUnit {
+    /// Flight levels, in hundreds of feet
+    Fl,
+    Ft,
+    M,
+}
+
+/// A lower or upper limit of a route segment availability.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum VerticalLimit {
+    /// The ground or water surface ("GND")
+    Surface,
+    /// No upper limit ("UNL")
+    Unlimited,
+    /// A value above the reference datum, e.g. FL245 (STD) or 2450 ft (MSL)
+    Level {
+        value: f64,
+        uom: LimitUnit,
+        reference: LimitReference,
+    },
+}
+
+impl VerticalLimit {
+    /// Parse the text and `uom` attribute of a limit element, with its reference code.
+    ///
+    /// Returns `None` for malformed values and unknown units.
+    pub fn parse(text: &str, uom: Option<&str>, reference: Option<&str>) -> Option<Self> {
+        match text.trim() {
+            "GND" => return Some(VerticalLimit::Surface),
+            "UNL" => return Some(VerticalLimit::Unlimited),
+            _ => (),
+        }
+        let uom = match uom? {
+            "FL" => LimitUnit::Fl,
+            "FT" => LimitUnit::Ft,
+            "M" => LimitUnit::M,
+            _ => return None,
+        };
+        let reference = match reference {
+            Some(code) => LimitReference::parse(code),
+            // Flight levels are always referenced to standard pressure
+            None if uom == LimitUnit::Fl => LimitReference::Std,
+            None => LimitReference::Other,
+        };
+        Some(VerticalLimit::Level {
+            value: parse_number(text).ok()?,
+            uom,
+            reference,
+        })
+    }
+
+    /// The limit in hundreds of feet, comparable to a flight level.
+    ///
+    /// Altitudes and heights are taken at face value, regardless of their reference.
+    pub fn flight_level(&self) -> f64 {
+        const FEET_PER_METER: f64 = 3.28084;
+        match self {
+            VerticalLimit::Surface => 0.,
+            VerticalLimit::Unlimited => f64::INFINITY,
+            VerticalLimit::Level { value, uom, .. } => match uom {
+                LimitUnit::Fl => *value,
+                LimitUnit::Ft => value / 100.,
+                LimitUnit::M => value * FEET_PER_METER / 100.,
+            },
+        }
+    }
+}
+
+/// Limits are written as in aeronautical publications, e.g. "FL245", "GND", "UNL"
+/// or "2450 FT MSL".
+impl std::fmt::Display for VerticalLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerticalLimit::Surface => write!(f, "GND"),
+            VerticalLimit::Unlimited => write!(f, "UNL"),
+            VerticalLimit::Level {
+                value,
+                uom: LimitUnit::Fl,
+                ..
+            } => write!(f, "FL{value}"),
+            VerticalLimit::Level { value, uom, reference } => {
+                let uom = if *uom == LimitUnit::Ft { "FT" } else { "M" };
+                let reference = match reference {
+                    LimitReference::Std => "STD",
+                    LimitReference::Msl => "MSL",
+                    LimitReference::Sfc => "SFC",
+                    LimitReference::W84 => "W84",
+                    LimitReference::Other => "OTHER",
+                };
+                write!(f, "{value} {uom} {reference}")
+            }
+        }
+    }
+}
+
+/// The availability of a route segment in one direction (`aixm:RouteAvailability`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct RouteAvailability {
+    pub direction: RouteDirection,
+    pub lower_limit: Option<VerticalLimit>,
+    pub upper_limit: Option<VerticalLimit>,
+    /// Cruising levels assigned to this direction, if any
+    pub level_series: Option<LevelSeries>,
+}
+
+impl RouteAvailability {
+    /// Whether this availability applies in the given direction of flight.
+    pub fn applies_to(&self, direction: RouteDirection) -> bool {
+        self.direction == RouteDirection::Both || self.direction == direction
+    }
+
+    /// Whether a flight level lies within the vertical limits.
+    pub fn contains(&self, flight_level: f64) -> bool {
+        self.lower_limit
+            .is_none_or(|lower| lower.flight_level() <= flight_level)
+            && self
+                .upper_limit
+                .is_none_or(|upper| flight_level <= upper.flight_level())
+    }
 }
 
 /// A reference to a navigation point (designated point, navaid, or airport).
@@ -57,14 +298,15 @@ pub struct RouteSegment {
 /// that can appear in aviation procedures and routes.
 ///
 /// # Variants
-/// - `DesignatedPoint(String)`: Published waypoint/fix identifier (e.g., "APTIN")
-/// - `Navaid(String)`: Navigation aid identifier (e.g., "SEA" for VOR)
-/// - `AirportHeliport(String)`: Airport or heliport identifier (e.g., "KSEA")
+/// - `DesignatedPoint(GmlId)`: Identifier of a published waypoint/fix
+/// - `Navaid(GmlId)`: Identifier of a navigation aid
+/// - `AirportHeliport(GmlId)`: Identifier of an airport or heliport
+/// - `Coordinates(f64, f64)`: Latitude and longitude given inline (`gml:pos`)
 /// - `None`: Point not resolved or undefined
 ///
 /// # Example
 /// ```ignore
-/// let point = PointReference::DesignatedPoint("APTIN".to_string());
+/// let point = PointReference::DesignatedPoint(GmlId::from("dp-aptin"));
 /// match point {
 ///     PointReference::DesignatedPoint(name) => println!("Waypoint: {}", name),
 ///     _ => println!("Other point type"),
@@ -72,20 +314,63 @@ pub struct RouteSegment {
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub enum PointReference {
-    DesignatedPoint(String),
-    Navaid(String),
-    AirportHeliport(String),
+    DesignatedPoint(GmlId),
+    Navaid(GmlId),
+    AirportHeliport(GmlId),
+    Coordinates(f64, f64),
     #[default]
     None,
 }
 
+// Inline coordinates compare by their bit patterns, so that references can be hashed
+impl PartialEq for PointReference {
+    fn eq(&self, other: &Self) -> bool {
+        use PointReference::*;
+        match (self, other) {
+            (DesignatedPoint(a), DesignatedPoint(b))
+            | (Navaid(a), Navaid(b))
+            | (AirportHeliport(a), AirportHeliport(b)) => a == b,
+            (Coordinates(a, b), Coordinates(c, d)) => (a.to_bits(), b.to_bits()) == (c.to_bits(), d.to_bits()),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for PointReference {}
+
+impl std::hash::Hash for PointReference {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            PointReference::DesignatedPoint(id) | PointReference::Navaid(id) | PointReference::AirportHeliport(id) => {
+                id.hash(state)
+            }
+            PointReference::Coordinates(latitude, longitude) => (latitude.to_bits(), longitude.to_bits()).hash(state),
+            PointReference::None => (),
+        }
+    }
+}
+
 impl PointReference {
+    /// The identifier of the referenced point, or an empty string for inline
+    /// coordinates and undefined points.
     pub fn name(&self) -> String {
         match self {
             PointReference::DesignatedPoint(id) => id.to_string(),
             PointReference::Navaid(id) => id.to_string(),
             PointReference::AirportHeliport(id) => id.to_string(),
-            PointReference::None => "".to_string(),
+            PointReference::Coordinates(..) | PointReference::None => "".to_string(),
+        }
+    }
+
+    /// The identifier of the referenced point, if any.
+    pub fn id(&self) -> Option<&GmlId> {
+        match self {
+            PointReference::DesignatedPoint(id) | PointReference::Navaid(id) | PointReference::AirportHeliport(id) => {
+                Some(id)
+            }
+            PointReference::Coordinates(..) | PointReference::None => None,
         }
     }
 
@@ -97,23 +382,33 @@ impl PointReference {
 /**
  * Parse route segment data from a ZIP file containing AIXM data.
  */
-pub fn parse_route_segment_zip_file<P: AsRef<Path>>(path: P) -> Result<HashMap<String, RouteSegment>, ThrustError> {
+pub fn parse_route_segment_zip_file<P: AsRef<Path>>(path: P) -> Result<HashMap<GmlId, RouteSegment>, ThrustError> {
+    parse_route_segment_zip_file_with_suffix(path, DEFAULT_ENTRY_SUFFIX)
+}
+
+/// Like [`parse_route_segment_zip_file`], reading the archive entries whose name ends with
+/// `entry_suffix` (e.g. `.xml`) instead of [`DEFAULT_ENTRY_SUFFIX`].
+pub fn parse_route_segment_zip_file_with_suffix<P: AsRef<Path>>(
+    path: P,
+    entry_suffix: &str,
+) -> Result<HashMap<GmlId, RouteSegment>, ThrustError> {
+    let path = path.as_ref();
     let file = File::open(path)?;
     let mut archive = ZipArchive::new(file)?;
+    let entries = AixmEntries::new(&archive, entry_suffix);
     let mut route_segments = HashMap::new();
 
     for i in 0..archive.len() {
-        let file = archive.by_index(i)?;
-        if file.name().ends_with(".BASELINE") {
-            let mut reader = Reader::from_reader(BufReader::new(file));
-
-            while let Ok(_node) = find_node(&mut reader, vec![QName(b"aixm:RouteSegment")], None) {
-                let route_segment = parse_route_segment(&mut reader)?;
-                route_segments.insert(route_segment.identifier.clone(), route_segment);
+        if let Some(mut reader) = baseline_reader(&mut archive, i, &entries)? {
+            while let Ok(node) = find_node(&mut reader, vec![QName(b"aixm:RouteSegment")], None) {
+                let mut route_segment = parse_route_segment(&mut reader)?;
+                identifier_fallback(&mut route_segment.identifier, &node);
+                route_segments.insert(route_segment.identifier.clone().into(), route_segment);
             }
         }
     }
 
+    log_parsed("route segments", path, route_segments.len());
     Ok(route_segments)
 }
 
@@ -127,24 +422,24 @@ fn parse_route_segment<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<Ro
             QName(b"aixm:routeFormed"),
             QName(b"aixm:start"),
             QName(b"aixm:end"),
-            //QName(b"aixm:lowerLimit"),
-            //QName(b"aixm:upperLimit"),
-            //QName(b"aixm:direction"),
             QName(b"aixm:extension"),
             QName(b"aixm:annotation"),
             QName(b"aixm:availability"),
         ],
         Some(QName(b"aixm:RouteSegment")),
     ) {
-        let Node { name, attributes } = node;
+        let Node { name, attributes, .. } = node;
         match name {
             QName(b"gml:identifier") => {
                 segment.identifier = read_text(reader, name)?;
             }
-            QName(b"aixm:extension") | QName(b"aixm:availability") | QName(b"aixm_annotation") => {
+            QName(b"aixm:extension") | QName(b"aixm_annotation") => {
                 // Skip the whole block
                 let _ = find_node(reader, vec![], Some(name));
             }
+            QName(b"aixm:availability") => {
+                segment.availability.push(parse_route_availability(reader, name)?);
+            }
             QName(b"aixm:routeFormed") => {
                 if let Some(id) = attributes
                     .get("xlink:href")
@@ -153,79 +448,303 @@ fn parse_route_segment<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<Ro
                     segment.route_formed = Some(id.to_string());
                 }
             }
-            /*QName(b"aixm:lowerLimit") => {
-                segment.lower_limit = Some(read_text(reader, node)?);
+            QName(b"aixm:start") => {
+                segment.start = parse_segment_point(reader, name)?;
+            }
+            QName(b"aixm:end") => {
+                segment.end = parse_segment_point(reader, name)?;
+            }
+            _ => (),
+        }
+    }
+    Ok(segment)
+}
+
+/// Parse the content of an `aixm:start` or `aixm:end` element, up to its end tag.
+///
+/// The point is either a reference to a designated point or navaid, or a position
+/// given inline (`aixm:pointChoice_position`).
+fn parse_segment_point<R: std::io::BufRead>(reader: &mut Reader<R>, end: QName) -> Result<PointReference, ThrustError> {
+    let mut point = PointReference::None;
+    while let Ok(node) = find_node(
+        reader,
+        vec![
+            QName(b"aixm:pointChoice_fixDesignatedPoint"),
+            QName(b"aixm:pointChoice_navaidSystem"),
+            QName(b"gml:pos"),
+        ],
+        Some(end),
+    ) {
+        let Node { name, attributes, .. } = node;
+        let id = attributes
+            .get("xlink:href")
+            .map(|s| s.strip_prefix("urn:uuid:").unwrap_or(s));
+        match (name, id) {
+            (QName(b"aixm:pointChoice_fixDesignatedPoint"), Some(id)) => {
+                point = PointReference::DesignatedPoint(id.into());
+            }
+            (QName(b"aixm:pointChoice_navaidSystem"), Some(id)) => {
+                point = PointReference::Navaid(id.into());
             }
-            QName(b"aixm:upperLimit") => {
-                segment.upper_limit = Some(read_text(reader, node)?);
+            (QName(b"gml:pos"), _) => {
+                if let Some((latitude, longitude)) = parse_pos(&read_text(reader, name)?) {
+                    point = PointReference::Coordinates(latitude, longitude);
+                }
             }
+            _ => (),
+        }
+    }
+    Ok(point)
+}
+
+/// Parse the content of an `aixm:availability` element, up to its end tag.
+///
+/// The level series is read from the `xlink:title` of the `aixm:discreteLevelSeries`
+/// reference, or from the `aixm:series` of a standard level column given inline.
+fn parse_route_availability<R: std::io::BufRead>(
+    reader: &mut Reader<R>,
+    end: QName,
+) -> Result<RouteAvailability, ThrustError> {
+    let mut availability = RouteAvailability::default();
+    // Limit values (with their unit) and references, in any order
+    let mut lower = None;
+    let mut upper = None;
+    let mut lower_reference = None;
+    let mut upper_reference = None;
+    while let Ok(node) = find_node(
+        reader,
+        vec![
+            QName(b"aixm:direction"),
+            QName(b"aixm:lowerLimit"),
+            QName(b"aixm:upperLimit"),
+            QName(b"aixm:lowerLimitReference"),
+            QName(b"aixm:upperLimitReference"),
+            QName(b"aixm:discreteLevelSeries"),
+            QName(b"aixm:series"),
+        ],
+        Some(end),
+    ) {
+        let Node {
+            name,
+            attributes,
+            empty,
+        } = node;
+        match name {
             QName(b"aixm:direction") => {
-                segment.direction = Some(read_text(reader, node)?);
-            }*/
-            QName(b"aixm:start") => {
-                while let Ok(node) = find_node(
-                    reader,
-                    vec![
-                        QName(b"aixm:pointChoice_fixDesignatedPoint"),
-                        QName(b"aixm:pointChoice_navaidSystem"),
-                    ],
-                    Some(name),
-                ) {
-                    let Node { name, attributes } = node;
-                    match name {
-                        QName(b"aixm:pointChoice_fixDesignatedPoint") => {
-                            if let Some(id) = attributes
-                                .get("xlink:href")
-                                .map(|s| s.strip_prefix("urn:uuid:").unwrap_or(s))
-                            {
-                                segment.start = PointReference::DesignatedPoint(id.to_string());
-                            }
-                        }
-                        QName(b"aixm:pointChoice_navaidSystem") => {
-                            if let Some(id) = attributes
-                                .get("xlink:href")
-                                .map(|s| s.strip_prefix("urn:uuid:").unwrap_or(s))
-                            {
-                                segment.start = PointReference::Navaid(id.to_string());
-                            }
-                        }
-                        _ => (),
-                    }
+                availability.direction = match read_text(reader, name)?.trim() {
+                    "FORWARD" => RouteDirection::Forward,
+                    "BACKWARD" => RouteDirection::Backward,
+                    _ => RouteDirection::Both,
+                };
+            }
+            QName(b"aixm:lowerLimit") if !empty => {
+                lower = Some((read_text(reader, name)?, attributes.get("uom").cloned()));
+            }
+            QName(b"aixm:upperLimit") if !empty => {
+                upper = Some((read_text(reader, name)?, attributes.get("uom").cloned()));
+            }
+            QName(b"aixm:lowerLimitReference") if !empty => {
+                lower_reference = Some(read_text(reader, name)?);
+            }
+            QName(b"aixm:upperLimitReference") if !empty => {
+                upper_reference = Some(read_text(reader, name)?);
+            }
+            QName(b"aixm:discreteLevelSeries") => {
+                if let Some(series) = attributes
+                    .get("xlink:title")
+                    .and_then(|title| LevelSeries::parse(title))
+                {
+                    availability.level_series = Some(series);
                 }
             }
-            QName(b"aixm:end") => {
-                while let Ok(node) = find_node(
-                    reader,
-                    vec![
-                        QName(b"aixm:pointChoice_fixDesignatedPoint"),
-                        QName(b"aixm:pointChoice_navaidSystem"),
-                    ],
-                    Some(name),
-                ) {
-                    let Node { name, attributes } = node;
-                    match name {
-                        QName(b"aixm:pointChoice_fixDesignatedPoint") => {
-                            if let Some(id) = attributes
-                                .get("xlink:href")
-                                .map(|s| s.strip_prefix("urn:uuid:").unwrap_or(s))
-                            {
-                                segment.end = PointReference::DesignatedPoint(id.to_string());
-                            }
-                        }
-                        QName(b"aixm:pointChoice_navaidSystem") => {
-                            if let Some(id) = attributes
-                                .get("xlink:href")
-                                .map(|s| s.strip_prefix("urn:uuid:").unwrap_or(s))
-                            {
-                                segment.end = PointReference::Navaid(id.to_string());
-                            }
-                        }
-                        _ => (),
-                    }
+            QName(b"aixm:series") => {
+                if let Some(series) = LevelSeries::parse(&read_text(reader, name)?) {
+                    availability.level_series = Some(series);
                 }
             }
             _ => (),
         }
     }
-    Ok(segment)
+    availability.lower_limit =
+        lower.and_then(|(text, uom)| VerticalLimit::parse(&text, uom.as_deref(), lower_reference.as_deref()));
+    availability.upper_limit =
+        upper.and_then(|(text, uom)| VerticalLimit::parse(&text, uom.as_deref(), upper_reference.as_deref()));
+    Ok(availability)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUTE_SEGMENT: &str = r#"<aixm:RouteSegment gml:id="seg-1">
+  <gml:identifier codeSpace="urn:uuid:">seg-1</gml:identifier>
+  <aixm:timeSlice>
+    <aixm:RouteSegmentTimeSlice>
+      <aixm:routeFormed xlink:href="urn:uuid:rte-un1"/>
+      <aixm:start>
+        <aixm:EnRouteSegmentPoint>
+          <aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-abc"/>
+        </aixm:EnRouteSegmentPoint>
+      </aixm:start>
+      <aixm:end>
+        <aixm:EnRouteSegmentPoint>
+          <aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-def"/>
+        </aixm:EnRouteSegmentPoint>
+      </aixm:end>
+      <aixm:availability>
+        <aixm:RouteAvailability>
+          <aixm:direction>FORWARD</aixm:direction>
+          <aixm:status>OPEN</aixm:status>
+          <aixm:levels>
+            <aixm:AirspaceLayer>
+              <aixm:upperLimit uom="FL">660</aixm:upperLimit>
+              <aixm:upperLimitReference>STD</aixm:upperLimitReference>
+              <aixm:lowerLimit uom="FL">245</aixm:lowerLimit>
+              <aixm:lowerLimitReference>STD</aixm:lowerLimitReference>
+              <aixm:discreteLevelSeries xlink:href="urn:uuid:odd" xlink:title="ODD"/>
+            </aixm:AirspaceLayer>
+          </aixm:levels>
+        </aixm:RouteAvailability>
+      </aixm:availability>
+      <aixm:availability>
+        <aixm:RouteAvailability>
+          <aixm:direction>BACKWARD</aixm:direction>
+          <aixm:levels>
+            <aixm:AirspaceLayer>
+              <aixm:upperLimit uom="OTHER">UNL</aixm:upperLimit>
+              <aixm:upperLimitReference>OTHER</aixm:upperLimitReference>
+              <aixm:lowerLimit uom="FT">2450</aixm:lowerLimit>
+              <aixm:lowerLimitReference>MSL</aixm:lowerLimitReference>
+              <aixm:discreteLevelSeries>
+                <aixm:StandardLevelColumn>
+                  <aixm:series>EVEN</aixm:series>
+                </aixm:StandardLevelColumn>
+              </aixm:discreteLevelSeries>
+            </aixm:AirspaceLayer>
+          </aixm:levels>
+        </aixm:RouteAvailability>
+      </aixm:availability>
+    </aixm:RouteSegmentTimeSlice>
+  </aixm:timeSlice>
+</aixm:RouteSegment>"#;
+
+    #[test]
+    fn test_parse_availability() {
+        let mut reader = Reader::from_str(ROUTE_SEGMENT);
+        find_node(&mut reader, vec![QName(b"aixm:RouteSegment")], None).unwrap();
+        let segment = parse_route_segment(&mut reader).unwrap();
+        assert_eq!(segment.identifier, "seg-1");
+        assert_eq!(segment.end, PointReference::DesignatedPoint("dp-def".into()));
+
+        let flight_level = |value| VerticalLimit::Level {
+            value,
+            uom: LimitUnit::Fl,
+            reference: LimitReference::Std,
+        };
+        let forward = RouteAvailability {
+            direction: RouteDirection::Forward,
+            lower_limit: Some(flight_level(245.)),
+            upper_limit: Some(flight_level(660.)),
+            level_series: Some(LevelSeries::Odd),
+        };
+        let backward = RouteAvailability {
+            direction: RouteDirection::Backward,
+            lower_limit: Some(VerticalLimit::Level {
+                value: 2450.,
+                uom: LimitUnit::Ft,
+                reference: LimitReference::Msl,
+            }),
+            upper_limit: Some(VerticalLimit::Unlimited),
+            level_series: Some(LevelSeries::Even),
+        };
+        assert_eq!(segment.availability, [forward.clone(), backward]);
+        // The segment spans the limits of both directions
+        assert_eq!(segment.direction(), Some(RouteDirection::Both));
+        assert_eq!(
+            segment.lower_limit().map(|limit| limit.to_string()).as_deref(),
+            Some("2450 FT MSL")
+        );
+        assert_eq!(
+            segment.upper_limit().map(|limit| limit.to_string()).as_deref(),
+            Some("UNL")
+        );
+        assert_eq!(flight_level(245.).to_string(), "FL245");
+        assert!(forward.applies_to(RouteDirection::Forward) && !forward.applies_to(RouteDirection::Backward));
+        assert!(forward.contains(350.) && !forward.contains(200.));
+        let series = |levels: &[u16]| {
+            levels
+                .iter()
+                .map(|&fl| LevelSeries::of_flight_level(fl))
+                .collect::<Vec<_>>()
+        };
+        let odd = Some(LevelSeries::Odd);
+        let even = Some(LevelSeries::Even);
+        assert_eq!(series(&[10, 290, 350, 410, 450, 490]), [odd; 6]);
+        assert_eq!(series(&[20, 300, 340, 400, 430, 470]), [even; 6]);
+        assert_eq!(series(&[0, 355, 420, 440, 460, 480]), [None; 6]);
+    }
+
+    #[test]
+    fn test_parse_inline_coordinates() {
+        let xml = ROUTE_SEGMENT.replace(
+            r#"<aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-def"/>"#,
+            r#"<aixm:pointChoice_position>
+            <aixm:Point>
+              <gml:pos>45.5 2.25</gml:pos>
+            </aixm:Point>
+          </aixm:pointChoice_position>"#,
+        );
+        let mut reader = Reader::from_str(&xml);
+        find_node(&mut reader, vec![QName(b"aixm:RouteSegment")], None).unwrap();
+        let segment = parse_route_segment(&mut reader).unwrap();
+        assert_eq!(segment.start, PointReference::DesignatedPoint("dp-abc".into()));
+        assert_eq!(segment.end, PointReference::Coordinates(45.5, 2.25));
+        assert_eq!(segment.end.id(), None);
+        // The rest of the segment is still parsed
+        assert_eq!(segment.availability.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_limit_reference() {
+        // FL245 and 2450 ft above mean sea level are different limits
+        let fl245 = VerticalLimit::parse("245", Some("FL"), Some("STD")).unwrap();
+        let msl = VerticalLimit::parse("2450", Some("FT"), Some("MSL")).unwrap();
+        assert_eq!((fl245.flight_level(), msl.flight_level()), (245., 24.5));
+        assert!(matches!(
+            msl,
+            VerticalLimit::Level {
+                reference: LimitReference::Msl,
+                ..
+            }
+        ));
+        // Flight levels default to standard pressure
+        assert_eq!(VerticalLimit::parse("245", Some("FL"), None), Some(fl245));
+
+        let height = VerticalLimit::parse("1000", Some("FT"), Some("SFC")).unwrap();
+        assert!(matches!(
+            height,
+            VerticalLimit::Level {
+                reference: LimitReference::Sfc,
+                ..
+            }
+        ));
+        assert_eq!(
+            VerticalLimit::parse("GND", None, Some("SFC")),
+            Some(VerticalLimit::Surface)
+        );
+        assert_eq!(
+            VerticalLimit::parse("UNL", Some("OTHER"), None),
+            Some(VerticalLimit::Unlimited)
+        );
+        assert_eq!(VerticalLimit::Unlimited.flight_level(), f64::INFINITY);
+        assert_eq!(VerticalLimit::parse("12", Some("NM"), None), None);
+        assert_eq!(VerticalLimit::parse("abc", Some("FL"), None), None);
+
+        let availability = RouteAvailability {
+            lower_limit: Some(VerticalLimit::Surface),
+            upper_limit: Some(fl245),
+            ..Default::default()
+        };
+        assert!(availability.contains(245.) && !availability.contains(250.));
+    }
 }
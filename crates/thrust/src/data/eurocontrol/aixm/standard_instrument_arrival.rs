@@ -4,14 +4,18 @@ use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::BufReader;
 use std::path::Path;
 use zip::read::ZipArchive;
 
+use crate::data::eurocontrol::aixm::identifier::GmlId;
+use crate::data::eurocontrol::aixm::instruction::{parse_instruction, InstructionStep};
 use crate::data::eurocontrol::aixm::route_segment::PointReference;
 use crate::data::eurocontrol::aixm::Node;
 
-use super::{find_node, read_text};
+use super::{
+    baseline_reader, find_node, identifier_fallback, log_parsed, parse_connecting_point, read_text, AixmEntries,
+    ConnectingPoint, DEFAULT_ENTRY_SUFFIX,
+};
 
 /// A Standard Arrival Route (STAR) instrument procedure.
 ///
@@ -25,6 +29,7 @@ use super::{find_node, read_text};
 /// - `airport_heliport`: Destination airport/heliport identifier
 /// - `instruction`: Operating procedure notes or restrictions
 /// - `connecting_points`: Sequence of waypoints and navaids defining the procedure
+/// - `connecting_routes`: Routes referenced by a connecting point instead of a single fix
 ///
 /// # Example
 /// ```ignore
@@ -33,8 +38,8 @@ use super::{find_node, read_text};
 ///     designator: "KSEA01".to_string(),
 ///     airport_heliport: Some("KSEA".to_string()),
 ///     connecting_points: vec![
-///         PointReference::Navaid("SEA".to_string()),
-///         PointReference::DesignatedPoint("APTIN".to_string()),
+///         PointReference::Navaid("SEA".into()),
+///         PointReference::DesignatedPoint("APTIN".into()),
 ///     ],
 ///     ..Default::default()
 /// };
@@ -47,27 +52,47 @@ pub struct StandardInstrumentArrival {
     pub airport_heliport: Option<String>,
     pub instruction: Option<String>,
     pub connecting_points: Vec<PointReference>,
+    #[serde(default)]
+    pub connecting_routes: Vec<String>,
+}
+
+impl StandardInstrumentArrival {
+    /// The altitude, heading and speed directives of the instruction, read on a
+    /// best-effort basis by [`parse_instruction`].
+    pub fn instruction_steps(&self) -> Vec<InstructionStep> {
+        self.instruction.as_deref().map(parse_instruction).unwrap_or_default()
+    }
 }
 
 pub fn parse_standard_instrument_arrival_zip_file<P: AsRef<Path>>(
     path: P,
-) -> Result<HashMap<String, StandardInstrumentArrival>, ThrustError> {
+) -> Result<HashMap<GmlId, StandardInstrumentArrival>, ThrustError> {
+    parse_standard_instrument_arrival_zip_file_with_suffix(path, DEFAULT_ENTRY_SUFFIX)
+}
+
+/// Like [`parse_standard_instrument_arrival_zip_file`], reading the archive entries whose name ends with
+/// `entry_suffix` (e.g. `.xml`) instead of [`DEFAULT_ENTRY_SUFFIX`].
+pub fn parse_standard_instrument_arrival_zip_file_with_suffix<P: AsRef<Path>>(
+    path: P,
+    entry_suffix: &str,
+) -> Result<HashMap<GmlId, StandardInstrumentArrival>, ThrustError> {
+    let path = path.as_ref();
     let file = File::open(path)?;
     let mut archive = ZipArchive::new(file)?;
+    let entries = AixmEntries::new(&archive, entry_suffix);
     let mut arrivals = HashMap::new();
 
     for i in 0..archive.len() {
-        let file = archive.by_index(i)?;
-        if file.name().ends_with(".BASELINE") {
-            let mut reader = Reader::from_reader(BufReader::new(file));
-
-            while let Ok(_node) = find_node(&mut reader, vec![QName(b"aixm:StandardInstrumentArrival")], None) {
-                let arrival = parse_standard_instrument_arrival(&mut reader)?;
-                arrivals.insert(arrival.identifier.clone(), arrival);
+        if let Some(mut reader) = baseline_reader(&mut archive, i, &entries)? {
+            while let Ok(node) = find_node(&mut reader, vec![QName(b"aixm:StandardInstrumentArrival")], None) {
+                let mut arrival = parse_standard_instrument_arrival(&mut reader)?;
+                identifier_fallback(&mut arrival.identifier, &node);
+                arrivals.insert(arrival.identifier.clone().into(), arrival);
             }
         }
     }
 
+    log_parsed("STARs", path, arrivals.len());
     Ok(arrivals)
 }
 
@@ -87,7 +112,11 @@ fn parse_standard_instrument_arrival<R: std::io::BufRead>(
         ],
         Some(QName(b"aixm:StandardInstrumentArrival")),
     ) {
-        let Node { name, attributes } = node;
+        let Node {
+            name,
+            attributes,
+            empty,
+        } = node;
         match name {
             QName(b"gml:identifier") => {
                 arrival.identifier = read_text(reader, name)?;
@@ -101,14 +130,16 @@ fn parse_standard_instrument_arrival<R: std::io::BufRead>(
             QName(b"aixm:instruction") => {
                 arrival.instruction = Some(read_text(reader, name)?);
             }
-            QName(b"aixm:extension") => {
+            QName(b"aixm:extension") if !empty => {
                 while let Ok(node) = find_node(
                     reader,
                     vec![QName(b"adrext:connectingPoint")],
                     Some(QName(b"aixm:extension")),
                 ) {
-                    if let Some(point) = parse_connecting_point(reader, node.name)? {
-                        arrival.connecting_points.push(point);
+                    match parse_connecting_point(reader, &node)? {
+                        Some(ConnectingPoint::Point(point)) => arrival.connecting_points.push(point),
+                        Some(ConnectingPoint::Route(route)) => arrival.connecting_routes.push(route),
+                        None => (),
                     }
                 }
             }
@@ -119,33 +150,6 @@ fn parse_standard_instrument_arrival<R: std::io::BufRead>(
     Ok(arrival)
 }
 
-fn parse_connecting_point<R: std::io::BufRead>(
-    reader: &mut Reader<R>,
-    end: QName,
-) -> Result<Option<PointReference>, ThrustError> {
-    while let Ok(node) = find_node(reader, vec![QName(b"aixm:TerminalSegmentPoint")], Some(end)) {
-        while let Ok(node) = find_node(
-            reader,
-            vec![
-                QName(b"aixm:pointChoice_fixDesignatedPoint"),
-                QName(b"aixm:pointChoice_navaidSystem"),
-            ],
-            Some(node.name),
-        ) {
-            let Node { name, attributes } = node;
-            if let Some(id) = extract_uuid_href(&attributes) {
-                return Ok(Some(match name {
-                    QName(b"aixm:pointChoice_fixDesignatedPoint") => PointReference::DesignatedPoint(id),
-                    QName(b"aixm:pointChoice_navaidSystem") => PointReference::Navaid(id),
-                    _ => PointReference::None,
-                }));
-            }
-        }
-    }
-
-    Ok(None)
-}
-
 fn extract_uuid_href(attributes: &HashMap<String, String>) -> Option<String> {
     attributes
         .get("xlink:href")
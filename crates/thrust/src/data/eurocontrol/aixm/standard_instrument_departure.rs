@@ -4,14 +4,18 @@ use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::BufReader;
 use std::path::Path;
 use zip::read::ZipArchive;
 
+use crate::data::eurocontrol::aixm::identifier::GmlId;
+use crate::data::eurocontrol::aixm::instruction::{parse_instruction, InstructionStep};
 use crate::data::eurocontrol::aixm::route_segment::PointReference;
 use crate::data::eurocontrol::aixm::Node;
 
-use super::{find_node, read_text};
+use super::{
+    baseline_reader, find_node, identifier_fallback, log_parsed, parse_connecting_point, read_text, AixmEntries,
+    ConnectingPoint, DEFAULT_ENTRY_SUFFIX,
+};
 
 /// A Standard Instrument Departure (SID) procedure.
 ///
@@ -25,6 +29,7 @@ use super::{find_node, read_text};
 /// - `airport_heliport`: Departure airport/heliport identifier
 /// - `instruction`: Operating procedure notes or restrictions
 /// - `connecting_points`: Sequence of waypoints and navaids defining the departure
+/// - `connecting_routes`: Routes referenced by a connecting point instead of a single fix
 ///
 /// # Example
 /// ```ignore
@@ -34,7 +39,7 @@ use super::{find_node, read_text};
 ///     airport_heliport: Some("KSEA".to_string()),
 ///     connecting_points: vec![
 ///         PointReference::Airport("KSEA".to_string()),
-///         PointReference::DesignatedPoint("KENRY".to_string()),
+///         PointReference::DesignatedPoint("KENRY".into()),
 ///     ],
 ///     ..Default::default()
 /// };
@@ -47,27 +52,47 @@ pub struct StandardInstrumentDeparture {
     pub airport_heliport: Option<String>,
     pub instruction: Option<String>,
     pub connecting_points: Vec<PointReference>,
+    #[serde(default)]
+    pub connecting_routes: Vec<String>,
+}
+
+impl StandardInstrumentDeparture {
+    /// The altitude, heading and speed directives of the instruction, read on a
+    /// best-effort basis by [`parse_instruction`].
+    pub fn instruction_steps(&self) -> Vec<InstructionStep> {
+        self.instruction.as_deref().map(parse_instruction).unwrap_or_default()
+    }
 }
 
 pub fn parse_standard_instrument_departure_zip_file<P: AsRef<Path>>(
     path: P,
-) -> Result<HashMap<String, StandardInstrumentDeparture>, ThrustError> {
+) -> Result<HashMap<GmlId, StandardInstrumentDeparture>, ThrustError> {
+    parse_standard_instrument_departure_zip_file_with_suffix(path, DEFAULT_ENTRY_SUFFIX)
+}
+
+/// Like [`parse_standard_instrument_departure_zip_file`], reading the archive entries whose name ends with
+/// `entry_suffix` (e.g. `.xml`) instead of [`DEFAULT_ENTRY_SUFFIX`].
+pub fn parse_standard_instrument_departure_zip_file_with_suffix<P: AsRef<Path>>(
+    path: P,
+    entry_suffix: &str,
+) -> Result<HashMap<GmlId, StandardInstrumentDeparture>, ThrustError> {
+    let path = path.as_ref();
     let file = File::open(path)?;
     let mut archive = ZipArchive::new(file)?;
-    let mut departures = HashMap::new();
+    let entries = AixmEntries::new(&archive, entry_suffix);
+    let mut departures: HashMap<GmlId, _> = HashMap::new();
 
     for i in 0..archive.len() {
-        let file = archive.by_index(i)?;
-        if file.name().ends_with(".BASELINE") {
-            let mut reader = Reader::from_reader(BufReader::new(file));
-
-            while let Ok(_node) = find_node(&mut reader, vec![QName(b"aixm:StandardInstrumentDeparture")], None) {
-                let departure = parse_standard_instrument_departure(&mut reader)?;
-                departures.insert(departure.identifier.clone(), departure);
+        if let Some(mut reader) = baseline_reader(&mut archive, i, &entries)? {
+            while let Ok(node) = find_node(&mut reader, vec![QName(b"aixm:StandardInstrumentDeparture")], None) {
+                let mut departure = parse_standard_instrument_departure(&mut reader)?;
+                identifier_fallback(&mut departure.identifier, &node);
+                departures.insert(departure.identifier.clone().into(), departure);
             }
         }
     }
 
+    log_parsed("SIDs", path, departures.len());
     Ok(departures)
 }
 
@@ -87,7 +112,11 @@ fn parse_standard_instrument_departure<R: std::io::BufRead>(
         ],
         Some(QName(b"aixm:StandardInstrumentDeparture")),
     ) {
-        let Node { name, attributes } = node;
+        let Node {
+            name,
+            attributes,
+            empty,
+        } = node;
         match name {
             QName(b"gml:identifier") => {
                 departure.identifier = read_text(reader, name)?;
@@ -101,14 +130,16 @@ fn parse_standard_instrument_departure<R: std::io::BufRead>(
             QName(b"aixm:instruction") => {
                 departure.instruction = Some(read_text(reader, name)?);
             }
-            QName(b"aixm:extension") => {
+            QName(b"aixm:extension") if !empty => {
                 while let Ok(node) = find_node(
                     reader,
                     vec![QName(b"adrext:connectingPoint")],
                     Some(QName(b"aixm:extension")),
                 ) {
-                    if let Some(point) = parse_connecting_point(reader, node.name)? {
-                        departure.connecting_points.push(point);
+                    match parse_connecting_point(reader, &node)? {
+                        Some(ConnectingPoint::Point(point)) => departure.connecting_points.push(point),
+                        Some(ConnectingPoint::Route(route)) => departure.connecting_routes.push(route),
+                        None => (),
                     }
                 }
             }
@@ -119,35 +150,123 @@ fn parse_standard_instrument_departure<R: std::io::BufRead>(
     Ok(departure)
 }
 
-fn parse_connecting_point<R: std::io::BufRead>(
-    reader: &mut Reader<R>,
-    end: QName,
-) -> Result<Option<PointReference>, ThrustError> {
-    while let Ok(node) = find_node(reader, vec![QName(b"aixm:TerminalSegmentPoint")], Some(end)) {
-        while let Ok(node) = find_node(
-            reader,
-            vec![
-                QName(b"aixm:pointChoice_fixDesignatedPoint"),
-                QName(b"aixm:pointChoice_navaidSystem"),
-            ],
-            Some(node.name),
-        ) {
-            let Node { name, attributes } = node;
-            if let Some(id) = extract_uuid_href(&attributes) {
-                return Ok(Some(match name {
-                    QName(b"aixm:pointChoice_fixDesignatedPoint") => PointReference::DesignatedPoint(id),
-                    QName(b"aixm:pointChoice_navaidSystem") => PointReference::Navaid(id),
-                    _ => PointReference::None,
-                }));
-            }
-        }
-    }
-
-    Ok(None)
-}
-
 fn extract_uuid_href(attributes: &HashMap<String, String>) -> Option<String> {
     attributes
         .get("xlink:href")
         .map(|s| s.strip_prefix("urn:uuid:").unwrap_or(s).to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SID: &str = r#"<aixm:StandardInstrumentDeparture gml:id="sid-abc1a">
+  <gml:identifier codeSpace="urn:uuid:">sid-abc1a</gml:identifier>
+  <aixm:timeSlice>
+    <aixm:StandardInstrumentDepartureTimeSlice>
+      <aixm:designator>ABC1A</aixm:designator>
+      <aixm:airportHeliport xlink:href="urn:uuid:ahp-lfbo"/>
+      <aixm:extension>
+        <adrext:StandardInstrumentDepartureExtension>
+          <adrext:connectingPoint>
+            <aixm:TerminalSegmentPoint>
+              <aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-abc"/>
+            </aixm:TerminalSegmentPoint>
+          </adrext:connectingPoint>
+          <adrext:connectingPoint>
+            <adrext:route xlink:href="urn:uuid:rte-un871"/>
+          </adrext:connectingPoint>
+        </adrext:StandardInstrumentDepartureExtension>
+      </aixm:extension>
+    </aixm:StandardInstrumentDepartureTimeSlice>
+  </aixm:timeSlice>
+</aixm:StandardInstrumentDeparture>"#;
+
+    #[test]
+    fn test_connecting_point_referencing_route() {
+        let mut reader = Reader::from_str(SID);
+        find_node(&mut reader, vec![QName(b"aixm:StandardInstrumentDeparture")], None).unwrap();
+        let departure = parse_standard_instrument_departure(&mut reader).unwrap();
+        assert_eq!(departure.designator, "ABC1A");
+        assert_eq!(departure.airport_heliport.as_deref(), Some("ahp-lfbo"));
+        assert_eq!(
+            departure.connecting_points,
+            vec![PointReference::DesignatedPoint("dp-abc".into())]
+        );
+        assert_eq!(departure.connecting_routes, vec!["rte-un871".to_string()]);
+    }
+
+    fn sid(id: &str, extensions: &str) -> String {
+        format!(
+            r#"<aixm:StandardInstrumentDeparture gml:id="{id}">
+  <gml:identifier codeSpace="urn:uuid:">{id}</gml:identifier>
+  <aixm:timeSlice>
+    <aixm:StandardInstrumentDepartureTimeSlice>
+      <aixm:designator>{id}</aixm:designator>
+      {extensions}
+    </aixm:StandardInstrumentDepartureTimeSlice>
+  </aixm:timeSlice>
+</aixm:StandardInstrumentDeparture>"#
+        )
+    }
+
+    fn extension(connecting_points: &str) -> String {
+        format!(
+            "<aixm:extension><adrext:StandardInstrumentDepartureExtension>{connecting_points}\
+             </adrext:StandardInstrumentDepartureExtension></aixm:extension>"
+        )
+    }
+
+    #[test]
+    fn test_connecting_points_stay_within_their_procedure() {
+        let point = |choice: &str, id: &str| {
+            format!(
+                r#"<adrext:connectingPoint><aixm:TerminalSegmentPoint>
+                  <aixm:{choice} xlink:href="urn:uuid:{id}"/>
+                </aixm:TerminalSegmentPoint></adrext:connectingPoint>"#
+            )
+        };
+        let xml = [
+            sid(
+                "sid-empty",
+                &extension(
+                    "<adrext:connectingPoint/>\
+                     <adrext:connectingPoint><aixm:TerminalSegmentPoint/></adrext:connectingPoint>",
+                ),
+            ),
+            sid(
+                "sid-airport",
+                &extension(&point("pointChoice_airportReferencePoint", "ahp-lfbo")),
+            ),
+            sid(
+                "sid-twice",
+                &format!(
+                    "{}{}",
+                    extension(&point("pointChoice_fixDesignatedPoint", "dp-abc")),
+                    extension(&point("pointChoice_navaidSystem", "nav-tou"))
+                ),
+            ),
+        ]
+        .join("\n");
+
+        let mut reader = Reader::from_str(&xml);
+        let mut departures: HashMap<GmlId, _> = HashMap::new();
+        while find_node(&mut reader, vec![QName(b"aixm:StandardInstrumentDeparture")], None).is_ok() {
+            let departure = parse_standard_instrument_departure(&mut reader).unwrap();
+            departures.insert(departure.identifier.clone().into(), departure);
+        }
+        assert_eq!(departures.len(), 3);
+        assert!(departures["sid-empty"].connecting_points.is_empty());
+        assert_eq!(
+            departures["sid-airport"].connecting_points,
+            vec![PointReference::AirportHeliport("ahp-lfbo".into())]
+        );
+        assert_eq!(
+            departures["sid-twice"].connecting_points,
+            vec![
+                PointReference::DesignatedPoint("dp-abc".into()),
+                PointReference::Navaid("nav-tou".into())
+            ]
+        );
+    }
+}
@@ -0,0 +1,251 @@
+//! Zero-copy archives of the navaids and designated points of an [`AirwayDatabase`].
+//!
+//! An alternative to the [point index](super::points), behind the `rkyv` feature:
+//! points are written with [`AirwayDatabase::save_archive`] as an [rkyv] archive,
+//! then memory-mapped by [`AirwayDatabase::load_archive`]. The archive is validated
+//! once when it is opened, then used in place: loading costs little more than
+//! reading the file, and records are only deserialised when they are looked up.
+//!
+//! The archive holds navaids sorted by uppercase name, designated points sorted by
+//! uppercase designator, and for each of them the positions of the records sorted
+//! by identifier, so that all lookups are binary searches.
+//!
+//! [`AirwayDatabase`]: crate::data::eurocontrol::database::AirwayDatabase
+//! [`AirwayDatabase::save_archive`]: crate::data::eurocontrol::database::AirwayDatabase::save_archive
+//! [`AirwayDatabase::load_archive`]: crate::data::eurocontrol::database::AirwayDatabase::load_archive
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use rkyv::rancor::{self, Panic};
+use rkyv::util::AlignedVec;
+
+use crate::data::eurocontrol::aixm::designated_point::{ArchivedDesignatedPoint, DesignatedPoint};
+use crate::data::eurocontrol::aixm::navaid::{ArchivedNavaid, Navaid};
+use crate::error::ThrustError;
+
+#[derive(rkyv::Archive, rkyv::Serialize)]
+struct Points {
+    navaids: Vec<Navaid>,
+    designated_points: Vec<DesignatedPoint>,
+    navaid_order: Vec<u32>,
+    designated_point_order: Vec<u32>,
+}
+
+/// The bytes of an archive, read in memory or memory-mapped.
+#[derive(Debug)]
+enum ArchiveBytes {
+    Owned(AlignedVec),
+    Mapped(memmap2::Mmap),
+}
+
+impl std::ops::Deref for ArchiveBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            ArchiveBytes::Owned(bytes) => bytes,
+            ArchiveBytes::Mapped(mmap) => mmap,
+        }
+    }
+}
+
+/// A validated point archive, as described in the [module documentation](self).
+#[derive(Debug)]
+pub struct PointArchive {
+    bytes: ArchiveBytes,
+}
+
+impl PointArchive {
+    /// Memory-map a point archive file.
+    ///
+    /// The file must not be modified while it is mapped.
+    pub fn open(path: &Path) -> Result<Self, ThrustError> {
+        let file = File::open(path)?;
+        // SAFETY: the archive is only read, and the file is not expected to change
+        // while the database is in use.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        Self::from_bytes(ArchiveBytes::Mapped(mmap))
+    }
+
+    /// Read a point archive from a byte buffer.
+    pub fn from_vec(bytes: Vec<u8>) -> Result<Self, ThrustError> {
+        let mut aligned = AlignedVec::with_capacity(bytes.len());
+        aligned.extend_from_slice(&bytes);
+        Self::from_bytes(ArchiveBytes::Owned(aligned))
+    }
+
+    fn from_bytes(bytes: ArchiveBytes) -> Result<Self, ThrustError> {
+        let invalid = |msg: &str| ThrustError::InvalidData(format!("Invalid point archive: {msg}"));
+        let points = rkyv::access::<ArchivedPoints, rancor::Error>(&bytes).map_err(|e| invalid(&e.to_string()))?;
+        let navaid_identifier = |i: usize| points.navaids[i].identifier.as_str();
+        let designated_point_identifier = |i: usize| points.designated_points[i].identifier.as_str();
+        if !ordered(&points.navaid_order, points.navaids.len(), navaid_identifier)
+            || !ordered(
+                &points.designated_point_order,
+                points.designated_points.len(),
+                designated_point_identifier,
+            )
+        {
+            return Err(invalid("bad identifier order"));
+        }
+        Ok(PointArchive { bytes })
+    }
+
+    /// Write the navaids and designated points to a point archive.
+    pub fn write<'a, W: Write>(
+        navaids: impl Iterator<Item = &'a Navaid>,
+        designated_points: impl Iterator<Item = &'a DesignatedPoint>,
+        writer: &mut W,
+    ) -> Result<(), ThrustError> {
+        let mut navaids = navaids.cloned().collect::<Vec<_>>();
+        navaids.sort_by_cached_key(|n| n.name.as_deref().map(|name| name.trim().to_ascii_uppercase()));
+        let mut designated_points = designated_points.cloned().collect::<Vec<_>>();
+        designated_points.sort_by_cached_key(|dp| dp.designator.trim().to_ascii_uppercase());
+
+        let too_large = || ThrustError::InvalidData("Too many points for a point archive".to_string());
+        let order = |identifiers: Vec<&str>| -> Result<Vec<u32>, ThrustError> {
+            let mut order = (0..identifiers.len()).collect::<Vec<_>>();
+            order.sort_by_key(|&i| identifiers[i]);
+            order
+                .into_iter()
+                .map(|i| u32::try_from(i).map_err(|_| too_large()))
+                .collect()
+        };
+        let points = Points {
+            navaid_order: order(navaids.iter().map(|n| n.identifier.as_str()).collect())?,
+            designated_point_order: order(designated_points.iter().map(|dp| dp.identifier.as_str()).collect())?,
+            navaids,
+            designated_points,
+        };
+        let bytes = rkyv::to_bytes::<rancor::Error>(&points).map_err(|e| ThrustError::InvalidData(e.to_string()))?;
+        writer.write_all(&bytes)?;
+        Ok(())
+    }
+
+    fn points(&self) -> &ArchivedPoints {
+        // SAFETY: the bytes were validated when the archive was opened, and are
+        // not modified since.
+        unsafe { rkyv::access_unchecked::<ArchivedPoints>(&self.bytes) }
+    }
+
+    pub(crate) fn navaid_count(&self) -> usize {
+        self.points().navaids.len()
+    }
+
+    pub(crate) fn designated_point_count(&self) -> usize {
+        self.points().designated_points.len()
+    }
+
+    pub(crate) fn navaid_at(&self, i: usize) -> Navaid {
+        deserialize_navaid(&self.points().navaids[i])
+    }
+
+    pub(crate) fn designated_point_at(&self, i: usize) -> DesignatedPoint {
+        deserialize_designated_point(&self.points().designated_points[i])
+    }
+
+    /// The position of the navaid with the given identifier, by binary search.
+    pub(crate) fn find_navaid(&self, identifier: &str) -> Option<usize> {
+        let points = self.points();
+        find_identifier(
+            &points.navaid_order,
+            |i| points.navaids[i].identifier.as_str(),
+            identifier,
+        )
+    }
+
+    /// The position of the designated point with the given identifier, by binary search.
+    pub(crate) fn find_designated_point(&self, identifier: &str) -> Option<usize> {
+        let points = self.points();
+        find_identifier(
+            &points.designated_point_order,
+            |i| points.designated_points[i].identifier.as_str(),
+            identifier,
+        )
+    }
+
+    /// The navaids named `name` (case-insensitive, ignoring surrounding spaces in names).
+    pub(crate) fn navaids_named(&self, name: &str) -> Vec<Navaid> {
+        let name = name.to_ascii_uppercase();
+        let navaids = &self.points().navaids;
+        let key = |n: &ArchivedNavaid| n.name.as_ref().map(|key| key.trim().to_ascii_uppercase());
+        let start = navaids.partition_point(|n| key(n).as_deref() < Some(name.as_str()));
+        navaids[start..]
+            .iter()
+            .take_while(|n| key(n).as_deref() == Some(name.as_str()))
+            .map(deserialize_navaid)
+            .collect()
+    }
+
+    /// The designated points with designator `name` (case-insensitive, ignoring
+    /// surrounding spaces in designators).
+    pub(crate) fn designated_points_named(&self, name: &str) -> Vec<DesignatedPoint> {
+        let name = name.to_ascii_uppercase();
+        let designated_points = &self.points().designated_points;
+        let key = |dp: &ArchivedDesignatedPoint| dp.designator.trim().to_ascii_uppercase();
+        let start = designated_points.partition_point(|dp| key(dp) < name);
+        designated_points[start..]
+            .iter()
+            .take_while(|dp| key(dp) == name)
+            .map(deserialize_designated_point)
+            .collect()
+    }
+}
+
+/// Whether `order` holds the position of each of `len` records, sorted by
+/// identifier, as expected by the binary searches of [`find_identifier`].
+fn ordered<'a>(order: &[rkyv::rend::u32_le], len: usize, identifier_at: impl Fn(usize) -> &'a str) -> bool {
+    order.len() == len
+        && order.iter().all(|&i| (i.to_native() as usize) < len)
+        && order
+            .windows(2)
+            .all(|w| identifier_at(w[0].to_native() as usize) <= identifier_at(w[1].to_native() as usize))
+}
+
+fn find_identifier<'a>(
+    order: &[rkyv::rend::u32_le],
+    identifier_at: impl Fn(usize) -> &'a str,
+    identifier: &str,
+) -> Option<usize> {
+    let k = order.partition_point(|i| identifier_at(i.to_native() as usize) < identifier);
+    let i = order.get(k)?.to_native() as usize;
+    (identifier_at(i) == identifier).then_some(i)
+}
+
+fn deserialize_navaid(navaid: &ArchivedNavaid) -> Navaid {
+    rkyv::deserialize::<Navaid, Panic>(navaid).unwrap_or_else(|never| match never {})
+}
+
+fn deserialize_designated_point(dp: &ArchivedDesignatedPoint) -> DesignatedPoint {
+    rkyv::deserialize::<DesignatedPoint, Panic>(dp).unwrap_or_else(|never| match never {})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn navaid(identifier: &str) -> Navaid {
+        Navaid {
+            identifier: identifier.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_reject_unsorted_order() {
+        let archive = |navaid_order| {
+            let points = Points {
+                navaids: vec![navaid("n-1"), navaid("n-2")],
+                designated_points: Vec::new(),
+                navaid_order,
+                designated_point_order: Vec::new(),
+            };
+            PointArchive::from_vec(rkyv::to_bytes::<rancor::Error>(&points).unwrap().to_vec())
+        };
+        assert!(archive(vec![0, 1]).is_ok());
+        assert!(archive(vec![1, 0]).is_err());
+        assert!(archive(vec![0, 2]).is_err());
+    }
+}
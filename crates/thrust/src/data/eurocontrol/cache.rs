@@ -0,0 +1,310 @@
+//! Caching of enriched routes.
+//!
+//! Operational clients often submit the same flight plan routes many times a
+//! day. [`CachedEnricher`] keeps the most recently enriched routes in memory, in
+//! front of the database, and drops them all whenever the database is reloaded or
+//! its overlay replaced. The cache of the database itself, if enabled with
+//! [`AirwayDatabase::with_cache`], is bypassed so that routes are only cached once.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use serde::Serialize;
+
+use crate::data::eurocontrol::database::{AirwayDatabase, Dataset, EnrichmentReport, HeapSize, ResolvedRouteSegment};
+use crate::data::eurocontrol::overlay::OverlayData;
+use crate::data::field15::Field15Parser;
+use crate::error::ThrustError;
+
+/// A database shared between threads, which may be reloaded while serving requests.
+pub type SharedDatabase = Arc<RwLock<AirwayDatabase>>;
+
+/// Default maximum number of cached routes.
+pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+/// Default maximum (estimated) memory used by cached routes, in bytes.
+pub const DEFAULT_MAX_BYTES: usize = 64 << 20;
+
+/// Hit and miss counters, and the current content of a [`CachedEnricher`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Number of cached routes
+    pub entries: usize,
+    /// Estimated memory used by cached routes, in bytes
+    pub bytes: usize,
+}
+
+type Enriched = (Vec<ResolvedRouteSegment>, EnrichmentReport);
+
+#[derive(Debug)]
+struct CacheEntry {
+    enriched: Enriched,
+    bytes: usize,
+    last_used: u64,
+}
+
+/// A least recently used cache of enriched routes, bounded by entry count and size.
+#[derive(Debug, Default)]
+struct RouteCache {
+    entries: HashMap<String, CacheEntry>,
+    /// Keys by last use, the least recently used first
+    recency: BTreeMap<u64, String>,
+    bytes: usize,
+    clock: u64,
+    /// Incremented whenever the cache is cleared
+    generation: u64,
+}
+
+impl RouteCache {
+    fn get(&mut self, key: &str) -> Option<Enriched> {
+        let entry = self.entries.get_mut(key)?;
+        self.clock += 1;
+        let key = self.recency.remove(&entry.last_used)?;
+        entry.last_used = self.clock;
+        self.recency.insert(self.clock, key);
+        Some(entry.enriched.clone())
+    }
+
+    fn insert(&mut self, key: String, enriched: Enriched, max_entries: usize, max_bytes: usize) {
+        let bytes = key.len() + std::mem::size_of::<Enriched>() + enriched.0.heap_size() + enriched.1.heap_size();
+        if bytes > max_bytes || max_entries == 0 {
+            return;
+        }
+        if let Some(previous) = self.entries.remove(&key) {
+            self.recency.remove(&previous.last_used);
+            self.bytes -= previous.bytes;
+        }
+        while self.entries.len() >= max_entries || self.bytes + bytes > max_bytes {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            if let Some(evicted) = self.entries.remove(&oldest) {
+                self.bytes -= evicted.bytes;
+            }
+        }
+        self.clock += 1;
+        self.recency.insert(self.clock, key.clone());
+        self.bytes += bytes;
+        self.entries.insert(
+            key,
+            CacheEntry {
+                enriched,
+                bytes,
+                last_used: self.clock,
+            },
+        );
+    }
+}
+
+/// A thread-safe LRU cache of enriched routes, with hit and miss counters.
+#[derive(Debug)]
+pub(crate) struct SharedRouteCache {
+    cache: Mutex<RouteCache>,
+    max_entries: usize,
+    max_bytes: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl SharedRouteCache {
+    pub(crate) fn new(max_entries: usize, max_bytes: usize) -> Self {
+        SharedRouteCache {
+            cache: Mutex::new(RouteCache::default()),
+            max_entries,
+            max_bytes,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The cached result for `key`, or the result of `enrich` which is then cached.
+    ///
+    /// The lock is not held while enriching, so that other threads are not blocked.
+    /// The result is not cached if the cache was cleared in the meantime, since
+    /// it may have been computed from the data before a reload.
+    pub(crate) fn get_or_insert_with(&self, key: String, enrich: impl FnOnce() -> Enriched) -> Enriched {
+        let generation = {
+            let mut cache = self.cache.lock().unwrap();
+            if let Some(enriched) = cache.get(&key) {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return enriched;
+            }
+            cache.generation
+        };
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let enriched = enrich();
+        let mut cache = self.cache.lock().unwrap();
+        if cache.generation == generation {
+            cache.insert(key, enriched.clone(), self.max_entries, self.max_bytes);
+        }
+        enriched
+    }
+
+    /// Drop all cached routes. Hit and miss counters are kept.
+    pub(crate) fn clear(&self) {
+        let mut cache = self.cache.lock().unwrap();
+        let generation = cache.generation + 1;
+        *cache = RouteCache {
+            generation,
+            ..RouteCache::default()
+        };
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        let cache = self.cache.lock().unwrap();
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: cache.entries.len(),
+            bytes: cache.bytes,
+        }
+    }
+}
+
+/// Enrich field 15 routes through an LRU cache keyed by the normalised route string.
+///
+/// Routes differing only by whitespace or letter case share the same entry.
+/// The cache is cleared, while the database is still locked for writing, by
+/// [`reload`](Self::reload), [`reload_dataset`](Self::reload_dataset) and
+/// [`set_overlay`](Self::set_overlay), and routes enriched before but completed
+/// after are not cached. Changes made through [`database`](Self::database) are
+/// told apart by the [revision](AirwayDatabase::revision) of the database.
+///
+/// # Example
+/// ```ignore
+/// let enricher = CachedEnricher::new(Arc::new(RwLock::new(db)));
+/// let (segments, report) = enricher.enrich_route_with_report("N0450F350 LACOU UM184 CNA");
+/// ```
+pub struct CachedEnricher {
+    database: SharedDatabase,
+    cache: SharedRouteCache,
+}
+
+impl CachedEnricher {
+    /// Wrap a database with a cache of default capacity.
+    pub fn new(database: SharedDatabase) -> Self {
+        Self::with_capacity(database, DEFAULT_MAX_ENTRIES, DEFAULT_MAX_BYTES)
+    }
+
+    /// Wrap a database with a cache bounded by entry count and estimated size in bytes.
+    pub fn with_capacity(database: SharedDatabase, max_entries: usize, max_bytes: usize) -> Self {
+        CachedEnricher {
+            database,
+            cache: SharedRouteCache::new(max_entries, max_bytes),
+        }
+    }
+
+    /// The underlying database.
+    pub fn database(&self) -> &SharedDatabase {
+        &self.database
+    }
+
+    /// Parse and enrich a field 15 route, reusing a previous result for the same route
+    /// and the same [revision](AirwayDatabase::revision) of the database.
+    pub fn enrich_route_with_report(&self, route: &str) -> (Vec<ResolvedRouteSegment>, EnrichmentReport) {
+        let route = normalise_route(route);
+        let elements = Field15Parser::parse(&route);
+        let database = self.database.read().unwrap();
+        let key = format!("{}\n{}", database.revision(), route);
+        self.cache
+            .get_or_insert_with(key, || database.enrich_uncached(&elements))
+    }
+
+    /// Reload all datasets from the specified directory path, and clear the cache.
+    pub fn reload(&self, path: &Path) -> Result<(), ThrustError> {
+        let mut database = self.database.write().unwrap();
+        let result = Dataset::ALL
+            .iter()
+            .try_for_each(|&dataset| database.reload_dataset(path, dataset));
+        self.clear();
+        result
+    }
+
+    /// Reload a single dataset from the specified directory path, and clear the cache.
+    pub fn reload_dataset(&self, path: &Path, dataset: Dataset) -> Result<(), ThrustError> {
+        let mut database = self.database.write().unwrap();
+        let result = database.reload_dataset(path, dataset);
+        self.clear();
+        result
+    }
+
+    /// Replace the overlay of the database, or remove it with `None`, and clear the cache.
+    pub fn set_overlay(&self, overlay: Option<OverlayData>) {
+        let mut database = self.database.write().unwrap();
+        database.set_overlay(overlay);
+        self.clear();
+    }
+
+    /// Drop all cached routes. Hit and miss counters are kept.
+    pub fn clear(&self) {
+        self.cache.clear();
+    }
+
+    /// Hit and miss counters, and the current size of the cache.
+    pub fn stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+}
+
+/// Normalise a route string: uppercase, with single spaces between tokens.
+pub(crate) fn normalise_route(route: &str) -> String {
+    route.split_whitespace().collect::<Vec<_>>().join(" ").to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::eurocontrol::database::UnresolvedToken;
+
+    fn enriched(n: usize) -> Enriched {
+        (Vec::with_capacity(n), EnrichmentReport::default())
+    }
+
+    #[test]
+    fn test_route_cache_eviction() {
+        let mut cache = RouteCache::default();
+        cache.insert("A".to_string(), enriched(0), 2, usize::MAX);
+        cache.insert("B".to_string(), enriched(0), 2, usize::MAX);
+        assert!(cache.get("A").is_some());
+        // B is the least recently used entry
+        cache.insert("C".to_string(), enriched(0), 2, usize::MAX);
+        assert!(cache.get("B").is_none());
+        assert!(cache.get("A").is_some() && cache.get("C").is_some());
+        assert_eq!(cache.entries.len(), cache.recency.len());
+
+        let bytes = cache.bytes;
+        cache.insert("D".to_string(), enriched(0), 10, bytes);
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.bytes <= bytes);
+
+        // Heap data, e.g. the suggestions for an unresolved point, is accounted for
+        let (segments, mut report) = enriched(0);
+        report.unresolved.push(UnresolvedToken {
+            index: 0,
+            token: "ABCDE".to_string(),
+            suggestions: vec!["X".repeat(1000)],
+            hint: None,
+        });
+        let bytes = cache.bytes;
+        cache.insert("E".to_string(), (segments, report), 10, usize::MAX);
+        assert!(cache.bytes > bytes + 1000);
+        assert_eq!(normalise_route(" n0450f350  abc\tDCT def "), "N0450F350 ABC DCT DEF");
+    }
+
+    #[test]
+    fn test_clear_while_enriching() {
+        let cache = SharedRouteCache::new(10, usize::MAX);
+        // e.g. the database is reloaded while the route is enriched from the previous data
+        cache.get_or_insert_with("A".to_string(), || {
+            cache.clear();
+            enriched(0)
+        });
+        assert_eq!(cache.stats().entries, 0);
+        cache.get_or_insert_with("A".to_string(), || enriched(0));
+        assert_eq!(cache.stats().entries, 1);
+    }
+}
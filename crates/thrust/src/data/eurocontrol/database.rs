@@ -1,29 +1,60 @@
 //! EUROCONTROL airway database handling.
 //!
 //! This module provides functionality to load and query an airway database
+//!
+//! # Logging
+//!
+//! Events are emitted with [`tracing`] under explicit targets, so that each part
+//! can be filtered on its own with `RUST_LOG` (or any `EnvFilter`):
+//!
+//! - `thrust::aixm`: loading and validation of the AIXM datasets, with one info
+//!   event per parsed file and warnings about inconsistent data;
+//! - `thrust::lookup`: warnings about names which cannot be looked up outside of
+//!   route enrichment;
+//! - `thrust::enrich`: route enrichment, with warnings about what could not be
+//!   resolved as filed, disambiguation choices at debug level and candidate
+//!   scores at trace level.
+//!
+//! A successful enrichment emits nothing at info level or above. Recommended
+//! filters are `RUST_LOG=thrust=info` in production, and e.g.
+//! `RUST_LOG=thrust::enrich=debug` or `RUST_LOG=thrust::enrich=trace` to
+//! understand why a route was resolved the way it was.
 
 use std::hash::Hash;
+use std::sync::{Arc, LazyLock, OnceLock};
 use std::{collections::HashMap, path};
 
 use geodesy::prelude::*;
-use once_cell::sync::Lazy;
 use serde::Serialize;
 
-use crate::data::field15::{Connector, Field15Element, Modifier, Point};
+use crate::data::airac::effective_date_from_airac_code;
+#[cfg(feature = "rkyv")]
+use crate::data::eurocontrol::archive::PointArchive;
+use crate::data::eurocontrol::cache::{normalise_route, CacheStats, SharedRouteCache, DEFAULT_MAX_BYTES};
+use crate::data::eurocontrol::events::{EnrichmentEvent, EnrichmentSink, EventLog, EventOutcome};
+use crate::data::eurocontrol::geometry::{self, LegGeometry};
+use crate::data::eurocontrol::overlay::{Overlay, OverlayData};
+use crate::data::eurocontrol::points::{PointIndex, PointStore};
+use crate::data::eurocontrol::spatial::SegmentIndex;
+use crate::data::field15::{Connector, Field15Element, Field15Parser, LevelConstraint, Point};
 use crate::data::{
     eurocontrol::aixm::{
         airport_heliport::{parse_airport_heliport_zip_file, AirportHeliport},
         arrival_leg::{parse_arrival_leg_zip_file, ArrivalLeg},
         departure_leg::{parse_departure_leg_zip_file, DepartureLeg},
-        designated_point::{parse_designated_point_zip_file, DesignatedPoint},
+        designated_point::{parse_designated_point_zip_file, DesignatedPoint, DesignatedPointType, ProcedureFixRole},
+        identifier::{Designator, GmlId},
         navaid::{parse_navaid_zip_file, Navaid},
-        route::{parse_route_zip_file, Route},
-        route_segment::{parse_route_segment_zip_file, PointReference, RouteSegment},
+        route::{parse_route_zip_file, Route, RouteDesignator},
+        route_segment::{
+            parse_route_segment_zip_file, LevelSeries, PointReference, RouteAvailability, RouteDirection, RouteSegment,
+        },
         standard_instrument_arrival::{parse_standard_instrument_arrival_zip_file, StandardInstrumentArrival},
         standard_instrument_departure::{parse_standard_instrument_departure_zip_file, StandardInstrumentDeparture},
     },
     field15::{Altitude, Speed},
 };
+use crate::distance::{DistanceModel, NAUTICAL_MILE};
 use crate::error::ThrustError;
 
 /// A compiled EUROCONTROL navigational database for querying routes and procedures.
@@ -46,56 +77,1190 @@ use crate::error::ThrustError;
 /// ```
 #[derive(Debug)]
 pub struct AirwayDatabase {
-    airports: HashMap<String, AirportHeliport>,
-    navaids: HashMap<String, Navaid>,
-    designated_points: HashMap<String, DesignatedPoint>,
-    route_segments: HashMap<String, RouteSegment>,
-    routes: HashMap<String, Route>,
-    arrival_legs: HashMap<String, ArrivalLeg>,
-    departure_legs: HashMap<String, DepartureLeg>,
-    standard_instrument_arrivals: HashMap<String, StandardInstrumentArrival>,
-    standard_instrument_departures: HashMap<String, StandardInstrumentDeparture>,
+    airports: HashMap<GmlId, AirportHeliport>,
+    /// Navaids and designated points, in memory or memory-mapped from a point index
+    points: PointStore,
+    /// Routes and route segments, parsed on first use in lazy mode
+    airways: OnceLock<Airways>,
+    /// Directory the airways are loaded from in lazy mode
+    airway_path: Option<path::PathBuf>,
+    airway_loader: AirwayLoader,
+    arrival_legs: HashMap<GmlId, ArrivalLeg>,
+    departure_legs: HashMap<GmlId, DepartureLeg>,
+    standard_instrument_arrivals: HashMap<GmlId, StandardInstrumentArrival>,
+    standard_instrument_departures: HashMap<GmlId, StandardInstrumentDeparture>,
+    /// Statistics are computed lazily and cached until a dataset is reloaded
+    stats: OnceLock<DatabaseStats>,
+    /// Designators sorted for completion, built on first use like statistics
+    completions: OnceLock<Vec<CompletionEntry>>,
+    /// Grid index of the route segments, built on first use like statistics
+    segment_index: OnceLock<SegmentIndex>,
+    distance_model: DistanceModel,
+    options: EnrichmentOptions,
+    point_type_preference: Vec<DesignatedPointType>,
+    /// AIRAC cycle of the loaded data (e.g. "2508"), if known
+    airac_cycle: Option<String>,
+    /// Enriched routes, when caching is enabled with [`AirwayDatabase::with_cache`]
+    route_cache: Option<SharedRouteCache>,
+    /// Incremented whenever the result of route enrichment may change
+    revision: u64,
+    /// Supplementary points and airways, see [`AirwayDatabase::with_overlay`]
+    overlay: Option<Overlay>,
+}
+
+/// Routes and the route segments forming them.
+///
+/// Both datasets are always loaded together since segments are indexed into routes.
+#[derive(Debug, Default)]
+struct Airways {
+    routes: HashMap<GmlId, Route>,
+    route_segments: HashMap<GmlId, RouteSegment>,
+    /// Identifiers of the route segments joining two points, in both directions
+    segments_by_endpoints: HashMap<(PointReference, PointReference), Vec<GmlId>>,
+}
+
+impl Airways {
+    /// Parse the Route and RouteSegment datasets from the specified directory path.
+    fn load(path: &path::Path) -> Result<Self, ThrustError> {
+        let mut airways = Airways {
+            route_segments: parse_route_segment_zip_file(path.join(Dataset::RouteSegment.file_name()))?,
+            routes: parse_route_zip_file(path.join(Dataset::Route.file_name()))?,
+            segments_by_endpoints: HashMap::new(),
+        };
+        airways.index_route_segments();
+        Ok(airways)
+    }
+
+    /// Fill in the list of segment identifiers forming each route.
+    fn index_route_segments(&mut self) {
+        for route in self.routes.values_mut() {
+            route.segments.clear();
+        }
+        for segment in self.route_segments.values() {
+            if let Some(route) = segment
+                .route_formed
+                .as_ref()
+                .and_then(|id| self.routes.get_mut(id.as_str()))
+            {
+                route.segments.push(segment.identifier.clone());
+            }
+        }
+        for route in self.routes.values_mut() {
+            route.segments.sort();
+        }
+        self.segments_by_endpoints.clear();
+        for segment in self.route_segments.values() {
+            for endpoints in [
+                (segment.start.clone(), segment.end.clone()),
+                (segment.end.clone(), segment.start.clone()),
+            ] {
+                self.segments_by_endpoints
+                    .entry(endpoints)
+                    .or_default()
+                    .push(segment.identifier.clone().into());
+            }
+        }
+        for segments in self.segments_by_endpoints.values_mut() {
+            segments.sort();
+            segments.dedup();
+        }
+    }
+
+    /// The route segments joining `a` and `b`, in either direction, by identifier.
+    fn segments_between(&self, a: &PointReference, b: &PointReference) -> impl Iterator<Item = &RouteSegment> {
+        self.segments_by_endpoints
+            .get(&(a.clone(), b.clone()))
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.route_segments.get(id))
+    }
+}
+
+type AirwayLoader = fn(&path::Path) -> Result<Airways, ThrustError>;
+
+/// Designated point types preferred by default when several types share a designator.
+const DEFAULT_POINT_TYPE_PREFERENCE: [DesignatedPointType; 2] =
+    [DesignatedPointType::Icao, DesignatedPointType::Terminal];
+
+/// Options controlling how [`AirwayDatabase::enrich_route`] resolves routes.
+///
+/// All options are disabled by default, and no event is recorded.
+#[derive(Debug, Clone, Default)]
+pub struct EnrichmentOptions {
+    /// Substitute another airway when the filed one does not connect its
+    /// bracketing points, instead of falling back to a direct leg
+    pub airway_substitution: bool,
+    /// Merge airways published as several `Route` records under the same
+    /// designator (e.g. one record per geographic portion) into a single airway
+    pub merge_split_airways: bool,
+    /// How to choose among several airway candidates connecting the same points
+    pub airway_selection: AirwaySelection,
+    /// Keep an airway whose bracketing points are not both resolved, from the
+    /// resolved one to the nearer end of the airway, instead of a direct leg
+    pub keep_partial_airways: bool,
+    /// Look up designated points only when no navaid matches a name, as in earlier
+    /// versions, instead of disambiguating among navaids and designated points
+    /// together (see [`ResolvedPoint::lookup`])
+    pub navaid_priority: bool,
+    /// Leave VFR reporting points out of point lookups (see
+    /// [`DesignatedPointType::is_vfr_reporting_point`]), e.g. for IFR traffic
+    /// where their short names may shadow other fixes
+    pub exclude_vfr_reporting_points: bool,
+    /// Bridge the discontinuities of a route with explicit gap segments (see
+    /// [`ResolvedRouteSegment::is_gap`]) instead of plain direct segments, or
+    /// none at all; gaps are listed in [`EnrichmentReport::gaps`] either way
+    pub emit_gaps: bool,
+    /// Receives the decisions taken on each enriched route, see
+    /// [`events`](crate::data::eurocontrol::events)
+    pub events: Option<Arc<dyn EnrichmentSink>>,
+}
+
+/// How [`AirwayDatabase::enrich_route`] chooses among several airway candidates
+/// which all connect the bracketing points of a filed airway.
+///
+/// Ties are broken by the identifier of the route records, so that the choice
+/// never depends on the order of the database maps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AirwaySelection {
+    /// The candidate with the most segments between the bracketing points
+    #[default]
+    MostSegments,
+    /// The candidate with the shortest path between the bracketing points,
+    /// i.e. the geographically closest to the direct track
+    Nearest,
+}
+
+/// The AIXM datasets composing an [`AirwayDatabase`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum Dataset {
+    AirportHeliport,
+    Navaid,
+    DesignatedPoint,
+    RouteSegment,
+    Route,
+    ArrivalLeg,
+    DepartureLeg,
+    StandardInstrumentArrival,
+    StandardInstrumentDeparture,
+}
+
+impl Dataset {
+    /// All datasets, in loading order.
+    pub const ALL: [Dataset; 9] = [
+        Dataset::AirportHeliport,
+        Dataset::Navaid,
+        Dataset::DesignatedPoint,
+        Dataset::RouteSegment,
+        Dataset::Route,
+        Dataset::ArrivalLeg,
+        Dataset::DepartureLeg,
+        Dataset::StandardInstrumentArrival,
+        Dataset::StandardInstrumentDeparture,
+    ];
+
+    /// The name of the dataset, as in the AIXM feature type.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dataset::AirportHeliport => "AirportHeliport",
+            Dataset::Navaid => "Navaid",
+            Dataset::DesignatedPoint => "DesignatedPoint",
+            Dataset::RouteSegment => "RouteSegment",
+            Dataset::Route => "Route",
+            Dataset::ArrivalLeg => "ArrivalLeg",
+            Dataset::DepartureLeg => "DepartureLeg",
+            Dataset::StandardInstrumentArrival => "StandardInstrumentArrival",
+            Dataset::StandardInstrumentDeparture => "StandardInstrumentDeparture",
+        }
+    }
+
+    /// The name of the baseline ZIP file containing the dataset.
+    pub fn file_name(&self) -> String {
+        format!("{}.BASELINE.zip", self.name())
+    }
+
+    /// Procedure datasets are optional and may be missing from the directory.
+    pub fn is_optional(&self) -> bool {
+        matches!(
+            self,
+            Dataset::ArrivalLeg
+                | Dataset::DepartureLeg
+                | Dataset::StandardInstrumentArrival
+                | Dataset::StandardInstrumentDeparture
+        )
+    }
 }
 
 impl AirwayDatabase {
     /// Load the airway database from the specified directory path.
     pub fn new(path: &path::Path) -> Result<Self, ThrustError> {
-        Ok(AirwayDatabase {
-            airports: parse_airport_heliport_zip_file(path.join("AirportHeliport.BASELINE.zip"))?,
-            navaids: parse_navaid_zip_file(path.join("Navaid.BASELINE.zip"))?,
-            designated_points: parse_designated_point_zip_file(path.join("DesignatedPoint.BASELINE.zip"))?,
-            route_segments: parse_route_segment_zip_file(path.join("RouteSegment.BASELINE.zip"))?,
-            routes: parse_route_zip_file(path.join("Route.BASELINE.zip"))?,
-            arrival_legs: if path.join("ArrivalLeg.BASELINE.zip").exists() {
-                parse_arrival_leg_zip_file(path.join("ArrivalLeg.BASELINE.zip"))?
-            } else {
-                HashMap::new()
-            },
-            departure_legs: if path.join("DepartureLeg.BASELINE.zip").exists() {
-                parse_departure_leg_zip_file(path.join("DepartureLeg.BASELINE.zip"))?
-            } else {
-                HashMap::new()
-            },
-            standard_instrument_arrivals: if path.join("StandardInstrumentArrival.BASELINE.zip").exists() {
-                parse_standard_instrument_arrival_zip_file(path.join("StandardInstrumentArrival.BASELINE.zip"))?
-            } else {
-                HashMap::new()
-            },
-            standard_instrument_departures: if path.join("StandardInstrumentDeparture.BASELINE.zip").exists() {
-                parse_standard_instrument_departure_zip_file(path.join("StandardInstrumentDeparture.BASELINE.zip"))?
-            } else {
-                HashMap::new()
-            },
+        let mut db = Self::empty();
+        db.airways = OnceLock::from(Airways::default());
+        for dataset in Dataset::ALL {
+            db.reload_dataset(path, dataset)?;
+        }
+        Ok(db)
+    }
+
+    /// Load the airway database from the specified directory path, deferring airways.
+    ///
+    /// Routes and route segments are only parsed on first use (e.g. by
+    /// [`enrich_route`](Self::enrich_route)), which saves time and memory when the
+    /// database only serves point lookups. Errors while loading them at that point
+    /// are logged and leave the database without any airway until a later attempt
+    /// succeeds: call [`load_airways`](Self::load_airways) to get them instead.
+    pub fn new_lazy(path: &path::Path) -> Result<Self, ThrustError> {
+        let mut db = Self::empty();
+        for dataset in Dataset::ALL {
+            db.reload_dataset(path, dataset)?;
+        }
+        Ok(db)
+    }
+
+    /// Load the airway database from the specified directory path, with navaids and
+    /// designated points memory-mapped from a point index (see [`points`](super::points)).
+    ///
+    /// Points are only materialised when looked up, and airways are deferred as with
+    /// [`new_lazy`](Self::new_lazy), which makes loading much faster. The index is not
+    /// checked against the datasets in `path`: write it again with
+    /// [`write_point_index`](Self::write_point_index) whenever they are updated.
+    pub fn new_with_point_index(path: &path::Path, index: &path::Path) -> Result<Self, ThrustError> {
+        Self::with_point_store(path, PointStore::Mapped(PointIndex::open(index)?))
+    }
+
+    /// Load the datasets other than points from the specified directory path, with
+    /// navaids and designated points from `points`.
+    fn with_point_store(path: &path::Path, points: PointStore) -> Result<Self, ThrustError> {
+        let mut db = Self::empty();
+        db.points = points;
+        for dataset in Dataset::ALL {
+            if !matches!(dataset, Dataset::Navaid | Dataset::DesignatedPoint) {
+                db.reload_dataset(path, dataset)?;
+            }
+        }
+        Ok(db)
+    }
+
+    /// Write the navaids and designated points to a point index file, to be loaded
+    /// with [`new_with_point_index`](Self::new_with_point_index).
+    pub fn write_point_index(&self, path: &path::Path) -> Result<(), ThrustError> {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_points(&mut writer)?;
+        std::io::Write::flush(&mut writer)?;
+        Ok(())
+    }
+
+    /// Write the navaids and designated points to a zero-copy archive file, to be
+    /// loaded with [`load_archive`](Self::load_archive).
+    #[cfg(feature = "rkyv")]
+    pub fn save_archive(&self, path: &path::Path) -> Result<(), ThrustError> {
+        let navaids = self.points.navaids().collect::<Vec<_>>();
+        let designated_points = self.points.designated_points().collect::<Vec<_>>();
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        PointArchive::write(
+            navaids.iter().map(AsRef::as_ref),
+            designated_points.iter().map(AsRef::as_ref),
+            &mut writer,
+        )?;
+        std::io::Write::flush(&mut writer)?;
+        Ok(())
+    }
+
+    /// Load the airway database from the specified directory path, with navaids and
+    /// designated points memory-mapped from an archive (see [`archive`](super::archive)).
+    ///
+    /// This behaves as [`new_with_point_index`](Self::new_with_point_index), but the
+    /// archive is validated when loaded: a corrupted file is an error rather than
+    /// missing points. Write it again with [`save_archive`](Self::save_archive)
+    /// whenever the datasets in `path` are updated.
+    #[cfg(feature = "rkyv")]
+    pub fn load_archive(path: &path::Path, archive: &path::Path) -> Result<Self, ThrustError> {
+        Self::with_point_store(path, PointStore::Archived(PointArchive::open(archive)?))
+    }
+
+    fn write_points<W: std::io::Write>(&self, writer: &mut W) -> Result<(), ThrustError> {
+        let navaids = self.points.navaids().collect::<Vec<_>>();
+        let designated_points = self.points.designated_points().collect::<Vec<_>>();
+        PointIndex::write(
+            navaids.iter().map(AsRef::as_ref),
+            designated_points.iter().map(AsRef::as_ref),
+            writer,
+        )
+    }
+
+    /// Write the navaids, designated points, routes and route segments to Parquet
+    /// files with flat schemas in the specified directory, created if missing.
+    ///
+    /// The end points of route segments are written as the kind and identifier of
+    /// the referenced point, to be joined with the point files. Availabilities are
+    /// not written. A `fixes.parquet` file holds the [fix table](Self::fix_table),
+    /// with records merged within one nautical mile.
+    #[cfg(feature = "polars")]
+    pub fn to_parquet(&self, dir: &path::Path) -> Result<(), ThrustError> {
+        use polars::prelude::*;
+
+        fn write(dir: &path::Path, name: &str, mut df: DataFrame) -> Result<(), ThrustError> {
+            let file = std::fs::File::create(dir.join(name))?;
+            ParquetWriter::new(file).finish(&mut df)?;
+            Ok(())
+        }
+
+        fn kind(point: &PointReference) -> Option<&'static str> {
+            match point {
+                PointReference::DesignatedPoint(_) => Some("DesignatedPoint"),
+                PointReference::Navaid(_) => Some("Navaid"),
+                PointReference::AirportHeliport(_) => Some("AirportHeliport"),
+                PointReference::Coordinates(..) => Some("Coordinates"),
+                PointReference::None => None,
+            }
+        }
+
+        std::fs::create_dir_all(dir)?;
+
+        let mut navaids = self.points.navaids().collect::<Vec<_>>();
+        navaids.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        let df = df!(
+            "identifier" => navaids.iter().map(|n| n.identifier.as_str()).collect::<Vec<_>>(),
+            "name" => navaids.iter().map(|n| n.name.as_deref()).collect::<Vec<_>>(),
+            "type" => navaids.iter().map(|n| n.r#type.as_str()).collect::<Vec<_>>(),
+            "description" => navaids.iter().map(|n| n.description.as_deref()).collect::<Vec<_>>(),
+            "latitude" => navaids.iter().map(|n| n.latitude).collect::<Vec<_>>(),
+            "longitude" => navaids.iter().map(|n| n.longitude).collect::<Vec<_>>(),
+            "frequency" => navaids.iter().map(|n| n.frequency).collect::<Vec<_>>(),
+            "call_sign" => navaids.iter().map(|n| n.call_sign.as_deref()).collect::<Vec<_>>(),
+        )?;
+        write(dir, "navaids.parquet", df)?;
+
+        let mut points = self.points.designated_points().collect::<Vec<_>>();
+        points.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        let df = df!(
+            "identifier" => points.iter().map(|p| p.identifier.as_str()).collect::<Vec<_>>(),
+            "designator" => points.iter().map(|p| p.designator.as_str()).collect::<Vec<_>>(),
+            "name" => points.iter().map(|p| p.name.as_deref()).collect::<Vec<_>>(),
+            "type" => points.iter().map(|p| p.r#type.to_string()).collect::<Vec<_>>(),
+            "latitude" => points.iter().map(|p| p.latitude).collect::<Vec<_>>(),
+            "longitude" => points.iter().map(|p| p.longitude).collect::<Vec<_>>(),
+        )?;
+        write(dir, "designated_points.parquet", df)?;
+
+        let airways = self.airways();
+        let mut routes = airways.routes.values().collect::<Vec<_>>();
+        routes.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        let df = df!(
+            "identifier" => routes.iter().map(|r| r.identifier.as_str()).collect::<Vec<_>>(),
+            "designator" => routes.iter().map(|r| r.designator()).collect::<Vec<_>>(),
+            "prefix" => routes.iter().map(|r| r.prefix.as_deref()).collect::<Vec<_>>(),
+            "second_letter" => routes.iter().map(|r| r.second_letter.as_deref()).collect::<Vec<_>>(),
+            "number" => routes.iter().map(|r| r.number.as_deref()).collect::<Vec<_>>(),
+            "multiple_identifier" => routes.iter().map(|r| r.multiple_identifier.as_deref()).collect::<Vec<_>>(),
+        )?;
+        write(dir, "routes.parquet", df)?;
+
+        let mut segments = airways.route_segments.values().collect::<Vec<_>>();
+        segments.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        let df = df!(
+            "identifier" => segments.iter().map(|s| s.identifier.as_str()).collect::<Vec<_>>(),
+            "route_formed" => segments.iter().map(|s| s.route_formed.as_deref()).collect::<Vec<_>>(),
+            "start_kind" => segments.iter().map(|s| kind(&s.start)).collect::<Vec<_>>(),
+            "start_identifier" => segments.iter().map(|s| s.start.id().map(GmlId::as_str)).collect::<Vec<_>>(),
+            "end_kind" => segments.iter().map(|s| kind(&s.end)).collect::<Vec<_>>(),
+            "end_identifier" => segments.iter().map(|s| s.end.id().map(GmlId::as_str)).collect::<Vec<_>>(),
+        )?;
+        write(dir, "route_segments.parquet", df)?;
+
+        let fixes = self.fix_table(NAUTICAL_MILE);
+        write(dir, "fixes.parquet", super::response::fix_table_dataframe(&fixes)?)?;
+        Ok(())
+    }
+
+    fn empty() -> Self {
+        AirwayDatabase {
+            airports: HashMap::new(),
+            points: PointStore::default(),
+            airways: OnceLock::new(),
+            airway_path: None,
+            airway_loader: Airways::load,
+            arrival_legs: HashMap::new(),
+            departure_legs: HashMap::new(),
+            standard_instrument_arrivals: HashMap::new(),
+            standard_instrument_departures: HashMap::new(),
+            stats: OnceLock::new(),
+            completions: OnceLock::new(),
+            segment_index: OnceLock::new(),
+            distance_model: DistanceModel::default(),
+            options: EnrichmentOptions::default(),
+            point_type_preference: DEFAULT_POINT_TYPE_PREFERENCE.to_vec(),
+            airac_cycle: None,
+            route_cache: None,
+            revision: 0,
+            overlay: None,
+        }
+    }
+
+    /// Reload a single dataset from the specified directory path.
+    ///
+    /// Optional (procedure) datasets are emptied if the file is missing. Airways
+    /// not loaded yet in lazy mode are only loaded from the new path on first use.
+    pub fn reload_dataset(&mut self, path: &path::Path, dataset: Dataset) -> Result<(), ThrustError> {
+        let file = path.join(dataset.file_name());
+        if dataset.is_optional() && !file.exists() {
+            match dataset {
+                Dataset::ArrivalLeg => self.arrival_legs.clear(),
+                Dataset::DepartureLeg => self.departure_legs.clear(),
+                Dataset::StandardInstrumentArrival => self.standard_instrument_arrivals.clear(),
+                Dataset::StandardInstrumentDeparture => self.standard_instrument_departures.clear(),
+                _ => {}
+            }
+        } else {
+            match dataset {
+                Dataset::AirportHeliport => self.airports = parse_airport_heliport_zip_file(file)?,
+                Dataset::Navaid => *self.points.navaids_mut() = parse_navaid_zip_file(file)?,
+                Dataset::DesignatedPoint => {
+                    *self.points.designated_points_mut() = parse_designated_point_zip_file(file)?
+                }
+                Dataset::RouteSegment | Dataset::Route if self.airways.get().is_none() => {
+                    self.airway_path = Some(path.to_path_buf())
+                }
+                Dataset::RouteSegment => self.airways_mut().route_segments = parse_route_segment_zip_file(file)?,
+                Dataset::Route => self.airways_mut().routes = parse_route_zip_file(file)?,
+                Dataset::ArrivalLeg => self.arrival_legs = parse_arrival_leg_zip_file(file)?,
+                Dataset::DepartureLeg => self.departure_legs = parse_departure_leg_zip_file(file)?,
+                Dataset::StandardInstrumentArrival => {
+                    self.standard_instrument_arrivals = parse_standard_instrument_arrival_zip_file(file)?
+                }
+                Dataset::StandardInstrumentDeparture => {
+                    self.standard_instrument_departures = parse_standard_instrument_departure_zip_file(file)?
+                }
+            }
+        }
+        if matches!(dataset, Dataset::Route | Dataset::RouteSegment) {
+            if let Some(airways) = self.airways.get_mut() {
+                airways.index_route_segments();
+            }
+        }
+        self.airac_cycle = airac_cycle_from_path(path);
+        self.stats = OnceLock::new();
+        self.completions = OnceLock::new();
+        self.segment_index = OnceLock::new();
+        self.clear_route_cache();
+        Ok(())
+    }
+
+    /// Load the routes and route segments now if they are deferred (see
+    /// [`new_lazy`](Self::new_lazy)), so that errors are returned rather than logged.
+    pub fn load_airways(&self) -> Result<(), ThrustError> {
+        self.try_airways().map(|_| ())
+    }
+
+    /// The routes and route segments, loaded on first use in lazy mode.
+    ///
+    /// A failed load is not kept: the next call tries again.
+    fn try_airways(&self) -> Result<&Airways, ThrustError> {
+        if let Some(airways) = self.airways.get() {
+            return Ok(airways);
+        }
+        let airways = match &self.airway_path {
+            Some(path) => (self.airway_loader)(path)?,
+            None => Airways::default(),
+        };
+        Ok(self.airways.get_or_init(|| airways))
+    }
+
+    /// The routes and route segments where errors cannot be returned: a failed
+    /// load is logged, and no airway is found until they load successfully.
+    fn airways(&self) -> &Airways {
+        static NO_AIRWAYS: LazyLock<Airways> = LazyLock::new(Airways::default);
+        self.try_airways().unwrap_or_else(|e| {
+            let path = self.airway_path.as_deref().unwrap_or(path::Path::new(""));
+            tracing::error!(target: "thrust::aixm", "Failed to load airways from {}: {}", path.display(), e);
+            &NO_AIRWAYS
+        })
+    }
+
+    fn airways_mut(&mut self) -> &mut Airways {
+        self.airways();
+        self.completions = OnceLock::new();
+        self.segment_index = OnceLock::new();
+        self.clear_route_cache();
+        self.airways.get_mut().expect("airways are loaded")
+    }
+
+    /// The distance model used for geodesic computations (ellipsoidal by default).
+    pub fn distance_model(&self) -> DistanceModel {
+        self.distance_model
+    }
+
+    /// Select the distance model used for geodesic computations.
+    pub fn set_distance_model(&mut self, model: DistanceModel) {
+        self.distance_model = model;
+        self.clear_route_cache();
+    }
+
+    /// The options used for route enrichment.
+    pub fn enrichment_options(&self) -> &EnrichmentOptions {
+        &self.options
+    }
+
+    /// Set the options used for route enrichment.
+    pub fn set_enrichment_options(&mut self, options: EnrichmentOptions) {
+        self.options = options;
+        self.clear_route_cache();
+    }
+
+    /// Whether route enrichment substitutes airways (disabled by default).
+    pub fn airway_substitution(&self) -> bool {
+        self.options.airway_substitution
+    }
+
+    /// Let route enrichment substitute another airway when the filed one does not
+    /// connect its bracketing points, instead of falling back to a direct leg.
+    ///
+    /// Substitutions are listed in the [`EnrichmentReport`].
+    pub fn set_airway_substitution(&mut self, enabled: bool) {
+        self.options.airway_substitution = enabled;
+        self.clear_route_cache();
+    }
+
+    /// The order in which designated point types are preferred when resolving a designator.
+    pub fn point_type_preference(&self) -> &[DesignatedPointType] {
+        &self.point_type_preference
+    }
+
+    /// Set the order in which designated point types are preferred when resolving a designator.
+    ///
+    /// When designated points of several types match a designator, only those whose
+    /// type comes first in `preference` are kept; types not listed come last. The
+    /// default is ICAO, then terminal points, then any other type (e.g. COORD points
+    /// generated from coordinates). An empty preference keeps all candidates.
+    ///
+    /// VFR reporting points are always kept, unless excluded with
+    /// [`EnrichmentOptions::exclude_vfr_reporting_points`]: they share short names
+    /// with distant IFR points, and are told apart by the geometry of the route.
+    pub fn set_point_type_preference(&mut self, preference: Vec<DesignatedPointType>) {
+        self.point_type_preference = preference;
+        self.clear_route_cache();
+    }
+
+    /// Cache the results of route enrichment, for up to `capacity` distinct routes.
+    ///
+    /// Routes are keyed by their field 15 elements, and the least recently used
+    /// ones are evicted first. The cache is safe to use from several threads, and
+    /// is cleared whenever a dataset is reloaded or an enrichment setting changes.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let db = AirwayDatabase::new(path)?.with_cache(10_000);
+    /// ```
+    pub fn with_cache(mut self, capacity: usize) -> Self {
+        self.route_cache = Some(SharedRouteCache::new(capacity, DEFAULT_MAX_BYTES));
+        self
+    }
+
+    /// Hit and miss counters of the route cache, if enabled.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.route_cache.as_ref().map(SharedRouteCache::stats)
+    }
+
+    /// A counter incremented whenever a dataset is reloaded or an enrichment
+    /// setting changes, so that caches kept outside the database (e.g. by a
+    /// [`CachedEnricher`](super::cache::CachedEnricher)) may tell stale results.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn clear_route_cache(&mut self) {
+        self.revision += 1;
+        if let Some(cache) = &self.route_cache {
+            cache.clear();
+        }
+    }
+
+    /// Overlay the database with supplementary points and airways, e.g. temporary
+    /// waypoints published by NOTAM: see the [overlay module](super::overlay).
+    ///
+    /// # Example
+    /// ```ignore
+    /// let overlay = OverlayData::from_file(Path::new("notam.toml"))?;
+    /// let db = AirwayDatabase::new(path)?.with_overlay(overlay);
+    /// ```
+    pub fn with_overlay(mut self, overlay: OverlayData) -> Self {
+        self.set_overlay(Some(overlay));
+        self
+    }
+
+    /// Replace the overlay of the database, or remove it with `None`.
+    ///
+    /// Segment endpoints missing from the overlay are looked up in the points
+    /// loaded at this time. The route cache is cleared.
+    pub fn set_overlay(&mut self, overlay: Option<OverlayData>) {
+        self.overlay = overlay.map(|overlay| Overlay::new(overlay, &self.points));
+        self.segment_index = OnceLock::new();
+        self.clear_route_cache();
+    }
+
+    /// Whether an overlay is set.
+    pub fn has_overlay(&self) -> bool {
+        self.overlay.is_some()
+    }
+
+    /// A route segment by identifier, in the overlay first.
+    pub(crate) fn route_segment(&self, identifier: &str) -> Option<&RouteSegment> {
+        self.overlay
+            .as_ref()
+            .and_then(|overlay| overlay.route_segments.get(identifier))
+            .or_else(|| self.airways().route_segments.get(identifier))
+    }
+
+    /// The AIRAC cycle of the loaded data (e.g. "2508"), if known.
+    pub fn airac_cycle(&self) -> Option<&str> {
+        self.airac_cycle.as_deref()
+    }
+
+    /// Record the AIRAC cycle of the loaded data.
+    ///
+    /// AIXM baselines do not carry their cycle in a way the parsers rely on, so
+    /// loaders read it from the name of the data directory (e.g. `aixm_2508`),
+    /// and every dataset reloaded from another directory replaces it. Callers may
+    /// set it when the directory is named otherwise. The route cache is cleared.
+    pub fn set_airac_cycle(&mut self, airac_cycle: Option<String>) {
+        self.airac_cycle = airac_cycle;
+        self.clear_route_cache();
+    }
+
+    /// A hash of a route string and of the AIRAC cycle of the data, to key caches of enriched routes.
+    ///
+    /// Routes differing only by whitespace or letter case share the same key, and
+    /// keys change with the AIRAC cycle so that cached results are invalidated.
+    /// Keys are 64-bit FNV-1a hashes, stable across builds and platforms, so that
+    /// they may key persistent caches.
+    pub fn route_cache_key(&self, route: &str) -> u64 {
+        let route = normalise_route(route);
+        let cycle = self
+            .airac_cycle
+            .iter()
+            .flat_map(|cycle| [0].into_iter().chain(cycle.bytes()));
+        route.bytes().chain(cycle).fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
+
+    /// Keep only the designated points of the most preferred type among `candidates`,
+    /// and the VFR reporting points.
+    fn prefer_point_types(&self, mut candidates: Vec<ResolvedPoint>) -> Vec<ResolvedPoint> {
+        if self.point_type_preference.is_empty() {
+            return candidates;
+        }
+        let rank = |point: &ResolvedPoint| match point {
+            ResolvedPoint::DesignatedPoint(dp) if dp.r#type.is_vfr_reporting_point() => None,
+            ResolvedPoint::DesignatedPoint(dp) => Some(
+                self.point_type_preference
+                    .iter()
+                    .position(|t| *t == dp.r#type)
+                    .unwrap_or(self.point_type_preference.len()),
+            ),
+            _ => Some(self.point_type_preference.len()),
+        };
+        if let Some(best) = candidates.iter().filter_map(rank).min() {
+            candidates.retain(|point| rank(point).is_none_or(|rank| rank == best));
+        }
+        candidates
+    }
+
+    /// Airways connecting any of the `before` candidates to any of the `after` candidates,
+    /// trimmed between these points and sorted by name.
+    fn connecting_routes(&self, before: &[ResolvedPoint], after: &[ResolvedPoint]) -> Vec<ResolvedRoute> {
+        let routes_through = |points: &[ResolvedPoint]| {
+            let refs = points
+                .iter()
+                .filter_map(ResolvedPoint::reference)
+                .collect::<std::collections::HashSet<_>>();
+            self.airways()
+                .route_segments
+                .values()
+                .filter(|segment| refs.contains(&segment.start) || refs.contains(&segment.end))
+                .filter_map(|segment| segment.route_formed.clone())
+                .collect::<std::collections::HashSet<_>>()
+        };
+        let through_after = routes_through(after);
+        let mut routes = routes_through(before)
+            .into_iter()
+            .filter(|id| through_after.contains(id))
+            .filter_map(|id| self.airways().routes.get(id.as_str()))
+            .filter(|route| route.is_well_formed())
+            .filter_map(|route| {
+                let route = ResolvedRoute::from_db(route, self);
+                before
+                    .iter()
+                    .flat_map(|b| after.iter().map(move |a| (b, a)))
+                    .find_map(|(b, a)| route.between(b, a).filter(|r| !r.segments.is_empty()))
+            })
+            .collect::<Vec<_>>();
+        routes.sort_by(|a, b| a.name.cmp(&b.name));
+        routes
+    }
+
+    /// All airports, navaids and designated points as a single table of fixes, with
+    /// duplicates collapsed.
+    ///
+    /// Each record is designated by the [code](AirportHeliport::code) of an airport,
+    /// the name of a navaid or the designator of a designated point, trimmed and
+    /// uppercased; navaids without a name are left out. Records are then merged as
+    /// follows:
+    ///
+    /// - only records with the same designator are merged, whatever their kind;
+    /// - records are taken airports first, then navaids, then designated points, and
+    ///   by identifier within each kind;
+    /// - a record within `dedup_radius` meters (geodesic distance, with the distance
+    ///   model of the database) of a fix already kept with the same designator is
+    ///   merged into the first such fix, otherwise it is kept as a new fix;
+    /// - a fix keeps the kind, identifier and position of its first record, and lists
+    ///   all its merged records, itself first, in `sources`.
+    ///
+    /// Distances are measured to the kept fix only, so that merges do not chain along
+    /// a line of close records. Fixes are sorted by designator, then by kind and
+    /// identifier. A radius of 0 only merges records at the exact same position.
+    pub fn fix_table(&self, dedup_radius: f64) -> Vec<FixRecord> {
+        let mut records = self
+            .airports
+            .values()
+            .filter(|airport| !airport.code().is_empty())
+            .map(|airport| {
+                let source = FixSource::new("AirportHeliport", &airport.identifier);
+                (
+                    airport.code().to_uppercase(),
+                    0,
+                    source,
+                    airport.latitude,
+                    airport.longitude,
+                )
+            })
+            .chain(self.points.navaids().filter_map(|navaid| {
+                let name = navaid.name.as_deref()?.trim().to_uppercase();
+                let source = FixSource::new("Navaid", &navaid.identifier);
+                Some((name, 1, source, navaid.latitude, navaid.longitude))
+            }))
+            .chain(self.points.designated_points().map(|dp| {
+                let source = FixSource::new("DesignatedPoint", &dp.identifier);
+                (
+                    dp.designator.trim().to_uppercase(),
+                    2,
+                    source,
+                    dp.latitude,
+                    dp.longitude,
+                )
+            }))
+            .filter(|record| !record.0.is_empty())
+            .collect::<Vec<_>>();
+        records.sort_by(|a, b| (&a.0, a.1, &a.2.identifier).cmp(&(&b.0, b.1, &b.2.identifier)));
+
+        let mut fixes: Vec<FixRecord> = Vec::new();
+        for group in records.chunk_by(|a, b| a.0 == b.0) {
+            let first = fixes.len();
+            for (designator, _, source, latitude, longitude) in group {
+                let position = Coor2D::geo(*latitude, *longitude);
+                let close = fixes[first..].iter_mut().find(|fix| {
+                    self.distance_model
+                        .distance(&Coor2D::geo(fix.latitude, fix.longitude), &position)
+                        <= dedup_radius
+                });
+                match close {
+                    Some(fix) => fix.sources.push(source.clone()),
+                    None => fixes.push(FixRecord {
+                        designator: designator.clone(),
+                        kind: source.kind.clone(),
+                        identifier: source.identifier.clone(),
+                        latitude: *latitude,
+                        longitude: *longitude,
+                        sources: vec![source.clone()],
+                    }),
+                }
+            }
+        }
+        fixes
+    }
+
+    /// Statistics about the content and the memory footprint of the database.
+    ///
+    /// The statistics are computed on the first call and cached afterwards.
+    pub fn stats(&self) -> &DatabaseStats {
+        self.stats.get_or_init(|| DatabaseStats::compute(self))
+    }
+
+    /// A quality report of every route in the database, one entry per route.
+    ///
+    /// Entries are computed lazily while iterating, in the order of route identifiers.
+    pub fn route_report(&self) -> impl Iterator<Item = RouteReportEntry> + '_ {
+        let mut routes = self.airways().routes.values().collect::<Vec<_>>();
+        routes.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        routes.into_iter().map(|route| {
+            let resolved = ResolvedRoute::from_db(route, self);
+            let (complete, incomplete): (Vec<_>, Vec<_>) = resolved
+                .segments
+                .into_iter()
+                .partition(ResolvedRouteSegment::is_complete);
+            let length = complete
+                .iter()
+                .filter_map(|segment| {
+                    Some(
+                        self.distance_model
+                            .distance(&segment.start.coor()?, &segment.end.coor()?),
+                    )
+                })
+                .sum();
+            let ordered = order_route_segments(complete);
+            let continuous = !ordered.is_empty() && ordered.windows(2).all(|pair| pair[0].end == pair[1].start);
+            RouteReportEntry {
+                identifier: route.identifier.clone(),
+                designator: resolved.name,
+                segments: route.segments.len(),
+                length,
+                unresolved_segments: incomplete.len(),
+                continuous,
+            }
         })
     }
 
+    /// Flag the navaids and designated points whose latitude and longitude are
+    /// probably swapped, and the route segments made suspiciously long by them.
+    ///
+    /// A point is suspect when it falls outside the bounding box of all points by
+    /// more than `options.margin` degrees. The bounding box ignores the most extreme
+    /// percent of latitudes and longitudes (at least one value on each side), so that
+    /// a few misplaced points do not stretch it. A route segment is suspect when its
+    /// endpoints are further apart than `options.max_segment_length`.
+    ///
+    /// With `options.fix_suspected_swaps`, suspect points whose swapped coordinates
+    /// fall inside the bounding box are swapped back before route segments are
+    /// checked. Each finding is also logged as a warning.
+    pub fn check_coordinate_swaps(&mut self, options: &CoordinateSwapOptions) -> CoordinateSwapReport {
+        let coords = self
+            .points
+            .navaids()
+            .map(|n| (n.latitude, n.longitude))
+            .chain(self.points.designated_points().map(|dp| (dp.latitude, dp.longitude)))
+            .collect::<Vec<_>>();
+        let mut navaids = Vec::new();
+        let mut designated_points = Vec::new();
+        if let Some([south, north, west, east]) = trimmed_bounds(&coords) {
+            let inside = |latitude: f64, longitude: f64, margin: f64| {
+                (south - margin..=north + margin).contains(&latitude)
+                    && (west - margin..=east + margin).contains(&longitude)
+            };
+            let suspect = |identifier: &str, name: Option<&str>, latitude: f64, longitude: f64| {
+                (!inside(latitude, longitude, options.margin)).then(|| SuspectedSwap {
+                    identifier: identifier.to_string(),
+                    name: name.map(str::to_string),
+                    latitude,
+                    longitude,
+                    fixed: options.fix_suspected_swaps && inside(longitude, latitude, 0.),
+                })
+            };
+            navaids = self
+                .points
+                .navaids()
+                .filter_map(|n| suspect(&n.identifier, n.name.as_deref(), n.latitude, n.longitude))
+                .collect();
+            designated_points = self
+                .points
+                .designated_points()
+                .filter_map(|dp| suspect(&dp.identifier, Some(&dp.designator), dp.latitude, dp.longitude))
+                .collect();
+        }
+
+        if navaids.iter().chain(&designated_points).any(|p| p.fixed) {
+            for fix in navaids.iter().filter(|p| p.fixed) {
+                if let Some(navaid) = self.points.navaids_mut().get_mut(fix.identifier.as_str()) {
+                    std::mem::swap(&mut navaid.latitude, &mut navaid.longitude);
+                }
+            }
+            for fix in designated_points.iter().filter(|p| p.fixed) {
+                if let Some(dp) = self.points.designated_points_mut().get_mut(fix.identifier.as_str()) {
+                    std::mem::swap(&mut dp.latitude, &mut dp.longitude);
+                }
+            }
+            self.stats = OnceLock::new();
+            self.completions = OnceLock::new();
+            self.clear_route_cache();
+        }
+        let mut points = navaids.into_iter().chain(designated_points).collect::<Vec<_>>();
+        points.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        for point in &points {
+            tracing::warn!(
+                target: "thrust::aixm",
+                "Probable latitude/longitude swap of {} ({}) at {}, {}{}",
+                point.identifier,
+                point.name.as_deref().unwrap_or_default(),
+                point.latitude,
+                point.longitude,
+                if point.fixed { ", swapped back" } else { "" }
+            );
+        }
+
+        let mut segments = self
+            .airways()
+            .route_segments
+            .values()
+            .filter_map(|segment| {
+                let start = ResolvedPoint::from_db(&segment.start, self);
+                let end = ResolvedPoint::from_db(&segment.end, self);
+                let length = self.distance_model.distance(&start.coor()?, &end.coor()?);
+                (length > options.max_segment_length).then(|| SuspectedSegment {
+                    identifier: segment.identifier.clone(),
+                    start: segment.start.id().map(|id| id.to_string()),
+                    end: segment.end.id().map(|id| id.to_string()),
+                    length,
+                })
+            })
+            .collect::<Vec<_>>();
+        segments.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        for segment in &segments {
+            tracing::warn!(
+                target: "thrust::aixm",
+                "Probable latitude/longitude swap at an end of route segment {} ({} to {}, {:.0} km)",
+                segment.identifier,
+                segment.start.as_deref().unwrap_or_default(),
+                segment.end.as_deref().unwrap_or_default(),
+                segment.length / 1000.
+            );
+        }
+
+        CoordinateSwapReport { points, segments }
+    }
+
+    /// All navaids and designated points of the database, as a single collection.
+    ///
+    /// Navaids come first, then designated points, each in no particular order.
+    pub fn all_points(&self) -> impl Iterator<Item = ResolvedPoint> + '_ {
+        let navaids = self.points.navaids().map(|n| ResolvedPoint::Navaid(n.into_owned()));
+        let points = self
+            .points
+            .designated_points()
+            .map(|dp| ResolvedPoint::DesignatedPoint(dp.into_owned()));
+        navaids.chain(points)
+    }
+
+    /// Resolve a list of fix names and chain them with direct legs, without airways.
+    ///
+    /// Names are resolved as the points of a field 15 route made of direct legs
+    /// (`ABC DCT DEF DCT GHI`) with the [options](Self::enrichment_options) of the
+    /// database, so that a name matching several points is settled by its
+    /// neighbours. Names which cannot be resolved are skipped.
+    pub fn route_from_fixes(&self, names: &[&str]) -> ResolvedRoute {
+        let mut elements = Vec::new();
+        for name in names {
+            if !elements.is_empty() {
+                elements.push(Field15Element::Connector(Connector::Direct));
+            }
+            elements.push(Field15Element::Point(Point::Waypoint(name.trim().to_uppercase())));
+        }
+        let (segments, _) = self.enrich_uncached(&elements);
+        ResolvedRoute {
+            segments,
+            name: names.join(" "),
+        }
+    }
+
+    /// The route segment nearest to a position, and its distance in meters.
+    ///
+    /// The distance is measured to the foot of the perpendicular, clamped to the
+    /// segment endpoints. Segments of the overlay are included, segments with an
+    /// unresolved endpoint are not. Queries go through a grid index of the segments,
+    /// built on first use, and only measure the distance to segments nearby.
+    pub fn nearest_segment(&self, latitude: f64, longitude: f64) -> Option<(&RouteSegment, f64)> {
+        let index = self.segment_index.get_or_init(|| {
+            let overlay = self.overlay.iter().flat_map(|overlay| overlay.route_segments.values());
+            SegmentIndex::new(
+                self.airways()
+                    .route_segments
+                    .values()
+                    .chain(overlay)
+                    .filter_map(|segment| {
+                        let start = ResolvedPoint::from_db(&segment.start, self).coor()?;
+                        let end = ResolvedPoint::from_db(&segment.end, self).coor()?;
+                        Some((segment.identifier.clone().into(), start, end))
+                    }),
+            )
+        });
+        let (identifier, distance) = index.nearest(latitude, longitude)?;
+        Some((self.route_segment(identifier)?, distance))
+    }
+
+    /// Designators starting with `prefix` (case-insensitive), at most `limit` of them.
+    ///
+    /// Airports (ICAO codes), navaids, designated points and routes are considered.
+    /// An exact match comes first, then the other designators in lexicographic order.
+    /// The sorted index is built on the first call, so lookups take O(log n + limit).
+    pub fn complete(&self, prefix: &str, limit: usize) -> Vec<CompletionEntry> {
+        let prefix = prefix.trim().to_uppercase();
+        let entries = self.completions.get_or_init(|| self.completion_index());
+        let start = entries.partition_point(|entry| entry.name < prefix);
+        entries[start..]
+            .iter()
+            .take_while(|entry| entry.name.starts_with(&prefix))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    fn completion_index(&self) -> Vec<CompletionEntry> {
+        let airports = self
+            .airports
+            .values()
+            .map(|a| CompletionEntry::point(a.code(), CompletionKind::Airport, a.latitude, a.longitude));
+        let navaids = self.points.navaids().filter_map(|n| {
+            let name = n.name.as_deref()?;
+            Some(CompletionEntry::point(
+                name,
+                CompletionKind::Navaid,
+                n.latitude,
+                n.longitude,
+            ))
+        });
+        let points = self.points.designated_points().map(|dp| {
+            CompletionEntry::point(
+                &dp.designator,
+                CompletionKind::DesignatedPoint,
+                dp.latitude,
+                dp.longitude,
+            )
+        });
+        // Routes published as several records are listed once, routes with an
+        // incomplete designator are not listed since they cannot be looked up
+        let mut routes = self
+            .airways()
+            .routes
+            .values()
+            .filter(|route| route.is_well_formed())
+            .map(Route::designator)
+            .collect::<Vec<_>>();
+        routes.sort();
+        routes.dedup();
+        let routes = routes.into_iter().map(|name| CompletionEntry {
+            name,
+            kind: CompletionKind::Route,
+            latitude: None,
+            longitude: None,
+        });
+
+        let mut entries = airports
+            .chain(navaids)
+            .chain(points)
+            .filter(|entry| !entry.name.is_empty())
+            .chain(routes)
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| {
+            (&a.name, a.kind)
+                .cmp(&(&b.name, b.kind))
+                .then(
+                    a.latitude
+                        .unwrap_or_default()
+                        .total_cmp(&b.latitude.unwrap_or_default()),
+                )
+                .then(
+                    a.longitude
+                        .unwrap_or_default()
+                        .total_cmp(&b.longitude.unwrap_or_default()),
+                )
+        });
+        entries
+    }
+
+    /// Find the route segment connecting two points, in either direction.
+    pub fn segment_between(&self, a: &PointReference, b: &PointReference) -> Option<&RouteSegment> {
+        self.airways().segments_between(a, b).next()
+    }
+
+    /// Resolve an ordered list of fix names into segments, naming each leg after the
+    /// airway joining its endpoints, if any.
+    ///
+    /// Names are resolved as in [`route_from_fixes`](Self::route_from_fixes). A leg is
+    /// then named after an airway when a single airway has a segment joining its
+    /// endpoints, and stays direct otherwise. With
+    /// [`EnrichmentOptions::merge_split_airways`], airways published as several
+    /// `Route` records under the same designator count as one airway.
+    pub fn reconstruct_route(&self, fix_names: &[&str]) -> Vec<ResolvedRouteSegment> {
+        let mut segments = self.route_from_fixes(fix_names).segments;
+
+        let airways = self.airways();
+        for segment in &mut segments {
+            let (Some(start), Some(end)) = (segment.start.reference(), segment.end.reference()) else {
+                continue;
+            };
+            let mut connecting = airways
+                .segments_between(&start, &end)
+                .filter_map(|s| {
+                    let route = airways.routes.get(s.route_formed.as_deref()?)?;
+                    let key = match self.options.merge_split_airways {
+                        true => route.designator(),
+                        false => route.identifier.clone(),
+                    };
+                    Some((key, route.designator(), s.identifier.clone(), s.start != start))
+                })
+                .collect::<Vec<_>>();
+            connecting.sort_by(|a, b| a.0.cmp(&b.0));
+            connecting.dedup_by(|a, b| a.0 == b.0);
+            if let [(_, name, identifier, reversed)] = connecting.as_slice() {
+                segment.name = Some(name.clone());
+                segment.source_segment = Some(identifier.clone());
+                segment.is_reversed = *reversed;
+            }
+        }
+        segments
+    }
+
+    /// Flag the airway segments where the requested flight level does not belong to
+    /// the series of cruising levels assigned to the direction of flight.
+    ///
+    /// The rule of each segment is read from the availabilities of its source route
+    /// segment (or of the route segment connecting its endpoints, for segments built
+    /// outside of the database), for the direction in which it is flown and within the
+    /// vertical limits containing the requested level. Direct segments, segments
+    /// without such a rule and levels other than flight levels are never flagged;
+    /// flight levels outside the table of cruising levels (e.g. FL 355) are flagged
+    /// under any rule, see [`LevelSeries::of_flight_level`].
+    pub fn check_level_parity(&self, segments: &[ResolvedRouteSegment], requested: &Altitude) -> Vec<ParityViolation> {
+        let Altitude::FlightLevel(flight_level) = *requested else {
+            return Vec::new();
+        };
+        let series = LevelSeries::of_flight_level(flight_level);
+        segments
+            .iter()
+            .enumerate()
+            .filter(|(_, segment)| segment.name.is_some())
+            .filter_map(|(index, segment)| {
+                let (start, end) = (segment.start.reference()?, segment.end.reference()?);
+                let route_segment = match &segment.source_segment {
+                    Some(id) => self.airways().route_segments.get(id.as_str())?,
+                    None => self.segment_between(&start, &end)?,
+                };
+                let direction = if route_segment.start == start {
+                    RouteDirection::Forward
+                } else {
+                    RouteDirection::Backward
+                };
+                let required = route_segment
+                    .availability
+                    .iter()
+                    .filter(|a| a.applies_to(direction) && a.contains(flight_level as f64))
+                    .find_map(|a| a.level_series)?;
+                (Some(required) != series).then(|| ParityViolation {
+                    index,
+                    segment: route_segment.identifier.clone(),
+                    airway: segment.name.clone(),
+                    flight_level,
+                    required,
+                })
+            })
+            .collect()
+    }
+
     /// Resolve SID connecting points by procedure designator.
     pub fn resolve_sid_points(&self, name: &str) -> Vec<ResolvedPoint> {
         let sid_ids = self
             .standard_instrument_departures
             .values()
             .filter(|sid| sid.designator.trim().eq_ignore_ascii_case(name))
-            .map(|sid| sid.identifier.clone())
+            .map(|sid| GmlId::from(&sid.identifier))
             .collect::<std::collections::HashSet<_>>();
 
         let fallback_points = self
@@ -128,7 +1293,7 @@ impl AirwayDatabase {
             .standard_instrument_arrivals
             .values()
             .filter(|star| star.designator.trim().eq_ignore_ascii_case(name))
-            .map(|star| star.identifier.clone())
+            .map(|star| GmlId::from(&star.identifier))
             .collect::<std::collections::HashSet<_>>();
 
         let fallback_points = self
@@ -157,116 +1322,154 @@ impl AirwayDatabase {
 
     /// Resolve SID procedures by designator as route-like segments.
     pub fn resolve_sid_routes(&self, name: &str) -> Vec<ResolvedRoute> {
-        self.standard_instrument_departures
-            .values()
-            .filter(|sid| sid.designator.trim().eq_ignore_ascii_case(name))
-            .map(|sid| {
-                let segments = self
-                    .departure_legs
-                    .values()
-                    .filter(|leg| leg.departure.as_ref().is_some_and(|id| id == &sid.identifier))
-                    .filter_map(|leg| {
-                        let start = ResolvedPoint::from_db(&leg.start, self);
-                        let end = ResolvedPoint::from_db(&leg.end, self);
-                        if matches!(start, ResolvedPoint::None) || matches!(end, ResolvedPoint::None) {
-                            None
-                        } else {
-                            Some(ResolvedRouteSegment {
-                                start,
-                                end,
-                                name: Some(sid.designator.clone()),
-                                altitude: None,
-                                speed: None,
-                            })
-                        }
-                    })
-                    .collect::<Vec<_>>();
-                ResolvedRoute {
-                    segments: order_route_segments(segments),
-                    name: sid.designator.clone(),
-                }
-            })
+        self.resolve_sid_legs(name)
+            .into_iter()
+            .map(ResolvedRoute::from)
             .collect()
     }
 
     /// Resolve STAR procedures by designator as route-like segments.
     pub fn resolve_star_routes(&self, name: &str) -> Vec<ResolvedRoute> {
+        self.resolve_star_legs(name)
+            .into_iter()
+            .map(ResolvedRoute::from)
+            .collect()
+    }
+
+    /// Resolve SID procedures by designator, with the geometry of each leg.
+    pub fn resolve_sid_legs(&self, name: &str) -> Vec<ResolvedProcedure> {
+        self.standard_instrument_departures
+            .values()
+            .filter(|sid| sid.designator.trim().eq_ignore_ascii_case(name))
+            .map(|sid| self.sid_procedure(sid))
+            .collect()
+    }
+
+    /// Resolve STAR procedures by designator, with the geometry of each leg.
+    pub fn resolve_star_legs(&self, name: &str) -> Vec<ResolvedProcedure> {
         self.standard_instrument_arrivals
             .values()
             .filter(|star| star.designator.trim().eq_ignore_ascii_case(name))
-            .map(|star| {
-                let segments = self
-                    .arrival_legs
-                    .values()
-                    .filter(|leg| leg.arrival.as_ref().is_some_and(|id| id == &star.identifier))
-                    .filter_map(|leg| {
-                        let start = ResolvedPoint::from_db(&leg.start, self);
-                        let end = ResolvedPoint::from_db(&leg.end, self);
-                        if matches!(start, ResolvedPoint::None) || matches!(end, ResolvedPoint::None) {
-                            None
-                        } else {
-                            Some(ResolvedRouteSegment {
-                                start,
-                                end,
-                                name: Some(star.designator.clone()),
-                                altitude: None,
-                                speed: None,
-                            })
-                        }
-                    })
-                    .collect::<Vec<_>>();
-                ResolvedRoute {
-                    segments: order_route_segments(segments),
-                    name: star.designator.clone(),
-                }
-            })
+            .map(|star| self.star_procedure(star))
             .collect()
     }
 
+    /// All SID procedures, with the geometry of each leg, sorted by airport and designator.
+    pub fn sid_procedures(&self) -> Vec<ResolvedProcedure> {
+        let mut sids = self.standard_instrument_departures.values().collect::<Vec<_>>();
+        sids.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        sort_procedures(sids.into_iter().map(|sid| self.sid_procedure(sid)).collect())
+    }
+
+    /// All STAR procedures, with the geometry of each leg, sorted by airport and designator.
+    pub fn star_procedures(&self) -> Vec<ResolvedProcedure> {
+        let mut stars = self.standard_instrument_arrivals.values().collect::<Vec<_>>();
+        stars.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        sort_procedures(stars.into_iter().map(|star| self.star_procedure(star)).collect())
+    }
+
+    fn sid_procedure(&self, sid: &StandardInstrumentDeparture) -> ResolvedProcedure {
+        let legs = self
+            .departure_legs
+            .values()
+            .filter(|leg| leg.departure.as_ref().is_some_and(|id| id == &sid.identifier))
+            .filter_map(|leg| {
+                ResolvedLeg::from_db(
+                    [&leg.start, &leg.end, &leg.arc_centre],
+                    [&leg.start_role, &leg.end_role],
+                    leg.leg_type.as_deref(),
+                    leg.course,
+                    leg.turn_direction.as_deref(),
+                    &sid.designator,
+                    self,
+                )
+            })
+            .collect::<Vec<_>>();
+        ResolvedProcedure {
+            legs: order_legs(legs),
+            name: sid.designator.clone(),
+            airport: self.airport_icao(sid.airport_heliport.as_deref()),
+        }
+    }
+
+    fn star_procedure(&self, star: &StandardInstrumentArrival) -> ResolvedProcedure {
+        let legs = self
+            .arrival_legs
+            .values()
+            .filter(|leg| leg.arrival.as_ref().is_some_and(|id| id == &star.identifier))
+            .filter_map(|leg| {
+                ResolvedLeg::from_db(
+                    [&leg.start, &leg.end, &leg.arc_centre],
+                    [&leg.start_role, &leg.end_role],
+                    leg.leg_type.as_deref(),
+                    leg.course,
+                    leg.turn_direction.as_deref(),
+                    &star.designator,
+                    self,
+                )
+            })
+            .collect::<Vec<_>>();
+        ResolvedProcedure {
+            legs: order_legs(legs),
+            name: star.designator.clone(),
+            airport: self.airport_icao(star.airport_heliport.as_deref()),
+        }
+    }
+
+    fn airport_icao(&self, id: Option<&str>) -> Option<String> {
+        id.and_then(|id| self.airports.get(id))
+            .map(|airport| airport.icao.clone())
+    }
+
     fn procedure_exit_points(
         &self,
-        procedure_ids: &std::collections::HashSet<String>,
+        procedure_ids: &std::collections::HashSet<GmlId>,
         is_departure: bool,
     ) -> Vec<PointReference> {
         let legs: Vec<(PointReference, PointReference)> = if is_departure {
             self.departure_legs
                 .values()
-                .filter(|leg| leg.departure.as_ref().is_some_and(|id| procedure_ids.contains(id)))
+                .filter(|leg| {
+                    leg.departure
+                        .as_ref()
+                        .is_some_and(|id| procedure_ids.contains(id.as_str()))
+                })
                 .map(|leg| (leg.start.clone(), leg.end.clone()))
                 .collect()
         } else {
             self.arrival_legs
                 .values()
-                .filter(|leg| leg.arrival.as_ref().is_some_and(|id| procedure_ids.contains(id)))
+                .filter(|leg| {
+                    leg.arrival
+                        .as_ref()
+                        .is_some_and(|id| procedure_ids.contains(id.as_str()))
+                })
                 .map(|leg| (leg.start.clone(), leg.end.clone()))
                 .collect()
         };
 
-        let mut indegree: HashMap<String, usize> = HashMap::new();
-        let mut outdegree: HashMap<String, usize> = HashMap::new();
-        let mut refs: HashMap<String, PointReference> = HashMap::new();
+        let mut indegree: HashMap<GmlId, usize> = HashMap::new();
+        let mut outdegree: HashMap<GmlId, usize> = HashMap::new();
+        let mut refs: HashMap<GmlId, PointReference> = HashMap::new();
 
         for (start, end) in &legs {
-            let s = start.name();
-            let e = end.name();
-
-            if !s.is_empty() {
+            if let Some(s) = start.id() {
                 refs.insert(s.clone(), start.clone());
-                outdegree.entry(s).and_modify(|v| *v += 1).or_insert(1);
+                outdegree.entry(s.clone()).and_modify(|v| *v += 1).or_insert(1);
             }
-            if !e.is_empty() {
+            if let Some(e) = end.id() {
                 refs.insert(e.clone(), end.clone());
-                indegree.entry(e).and_modify(|v| *v += 1).or_insert(1);
+                indegree.entry(e.clone()).and_modify(|v| *v += 1).or_insert(1);
             }
         }
 
         refs.into_iter()
-            .filter_map(|(name, point_ref)| {
+            .filter_map(|(id, point_ref)| {
                 if point_ref.is_airport_heliport() {
                     return None;
                 }
-                let in_d = *indegree.get(&name).unwrap_or(&0);
-                let out_d = *outdegree.get(&name).unwrap_or(&0);
+                let in_d = *indegree.get(&id).unwrap_or(&0);
+                let out_d = *outdegree.get(&id).unwrap_or(&0);
                 if out_d == 0 && in_d > 0 {
                     Some(point_ref)
                 } else {
@@ -278,48 +1481,53 @@ impl AirwayDatabase {
 
     fn procedure_entry_points(
         &self,
-        procedure_ids: &std::collections::HashSet<String>,
+        procedure_ids: &std::collections::HashSet<GmlId>,
         is_departure: bool,
     ) -> Vec<PointReference> {
         let legs: Vec<(PointReference, PointReference)> = if is_departure {
             self.departure_legs
                 .values()
-                .filter(|leg| leg.departure.as_ref().is_some_and(|id| procedure_ids.contains(id)))
+                .filter(|leg| {
+                    leg.departure
+                        .as_ref()
+                        .is_some_and(|id| procedure_ids.contains(id.as_str()))
+                })
                 .map(|leg| (leg.start.clone(), leg.end.clone()))
                 .collect()
         } else {
             self.arrival_legs
                 .values()
-                .filter(|leg| leg.arrival.as_ref().is_some_and(|id| procedure_ids.contains(id)))
+                .filter(|leg| {
+                    leg.arrival
+                        .as_ref()
+                        .is_some_and(|id| procedure_ids.contains(id.as_str()))
+                })
                 .map(|leg| (leg.start.clone(), leg.end.clone()))
                 .collect()
         };
 
-        let mut indegree: HashMap<String, usize> = HashMap::new();
-        let mut outdegree: HashMap<String, usize> = HashMap::new();
-        let mut refs: HashMap<String, PointReference> = HashMap::new();
+        let mut indegree: HashMap<GmlId, usize> = HashMap::new();
+        let mut outdegree: HashMap<GmlId, usize> = HashMap::new();
+        let mut refs: HashMap<GmlId, PointReference> = HashMap::new();
 
         for (start, end) in &legs {
-            let s = start.name();
-            let e = end.name();
-
-            if !s.is_empty() {
+            if let Some(s) = start.id() {
                 refs.insert(s.clone(), start.clone());
-                outdegree.entry(s).and_modify(|v| *v += 1).or_insert(1);
+                outdegree.entry(s.clone()).and_modify(|v| *v += 1).or_insert(1);
             }
-            if !e.is_empty() {
+            if let Some(e) = end.id() {
                 refs.insert(e.clone(), end.clone());
-                indegree.entry(e).and_modify(|v| *v += 1).or_insert(1);
+                indegree.entry(e.clone()).and_modify(|v| *v += 1).or_insert(1);
             }
         }
 
         refs.into_iter()
-            .filter_map(|(name, point_ref)| {
+            .filter_map(|(id, point_ref)| {
                 if point_ref.is_airport_heliport() {
                     return None;
                 }
-                let in_d = *indegree.get(&name).unwrap_or(&0);
-                let out_d = *outdegree.get(&name).unwrap_or(&0);
+                let in_d = *indegree.get(&id).unwrap_or(&0);
+                let out_d = *outdegree.get(&id).unwrap_or(&0);
                 if in_d == 0 && out_d > 0 {
                     Some(point_ref)
                 } else {
@@ -330,7 +1538,555 @@ impl AirwayDatabase {
     }
 }
 
-fn order_route_segments(segments: Vec<ResolvedRouteSegment>) -> Vec<ResolvedRouteSegment> {
+/// Record count and estimated memory footprint of a single dataset.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DatasetStats {
+    pub dataset: Dataset,
+    /// Number of records in the dataset
+    pub records: usize,
+    /// Estimated heap usage in bytes
+    pub estimated_bytes: usize,
+}
+
+/// Content and memory footprint statistics of an [`AirwayDatabase`].
+///
+/// Memory usage is only an estimate (struct sizes plus string lengths),
+/// good enough for capacity planning.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DatabaseStats {
+    pub datasets: Vec<DatasetStats>,
+    /// Estimated heap usage in bytes, for all datasets
+    pub estimated_bytes: usize,
+    /// Number of routes without any route segment
+    pub empty_routes: usize,
+    /// Number of navaids and designated points not referenced by any segment or leg
+    pub orphan_points: usize,
+    /// Number of route segments with an endpoint missing from the database
+    pub dangling_segments: usize,
+}
+
+/// The kind of object a [`CompletionEntry`] refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompletionKind {
+    Airport,
+    Navaid,
+    DesignatedPoint,
+    Route,
+}
+
+/// A designator matching a completion prefix, as returned by [`AirwayDatabase::complete`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompletionEntry {
+    /// Designator, in uppercase (e.g. "NARAK", "LFBO", "UN869")
+    pub name: String,
+    pub kind: CompletionKind,
+    /// Latitude in decimal degrees, for points only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latitude: Option<f64>,
+    /// Longitude in decimal degrees, for points only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub longitude: Option<f64>,
+}
+
+impl CompletionEntry {
+    fn point(name: &str, kind: CompletionKind, latitude: f64, longitude: f64) -> Self {
+        CompletionEntry {
+            name: name.trim().to_uppercase(),
+            kind,
+            latitude: Some(latitude),
+            longitude: Some(longitude),
+        }
+    }
+}
+
+/// Quality indicators of a single route, as listed by [`AirwayDatabase::route_report`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RouteReportEntry {
+    /// Identifier of the route in the database
+    pub identifier: String,
+    /// Route designator (e.g. "UN869")
+    pub designator: String,
+    /// Number of route segments forming the route
+    pub segments: usize,
+    /// Total length of the resolved segments, in meters
+    pub length: f64,
+    /// Number of segments with an endpoint missing from the database
+    pub unresolved_segments: usize,
+    /// Whether the resolved segments chain into a single line
+    pub continuous: bool,
+}
+
+/// One fix of [`AirwayDatabase::fix_table`], merging records with the same
+/// designator at about the same position.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FixRecord {
+    /// ICAO code, navaid name or designator, trimmed and uppercased
+    pub designator: String,
+    /// Kind of the first record: `AirportHeliport`, `Navaid` or `DesignatedPoint`
+    pub kind: String,
+    /// Identifier of the first record
+    pub identifier: String,
+    /// Latitude of the first record, in decimal degrees
+    pub latitude: f64,
+    /// Longitude of the first record, in decimal degrees
+    pub longitude: f64,
+    /// All the records merged into this fix, the first one included
+    pub sources: Vec<FixSource>,
+}
+
+/// A database record merged into a [`FixRecord`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FixSource {
+    /// `AirportHeliport`, `Navaid` or `DesignatedPoint`
+    pub kind: String,
+    /// Identifier of the record in the database
+    pub identifier: String,
+}
+
+impl FixSource {
+    fn new(kind: &str, identifier: &str) -> Self {
+        FixSource {
+            kind: kind.to_string(),
+            identifier: identifier.to_string(),
+        }
+    }
+}
+
+/// Options of [`AirwayDatabase::check_coordinate_swaps`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoordinateSwapOptions {
+    /// Distance to the bounding box of all points beyond which a point is suspect, in degrees
+    pub margin: f64,
+    /// Length beyond which a route segment is suspect, in meters
+    pub max_segment_length: f64,
+    /// Swap back the coordinates of suspect points which then fall inside the bounding box
+    pub fix_suspected_swaps: bool,
+}
+
+impl Default for CoordinateSwapOptions {
+    fn default() -> Self {
+        CoordinateSwapOptions {
+            margin: 10.,
+            max_segment_length: 3_000_000.,
+            fix_suspected_swaps: false,
+        }
+    }
+}
+
+/// A navaid or designated point whose coordinates are probably swapped.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SuspectedSwap {
+    /// Identifier of the point in the database
+    pub identifier: String,
+    /// Name of the navaid or designator of the designated point
+    pub name: Option<String>,
+    /// Latitude as published, in decimal degrees
+    pub latitude: f64,
+    /// Longitude as published, in decimal degrees
+    pub longitude: f64,
+    /// Whether the coordinates were swapped back
+    pub fixed: bool,
+}
+
+/// A route segment whose length suggests swapped coordinates at one of its ends.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SuspectedSegment {
+    /// Identifier of the route segment in the database
+    pub identifier: String,
+    /// Identifier of the start point
+    pub start: Option<String>,
+    /// Identifier of the end point
+    pub end: Option<String>,
+    /// Length of the segment, in meters
+    pub length: f64,
+}
+
+/// The findings of [`AirwayDatabase::check_coordinate_swaps`], sorted by identifier.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct CoordinateSwapReport {
+    pub points: Vec<SuspectedSwap>,
+    pub segments: Vec<SuspectedSegment>,
+}
+
+/// The AIRAC cycle in the name of a data directory, e.g. "2508" for `aixm_2508`
+/// or `2508/aixm`: the last group of four digits which is a valid AIRAC code.
+fn airac_cycle_from_path(path: &path::Path) -> Option<String> {
+    path.components().rev().find_map(|component| {
+        component
+            .as_os_str()
+            .to_str()?
+            .split(|c: char| !c.is_ascii_digit())
+            .rev()
+            .find(|digits| digits.len() == 4 && effective_date_from_airac_code(digits).is_ok())
+            .map(str::to_string)
+    })
+}
+
+/// The `[south, north, west, east]` bounds of coordinates, ignoring the most
+/// extreme percent of values on each side (at least one), or `None` when too
+/// few coordinates are left.
+fn trimmed_bounds(coords: &[(f64, f64)]) -> Option<[f64; 4]> {
+    let trim = (coords.len() / 100).max(1);
+    if coords.len() <= 2 * trim {
+        return None;
+    }
+    let bounds = |mut values: Vec<f64>| {
+        values.sort_by(f64::total_cmp);
+        (values[trim], values[values.len() - 1 - trim])
+    };
+    let (south, north) = bounds(coords.iter().map(|c| c.0).collect());
+    let (west, east) = bounds(coords.iter().map(|c| c.1).collect());
+    Some([south, north, west, east])
+}
+
+impl DatabaseStats {
+    fn compute(db: &AirwayDatabase) -> Self {
+        let airways = db.airways();
+        let datasets = Dataset::ALL
+            .iter()
+            .map(|&dataset| {
+                let (records, estimated_bytes) = match dataset {
+                    Dataset::AirportHeliport => (db.airports.len(), estimate_map_size(&db.airports)),
+                    // Mapped or archived points are backed by their file, not the heap
+                    Dataset::Navaid => match &db.points {
+                        PointStore::Memory { navaids, .. } => (navaids.len(), estimate_map_size(navaids)),
+                        _ => (db.points.navaid_count(), 0),
+                    },
+                    Dataset::DesignatedPoint => match &db.points {
+                        PointStore::Memory { designated_points, .. } => {
+                            (designated_points.len(), estimate_map_size(designated_points))
+                        }
+                        _ => (db.points.designated_point_count(), 0),
+                    },
+                    Dataset::RouteSegment => (airways.route_segments.len(), estimate_map_size(&airways.route_segments)),
+                    Dataset::Route => (airways.routes.len(), estimate_map_size(&airways.routes)),
+                    Dataset::ArrivalLeg => (db.arrival_legs.len(), estimate_map_size(&db.arrival_legs)),
+                    Dataset::DepartureLeg => (db.departure_legs.len(), estimate_map_size(&db.departure_legs)),
+                    Dataset::StandardInstrumentArrival => (
+                        db.standard_instrument_arrivals.len(),
+                        estimate_map_size(&db.standard_instrument_arrivals),
+                    ),
+                    Dataset::StandardInstrumentDeparture => (
+                        db.standard_instrument_departures.len(),
+                        estimate_map_size(&db.standard_instrument_departures),
+                    ),
+                };
+                DatasetStats {
+                    dataset,
+                    records,
+                    estimated_bytes,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let empty_routes = airways
+            .routes
+            .values()
+            .filter(|route| route.segments.is_empty())
+            .count();
+
+        let referenced = airways
+            .route_segments
+            .values()
+            .flat_map(|segment| [&segment.start, &segment.end])
+            .chain(db.arrival_legs.values().flat_map(|leg| [&leg.start, &leg.end]))
+            .chain(db.departure_legs.values().flat_map(|leg| [&leg.start, &leg.end]))
+            .filter_map(|point| match point {
+                PointReference::Navaid(id) | PointReference::DesignatedPoint(id) => Some(id.as_str()),
+                _ => None,
+            })
+            .collect::<std::collections::HashSet<_>>();
+        let orphan_points = db
+            .points
+            .navaids()
+            .filter(|navaid| !referenced.contains(navaid.identifier.as_str()))
+            .count()
+            + db.points
+                .designated_points()
+                .filter(|dp| !referenced.contains(dp.identifier.as_str()))
+                .count();
+
+        let exists = |point: &PointReference| match point {
+            PointReference::AirportHeliport(id) => db.airports.contains_key(id),
+            PointReference::Navaid(id) => db.points.contains_navaid(id),
+            PointReference::DesignatedPoint(id) => db.points.contains_designated_point(id),
+            PointReference::Coordinates(..) => true,
+            PointReference::None => false,
+        };
+        let dangling_segments = airways
+            .route_segments
+            .values()
+            .filter(|segment| !exists(&segment.start) || !exists(&segment.end))
+            .count();
+
+        DatabaseStats {
+            estimated_bytes: datasets.iter().map(|d| d.estimated_bytes).sum(),
+            datasets,
+            empty_routes,
+            orphan_points,
+            dangling_segments,
+        }
+    }
+}
+
+/// Rough estimation of the heap memory owned by a value.
+pub(crate) trait HeapSize {
+    fn heap_size(&self) -> usize;
+}
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl HeapSize for GmlId {
+    fn heap_size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, HeapSize::heap_size)
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.len() * std::mem::size_of::<T>() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl HeapSize for PointReference {
+    fn heap_size(&self) -> usize {
+        self.name().len()
+    }
+}
+
+impl HeapSize for AirportHeliport {
+    fn heap_size(&self) -> usize {
+        self.identifier.heap_size()
+            + self.iata.heap_size()
+            + self.icao.heap_size()
+            + self.designator.heap_size()
+            + self.name.heap_size()
+            + self.city.heap_size()
+            + self.control_type.heap_size()
+            + self.time_zone.heap_size()
+    }
+}
+
+impl HeapSize for Navaid {
+    fn heap_size(&self) -> usize {
+        self.identifier.heap_size()
+            + self.name.heap_size()
+            + self.r#type.heap_size()
+            + self.description.heap_size()
+            + self.call_sign.heap_size()
+    }
+}
+
+impl HeapSize for DesignatedPointType {
+    fn heap_size(&self) -> usize {
+        match self {
+            DesignatedPointType::Other(value) => value.heap_size(),
+            _ => 0,
+        }
+    }
+}
+
+impl HeapSize for ProcedureFixRole {
+    fn heap_size(&self) -> usize {
+        match self {
+            ProcedureFixRole::Other(value) => value.heap_size(),
+            _ => 0,
+        }
+    }
+}
+
+impl HeapSize for DesignatedPoint {
+    fn heap_size(&self) -> usize {
+        self.identifier.heap_size() + self.designator.heap_size() + self.name.heap_size() + self.r#type.heap_size()
+    }
+}
+
+impl HeapSize for RouteSegment {
+    fn heap_size(&self) -> usize {
+        self.identifier.heap_size()
+            + self.route_formed.heap_size()
+            + self.start.heap_size()
+            + self.end.heap_size()
+            + self.availability.capacity() * std::mem::size_of::<RouteAvailability>()
+    }
+}
+
+impl HeapSize for Route {
+    fn heap_size(&self) -> usize {
+        self.identifier.heap_size()
+            + self.prefix.heap_size()
+            + self.second_letter.heap_size()
+            + self.number.heap_size()
+            + self.multiple_identifier.heap_size()
+            + self.segments.heap_size()
+    }
+}
+
+impl HeapSize for ArrivalLeg {
+    fn heap_size(&self) -> usize {
+        self.identifier.heap_size()
+            + self.arrival.heap_size()
+            + self.start.heap_size()
+            + self.end.heap_size()
+            + self.leg_type.heap_size()
+            + self.turn_direction.heap_size()
+            + self.arc_centre.heap_size()
+            + self.start_role.heap_size()
+            + self.end_role.heap_size()
+    }
+}
+
+impl HeapSize for DepartureLeg {
+    fn heap_size(&self) -> usize {
+        self.identifier.heap_size()
+            + self.departure.heap_size()
+            + self.start.heap_size()
+            + self.end.heap_size()
+            + self.leg_type.heap_size()
+            + self.turn_direction.heap_size()
+            + self.arc_centre.heap_size()
+            + self.start_role.heap_size()
+            + self.end_role.heap_size()
+    }
+}
+
+impl HeapSize for StandardInstrumentArrival {
+    fn heap_size(&self) -> usize {
+        self.identifier.heap_size()
+            + self.designator.heap_size()
+            + self.airport_heliport.heap_size()
+            + self.instruction.heap_size()
+            + self.connecting_points.heap_size()
+            + self.connecting_routes.heap_size()
+    }
+}
+
+impl HeapSize for StandardInstrumentDeparture {
+    fn heap_size(&self) -> usize {
+        self.identifier.heap_size()
+            + self.designator.heap_size()
+            + self.airport_heliport.heap_size()
+            + self.instruction.heap_size()
+            + self.connecting_points.heap_size()
+            + self.connecting_routes.heap_size()
+    }
+}
+
+impl HeapSize for ResolvedPoint {
+    fn heap_size(&self) -> usize {
+        match self {
+            ResolvedPoint::AirportHeliport(airport) => airport.heap_size(),
+            ResolvedPoint::Navaid(navaid) => navaid.heap_size(),
+            ResolvedPoint::DesignatedPoint(point) => point.heap_size(),
+            ResolvedPoint::Coordinates { .. } | ResolvedPoint::None => 0,
+        }
+    }
+}
+
+impl HeapSize for ResolvedRouteSegment {
+    fn heap_size(&self) -> usize {
+        self.start.heap_size() + self.end.heap_size() + self.name.heap_size() + self.source_segment.heap_size()
+    }
+}
+
+impl HeapSize for UnresolvedToken {
+    fn heap_size(&self) -> usize {
+        self.token.heap_size() + self.suggestions.heap_size() + self.hint.heap_size()
+    }
+}
+
+impl HeapSize for InconsistentToken {
+    fn heap_size(&self) -> usize {
+        self.token.heap_size()
+    }
+}
+
+impl HeapSize for AirwaySubstitution {
+    fn heap_size(&self) -> usize {
+        self.filed.heap_size() + self.substitute.heap_size()
+    }
+}
+
+impl HeapSize for BoundaryAirway {
+    fn heap_size(&self) -> usize {
+        self.airway.heap_size()
+    }
+}
+
+impl HeapSize for PartialAirway {
+    fn heap_size(&self) -> usize {
+        self.airway.heap_size()
+    }
+}
+
+impl HeapSize for RouteGap {
+    fn heap_size(&self) -> usize {
+        self.start.heap_size() + self.end.heap_size()
+    }
+}
+
+impl HeapSize for EnrichmentReport {
+    fn heap_size(&self) -> usize {
+        self.unresolved.heap_size()
+            + self.inconsistencies.heap_size()
+            + self.substitutions.heap_size()
+            + self.boundary_airways.heap_size()
+            + self.partial_airways.heap_size()
+            + self.gaps.heap_size()
+    }
+}
+
+fn estimate_map_size<K: HeapSize, V: HeapSize>(map: &HashMap<K, V>) -> usize {
+    map.capacity() * std::mem::size_of::<(K, V)>()
+        + map
+            .iter()
+            .map(|(key, value)| key.heap_size() + value.heap_size())
+            .sum::<usize>()
+}
+
+fn order_legs(legs: Vec<ResolvedLeg>) -> Vec<ResolvedLeg> {
+    let mut details = legs
+        .iter()
+        .map(|leg| {
+            let key = (leg.segment.start.clone(), leg.segment.end.clone());
+            (key, (leg.geometry, leg.start_role.clone(), leg.end_role.clone()))
+        })
+        .collect::<HashMap<_, _>>();
+    let segments = legs.into_iter().map(|leg| leg.segment).collect();
+    order_route_segments(segments)
+        .into_iter()
+        .map(|segment| {
+            let (geometry, start_role, end_role) = details
+                .remove(&(segment.start.clone(), segment.end.clone()))
+                .unwrap_or((LegGeometry::Straight, None, None));
+            ResolvedLeg {
+                segment,
+                geometry,
+                start_role,
+                end_role,
+            }
+        })
+        .collect()
+}
+
+fn sort_procedures(mut procedures: Vec<ResolvedProcedure>) -> Vec<ResolvedProcedure> {
+    procedures.sort_by(|a, b| (&a.airport, &a.name).cmp(&(&b.airport, &b.name)));
+    procedures
+}
+
+/// Order segments so that each one starts where the previous one ends, when possible.
+pub(crate) fn order_route_segments(segments: Vec<ResolvedRouteSegment>) -> Vec<ResolvedRouteSegment> {
     let mut out_map: HashMap<ResolvedPoint, Vec<ResolvedRouteSegment>> = HashMap::new();
     let mut indegree: HashMap<ResolvedPoint, usize> = HashMap::new();
 
@@ -393,9 +2149,6 @@ const VALID_ROUTE_PREFIXES: [&str; 32] = [
     "H", "J", "Q", "R", "T", "V", "W", "Y", "Z", "M", "N", "P",
 ];
 
-/// The WGS84 ellipsoid.
-static WGS84: Lazy<Ellipsoid> = Lazy::new(|| Ellipsoid::named("WGS84").unwrap());
-
 /// A complete flight route decomposed into segments with geographic and altitude/speed constraints.
 ///
 /// Represents a resolved or enriched route with waypoints and metadata about altitude and speed
@@ -422,7 +2175,14 @@ pub struct ResolvedRoute {
 /// - `name`: Optional identifier for the segment (e.g., "Q400", route number)
 /// - `altitude`: Altitude constraint if specified in the procedure or route definition
 /// - `speed`: Speed constraint if specified in the procedure or route definition
+/// - `level`: Requested level (single level, block or VFR) from the flight plan
+/// - `overfly`: Whether the end point must be overflown
+/// - `stay`: Duration of a STAY at the end point, in minutes, if filed with one
+/// - `source_segment`: Identifier (UUID) of the AIXM route segment this segment comes from, if any
+/// - `is_reversed`: Whether the segment is traversed from the end to the start of its AIXM definition
+/// - `overlay`: Whether the segment comes from the [overlay](AirwayDatabase::with_overlay), or has an overlay endpoint
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ResolvedRouteSegment {
     pub start: ResolvedPoint,
     pub end: ResolvedPoint,
@@ -432,6 +2192,128 @@ pub struct ResolvedRouteSegment {
     pub altitude: Option<Altitude>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub speed: Option<Speed>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<LevelConstraint>,
+    /// The end point is filed with an overfly prefix, and must be overflown
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub overfly: bool,
+    /// The end point is followed by a STAY group (e.g. `STAY1/0130`), of this many minutes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stay: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_segment: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_reversed: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub overlay: bool,
+}
+
+/// Name of the segments bridging the discontinuities of an enriched route, see
+/// [`ResolvedRouteSegment::is_gap`].
+pub const GAP: &str = "GAP";
+
+/// A resolved SID or STAR procedure, keeping the geometry of each leg.
+///
+/// # Fields
+/// - `legs`: Ordered list of legs from the first to the last point of the procedure
+/// - `name`: Procedure designator (e.g., "RCKT2")
+/// - `airport`: ICAO code of the airport of the procedure, if known
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedProcedure {
+    pub legs: Vec<ResolvedLeg>,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub airport: Option<String>,
+}
+
+impl ResolvedRoute {
+    /// Distance flown inside each named region (e.g. a FIR), in meters.
+    ///
+    /// Regions are polygons of (latitude, longitude) vertices, in degrees, see
+    /// [`geometry::point_in_polygon`].
+    /// Only regions the route enters are returned; a name given for several
+    /// polygons sums the distance flown in each of them.
+    pub fn distance_by_region(&self, regions: &[(String, Vec<(f64, f64)>)]) -> HashMap<String, f64> {
+        let mut distances = HashMap::new();
+        for (name, polygon) in regions {
+            let distance = geometry::distance_in_polygon(&self.segments, polygon);
+            if distance > 0. {
+                *distances.entry(name.clone()).or_insert(0.) += distance;
+            }
+        }
+        distances
+    }
+}
+
+impl From<ResolvedProcedure> for ResolvedRoute {
+    fn from(procedure: ResolvedProcedure) -> Self {
+        ResolvedRoute {
+            segments: procedure.legs.into_iter().map(|leg| leg.segment).collect(),
+            name: procedure.name,
+        }
+    }
+}
+
+/// A single leg of a terminal procedure, with its geometry.
+///
+/// Use [`ResolvedLeg::samples`] to get a polyline which follows arcs instead of
+/// cutting corners between the start and end points.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedLeg {
+    #[serde(flatten)]
+    pub segment: ResolvedRouteSegment,
+    pub geometry: LegGeometry,
+    /// Procedural role of the start point (e.g. IAF), if published
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_role: Option<ProcedureFixRole>,
+    /// Procedural role of the end point (e.g. FAF), if published
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_role: Option<ProcedureFixRole>,
+}
+
+impl ResolvedLeg {
+    /// Resolve a leg from its `[start, end, arc_centre]` points, the roles of its
+    /// endpoints and ARINC 424 fields.
+    fn from_db(
+        [start, end, arc_centre]: [&PointReference; 3],
+        [start_role, end_role]: [&Option<ProcedureFixRole>; 2],
+        leg_type: Option<&str>,
+        course: Option<f64>,
+        turn_direction: Option<&str>,
+        name: &str,
+        db: &AirwayDatabase,
+    ) -> Option<Self> {
+        let start = ResolvedPoint::from_db(start, db);
+        let end = ResolvedPoint::from_db(end, db);
+        if matches!(start, ResolvedPoint::None) || matches!(end, ResolvedPoint::None) {
+            return None;
+        }
+        let arc_centre = ResolvedPoint::from_db(arc_centre, db);
+        let geometry = LegGeometry::from_arinc(leg_type, course, turn_direction, &arc_centre, &end);
+        Some(ResolvedLeg {
+            segment: ResolvedRouteSegment {
+                start,
+                end,
+                name: Some(name.to_string()),
+                altitude: None,
+                speed: None,
+                level: None,
+                overfly: false,
+                stay: None,
+                source_segment: None,
+                is_reversed: false,
+                overlay: false,
+            },
+            geometry,
+            start_role: start_role.clone(),
+            end_role: end_role.clone(),
+        })
+    }
+
+    /// Sample the leg as (latitude, longitude) pairs at most `spacing_m` meters apart.
+    pub fn samples(&self, spacing_m: f64) -> Vec<(f64, f64)> {
+        geometry::sample(self, spacing_m)
+    }
 }
 
 /// A resolved waypoint on a flight route, resolved to a specific geographic type.
@@ -447,6 +2329,7 @@ pub struct ResolvedRouteSegment {
 /// - `Coordinates`: Raw latitude/longitude pair (typically for procedural segments)
 /// - `None`: Point reference could not be resolved to a known location
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum ResolvedPoint {
     AirportHeliport(AirportHeliport),
@@ -463,7 +2346,9 @@ impl std::fmt::Display for ResolvedPoint {
                 write!(
                     f,
                     "AirportHeliport({}: {:.3},{:.3})",
-                    airport.icao, airport.latitude, airport.longitude
+                    airport.code(),
+                    airport.latitude,
+                    airport.longitude
                 )
             }
             ResolvedPoint::Navaid(navaid) => write!(
@@ -486,18 +2371,6 @@ impl std::fmt::Display for ResolvedPoint {
     }
 }
 
-impl From<&ResolvedPoint> for Coor2D {
-    fn from(val: &ResolvedPoint) -> Self {
-        match val {
-            ResolvedPoint::AirportHeliport(airport) => Coor2D::geo(airport.latitude, airport.longitude),
-            ResolvedPoint::Navaid(navaid) => Coor2D::geo(navaid.latitude, navaid.longitude),
-            ResolvedPoint::DesignatedPoint(dp) => Coor2D::geo(dp.latitude, dp.longitude),
-            ResolvedPoint::Coordinates { latitude, longitude } => Coor2D::geo(*latitude, *longitude),
-            ResolvedPoint::None => Coor2D::default(),
-        }
-    }
-}
-
 impl PartialEq for ResolvedPoint {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -547,63 +2420,205 @@ impl Hash for ResolvedPoint {
 }
 
 impl ResolvedPoint {
-    /// Resolve a point from the database.
-    pub fn from_db(point: &PointReference, db: &AirwayDatabase) -> Self {
-        match point {
-            PointReference::AirportHeliport(id) => {
-                if let Some(airport) = db.airports.get(id) {
-                    ResolvedPoint::AirportHeliport(airport.clone())
-                } else {
+    /// The position of the point for geodesic computations, or `None` if unresolved.
+    pub fn coor(&self) -> Option<Coor2D> {
+        match self {
+            ResolvedPoint::AirportHeliport(airport) => Some(Coor2D::geo(airport.latitude, airport.longitude)),
+            ResolvedPoint::Navaid(navaid) => Some(Coor2D::geo(navaid.latitude, navaid.longitude)),
+            ResolvedPoint::DesignatedPoint(dp) => Some(Coor2D::geo(dp.latitude, dp.longitude)),
+            ResolvedPoint::Coordinates { latitude, longitude } => Some(Coor2D::geo(*latitude, *longitude)),
+            ResolvedPoint::None => None,
+        }
+    }
+
+    /// The magnetic variation at the point in degrees, positive East, if published:
+    /// only airports and navaids publish one.
+    pub fn magnetic_variation(&self) -> Option<f64> {
+        match self {
+            ResolvedPoint::AirportHeliport(airport) => airport.magnetic_variation,
+            ResolvedPoint::Navaid(navaid) => navaid.magnetic_variation,
+            _ => None,
+        }
+    }
+
+    /// Resolve a point from the database.
+    pub fn from_db(point: &PointReference, db: &AirwayDatabase) -> Self {
+        match point {
+            PointReference::AirportHeliport(id) => {
+                if let Some(airport) = db.airports.get(id) {
+                    ResolvedPoint::AirportHeliport(airport.clone())
+                } else {
                     ResolvedPoint::None
                 }
             }
             PointReference::Navaid(id) => {
-                if let Some(navaid) = db.navaids.get(id) {
-                    ResolvedPoint::Navaid(navaid.clone())
+                let overlay = db.overlay.as_ref().and_then(|overlay| overlay.points.navaid(id));
+                if let Some(navaid) = overlay.or_else(|| db.points.navaid(id)) {
+                    ResolvedPoint::Navaid(navaid.into_owned())
                 } else {
                     ResolvedPoint::None
                 }
             }
             PointReference::DesignatedPoint(id) => {
-                if let Some(dp) = db.designated_points.get(id) {
-                    ResolvedPoint::DesignatedPoint(dp.clone())
+                let overlay = db
+                    .overlay
+                    .as_ref()
+                    .and_then(|overlay| overlay.points.designated_point(id));
+                if let Some(dp) = overlay.or_else(|| db.points.designated_point(id)) {
+                    ResolvedPoint::DesignatedPoint(dp.into_owned())
                 } else {
                     ResolvedPoint::None
                 }
             }
+            PointReference::Coordinates(latitude, longitude) => ResolvedPoint::Coordinates {
+                latitude: *latitude,
+                longitude: *longitude,
+            },
             PointReference::None => ResolvedPoint::None,
         }
     }
+
+    /// The reference of a navaid or designated point, as found in route segments.
+    pub fn reference(&self) -> Option<PointReference> {
+        match self {
+            ResolvedPoint::Navaid(navaid) => Some(PointReference::Navaid(navaid.identifier.clone().into())),
+            ResolvedPoint::DesignatedPoint(dp) => Some(PointReference::DesignatedPoint(dp.identifier.clone().into())),
+            _ => None,
+        }
+    }
+
     /// Resolve a point by its name from the database.
+    ///
+    /// Points of the [overlay](AirwayDatabase::with_overlay) take precedence over
+    /// all database points. Then navaids and designated points are returned
+    /// together, navaids first, and left to the geometric disambiguation of route
+    /// enrichment: a designated point may be the fix meant in a route although a
+    /// navaid elsewhere shares its name. With [`EnrichmentOptions::navaid_priority`],
+    /// designated points are only returned when no navaid matches the name.
+    /// Airports and heliports are only returned when no fix matches the name (e.g. a
+    /// refuelling stop) by their ICAO code or designator, see
+    /// [`ResolvedPoint::lookup_airports`].
     pub fn lookup(name: &str, db: &AirwayDatabase) -> Vec<Self> {
-        let candidates = db
-            .navaids
-            .values()
-            .filter(|n| {
-                n.name
-                    .as_deref()
-                    .is_some_and(|n_name| n_name.trim().eq_ignore_ascii_case(name))
-            })
+        if let Some(overlay) = &db.overlay {
+            let candidates = (overlay
+                .points
+                .navaids_named(name)
+                .into_iter()
+                .map(ResolvedPoint::Navaid))
+            .chain(
+                overlay
+                    .points
+                    .designated_points_named(name)
+                    .into_iter()
+                    .map(ResolvedPoint::DesignatedPoint),
+            )
             .collect::<Vec<_>>();
-        if !candidates.is_empty() {
-            return candidates.iter().map(|n| ResolvedPoint::Navaid((*n).clone())).collect();
+            if !candidates.is_empty() {
+                return candidates;
+            }
         }
-        let candidates = db
-            .designated_points
-            .values()
-            .filter(|dp| dp.designator.trim().eq_ignore_ascii_case(name))
-            .collect::<Vec<_>>();
+        let navaids = Self::lookup_navaids(name, db);
+        if db.options.navaid_priority && !navaids.is_empty() {
+            return navaids;
+        }
+        let designated_points = db.prefer_point_types(Self::lookup_designated_points(name, db));
+        let candidates = navaids.into_iter().chain(designated_points).collect::<Vec<_>>();
         if !candidates.is_empty() {
-            return candidates
-                .iter()
-                .map(|dp| ResolvedPoint::DesignatedPoint((*dp).clone()))
-                .collect();
+            return candidates;
         }
-        vec![]
+        Self::lookup_airports(name, db)
+    }
+
+    /// Resolve navaids by their name from the database, in the overlay first.
+    pub fn lookup_navaids(name: &str, db: &AirwayDatabase) -> Vec<Self> {
+        db.overlay
+            .as_ref()
+            .map(|overlay| overlay.points.navaids_named(name))
+            .filter(|navaids| !navaids.is_empty())
+            .unwrap_or_else(|| db.points.navaids_named(name))
+            .into_iter()
+            .map(ResolvedPoint::Navaid)
+            .collect()
+    }
+
+    /// Resolve airports by their ICAO location indicator from the database.
+    ///
+    /// Facilities without ICAO location indicator, like many heliports, are
+    /// resolved by their designator instead, never by their free text name.
+    /// ZZZZ, filed for aerodromes without location indicator, never resolves.
+    pub fn lookup_airports(name: &str, db: &AirwayDatabase) -> Vec<Self> {
+        let name = name.trim();
+        if name.is_empty() || name.eq_ignore_ascii_case(UNNAMED_AERODROME) {
+            return Vec::new();
+        }
+        let matches = |airport: &&AirportHeliport| {
+            if !airport.icao.trim().is_empty() {
+                return airport.icao.trim().eq_ignore_ascii_case(name);
+            }
+            airport
+                .designator
+                .as_deref()
+                .is_some_and(|designator| designator.trim().eq_ignore_ascii_case(name))
+        };
+        let mut airports = db.airports.values().filter(matches).collect::<Vec<_>>();
+        airports.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        airports
+            .into_iter()
+            .cloned()
+            .map(ResolvedPoint::AirportHeliport)
+            .collect()
     }
+
+    /// Resolve designated points by their designator from the database, in the overlay first.
+    ///
+    /// VFR reporting points are left out with [`EnrichmentOptions::exclude_vfr_reporting_points`].
+    pub fn lookup_designated_points(name: &str, db: &AirwayDatabase) -> Vec<Self> {
+        db.overlay
+            .as_ref()
+            .map(|overlay| overlay.points.designated_points_named(name))
+            .filter(|points| !points.is_empty())
+            .unwrap_or_else(|| db.points.designated_points_named(name))
+            .into_iter()
+            .filter(|dp| !(db.options.exclude_vfr_reporting_points && dp.r#type.is_vfr_reporting_point()))
+            .map(ResolvedPoint::DesignatedPoint)
+            .collect()
+    }
+}
+
+/// A navaid of a route, as listed in a navigation log.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NavLogEntry {
+    /// Name of the navaid
+    pub name: String,
+    /// Radio frequency in MHz
+    pub frequency: Option<f64>,
+    /// Audio identification of the navaid, if published
+    pub call_sign: Option<String>,
+    /// Initial true bearing to the next point, in degrees
+    pub bearing: Option<f64>,
+    /// Distance of the leg to the next point, in meters
+    pub distance: Option<f64>,
 }
 
 impl ResolvedRouteSegment {
+    /// Whether both endpoints of the segment are resolved, and the segment is not a gap.
+    ///
+    /// Segments referencing points missing from the database (e.g. withdrawn
+    /// after mixing AIRAC cycles) are kept by [`ResolvedRouteSegment::from_db`]
+    /// but ignored for routing and geometry.
+    pub fn is_complete(&self) -> bool {
+        !matches!(self.start, ResolvedPoint::None) && !matches!(self.end, ResolvedPoint::None) && !self.is_gap()
+    }
+
+    /// Whether the segment bridges a discontinuity of an enriched route, e.g. over
+    /// an unresolved point, see [`EnrichmentOptions::emit_gaps`].
+    ///
+    /// Gap segments are named [`GAP`]: they are not flown along any airway, and
+    /// the actual path between their endpoints is unknown.
+    pub fn is_gap(&self) -> bool {
+        self.name.as_deref() == Some(GAP)
+    }
+
     /// Resolve a route segment from the database.
     pub fn from_db(segment: &RouteSegment, db: &AirwayDatabase) -> Self {
         ResolvedRouteSegment {
@@ -612,6 +2627,30 @@ impl ResolvedRouteSegment {
             name: None,
             altitude: None,
             speed: None,
+            level: None,
+            overfly: false,
+            stay: None,
+            source_segment: Some(segment.identifier.clone()),
+            is_reversed: false,
+            overlay: false,
+        }
+    }
+
+    /// A segment flown direct between two points, outside any airway, without
+    /// speed or level changes.
+    pub fn direct(start: ResolvedPoint, end: ResolvedPoint) -> Self {
+        ResolvedRouteSegment {
+            start,
+            end,
+            name: None,
+            altitude: None,
+            speed: None,
+            level: None,
+            overfly: false,
+            stay: None,
+            source_segment: None,
+            is_reversed: false,
+            overlay: false,
         }
     }
 }
@@ -619,10 +2658,10 @@ impl ResolvedRouteSegment {
 impl ResolvedRoute {
     /// Resolve a route from the database.
     pub fn from_db(route: &Route, db: &AirwayDatabase) -> Self {
-        let segments = db
-            .route_segments
-            .values()
-            .filter(|segment| segment.route_formed.as_deref() == Some(&route.identifier))
+        let segments = route
+            .segments
+            .iter()
+            .filter_map(|id| db.route_segment(id))
             .map(|segment| ResolvedRouteSegment::from_db(segment, db))
             .collect::<Vec<_>>();
         ResolvedRoute {
@@ -636,43 +2675,259 @@ impl ResolvedRoute {
         }
     }
 
+    /// Named fixes along the route, in order, as (name, latitude, longitude).
+    ///
+    /// Only navaids and designated points are returned: bare coordinates,
+    /// airports and unresolved points are skipped, and a fix shared by two
+    /// consecutive segments is only listed once.
+    pub fn named_fixes(&self) -> Vec<(String, f64, f64)> {
+        let mut fixes: Vec<(String, f64, f64)> = Vec::new();
+        for point in self.segments.iter().flat_map(|s| [&s.start, &s.end]) {
+            let fix = match point {
+                ResolvedPoint::Navaid(navaid) => navaid
+                    .name
+                    .clone()
+                    .map(|name| (name, navaid.latitude, navaid.longitude)),
+                ResolvedPoint::DesignatedPoint(dp) => Some((dp.designator.clone(), dp.latitude, dp.longitude)),
+                _ => None,
+            };
+            if let Some(fix) = fix {
+                if fixes.last() != Some(&fix) {
+                    fixes.push(fix);
+                }
+            }
+        }
+        fixes
+    }
+
+    /// Navigation log entries for the navaids along the route, in order.
+    ///
+    /// Each entry gives the navaid frequency (when known) together with the
+    /// initial bearing and the distance of the leg to the next point of the route.
+    /// The last point of the route has no next leg.
+    pub fn nav_log(&self) -> Vec<NavLogEntry> {
+        let mut points: Vec<&ResolvedPoint> = Vec::new();
+        for point in self.segments.iter().flat_map(|s| [&s.start, &s.end]) {
+            if points.last() != Some(&point) {
+                points.push(point);
+            }
+        }
+        points
+            .iter()
+            .enumerate()
+            .filter_map(|(i, point)| match point {
+                ResolvedPoint::Navaid(navaid) => {
+                    let leg = points.get(i + 1).and_then(|next| next.coor()).map(|next| {
+                        let here = Coor2D::geo(navaid.latitude, navaid.longitude);
+                        let (bearing, distance) = DistanceModel::Ellipsoidal.inverse(&here, &next);
+                        (bearing.rem_euclid(360.), distance)
+                    });
+                    Some(NavLogEntry {
+                        name: navaid.name.clone().unwrap_or_default(),
+                        frequency: navaid.frequency,
+                        call_sign: navaid.call_sign.clone(),
+                        bearing: leg.map(|(bearing, _)| bearing),
+                        distance: leg.map(|(_, distance)| distance),
+                    })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Points the route passes through more than once, e.g. a racetrack or a
+    /// filing error looping back through a fix.
+    ///
+    /// The endpoint shared by consecutive segments counts as a single visit.
+    /// Each revisited point is returned once, in the order of its first visit;
+    /// unresolved points are skipped.
+    pub fn revisited_points(&self) -> Vec<ResolvedPoint> {
+        let mut points: Vec<&ResolvedPoint> = Vec::new();
+        for point in self.segments.iter().flat_map(|s| [&s.start, &s.end]) {
+            if points.last() != Some(&point) {
+                points.push(point);
+            }
+        }
+        let mut visits: HashMap<&ResolvedPoint, usize> = HashMap::new();
+        for point in points.iter().filter(|p| !matches!(p, ResolvedPoint::None)) {
+            *visits.entry(point).or_insert(0) += 1;
+        }
+        let mut revisited: Vec<ResolvedPoint> = Vec::new();
+        for point in points {
+            if visits.get(point).is_some_and(|&n| n > 1) && !revisited.contains(point) {
+                revisited.push(point.clone());
+            }
+        }
+        revisited
+    }
+
+    /// The position of a fix along the route, as the fraction (between 0 and 1)
+    /// of the length of the route flown before reaching it.
+    ///
+    /// A fix visited several times is located at its first visit. Returns `None`
+    /// if the fix is not an endpoint of the route, or if the route has no length.
+    /// Segments with an unresolved endpoint do not count in the length.
+    pub fn fix_progress(&self, point: &ResolvedPoint) -> Option<f64> {
+        let length = |segment: &ResolvedRouteSegment| match (segment.start.coor(), segment.end.coor()) {
+            (Some(start), Some(end)) => DistanceModel::Ellipsoidal.distance(&start, &end),
+            _ => 0.,
+        };
+        let total = self.segments.iter().map(length).sum::<f64>();
+        let mut flown = 0.;
+        let mut at = None;
+        for segment in &self.segments {
+            if segment.start == *point {
+                at = Some(flown);
+                break;
+            }
+            flown += length(segment);
+            if segment.end == *point {
+                at = Some(flown);
+                break;
+            }
+        }
+        at.filter(|_| total > 0.).map(|flown| flown / total)
+    }
+
+    /// The point of the route closest to the given coordinates.
+    ///
+    /// Returns the foot of the perpendicular from (`latitude`, `longitude`) to the
+    /// nearest segment, as (latitude, longitude, segment index). The foot is
+    /// clamped to the segment endpoints, and along-track and cross-track distances
+    /// use a spherical approximation, which is well below a meter for legs of a
+    /// few hundred kilometers. Segments with an unresolved endpoint are skipped.
+    pub fn closest_point(&self, latitude: f64, longitude: f64) -> Option<(f64, f64, usize)> {
+        let point = Coor2D::geo(latitude, longitude);
+        self.segments
+            .iter()
+            .enumerate()
+            .filter_map(|(i, segment)| {
+                let (foot, distance) = geometry::project(&segment.start.coor()?, &segment.end.coor()?, &point);
+                Some((distance, foot, i))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, foot, i)| (foot[1].to_degrees(), foot[0].to_degrees(), i))
+    }
+
+    /// Remaining distance in nautical miles along the route, from the point of
+    /// the route closest to the given coordinates to its end.
+    ///
+    /// Positions before the start of the route give its full length, positions
+    /// past its end give zero. Segments with an unresolved endpoint are skipped.
+    pub fn distance_to_go_nm(&self, latitude: f64, longitude: f64) -> Option<f64> {
+        let (lat, lon, index) = self.closest_point(latitude, longitude)?;
+        let length = |a: Coor2D, b: Coor2D| DistanceModel::Ellipsoidal.distance(&a, &b);
+        let current = self.segments[index]
+            .end
+            .coor()
+            .map(|end| length(Coor2D::geo(lat, lon), end))?;
+        let remaining = self.segments[index + 1..]
+            .iter()
+            .filter_map(|segment| Some(length(segment.start.coor()?, segment.end.coor()?)))
+            .sum::<f64>();
+        Some((current + remaining) / NAUTICAL_MILE)
+    }
+
+    /// Split the segments of the route into those flown direct and those flown
+    /// along a published airway or procedure, i.e. segments carrying a `name`.
+    ///
+    /// Returns `(direct, airway)`, both in route order.
+    pub fn partition_direct_vs_airway(&self) -> (Vec<ResolvedRouteSegment>, Vec<ResolvedRouteSegment>) {
+        self.segments
+            .iter()
+            .cloned()
+            .partition(|segment| segment.name.is_none())
+    }
+
+    /// Merge several routes into one, e.g. the portions of an airway published
+    /// as distinct records under the same designator.
+    ///
+    /// The segments of the merged route are the union of all segments, and a
+    /// segment listed in several routes (in either direction) is only kept once.
+    /// The merged route is named after the first route.
+    pub fn merge(routes: Vec<ResolvedRoute>) -> ResolvedRoute {
+        let name = routes.first().map(|route| route.name.clone()).unwrap_or_default();
+        let mut seen = std::collections::HashSet::new();
+        let segments = routes
+            .into_iter()
+            .flat_map(|route| route.segments)
+            .filter(|segment| {
+                let reversed = (segment.end.clone(), segment.start.clone());
+                !seen.contains(&reversed) && seen.insert((segment.start.clone(), segment.end.clone()))
+            })
+            .collect();
+        ResolvedRoute { segments, name }
+    }
+
     /// Lookup routes by their name from the database.
+    ///
+    /// Routes whose designator is still incomplete or malformed after
+    /// [normalization](Route::normalize), e.g. without a number, are never returned.
     pub fn lookup(name: &str, db: &AirwayDatabase) -> Vec<Self> {
         if VALID_ROUTE_PREFIXES.iter().any(|prefix| name.starts_with(prefix)) {
             // First decompose the name into its components
             // Another approach would be to make a single string match,
             // but this serves as sanity check as well.
-            let last = name.chars().last().unwrap();
-            let (name, multiple) = if last.is_alphabetic() {
-                (&name[..name.len() - 1], Some(last.to_string()))
-            } else {
-                (name, None)
+            let Some(RouteDesignator {
+                prefix,
+                second_letter,
+                number,
+                multiple_identifier: multiple,
+            }) = RouteDesignator::parse(name)
+            else {
+                tracing::warn!(target: "thrust::lookup", "Invalid route designator '{}'", name);
+                return vec![];
             };
-            let (prefix, second_letter, number) = if name.starts_with('U') && name.len() >= 3 {
-                (
-                    Some("U".to_string()),
-                    name.chars().nth(1).unwrap().to_string(),
-                    name[2..].to_string(),
-                )
-            } else if name.len() >= 2 {
-                (None, name.chars().next().unwrap().to_string(), name[1..].to_string())
-            } else {
-                (None, "".to_string(), "".to_string())
+            let designated = |route: &&Route| {
+                route.prefix.as_deref() == prefix.as_deref()
+                    && route.second_letter.as_deref() == Some(&second_letter)
+                    && route.number.as_deref() == Some(&number)
+                    && route.multiple_identifier.as_deref() == multiple.as_deref()
             };
-            let candidates = db
-                .routes
-                .values()
-                .filter(|route| {
-                    route.prefix.as_deref() == prefix.as_deref()
-                        && route.second_letter.as_deref() == Some(&second_letter)
-                        && route.number.as_deref() == Some(&number)
-                        && route.multiple_identifier.as_deref() == multiple.as_deref()
-                })
-                .collect::<Vec<_>>();
-            return candidates
+            let mut candidates = db.airways().routes.values().filter(designated).collect::<Vec<_>>();
+            candidates.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+            let mut candidates = candidates
                 .iter()
                 .map(|route| ResolvedRoute::from_db(route, db))
-                .collect();
+                .collect::<Vec<_>>();
+            // Overlay segments extend the candidates sharing one of their endpoints,
+            // possibly through other overlay segments, or form an airway of their own
+            let mut overlay = db
+                .overlay
+                .iter()
+                .flat_map(|overlay| overlay.routes.values().filter(designated))
+                .collect::<Vec<_>>();
+            overlay.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+            let Some(first) = overlay.first() else {
+                return candidates;
+            };
+            let name = ResolvedRoute::from_db(first, db).name;
+            let mut pending = overlay
+                .into_iter()
+                .flat_map(|route| ResolvedRoute::from_db(route, db).segments)
+                .collect::<Vec<_>>();
+            loop {
+                let count = pending.len();
+                pending.retain(|segment| {
+                    let mut connected = candidates
+                        .iter_mut()
+                        .filter(|candidate| candidate.contains(&segment.start) || candidate.contains(&segment.end))
+                        .peekable();
+                    let attached = connected.peek().is_some();
+                    connected.for_each(|candidate| candidate.segments.push(segment.clone()));
+                    !attached
+                });
+                if pending.len() == count {
+                    break;
+                }
+            }
+            if !pending.is_empty() {
+                candidates.push(ResolvedRoute {
+                    segments: pending,
+                    name,
+                });
+            }
+            return candidates;
         }
         vec![]
     }
@@ -681,6 +2936,7 @@ impl ResolvedRoute {
     pub fn contains(&self, point: &ResolvedPoint) -> bool {
         self.segments
             .iter()
+            .filter(|segment| segment.is_complete())
             .any(|segment| &segment.start == point || &segment.end == point)
     }
 
@@ -691,7 +2947,7 @@ impl ResolvedRoute {
         // Build adjacency map: point -> list of (next_point, segment_index, is_forward)
         let mut graph: HashMap<&ResolvedPoint, Vec<(&ResolvedPoint, usize, bool)>> = HashMap::new();
 
-        for (i, segment) in self.segments.iter().enumerate() {
+        for (i, segment) in self.segments.iter().enumerate().filter(|(_, s)| s.is_complete()) {
             // Forward direction: start -> end
             graph.entry(&segment.start).or_default().push((&segment.end, i, true));
 
@@ -758,6 +3014,12 @@ impl ResolvedRoute {
                     name: Some(self.name.clone()),
                     altitude: segment.altitude.clone(),
                     speed: segment.speed.clone(),
+                    level: segment.level.clone(),
+                    overfly: false,
+                    stay: None,
+                    source_segment: segment.source_segment.clone(),
+                    is_reversed: !segment.is_reversed,
+                    overlay: segment.overlay,
                 });
             }
         }
@@ -769,73 +3031,793 @@ impl ResolvedRoute {
     }
 }
 
+/// Maximum distance (in meters) between a suggestion and the reference point.
+const SUGGESTION_RADIUS: f64 = 500_000.;
+
+/// Maximum edit distance for suggestions of unresolved identifiers.
+const SUGGESTION_MAX_EDITS: usize = 2;
+
+/// Number of suggestions reported for each unresolved identifier.
+const SUGGESTION_COUNT: usize = 5;
+
+/// A repeated identifier keeps its first resolution unless another candidate scores
+/// at least this many times better.
+const DECISION_OVERRIDE_RATIO: f64 = 0.2;
+
+/// Points closer than this distance (in meters) are considered the same location.
+const COLLOCATION_TOLERANCE: f64 = 10.;
+
+/// Location indicator of an aerodrome without one, named in field 18 instead.
+const UNNAMED_AERODROME: &str = "ZZZZ";
+
+/// Default radius around coordinates in which [`describe_relative`] looks for a
+/// named fix, in nautical miles.
+pub const DEFAULT_RELATIVE_RADIUS_NM: f64 = 50.;
+
+/// Diagnostics collected while enriching a route.
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EnrichmentReport {
+    /// Waypoint identifiers which could not be found in the database
+    pub unresolved: Vec<UnresolvedToken>,
+    /// Repeated identifiers resolved to a different point than their first occurrence
+    pub inconsistencies: Vec<InconsistentToken>,
+    /// Filed airways replaced by another airway connecting the same points
+    pub substitutions: Vec<AirwaySubstitution>,
+    /// Airways filed as the first or last element of the route, repaired or dropped
+    pub boundary_airways: Vec<BoundaryAirway>,
+    /// Airways with a single resolved bracketing point, see
+    /// [`EnrichmentOptions::keep_partial_airways`]
+    pub partial_airways: Vec<PartialAirway>,
+    /// Number of airway segments skipped because an endpoint is missing from the database
+    pub broken_segments: usize,
+    /// The route does not begin with a valid speed/level group, so the first
+    /// segments carry no speed or altitude
+    pub missing_initial_group: bool,
+    /// Discontinuities of the enriched route: unresolved points between two
+    /// resolved ones, and airways not starting where the route stands
+    pub gaps: Vec<RouteGap>,
+    /// How many of the filed points and airways were found in the database
+    pub completeness: RouteCompleteness,
+}
+
+/// The share of the filed points, airways and procedures of a route found in the database.
+///
+/// Direct legs and the `SID`/`STAR` keywords are not counted; NAT and PTS tracks,
+/// which are not handled yet, count as unresolved.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RouteCompleteness {
+    /// Number of filed elements found in the database
+    pub resolved: usize,
+    /// Number of filed elements
+    pub total: usize,
+    /// `resolved / total`, or 1 for a route without any element to resolve
+    pub score: f64,
+}
+
+impl RouteCompleteness {
+    pub fn new(resolved: usize, total: usize) -> Self {
+        let score = if total == 0 { 1. } else { resolved as f64 / total as f64 };
+        RouteCompleteness { resolved, total, score }
+    }
+
+    /// Whether every filed element was found in the database.
+    pub fn is_complete(&self) -> bool {
+        self.resolved == self.total
+    }
+}
+
+impl Default for RouteCompleteness {
+    fn default() -> Self {
+        RouteCompleteness::new(0, 0)
+    }
+}
+
+/// An airway segment flown at a flight level outside the series assigned to its direction.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParityViolation {
+    /// Index of the segment in the enriched route
+    pub index: usize,
+    /// Identifier of the route segment carrying the level rule
+    pub segment: String,
+    /// Name of the airway
+    pub airway: Option<String>,
+    /// The requested flight level
+    pub flight_level: u16,
+    /// The series of cruising levels required in this direction
+    pub required: LevelSeries,
+}
+
+/// The database which resolved a segment in [`AirwayDatabase::enrich_route_with_fallback`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ResolvedBy {
+    #[default]
+    Primary,
+    Fallback,
+}
+
+/// A filed airway which did not connect its bracketing points, and the airway used instead.
+#[derive(Debug, Clone, PartialEq, Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AirwaySubstitution {
+    /// Index of the airway in the parsed field 15 route
+    pub index: usize,
+    /// The filed airway designator
+    pub filed: String,
+    /// The designator of the airway used instead
+    pub substitute: String,
+}
+
+/// An airway filed as the first or last element of a route, with a point on one side only.
+///
+/// When the adjacent point lies on the airway, only the segment leading to it
+/// (or leaving from it) is kept; otherwise the airway is replaced by a direct leg.
+#[derive(Debug, Clone, PartialEq, Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct BoundaryAirway {
+    /// Index of the airway in the parsed field 15 route
+    pub index: usize,
+    /// The filed airway designator
+    pub airway: String,
+    /// Whether a single segment adjacent to the point was kept, rather than a direct leg
+    pub repaired: bool,
+}
+
+/// An airway with a single resolved bracketing point, kept from this point to the
+/// nearer end of the airway.
+#[derive(Debug, Clone, PartialEq, Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PartialAirway {
+    /// Index of the airway in the parsed field 15 route
+    pub index: usize,
+    /// The filed airway designator
+    pub airway: String,
+    /// Whether the resolved point is the entry point of the airway, rather than the exit
+    pub entry: bool,
+}
+
+/// A discontinuity of an enriched route, see [`EnrichmentReport::gaps`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RouteGap {
+    /// Index of the segment after the gap in the enriched route: the gap segment
+    /// itself with [`EnrichmentOptions::emit_gaps`]
+    pub index: usize,
+    /// The last resolved point before the gap
+    pub start: ResolvedPoint,
+    /// The first resolved point after the gap
+    pub end: ResolvedPoint,
+    /// Straight-line distance between both points, in meters
+    pub distance: f64,
+}
+
+/// A waypoint identifier listed several times which did not resolve to the same point.
+#[derive(Debug, Clone, PartialEq, Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct InconsistentToken {
+    /// Index of the element in the parsed field 15 route
+    pub index: usize,
+    /// Index of the first occurrence of the identifier
+    pub first_index: usize,
+    /// The ambiguous identifier
+    pub token: String,
+}
+
+/// A waypoint identifier which could not be resolved, with "did you mean" suggestions.
+#[derive(Debug, Clone, PartialEq, Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct UnresolvedToken {
+    /// Index of the element in the parsed field 15 route
+    pub index: usize,
+    /// The unresolved identifier
+    pub token: String,
+    /// Closest designators in the database, best match first
+    pub suggestions: Vec<String>,
+    /// How to find out the point, when it cannot be resolved from field 15 (e.g. ZZZZ)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+/// The segments of a route of a batch, with their report, or why they could not be found.
+pub type EnrichmentResult = Result<(Vec<ResolvedRouteSegment>, EnrichmentReport), EnrichmentError>;
+
+/// Reasons why a route of a batch could not be enriched.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EnrichmentError {
+    /// The route string contains no field 15 element
+    Empty,
+    /// No segment could be resolved; carries the identifiers missing from the database
+    Unresolved(Vec<UnresolvedToken>),
+    /// Enrichment panicked, with the panic message
+    Panicked(String),
+}
+
+impl std::fmt::Display for EnrichmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnrichmentError::Empty => write!(f, "empty route"),
+            EnrichmentError::Unresolved(tokens) => {
+                let tokens = tokens.iter().map(|t| t.token.as_str()).collect::<Vec<_>>();
+                write!(f, "no segment resolved (unresolved: {})", tokens.join(", "))
+            }
+            EnrichmentError::Panicked(msg) => write!(f, "enrichment panicked: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for EnrichmentError {}
+
+/// A route being enriched: the candidates for each point and airway of a field 15
+/// route, narrowed down by the successive stages of
+/// [`AirwayDatabase::enrich_route_with_report`].
+#[derive(Debug, Default)]
+pub(crate) struct CandidateRoute {
+    candidates: Vec<EnrichedCandidates>,
+    /// Index in the field 15 elements of each candidate
+    indices: Vec<usize>,
+    /// Waypoint identifier of each point candidate, in uppercase
+    tokens: Vec<Option<String>>,
+    /// Whether each point candidate is filed with an overfly prefix
+    overfly: Vec<bool>,
+    /// Duration of the STAY following each point candidate, in minutes
+    stays: Vec<Option<u16>>,
+    /// The database in which each candidate was found
+    sources: Vec<ResolvedBy>,
+    report: EnrichmentReport,
+    /// Scores of the candidates of ambiguous points (lower is better), by index in
+    /// `candidates`, with the index of the chosen candidate
+    scores: Vec<(usize, Vec<f64>, Option<usize>)>,
+    /// Decisions taken by the stages, collected when an event sink is set
+    events: EventLog,
+}
+
+impl CandidateRoute {
+    /// The candidates of each element, as listed in an [`EnrichmentTrace`].
+    fn trace_stage(&self, stage: &'static str, elements: &[Field15Element]) -> TraceStage {
+        let tokens = self
+            .candidates
+            .iter()
+            .zip(&self.indices)
+            .map(|(candidate, &index)| {
+                let candidates = candidate.labels();
+                TraceToken {
+                    index,
+                    token: trace_token(&elements[index]),
+                    count: candidates.len(),
+                    candidates: candidates.into_iter().take(TRACE_CANDIDATE_LIMIT).collect(),
+                }
+            })
+            .collect();
+        TraceStage { stage, tokens }
+    }
+}
+
+/// Maximum number of candidates listed for each token of an [`EnrichmentTrace`].
+pub const TRACE_CANDIDATE_LIMIT: usize = 8;
+
+/// The candidates considered at each stage of route enrichment, for debugging.
+///
+/// Only the first [`TRACE_CANDIDATE_LIMIT`] candidates of each token are listed,
+/// so that the size of a trace is bounded by the length of the route.
+#[derive(Debug, Clone, Serialize)]
+pub struct EnrichmentTrace {
+    /// Candidates remaining after each stage, in pipeline order
+    pub stages: Vec<TraceStage>,
+    /// Scores of the candidates of ambiguous points
+    pub scores: Vec<TraceScores>,
+}
+
+/// The candidates remaining for each token after a stage of route enrichment.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceStage {
+    /// Name of the stage (e.g. "trim_airways")
+    pub stage: &'static str,
+    pub tokens: Vec<TraceToken>,
+}
+
+/// The candidates of a point or connector of the route at a given stage.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceToken {
+    /// Index of the element in the parsed field 15 route
+    pub index: usize,
+    /// The element as filed, e.g. "ABC", "UN1" or "DCT"
+    pub token: String,
+    /// Number of candidates, possibly more than listed
+    pub count: usize,
+    /// Description of the candidates, e.g. points or airways with their segment count
+    pub candidates: Vec<String>,
+}
+
+/// The scores of the candidates of an ambiguous point, lower is better.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceScores {
+    /// Index of the element in the parsed field 15 route
+    pub index: usize,
+    pub token: String,
+    /// Candidates with their score, best first
+    pub candidates: Vec<(String, f64)>,
+    /// The chosen candidate, if any
+    pub chosen: Option<String>,
+}
+
+impl std::fmt::Display for EnrichmentTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for stage in &self.stages {
+            writeln!(f, "{}", stage.stage)?;
+            for token in &stage.tokens {
+                writeln!(
+                    f,
+                    "  {:<12} {:>3}  {}",
+                    token.token,
+                    token.count,
+                    token.candidates.join(", ")
+                )?;
+            }
+        }
+        if !self.scores.is_empty() {
+            writeln!(f, "scores")?;
+        }
+        for scores in &self.scores {
+            let chosen = scores.chosen.as_deref().unwrap_or("none");
+            writeln!(f, "  {:<12} -> {}", scores.token, chosen)?;
+            for (candidate, score) in &scores.candidates {
+                writeln!(f, "  {:<12}    {:.3}  {}", "", score, candidate)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The element as filed, as shown in an [`EnrichmentTrace`] or an [`EnrichmentEvent`].
+fn trace_token(element: &Field15Element) -> String {
+    match element {
+        Field15Element::Modifier(modifier) => modifier.to_string(),
+        Field15Element::Point(Point::Waypoint(name) | Point::Aerodrome(name)) => name.clone(),
+        Field15Element::Point(Point::BearingDistance {
+            point,
+            bearing,
+            distance,
+        }) => match point.as_ref() {
+            Point::Waypoint(name) | Point::Aerodrome(name) => format!("{name}{bearing:03}{distance:03}"),
+            _ => element.to_string(),
+        },
+        Field15Element::Connector(Connector::Airway(name) | Connector::Sid(name) | Connector::Star(name)) => {
+            name.clone()
+        }
+        Field15Element::Connector(Connector::Direct) => "DCT".to_string(),
+        other => other.to_string(),
+    }
+}
+
 #[derive(Debug)]
 enum EnrichedCandidates {
-    Point((Vec<ResolvedPoint>, Option<Altitude>, Option<Speed>)),
-    PointCoords((ResolvedPoint, Option<Altitude>, Option<Speed>)),
-    Airway((Vec<ResolvedRoute>, String, Option<Altitude>, Option<Speed>)),
+    Point(
+        (
+            Vec<ResolvedPoint>,
+            Option<Altitude>,
+            Option<Speed>,
+            Option<LevelConstraint>,
+        ),
+    ),
+    /// Points given by coordinates, boxed as they are rare and `ResolvedPoint` is large
+    PointCoords(
+        (
+            Box<ResolvedPoint>,
+            Option<Altitude>,
+            Option<Speed>,
+            Option<LevelConstraint>,
+        ),
+    ),
+    Airway(
+        (
+            Vec<ResolvedRoute>,
+            String,
+            Option<Altitude>,
+            Option<Speed>,
+            Option<LevelConstraint>,
+        ),
+    ),
     Direct(),
 }
 
+impl EnrichedCandidates {
+    /// Description of each candidate, as listed in an [`EnrichmentTrace`].
+    fn labels(&self) -> Vec<String> {
+        match self {
+            EnrichedCandidates::Point((points, _, _, _)) => points.iter().map(ToString::to_string).collect(),
+            EnrichedCandidates::PointCoords((point, _, _, _)) => vec![point.to_string()],
+            EnrichedCandidates::Airway((routes, _, _, _, _)) => routes
+                .iter()
+                .map(|route| format!("{} ({} segments)", route.name, route.segments.len()))
+                .collect(),
+            EnrichedCandidates::Direct() => vec![],
+        }
+    }
+
+    /// Number of candidates, as considered in an [`EnrichmentEvent`].
+    fn count(&self) -> usize {
+        match self {
+            EnrichedCandidates::Point((points, _, _, _)) => points.len(),
+            EnrichedCandidates::PointCoords(_) => 1,
+            EnrichedCandidates::Airway((routes, _, _, _, _)) => routes.len(),
+            EnrichedCandidates::Direct() => 0,
+        }
+    }
+}
+
 impl AirwayDatabase {
     /// Enrich a sequence of Field15Elements into resolved route segments.
     /// A resolved route segment consists of start and end points,
     /// along with optional altitude and speed constraints.
     /// All points and airways are resolved against the database.
     pub fn enrich_route(&self, elements: Vec<Field15Element>) -> Vec<ResolvedRouteSegment> {
-        let mut altitude = None;
-        let mut speed = None;
+        self.enrich_route_with_report(elements).0
+    }
 
-        // First, resolve all candidates
-        let mut resolved: Vec<EnrichedCandidates> = Vec::new();
-        for element in &elements {
-            match element {
-                Field15Element::Modifier(m) => {
-                    let Modifier {
-                        speed: s, altitude: a, ..
-                    } = m;
-                    altitude = a.clone();
-                    speed = s.clone();
-                }
-                Field15Element::Point(Point::Waypoint(name)) => {
-                    let lookup = ResolvedPoint::lookup(name, self);
-                    if lookup.is_empty() {
-                        tracing::warn!("No point found for identifier '{}'", name);
-                    }
-                    resolved.push(EnrichedCandidates::Point((
-                        ResolvedPoint::lookup(name, self),
-                        altitude.clone(),
-                        speed.clone(),
-                    )));
-                }
-                Field15Element::Point(Point::Coordinates((lat, lon))) => {
-                    resolved.push(EnrichedCandidates::PointCoords((
-                        ResolvedPoint::Coordinates {
-                            latitude: *lat,
-                            longitude: *lon,
-                        },
-                        altitude.clone(),
-                        speed.clone(),
-                    )));
-                }
-                Field15Element::Connector(Connector::Airway(name)) => {
-                    let lookup = ResolvedRoute::lookup(name, self);
-                    if lookup.is_empty() {
-                        tracing::warn!("No airway found for identifier '{}'", name);
-                        resolved.push(EnrichedCandidates::Direct());
-                    } else {
-                        resolved.push(EnrichedCandidates::Airway((
-                            lookup,
-                            name.to_string(),
-                            altitude.clone(),
-                            speed.clone(),
-                        )));
-                    }
+    /// Enrich a sequence of Field15Elements into the coordinates of the points
+    /// along the route, as `Coor2D::geo`, for direct use with `geodesy`.
+    ///
+    /// Endpoints shared by consecutive segments are only listed once, and
+    /// unresolved points are skipped.
+    pub fn enrich_route_coords(&self, elements: Vec<Field15Element>) -> Vec<Coor2D> {
+        let mut coords: Vec<Coor2D> = Vec::new();
+        for segment in self.enrich_route(elements) {
+            for coor in [segment.start.coor(), segment.end.coor()].into_iter().flatten() {
+                if coords.last() != Some(&coor) {
+                    coords.push(coor);
                 }
-                Field15Element::Connector(Connector::Sid(name)) => {
-                    let lookup = self.resolve_sid_routes(name);
+            }
+        }
+        coords
+    }
+
+    /// Enrich a batch of field 15 route strings in parallel, keyed by caller-provided identifiers.
+    ///
+    /// Results are returned in input order together with their key, as the segments
+    /// and the report of [`enrich_route_with_report`](Self::enrich_route_with_report).
+    /// A route which cannot be enriched (empty, nothing resolved, or a panic in the
+    /// resolver) yields an error for its key without affecting the others.
+    pub fn enrich_batch<K, I>(&self, routes: I) -> Vec<(K, EnrichmentResult)>
+    where
+        I: IntoIterator<Item = (K, String)>,
+    {
+        let (keys, routes): (Vec<K>, Vec<String>) = routes.into_iter().unzip();
+        let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = routes.len().div_ceil(workers).max(1);
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = routes
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || chunk.iter().map(|route| self.try_enrich(route)).collect::<Vec<_>>()))
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("panics are caught for each route"))
+                .collect()
+        });
+        keys.into_iter().zip(results).collect()
+    }
+
+    /// Parse and enrich a single route string, turning failures into an [`EnrichmentError`].
+    fn try_enrich(&self, route: &str) -> EnrichmentResult {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let elements = Field15Parser::parse(route);
+            if elements.is_empty() {
+                return Err(EnrichmentError::Empty);
+            }
+            Ok(self.enrich_route_with_report(elements))
+        }));
+        let (segments, report) = match result {
+            Ok(enriched) => enriched?,
+            Err(payload) => {
+                let msg = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                return Err(EnrichmentError::Panicked(msg));
+            }
+        };
+        if segments.is_empty() {
+            return Err(EnrichmentError::Unresolved(report.unresolved));
+        }
+        Ok((segments, report))
+    }
+
+    /// Whether the points and airways of a route can all be found in the database,
+    /// and which share of them can: see [`EnrichmentReport::completeness`].
+    pub fn route_completeness(&self, elements: Vec<Field15Element>) -> RouteCompleteness {
+        self.enrich_route_with_report(elements).1.completeness
+    }
+
+    /// Enrich a sequence of Field15Elements, and report the identifiers which
+    /// could not be resolved together with suggestions of close designators.
+    ///
+    /// Enrichment runs as a pipeline of stages, each narrowing down the candidates
+    /// of a [`CandidateRoute`] before the final segments are assembled.
+    pub fn enrich_route_with_report(
+        &self,
+        elements: Vec<Field15Element>,
+    ) -> (Vec<ResolvedRouteSegment>, EnrichmentReport) {
+        if let Some(cache) = &self.route_cache {
+            // Display is not lossless (e.g. Mach numbers are rounded): key on the serialised elements
+            let key = serde_json::to_string(&elements).unwrap_or_default().to_uppercase();
+            return cache.get_or_insert_with(key, || self.enrich_uncached(&elements));
+        }
+        self.enrich_uncached(&elements)
+    }
+
+    /// Enrich a sequence of Field15Elements, looking up in `fallback` the points and
+    /// airways missing from this database, e.g. in the adjacent AIRAC cycle.
+    ///
+    /// Each segment is tagged with the database which resolved it: direct segments
+    /// are resolved by the fallback database if any of their endpoints is, airway
+    /// segments by the database where the airway was found. Only identifiers missing
+    /// from both databases are reported as unresolved. Results are never cached.
+    pub fn enrich_route_with_fallback(
+        &self,
+        elements: Vec<Field15Element>,
+        fallback: &AirwayDatabase,
+    ) -> (Vec<(ResolvedRouteSegment, ResolvedBy)>, EnrichmentReport) {
+        let route = self.resolve_candidates_with(&elements, Some(fallback));
+        let route = self.filter_airways_by_adjacent_points(route);
+        let route = self.filter_points_by_airways(route);
+        let route = self.trim_airways(route);
+        let mut route = self.disambiguate(route);
+        self.record_events(&elements, &mut route);
+        let (segments, sources, report) = self.assemble(route);
+        (segments.into_iter().zip(sources).collect(), report)
+    }
+
+    /// Enrich a sequence of Field15Elements, recording the candidates remaining after
+    /// each stage and the scores of ambiguous points, to investigate wrong resolutions.
+    pub fn enrich_route_trace(&self, elements: Vec<Field15Element>) -> EnrichmentTrace {
+        let mut stages = Vec::new();
+        let route = self.resolve_candidates(&elements);
+        stages.push(route.trace_stage("resolve_candidates", &elements));
+        let route = self.filter_airways_by_adjacent_points(route);
+        stages.push(route.trace_stage("filter_airways_by_adjacent_points", &elements));
+        let route = self.filter_points_by_airways(route);
+        stages.push(route.trace_stage("filter_points_by_airways", &elements));
+        let route = self.trim_airways(route);
+        stages.push(route.trace_stage("trim_airways", &elements));
+        let labels = route
+            .candidates
+            .iter()
+            .map(EnrichedCandidates::labels)
+            .collect::<Vec<_>>();
+        let route = self.disambiguate(route);
+        stages.push(route.trace_stage("disambiguate", &elements));
+
+        let scores = route
+            .scores
+            .iter()
+            .map(|(i, scores, choice)| {
+                let labels = &labels[*i];
+                let mut candidates = labels.iter().cloned().zip(scores.iter().copied()).collect::<Vec<_>>();
+                candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+                candidates.truncate(TRACE_CANDIDATE_LIMIT);
+                let index = route.indices[*i];
+                TraceScores {
+                    index,
+                    token: trace_token(&elements[index]),
+                    candidates,
+                    chosen: choice.and_then(|choice| labels.get(choice).cloned()),
+                }
+            })
+            .collect();
+        EnrichmentTrace { stages, scores }
+    }
+
+    /// Enrich a sequence of Field15Elements, bypassing the route cache.
+    pub(crate) fn enrich_uncached(&self, elements: &[Field15Element]) -> (Vec<ResolvedRouteSegment>, EnrichmentReport) {
+        let route = self.resolve_candidates(elements);
+        let route = self.filter_airways_by_adjacent_points(route);
+        let route = self.filter_points_by_airways(route);
+        let route = self.trim_airways(route);
+        let mut route = self.disambiguate(route);
+        self.record_events(elements, &mut route);
+        self.assemble_segments(route)
+    }
+
+    /// Pass the events of a route to the sink set in the enrichment options, if any.
+    fn record_events(&self, elements: &[Field15Element], route: &mut CandidateRoute) {
+        if let Some(sink) = &self.options.events {
+            let events = route.events.take();
+            let elements = elements.iter().map(trace_token).collect::<Vec<_>>();
+            sink.record(&elements.join(" "), &events);
+        }
+    }
+
+    /// Look up the candidate points and airways of each field 15 element, carrying
+    /// the speed and level constraints in force along.
+    ///
+    /// Navaid candidates off the adjacent airways are complemented with the designated
+    /// points named after them.
+    pub(crate) fn resolve_candidates(&self, elements: &[Field15Element]) -> CandidateRoute {
+        self.resolve_candidates_with(elements, None)
+    }
+
+    /// Look up the candidates of each field 15 element as [`resolve_candidates`](Self::resolve_candidates),
+    /// then in the `fallback` database for the points and airways not found.
+    fn resolve_candidates_with(
+        &self,
+        elements: &[Field15Element],
+        fallback: Option<&AirwayDatabase>,
+    ) -> CandidateRoute {
+        const STAGE: &str = "resolve_candidates";
+        let mut altitude = None;
+        let mut speed = None;
+        let mut level = None;
+        let mut report = EnrichmentReport::default();
+        let mut events = EventLog::new(self.options.events.is_some());
+        let mut last_resolved: Option<ResolvedPoint> = None;
+
+        let mut resolved: Vec<EnrichedCandidates> = Vec::new();
+        let mut indices: Vec<usize> = Vec::new();
+        let mut tokens: Vec<Option<String>> = Vec::new();
+        let mut overfly: Vec<bool> = Vec::new();
+        let mut overfly_next = false;
+        let mut stays: Vec<Option<u16>> = Vec::new();
+        let mut sources: Vec<ResolvedBy> = Vec::new();
+        let (mut filed, mut found) = (0, 0);
+        for (index, element) in elements.iter().enumerate() {
+            let mut source = ResolvedBy::Primary;
+            let unresolved = report.unresolved.len();
+            let mut missing = false;
+            match element {
+                Field15Element::Modifier(m) => {
+                    altitude = m.altitude.clone();
+                    speed = m.speed.clone();
+                    level = m.level();
+                }
+                Field15Element::MissingInitialGroup { malformed } => {
+                    tracing::warn!(
+                        target: "thrust::enrich",
+                        "Route does not begin with a speed/level group: {:?}",
+                        malformed
+                    );
+                    report.missing_initial_group = true;
+                }
+                Field15Element::Connector(Connector::Vfr) => {
+                    level = Some(LevelConstraint::Vfr);
+                }
+                Field15Element::Point(Point::Waypoint(name) | Point::Aerodrome(name)) => {
+                    let unnamed = name.eq_ignore_ascii_case(UNNAMED_AERODROME);
+                    let mut lookup = if unnamed {
+                        Vec::new()
+                    } else {
+                        ResolvedPoint::lookup(name, self)
+                    };
+                    if let Some(fallback) = fallback.filter(|_| lookup.is_empty() && !unnamed) {
+                        lookup = ResolvedPoint::lookup(name, fallback);
+                        if !lookup.is_empty() {
+                            tracing::debug!(
+                                target: "thrust::enrich",
+                                "Point '{}' resolved in the fallback database",
+                                name
+                            );
+                            source = ResolvedBy::Fallback;
+                            events.push(|| {
+                                EnrichmentEvent::new(STAGE, index, name, EventOutcome::Fallback, lookup.len())
+                            });
+                        }
+                    }
+                    if unnamed {
+                        tracing::warn!(target: "thrust::enrich", "Aerodrome '{}' is named in field 18", name);
+                        events.push(|| EnrichmentEvent::new(STAGE, index, name, EventOutcome::Unresolved, 0));
+                        report.unresolved.push(UnresolvedToken {
+                            index,
+                            token: name.to_string(),
+                            suggestions: Vec::new(),
+                            hint: Some(
+                                "ZZZZ stands for an aerodrome without location indicator: see its name \
+                                 in field 18 (e.g. DEP/, DEST/, ALTN/ or RALT/)"
+                                    .to_string(),
+                            ),
+                        });
+                    } else if lookup.is_empty() {
+                        tracing::warn!(target: "thrust::enrich", "No point found for identifier '{}'", name);
+                        events.push(|| EnrichmentEvent::new(STAGE, index, name, EventOutcome::Unresolved, 0));
+                        let near = last_resolved.as_ref().and_then(ResolvedPoint::coor).map(|coords| {
+                            let coords = coords.to_degrees();
+                            (coords[1], coords[0])
+                        });
+                        report.unresolved.push(UnresolvedToken {
+                            index,
+                            token: name.to_string(),
+                            suggestions: self.suggest(name, near, SUGGESTION_COUNT),
+                            hint: None,
+                        });
+                    } else {
+                        last_resolved = lookup.first().cloned();
+                    }
+                    resolved.push(EnrichedCandidates::Point((
+                        lookup,
+                        altitude.clone(),
+                        speed.clone(),
+                        level.clone(),
+                    )));
+                }
+                Field15Element::Point(Point::Coordinates((lat, lon))) => {
+                    let point = ResolvedPoint::Coordinates {
+                        latitude: *lat,
+                        longitude: *lon,
+                    };
+                    last_resolved = Some(point.clone());
+                    resolved.push(EnrichedCandidates::PointCoords((
+                        Box::new(point),
+                        altitude.clone(),
+                        speed.clone(),
+                        level.clone(),
+                    )));
+                }
+                Field15Element::Point(point @ Point::BearingDistance { .. }) => {
+                    match self.resolve_bearing_distance(point, last_resolved.as_ref()) {
+                        Some(point) => {
+                            last_resolved = Some(point.clone());
+                            resolved.push(EnrichedCandidates::PointCoords((
+                                Box::new(point),
+                                altitude.clone(),
+                                speed.clone(),
+                                level.clone(),
+                            )));
+                        }
+                        None => {
+                            let token = trace_token(element);
+                            tracing::warn!(target: "thrust::enrich", "No base point found for '{}'", token);
+                            events.push(|| EnrichmentEvent::new(STAGE, index, &token, EventOutcome::Unresolved, 0));
+                            report.unresolved.push(UnresolvedToken {
+                                index,
+                                token,
+                                suggestions: Vec::new(),
+                                hint: None,
+                            });
+                        }
+                    }
+                }
+                Field15Element::Connector(Connector::Airway(name)) => {
+                    let mut lookup = ResolvedRoute::lookup(name, self);
+                    if let Some(fallback) = fallback.filter(|_| lookup.is_empty()) {
+                        lookup = ResolvedRoute::lookup(name, fallback);
+                        if !lookup.is_empty() {
+                            tracing::debug!(
+                                target: "thrust::enrich",
+                                "Airway '{}' resolved in the fallback database",
+                                name
+                            );
+                            source = ResolvedBy::Fallback;
+                            events.push(|| {
+                                EnrichmentEvent::new(STAGE, index, name, EventOutcome::Fallback, lookup.len())
+                            });
+                        }
+                    }
+                    if self.options.merge_split_airways && lookup.len() > 1 {
+                        lookup = vec![ResolvedRoute::merge(lookup)];
+                    }
+                    if lookup.is_empty() {
+                        tracing::warn!(target: "thrust::enrich", "No airway found for identifier '{}'", name);
+                        events.push(|| EnrichmentEvent::new(STAGE, index, name, EventOutcome::Unresolved, 0));
+                        missing = true;
+                        resolved.push(EnrichedCandidates::Direct());
+                    } else {
+                        resolved.push(EnrichedCandidates::Airway((
+                            lookup,
+                            name.to_string(),
+                            altitude.clone(),
+                            speed.clone(),
+                            level.clone(),
+                        )));
+                    }
+                }
+                Field15Element::Connector(Connector::Sid(name)) => {
+                    let lookup = self.resolve_sid_routes(name);
                     if lookup.is_empty() {
-                        tracing::warn!("No SID found for identifier '{}'", name);
+                        tracing::warn!(target: "thrust::enrich", "No SID found for identifier '{}'", name);
+                        events.push(|| EnrichmentEvent::new(STAGE, index, name, EventOutcome::Unresolved, 0));
+                        missing = true;
                         resolved.push(EnrichedCandidates::Direct());
                     } else {
                         resolved.push(EnrichedCandidates::Airway((
@@ -843,13 +3825,16 @@ impl AirwayDatabase {
                             name.to_string(),
                             altitude.clone(),
                             speed.clone(),
+                            level.clone(),
                         )));
                     }
                 }
                 Field15Element::Connector(Connector::Star(name)) => {
                     let lookup = self.resolve_star_routes(name);
                     if lookup.is_empty() {
-                        tracing::warn!("No STAR found for identifier '{}'", name);
+                        tracing::warn!(target: "thrust::enrich", "No STAR found for identifier '{}'", name);
+                        events.push(|| EnrichmentEvent::new(STAGE, index, name, EventOutcome::Unresolved, 0));
+                        missing = true;
                         resolved.push(EnrichedCandidates::Direct());
                     } else {
                         resolved.push(EnrichedCandidates::Airway((
@@ -857,6 +3842,7 @@ impl AirwayDatabase {
                             name.to_string(),
                             altitude.clone(),
                             speed.clone(),
+                            level.clone(),
                         )));
                     }
                 }
@@ -865,66 +3851,271 @@ impl AirwayDatabase {
                 }
                 Field15Element::Connector(Connector::Nat(_)) | Field15Element::Connector(Connector::Pts(_)) => {
                     // NAT and PTS are not handled yet
+                    missing = true;
                     resolved.push(EnrichedCandidates::Direct());
                 }
                 _ => {}
             }
+            let counted = match element {
+                Field15Element::Point(_) => true,
+                Field15Element::Connector(Connector::Sid(name) | Connector::Star(name)) => {
+                    name != "SID" && name != "STAR"
+                }
+                Field15Element::Connector(Connector::Airway(_) | Connector::Nat(_) | Connector::Pts(_)) => true,
+                _ => false,
+            };
+            if counted {
+                filed += 1;
+                if !missing && report.unresolved.len() == unresolved {
+                    found += 1;
+                }
+            }
+            indices.resize(resolved.len(), index);
+            let token = match element {
+                Field15Element::Point(Point::Waypoint(name) | Point::Aerodrome(name)) => Some(name.to_uppercase()),
+                _ => None,
+            };
+            tokens.resize(resolved.len(), token);
+            // An overfly marker applies to the point element right after it
+            let marked = std::mem::replace(&mut overfly_next, matches!(element, Field15Element::Overfly { .. }));
+            overfly.resize(resolved.len(), marked);
+            stays.resize(resolved.len(), None);
+            // A STAY group applies to the point element right before it
+            if let Field15Element::Connector(Connector::StayTime { minutes }) = element {
+                if let (Some(EnrichedCandidates::Point(_) | EnrichedCandidates::PointCoords(_)), Some(stay)) =
+                    (resolved.last(), stays.last_mut())
+                {
+                    *stay = *minutes;
+                }
+            }
+            sources.resize(resolved.len(), source);
+        }
+
+        // A navaid and a collocated designated point may share the same name. With navaid priority,
+        // when none of the navaid candidates lie on an adjacent airway, also consider the designated points.
+        if self.options.navaid_priority {
+            for i in 0..resolved.len() {
+                let (before_i, i_and_after) = &mut resolved.split_at_mut(i);
+                if let (EnrichedCandidates::Point((points, _, _, _)), after_i) = i_and_after.split_first_mut().unwrap()
+                {
+                    let name = match points.first() {
+                        Some(ResolvedPoint::Navaid(navaid)) => navaid.name.clone(),
+                        _ => None,
+                    };
+                    let Some(name) = name else { continue };
+                    let adjacent = [before_i.last(), after_i.first()]
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|candidate| match candidate {
+                            EnrichedCandidates::Airway((routes, _, _, _, _)) => Some(routes),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>();
+                    if adjacent.is_empty() {
+                        continue;
+                    }
+                    let on_airway =
+                        |p: &ResolvedPoint| adjacent.iter().any(|routes| routes.iter().any(|r| r.contains(p)));
+                    if points.iter().any(on_airway) {
+                        continue;
+                    }
+                    let fallback = ResolvedPoint::lookup_designated_points(name.trim(), self)
+                        .into_iter()
+                        .filter(on_airway)
+                        .collect::<Vec<_>>();
+                    if !fallback.is_empty() {
+                        tracing::debug!(
+                            target: "thrust::enrich",
+                            "Navaid '{}' not on adjacent airway, considering designated points",
+                            name
+                        );
+                        points.extend(fallback);
+                    }
+                }
+            }
+        }
+        report.completeness = RouteCompleteness::new(found, filed);
+
+        CandidateRoute {
+            candidates: resolved,
+            indices,
+            tokens,
+            overfly,
+            stays,
+            sources,
+            report,
+            scores: Vec::new(),
+            events,
         }
+    }
 
-        // 1. For each candidate airway, retain only those that contain both the previous and next point.
+    /// Keep the airway candidates containing both bracketing points, and turn airways
+    /// left without candidates into direct legs (unless another airway can be substituted).
+    pub(crate) fn filter_airways_by_adjacent_points(&self, mut route: CandidateRoute) -> CandidateRoute {
+        const STAGE: &str = "filter_airways_by_adjacent_points";
+        let CandidateRoute {
+            candidates: resolved,
+            indices,
+            report,
+            events,
+            ..
+        } = &mut route;
+        // Number of candidates of each airway before filtering
+        let considered = if events.is_enabled() {
+            resolved.iter().map(EnrichedCandidates::count).collect()
+        } else {
+            Vec::new()
+        };
+
+        // For each candidate airway, retain only those that contain both the previous and next point.
+        // Unresolved points do not filter airways kept from their other point.
+        let unresolved = |candidate: &&EnrichedCandidates| {
+            self.options.keep_partial_airways
+                && matches!(candidate, EnrichedCandidates::Point((points, _, _, _)) if points.is_empty())
+        };
         for i in 1..resolved.len() - 1 {
             let (before_i, i_and_after) = &mut resolved.split_at_mut(i);
-            if let (EnrichedCandidates::Airway((routes, _, _, _)), after_i) = i_and_after.split_first_mut().unwrap() {
-                tracing::debug!("Filtering airway candidates: {:?}", routes);
-                if let Some(EnrichedCandidates::Point((points, _, _))) = before_i.last() {
+            if let (EnrichedCandidates::Airway((routes, _, _, _, _)), after_i) = i_and_after.split_first_mut().unwrap()
+            {
+                tracing::debug!(target: "thrust::enrich", "Filtering airway candidates: {:?}", routes);
+                if let Some(EnrichedCandidates::Point((points, _, _, _))) = before_i.last().filter(|c| !unresolved(c)) {
                     routes.retain(|r| points.iter().any(|p| r.contains(p)));
-                    tracing::debug!("Filtering airway candidates with point {:?}: {:?}", points, routes);
+                    tracing::debug!(
+                        target: "thrust::enrich",
+                        "Filtering airway candidates with point {:?}: {:?}",
+                        points,
+                        routes
+                    );
                 }
-                if let Some(EnrichedCandidates::Point((points, _, _))) = after_i.first() {
+                if let Some(EnrichedCandidates::Point((points, _, _, _))) = after_i.first().filter(|c| !unresolved(c)) {
                     routes.retain(|r| points.iter().any(|p| r.contains(p)));
-                    tracing::debug!("Filtering airway candidates with point {:?}: {:?}", points, routes);
+                    tracing::debug!(
+                        target: "thrust::enrich",
+                        "Filtering airway candidates with point {:?}: {:?}",
+                        points,
+                        routes
+                    );
                 }
             }
         }
 
-        // 2. Transform now empty airway candidates to Direct
-        for candidate in resolved.iter_mut() {
-            if let EnrichedCandidates::Airway((routes, name, _, _)) = candidate {
-                if routes.is_empty() {
-                    tracing::warn!("No valid airway remaining for '{}'", name);
-                    *candidate = EnrichedCandidates::Direct();
+        // Transform now empty airway candidates to Direct, unless airway substitution is
+        // enabled and another airway connects the bracketing points.
+        for (i, &index) in indices.iter().enumerate() {
+            let (before_i, i_and_after) = &mut resolved.split_at_mut(i);
+            let (candidate, after_i) = i_and_after.split_first_mut().unwrap();
+            let EnrichedCandidates::Airway((routes, name, _, _, _)) = candidate else {
+                continue;
+            };
+            if !routes.is_empty() {
+                continue;
+            }
+            if self.options.airway_substitution {
+                if let (
+                    Some(EnrichedCandidates::Point((before, _, _, _))),
+                    Some(EnrichedCandidates::Point((after, _, _, _))),
+                ) = (before_i.last(), after_i.first())
+                {
+                    let alternates = self.connecting_routes(before, after);
+                    if let Some(alternate) = alternates.first() {
+                        tracing::warn!(
+                            target: "thrust::enrich",
+                            "Airway '{}' substituted with '{}'",
+                            name,
+                            alternate.name
+                        );
+                        events.push(|| {
+                            EnrichmentEvent::new(
+                                STAGE,
+                                index,
+                                name.clone(),
+                                EventOutcome::Substituted,
+                                alternates.len(),
+                            )
+                        });
+                        report.substitutions.push(AirwaySubstitution {
+                            index,
+                            filed: name.clone(),
+                            substitute: alternate.name.clone(),
+                        });
+                        *name = alternate.name.clone();
+                        *routes = alternates;
+                        continue;
+                    }
                 }
             }
+            tracing::warn!(target: "thrust::enrich", "No valid airway remaining for '{}'", name);
+            events.push(|| EnrichmentEvent::new(STAGE, index, name.clone(), EventOutcome::Direct, considered[i]));
+            *candidate = EnrichedCandidates::Direct();
         }
+        route
+    }
 
-        // 3. For each point, retain only those that are present in the adjacent airway segments.
+    /// Keep the point candidates lying on the adjacent airways.
+    pub(crate) fn filter_points_by_airways(&self, mut route: CandidateRoute) -> CandidateRoute {
+        let resolved = &mut route.candidates;
+        let last = resolved.len().saturating_sub(1);
+
+        // For each point, retain only those that are present in the adjacent airway segments.
+        // Airways filed first or last are left to `trim_airways`, as the point may not lie on them.
         for i in 0..resolved.len() {
             let (before_i, i_and_after) = &mut resolved.split_at_mut(i);
-            if let (EnrichedCandidates::Point((points, _, _)), after_i) = i_and_after.split_first_mut().unwrap() {
-                tracing::debug!("Filtering point candidates: {:?}", points);
-                if let Some(EnrichedCandidates::Airway((routes, _, _, _))) = before_i.last() {
+            if let (EnrichedCandidates::Point((points, _, _, _)), after_i) = i_and_after.split_first_mut().unwrap() {
+                tracing::debug!(target: "thrust::enrich", "Filtering point candidates: {:?}", points);
+                if let Some(EnrichedCandidates::Airway((routes, _, _, _, _))) = before_i.last().filter(|_| i != 1) {
                     points.retain(|p| routes.iter().any(|r| r.contains(p)));
-                    tracing::debug!("Filtering point candidates with airway {:?}: {:?}", routes, points);
+                    tracing::debug!(
+                        target: "thrust::enrich",
+                        "Filtering point candidates with airway {:?}: {:?}",
+                        routes,
+                        points
+                    );
                 }
-                if let Some(EnrichedCandidates::Airway((routes, _, _, _))) = after_i.first() {
+                if let Some(EnrichedCandidates::Airway((routes, _, _, _, _))) =
+                    after_i.first().filter(|_| i + 1 != last)
+                {
                     points.retain(|p| routes.iter().any(|r| r.contains(p)));
-                    tracing::debug!("Filtering point candidates with airway {:?}: {:?}", routes, points);
+                    tracing::debug!(
+                        target: "thrust::enrich",
+                        "Filtering point candidates with airway {:?}: {:?}",
+                        routes,
+                        points
+                    );
                 }
             }
         }
+        route
+    }
 
-        // 4. Trim airways to the segments between the before and after points.
-        for i in 1..resolved.len() - 1 {
+    /// Trim airway candidates to the segments between their bracketing points, and
+    /// turn airways left without any segment into direct legs.
+    ///
+    /// An airway filed first or last keeps the single segment adjacent to its point,
+    /// if it lies on the airway, and is listed in [`EnrichmentReport::boundary_airways`].
+    pub(crate) fn trim_airways(&self, mut route: CandidateRoute) -> CandidateRoute {
+        const STAGE: &str = "trim_airways";
+        let CandidateRoute {
+            candidates: resolved,
+            indices,
+            report,
+            events,
+            ..
+        } = &mut route;
+
+        // Trim airways to the segments between the before and after points.
+        for i in 1..resolved.len().saturating_sub(1) {
             let (before_i, i_and_after) = &mut resolved.split_at_mut(i);
-            if let (EnrichedCandidates::Airway((routes, _, _, _)), after_i) = i_and_after.split_first_mut().unwrap() {
-                if let Some(EnrichedCandidates::Point((before, _, _))) = before_i.last() {
-                    if let Some(EnrichedCandidates::Point((after, _, _))) = after_i.first() {
+            if let (EnrichedCandidates::Airway((routes, _, _, _, _)), after_i) = i_and_after.split_first_mut().unwrap()
+            {
+                if let Some(EnrichedCandidates::Point((before, _, _, _))) = before_i.last() {
+                    if let Some(EnrichedCandidates::Point((after, _, _, _))) = after_i.first() {
                         if let Some(before) = before.first() {
                             if let Some(after) = after.first() {
                                 for route in routes.iter_mut() {
                                     if let Some(trimmed) = route.between(before, after) {
                                         *route = trimmed;
                                         tracing::debug!(
+                                            target: "thrust::enrich",
                                             "Trimmed airway '{}' between points {} and {}: {:?}",
                                             route.name,
                                             before,
@@ -940,31 +4131,226 @@ impl AirwayDatabase {
             }
         }
 
-        // 5. Replace empty routes with Direct
-        for candidate in resolved.iter_mut() {
-            if let EnrichedCandidates::Airway((routes, name, _, _)) = candidate {
+        // An airway next to an unresolved point is kept from its other point to the nearer end
+        if self.options.keep_partial_airways {
+            for i in 1..resolved.len().saturating_sub(1) {
+                let point = |candidate: &EnrichedCandidates| match candidate {
+                    EnrichedCandidates::Point((points, _, _, _)) => Some(points.first().cloned()),
+                    _ => None,
+                };
+                let (fix, entry) = match (point(&resolved[i - 1]), point(&resolved[i + 1])) {
+                    (Some(Some(fix)), Some(None)) => (fix, true),
+                    (Some(None), Some(Some(fix))) => (fix, false),
+                    _ => continue,
+                };
+                let EnrichedCandidates::Airway((routes, name, _, _, _)) = &mut resolved[i] else {
+                    continue;
+                };
+                let partial = routes
+                    .iter()
+                    .filter_map(|route| self.partial_airway(route, &fix, entry))
+                    .collect::<Vec<_>>();
+                if partial.is_empty() {
+                    continue;
+                }
+                tracing::warn!(target: "thrust::enrich", "Airway '{}' kept from {} to its nearer end only", name, fix);
+                events.push(|| {
+                    EnrichmentEvent::new(STAGE, indices[i], name.clone(), EventOutcome::Partial, routes.len())
+                });
+                report.partial_airways.push(PartialAirway {
+                    index: indices[i],
+                    airway: name.clone(),
+                    entry,
+                });
+                *routes = partial;
+            }
+        }
+
+        // An airway filed first or last (e.g. a mangled plan starting with "UN871 NATOR")
+        // keeps the segment adjacent to its only bracketing point, or is flown direct.
+        let last = resolved.len().saturating_sub(1);
+        for (i, leading) in [(0, true), (last, false)] {
+            let Some(EnrichedCandidates::Airway((routes, name, _, _, _))) = resolved.get(i) else {
+                continue;
+            };
+            let points = |candidate: &EnrichedCandidates| match candidate {
+                EnrichedCandidates::Point((points, _, _, _)) => points.clone(),
+                EnrichedCandidates::PointCoords((point, _, _, _)) => vec![(**point).clone()],
+                _ => Vec::new(),
+            };
+            // The adjacent point, and the first point beyond it to orient the segment
+            let (adjacent, beyond) = if leading {
+                (
+                    Some(1),
+                    resolved.iter().skip(2).find_map(|c| points(c).first().cloned()),
+                )
+            } else {
+                let mut before = resolved[..i.saturating_sub(1)].iter().rev();
+                (i.checked_sub(1), before.find_map(|c| points(c).first().cloned()))
+            };
+            let on_airway = |point: &ResolvedPoint| routes.iter().any(|route| route.contains(point));
+            let candidates = adjacent.and_then(|j| resolved.get(j)).map(points).unwrap_or_default();
+            let repaired = candidates
+                .iter()
+                .find(|point| on_airway(point))
+                .map(|fix| {
+                    routes
+                        .iter()
+                        .filter_map(|route| self.boundary_segment(route, fix, leading, beyond.as_ref()))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            let outcome = if repaired.is_empty() {
+                EventOutcome::Direct
+            } else {
+                EventOutcome::Repaired
+            };
+            events.push(|| EnrichmentEvent::new(STAGE, indices[i], name.clone(), outcome, routes.len()));
+            report.boundary_airways.push(BoundaryAirway {
+                index: indices[i],
+                airway: name.clone(),
+                repaired: !repaired.is_empty(),
+            });
+            if repaired.is_empty() {
+                tracing::warn!(
+                    target: "thrust::enrich",
+                    "Airway '{}' at the boundary of the route replaced by a direct leg",
+                    name
+                );
+                resolved[i] = EnrichedCandidates::Direct();
+                continue;
+            }
+            if let Some(EnrichedCandidates::Point((points, _, _, _))) = adjacent.and_then(|j| resolved.get_mut(j)) {
+                points.retain(|point| repaired.iter().any(|route| route.contains(point)));
+            }
+            if let EnrichedCandidates::Airway((routes, _, _, _, _)) = &mut resolved[i] {
+                *routes = repaired;
+            }
+        }
+
+        // Replace empty routes with Direct
+        for (candidate, &index) in resolved.iter_mut().zip(indices.iter()) {
+            if let EnrichedCandidates::Airway((routes, name, _, _, _)) = candidate {
                 if routes.iter().all(|r| r.segments.is_empty()) {
-                    tracing::warn!("No valid segments remaining for airway '{}'", name);
+                    tracing::warn!(target: "thrust::enrich", "No valid segments remaining for airway '{}'", name);
+                    events
+                        .push(|| EnrichmentEvent::new(STAGE, index, name.clone(), EventOutcome::Direct, routes.len()));
                     *candidate = EnrichedCandidates::Direct();
                 }
             }
         }
+        route
+    }
+
+    /// The portion of an airway from `fix` to its nearer end if `entry`, or from the
+    /// nearer end to `fix` otherwise; the end first in airway order wins ties.
+    fn partial_airway(&self, route: &ResolvedRoute, fix: &ResolvedPoint, entry: bool) -> Option<ResolvedRoute> {
+        // Ends of the airway are the points of a single complete segment
+        let mut degrees: Vec<(&ResolvedPoint, usize)> = Vec::new();
+        for point in route
+            .segments
+            .iter()
+            .filter(|s| s.is_complete())
+            .flat_map(|s| [&s.start, &s.end])
+        {
+            match degrees.iter_mut().find(|(p, _)| *p == point) {
+                Some((_, degree)) => *degree += 1,
+                None => degrees.push((point, 1)),
+            }
+        }
+        let length = |route: &ResolvedRoute| -> f64 {
+            route
+                .segments
+                .iter()
+                .filter_map(|s| Some(self.distance_model.distance(&s.start.coor()?, &s.end.coor()?)))
+                .sum()
+        };
+        degrees
+            .into_iter()
+            .filter(|(point, degree)| *degree == 1 && *point != fix)
+            .filter_map(|(end, _)| {
+                if entry {
+                    route.between(fix, end)
+                } else {
+                    route.between(end, fix)
+                }
+            })
+            .map(|portion| (length(&portion), portion))
+            .reduce(|best, other| if other.0 < best.0 { other } else { best })
+            .map(|(_, portion)| portion)
+    }
+
+    /// The single segment of an airway ending at `fix` (`leading`) or starting from it,
+    /// on the side of the fix away from the point `beyond` it, if known.
+    fn boundary_segment(
+        &self,
+        route: &ResolvedRoute,
+        fix: &ResolvedPoint,
+        leading: bool,
+        beyond: Option<&ResolvedPoint>,
+    ) -> Option<ResolvedRoute> {
+        // Segments touching the fix, oriented towards it for a leading airway
+        let adjacent = route
+            .segments
+            .iter()
+            .enumerate()
+            .filter(|(_, segment)| segment.is_complete())
+            .filter_map(|(i, segment)| {
+                let (other, forward) = if &segment.end == fix {
+                    (&segment.start, true)
+                } else if &segment.start == fix {
+                    (&segment.end, false)
+                } else {
+                    return None;
+                };
+                Some((other, (i, forward == leading)))
+            });
+        let distance = |point: &ResolvedPoint| match (point.coor(), beyond.and_then(|p| p.coor())) {
+            (Some(a), Some(b)) => self.distance_model.distance(&a, &b),
+            _ => 0.,
+        };
+        // The first segment in airway order wins ties
+        let (_, step) = adjacent.fold(None, |best: Option<(f64, (usize, bool))>, (other, step)| {
+            let d = distance(other);
+            match best {
+                Some((best_d, _)) if best_d >= d => best,
+                _ => Some((d, step)),
+            }
+        })?;
+        Some(route.build_route_from_path(vec![step]))
+    }
+
+    /// Choose a single candidate for each ambiguous point.
+    pub(crate) fn disambiguate(&self, mut route: CandidateRoute) -> CandidateRoute {
+        let CandidateRoute {
+            candidates: resolved,
+            indices,
+            tokens,
+            report,
+            scores: recorded,
+            events,
+            ..
+        } = &mut route;
 
-        // 6. Break the tie for remaining multiple candidate points
+        // Break the tie for remaining multiple candidate points. A name listed several times
+        // (e.g. when replanning a step climb) keeps the point chosen for its first occurrence,
+        // unless another candidate scores dramatically better, which is reported.
         let mut last_known: Option<ResolvedPoint> = None;
+        let mut decisions: HashMap<Designator, (ResolvedPoint, usize)> = HashMap::new();
 
         for i in 0..resolved.len() {
-            if let EnrichedCandidates::Point((points, _, _)) = &resolved[i] {
+            let token = tokens[i].clone();
+            if let EnrichedCandidates::Point((points, _, _, _)) = &resolved[i] {
                 if points.len() > 1 {
                     // Find the next definitive point ahead
                     let mut next_definitive: Option<&ResolvedPoint> = None;
                     for candidate in resolved[i..].iter() {
                         match candidate {
-                            EnrichedCandidates::Point((pts, _, _)) if pts.len() == 1 => {
+                            EnrichedCandidates::Point((pts, _, _, _)) if pts.len() == 1 => {
                                 next_definitive = pts.first();
                                 break;
                             }
-                            EnrichedCandidates::PointCoords((pt, _, _)) => {
+                            EnrichedCandidates::PointCoords((pt, _, _, _)) => {
                                 next_definitive = Some(pt);
                                 break;
                             }
@@ -972,119 +4358,299 @@ impl AirwayDatabase {
                         }
                     }
 
-                    match (&last_known, next_definitive) {
+                    let scores = match (&last_known, next_definitive) {
                         (None, None) => {
-                            tracing::warn!("Cannot disambiguate point {:?}: no reference points available", points);
+                            tracing::warn!(
+                                target: "thrust::enrich",
+                                "Cannot disambiguate point {:?}: no reference points available",
+                                points
+                            );
+                            None
                         }
                         (None, Some(_)) => {
-                            tracing::info!("Disambiguating point {:?} using only next definitive point", points);
+                            tracing::debug!(
+                                target: "thrust::enrich",
+                                "Disambiguating point {:?} using only next definitive point",
+                                points
+                            );
+                            None
                         }
                         (Some(a), None) => {
-                            tracing::info!("Disambiguating point {:?} using only last known point", points);
-
-                            // Only last known point is available, pick the closest candidate
-                            let mut best_idx = 0;
-                            let mut best_distance = f64::INFINITY;
-
-                            for (idx, candidate) in points.iter().enumerate() {
-                                let distance =
-                                    WGS84.distance(&Into::<Coor2D>::into(a), &Into::<Coor2D>::into(candidate));
-                                if distance < best_distance {
-                                    best_distance = distance;
-                                    best_idx = idx;
-                                }
-                            }
+                            tracing::debug!(
+                                target: "thrust::enrich",
+                                "Disambiguating point {:?} using only last known point",
+                                points
+                            );
 
-                            // Keep only the best candidate
-                            if let EnrichedCandidates::Point((points, _, _)) = &mut resolved[i] {
-                                let best = points[best_idx].clone();
-                                points.clear();
-                                points.push(best);
-                            }
+                            // Only last known point is available, score candidates by distance
+                            let scores = points
+                                .iter()
+                                .map(|candidate| match (a.coor(), candidate.coor()) {
+                                    (Some(a), Some(candidate)) => self.distance_model.distance(&a, &candidate),
+                                    _ => f64::INFINITY,
+                                })
+                                .collect::<Vec<_>>();
+                            Some(scores)
                         }
                         (Some(a), Some(b)) => {
-                            tracing::info!("Disambiguating point {:?} using both reference points", points);
+                            tracing::debug!(
+                                target: "thrust::enrich",
+                                "Disambiguating point {:?} using both reference points",
+                                points
+                            );
 
-                            let mut best_idx = 0;
-                            let mut best_score = f64::INFINITY;
+                            let scores = points
+                                .iter()
+                                .enumerate()
+                                .map(|(idx, candidate)| {
+                                    tracing::trace!(
+                                        target: "thrust::enrich",
+                                        "Scoring candidate {}: {} ({}-{})",
+                                        idx,
+                                        candidate,
+                                        a,
+                                        b
+                                    );
+                                    match (a.coor(), b.coor(), candidate.coor()) {
+                                        (Some(a), Some(b), Some(x)) => score_hybrid(self.distance_model, &a, &b, &x),
+                                        _ => f64::INFINITY,
+                                    }
+                                })
+                                .collect::<Vec<_>>();
+                            Some(scores)
+                        }
+                    };
 
-                            for (idx, candidate) in points.iter().enumerate() {
-                                tracing::info!("Scoring candidate {}: {} ({}-{})", idx, candidate, a, b);
-                                let score = score_hybrid(&a.into(), &b.into(), &candidate.into());
-                                if score < best_score {
-                                    best_score = score;
-                                    best_idx = idx;
-                                }
-                            }
+                    let best_idx = scores
+                        .as_ref()
+                        .and_then(|scores| (0..scores.len()).min_by(|&x, &y| scores[x].total_cmp(&scores[y])));
+                    let previous = token.as_ref().and_then(|token| {
+                        let (point, first_index) = decisions.get(token.as_str())?;
+                        let idx = points.iter().position(|p| p == point)?;
+                        Some((idx, *first_index))
+                    });
 
-                            // Keep only the best candidate
-                            if let EnrichedCandidates::Point((points, _, _)) = &mut resolved[i] {
-                                let best = points[best_idx].clone();
-                                points.clear();
-                                points.push(best);
-                            }
+                    let mut outcome = EventOutcome::Chosen;
+                    let choice = match (previous, best_idx, &scores) {
+                        (Some((prev_idx, first_index)), Some(best_idx), Some(scores))
+                            if scores[best_idx] < scores[prev_idx] * DECISION_OVERRIDE_RATIO =>
+                        {
+                            let token = token.clone().unwrap_or_default();
+                            tracing::warn!(
+                                target: "thrust::enrich",
+                                "Point '{}' resolves differently than at element {}: {} instead of {}",
+                                token,
+                                first_index,
+                                points[best_idx],
+                                points[prev_idx]
+                            );
+                            report.inconsistencies.push(InconsistentToken {
+                                index: indices[i],
+                                first_index,
+                                token,
+                            });
+                            outcome = EventOutcome::Inconsistent;
+                            Some(best_idx)
+                        }
+                        (Some((prev_idx, _)), _, _) => Some(prev_idx),
+                        (None, best_idx, _) => best_idx,
+                    };
+                    events.push(|| EnrichmentEvent {
+                        score: choice.zip(scores.as_ref()).map(|(idx, scores)| scores[idx]),
+                        ..EnrichmentEvent::new(
+                            "disambiguate",
+                            indices[i],
+                            token.clone().unwrap_or_default(),
+                            if choice.is_some() {
+                                outcome
+                            } else {
+                                EventOutcome::Undecided
+                            },
+                            points.len(),
+                        )
+                    });
+                    if let Some(scores) = scores {
+                        recorded.push((i, scores, choice));
+                    }
+
+                    // Keep only the chosen candidate
+                    if let Some(idx) = choice {
+                        if let EnrichedCandidates::Point((points, _, _, _)) = &mut resolved[i] {
+                            let best = points[idx].clone();
+                            points.clear();
+                            points.push(best);
                         }
                     }
                 }
 
-                // Update last_known point
-                if let EnrichedCandidates::Point((pts, _, _)) = &resolved[i] {
+                // Update last_known point and remember the decision for this name
+                if let EnrichedCandidates::Point((pts, _, _, _)) = &resolved[i] {
                     if let Some(pt) = pts.first() {
                         last_known = Some(pt.clone());
                     }
+                    if let (Some(token), [pt]) = (token, pts.as_slice()) {
+                        decisions.entry(token.into()).or_insert((pt.clone(), indices[i]));
+                    }
                 }
-            } else if let EnrichedCandidates::PointCoords((pt, _, _)) = &resolved[i] {
-                last_known = Some(pt.clone());
+            } else if let EnrichedCandidates::PointCoords((pt, _, _, _)) = &resolved[i] {
+                last_known = Some((**pt).clone());
             }
         }
+        route
+    }
 
-        // 7. Build the final sequence of resolved route segments.
-        let mut segments = Vec::new();
+    /// Build the final sequence of resolved route segments.
+    pub(crate) fn assemble_segments(&self, route: CandidateRoute) -> (Vec<ResolvedRouteSegment>, EnrichmentReport) {
+        let (segments, _, report) = self.assemble(route);
+        (segments, report)
+    }
+
+    /// Build the final sequence of resolved route segments, with the database which
+    /// resolved each of them.
+    fn assemble(&self, route: CandidateRoute) -> (Vec<ResolvedRouteSegment>, Vec<ResolvedBy>, EnrichmentReport) {
+        let CandidateRoute {
+            candidates: resolved,
+            overfly: overflown,
+            stays,
+            sources: resolved_by,
+            mut report,
+            ..
+        } = route;
+
+        let mut segments: Vec<ResolvedRouteSegment> = Vec::new();
+        let mut sources: Vec<ResolvedBy> = Vec::new();
         let mut previous_point: Option<ResolvedPoint> = None;
+        let mut previous_source = ResolvedBy::Primary;
+        // Whether an unresolved point was skipped since the previous point
+        let mut skipped = false;
 
-        for enriched in resolved {
+        let marks = overflown.into_iter().zip(stays);
+        for ((enriched, (overfly, stay)), source) in resolved.into_iter().zip(marks).zip(resolved_by) {
             match enriched {
-                EnrichedCandidates::Point((points, alt, spd)) => {
-                    if let Some(point) = points.first() {
-                        if let Some(prev) = &previous_point {
-                            if prev == point {
-                                continue;
+                EnrichedCandidates::Point((points, alt, spd, lvl)) => {
+                    let Some(point) = points.first() else {
+                        skipped = true;
+                        continue;
+                    };
+                    if let Some(prev) = &previous_point {
+                        if prev == point || self.collocated(prev, point) {
+                            // The point is already the end of the previous segment
+                            if let Some(last) = segments.last_mut() {
+                                last.overfly |= overfly;
+                                last.stay = last.stay.or(stay);
                             }
-                            segments.push(ResolvedRouteSegment {
-                                start: prev.clone(),
-                                end: point.clone(),
-                                name: None,
-                                altitude: alt,
-                                speed: spd,
-                            });
+                            skipped = false;
+                            continue;
                         }
-                        previous_point = Some(point.clone());
-                    }
-                }
-                EnrichedCandidates::PointCoords((point, alt, spd)) => {
-                    if let Some(prev) = previous_point {
+                        let gap = skipped.then(|| self.route_gap(prev, point, segments.len()));
                         segments.push(ResolvedRouteSegment {
-                            start: prev,
+                            start: prev.clone(),
                             end: point.clone(),
-                            name: None,
+                            name: self.gap_name(&gap),
                             altitude: alt,
                             speed: spd,
+                            level: lvl,
+                            overfly,
+                            stay,
+                            source_segment: None,
+                            is_reversed: false,
+                            overlay: false,
                         });
+                        sources.push(previous_source.max(source));
+                        report.gaps.extend(gap);
+                    }
+                    previous_point = Some(point.clone());
+                    previous_source = source;
+                    skipped = false;
+                }
+                EnrichedCandidates::PointCoords((point, alt, spd, lvl)) => {
+                    let point = *point;
+                    match previous_point.filter(|prev| !self.collocated(prev, &point)) {
+                        Some(prev) => {
+                            let gap = skipped.then(|| self.route_gap(&prev, &point, segments.len()));
+                            segments.push(ResolvedRouteSegment {
+                                start: prev,
+                                end: point.clone(),
+                                name: self.gap_name(&gap),
+                                altitude: alt,
+                                speed: spd,
+                                level: lvl,
+                                overfly,
+                                stay,
+                                source_segment: None,
+                                is_reversed: false,
+                                overlay: false,
+                            });
+                            sources.push(previous_source.max(source));
+                            report.gaps.extend(gap);
+                        }
+                        None => {
+                            if let Some(last) = segments.last_mut() {
+                                last.overfly |= overfly;
+                                last.stay = last.stay.or(stay);
+                            }
+                        }
                     }
                     previous_point = Some(point.clone());
+                    previous_source = source;
+                    skipped = false;
                 }
-                EnrichedCandidates::Airway((routes, name, alt, spd)) => {
-                    if let Some(route) = routes.first() {
-                        for segment in &route.segments {
+                EnrichedCandidates::Airway((routes, name, alt, spd, lvl)) => {
+                    if let Some(route) = self.select_airway(&routes) {
+                        let broken = route.segments.iter().filter(|s| !s.is_complete()).count();
+                        if broken > 0 {
+                            tracing::warn!(
+                                target: "thrust::enrich",
+                                "Skipping {} incomplete segments of airway '{}'",
+                                broken,
+                                name
+                            );
+                            report.broken_segments += broken;
+                        }
+                        let first = route.segments.iter().find(|s| s.is_complete());
+                        if let (Some(prev), Some(first)) = (&previous_point, first) {
+                            if prev != &first.start && !self.collocated(prev, &first.start) {
+                                let gap = self.route_gap(prev, &first.start, segments.len());
+                                if self.options.emit_gaps {
+                                    segments.push(ResolvedRouteSegment {
+                                        start: prev.clone(),
+                                        end: first.start.clone(),
+                                        name: Some(GAP.to_string()),
+                                        altitude: alt.clone(),
+                                        speed: spd.clone(),
+                                        level: lvl.clone(),
+                                        overfly: false,
+                                        stay: None,
+                                        source_segment: None,
+                                        is_reversed: false,
+                                        overlay: false,
+                                    });
+                                    sources.push(previous_source.max(source));
+                                }
+                                report.gaps.push(gap);
+                            }
+                        }
+                        for segment in route.segments.iter().filter(|s| s.is_complete()) {
                             segments.push(ResolvedRouteSegment {
                                 start: segment.start.clone(),
                                 end: segment.end.clone(),
                                 name: Some(name.clone()),
                                 altitude: alt.clone(),
                                 speed: spd.clone(),
+                                level: lvl.clone(),
+                                overfly: false,
+                                stay: None,
+                                source_segment: segment.source_segment.clone(),
+                                is_reversed: segment.is_reversed,
+                                overlay: segment.overlay,
                             });
+                            sources.push(source);
+                        }
+                        if let Some(last) = route.segments.iter().rev().find(|s| s.is_complete()) {
+                            previous_point = Some(last.end.clone());
+                            previous_source = source;
+                            skipped = false;
                         }
-                        previous_point = Some(route.segments.last().unwrap().end.clone());
                     }
                 }
                 EnrichedCandidates::Direct() => {
@@ -1092,31 +4658,2919 @@ impl AirwayDatabase {
                 }
             }
         }
-        segments
+        if let Some(overlay) = &self.overlay {
+            for segment in &mut segments {
+                segment.overlay = overlay.contains_segment(segment);
+            }
+        }
+        (segments, sources, report)
     }
-}
 
-fn score_hybrid(a: &Coor2D, b: &Coor2D, x: &Coor2D) -> f64 {
-    // Ideally gap_ration is close to 1.0 and the bearing difference close to 0.0
-    let ab = WGS84.geodesic_inv(a, b).to_degrees();
-    let ax = WGS84.geodesic_inv(a, x).to_degrees();
-    let xb = WGS84.geodesic_inv(x, b).to_degrees();
+    /// A gap from `start` to `end`, before the segment at `index` of the enriched route.
+    fn route_gap(&self, start: &ResolvedPoint, end: &ResolvedPoint, index: usize) -> RouteGap {
+        let distance = match (start.coor(), end.coor()) {
+            (Some(a), Some(b)) => self.distance_model.distance(&a, &b),
+            _ => 0.,
+        };
+        RouteGap {
+            index,
+            start: start.clone(),
+            end: end.clone(),
+            distance,
+        }
+    }
 
-    // Think about triangular inequality, we want x to be "between" a and b
-    let gap_ratio = (ax[2] + xb[2]) / ab[2].max(1e-9);
+    /// The name of a segment bridging `gap`, if any, with [`EnrichmentOptions::emit_gaps`].
+    fn gap_name(&self, gap: &Option<RouteGap>) -> Option<String> {
+        gap.as_ref().filter(|_| self.options.emit_gaps).map(|_| GAP.to_string())
+    }
 
-    let delta_a = (ax[0] - ab[0]).abs().min(360.0 - (ax[0] - ab[0]).abs());
-    let delta_b = (xb[0] - ab[0]).abs().min(360.0 - (xb[0] - ab[0]).abs());
-    let bearing_diff = (delta_a + delta_b) / 2.0; // Normalize to [0,1]
+    /// Choose among airway candidates according to [`EnrichmentOptions::airway_selection`].
+    ///
+    /// Candidates are expected in the order of their route identifiers: the first one wins ties.
+    fn select_airway<'a>(&self, routes: &'a [ResolvedRoute]) -> Option<&'a ResolvedRoute> {
+        let complete = |route: &ResolvedRoute| route.segments.iter().filter(|s| s.is_complete()).count();
+        let length = |route: &ResolvedRoute| -> f64 {
+            route
+                .segments
+                .iter()
+                .filter_map(|s| Some(self.distance_model.distance(&s.start.coor()?, &s.end.coor()?)))
+                .sum()
+        };
+        match self.options.airway_selection {
+            AirwaySelection::MostSegments => routes.iter().min_by_key(|route| std::cmp::Reverse(complete(route))),
+            AirwaySelection::Nearest => routes
+                .iter()
+                .filter(|route| complete(route) > 0)
+                .min_by(|a, b| length(a).total_cmp(&length(b))),
+        }
+    }
 
-    tracing::info!(
-        "Scoring point: {} = {} + {}; bearing_diff = {:.3}, gap_ratio = {:.3}",
-        ab[2],
-        ax[2],
-        xb[2],
-        bearing_diff,
-        gap_ratio
-    );
-    // Combine the two metrics into a score
-    bearing_diff / 180. + (gap_ratio - 1.0).max(0.)
+    /// Whether two points lie at the same location, e.g. a navaid and a designated point
+    /// published under different identifiers.
+    fn collocated(&self, a: &ResolvedPoint, b: &ResolvedPoint) -> bool {
+        match (a.coor(), b.coor()) {
+            (Some(a), Some(b)) => self.distance_model.distance(&a, &b) < COLLOCATION_TOLERANCE,
+            _ => false,
+        }
+    }
+
+    /// Suggest up to `n` designators (navaids and designated points) close to `name`.
+    ///
+    /// Candidates are within a Damerau-Levenshtein distance of 2, closest match first.
+    /// If `near` is provided, as a (latitude, longitude) pair, only candidates located
+    /// within a radius around this position are considered and ties are broken by distance.
+    pub fn suggest(&self, name: &str, near: Option<(f64, f64)>, n: usize) -> Vec<String> {
+        let name = name.trim().to_ascii_uppercase();
+        let reference = near.map(|(lat, lon)| Coor2D::geo(lat, lon));
+        let designators = self
+            .points
+            .navaids()
+            .filter_map(|navaid| {
+                let designator = navaid.name.as_deref()?.trim().to_ascii_uppercase();
+                Some((designator, navaid.latitude, navaid.longitude))
+            })
+            .chain(
+                self.points
+                    .designated_points()
+                    .map(|dp| (dp.designator.trim().to_ascii_uppercase(), dp.latitude, dp.longitude)),
+            );
+
+        let mut best: HashMap<Designator, (usize, f64)> = HashMap::new();
+        for (designator, latitude, longitude) in designators {
+            if designator.len().abs_diff(name.len()) > SUGGESTION_MAX_EDITS {
+                continue;
+            }
+            let edits = damerau_levenshtein(&name, &designator);
+            if edits > SUGGESTION_MAX_EDITS {
+                continue;
+            }
+            let distance = match &reference {
+                Some(reference) => {
+                    let distance = self
+                        .distance_model
+                        .distance(reference, &Coor2D::geo(latitude, longitude));
+                    if distance > SUGGESTION_RADIUS {
+                        continue;
+                    }
+                    distance
+                }
+                None => 0.,
+            };
+            best.entry(designator.into())
+                .and_modify(|v| {
+                    if (edits, distance) < *v {
+                        *v = (edits, distance)
+                    }
+                })
+                .or_insert((edits, distance));
+        }
+
+        let mut suggestions = best.into_iter().collect::<Vec<_>>();
+        suggestions.sort_by(|(a, (ea, da)), (b, (eb, db))| ea.cmp(eb).then(da.total_cmp(db)).then(a.cmp(b)));
+        suggestions
+            .into_iter()
+            .take(n)
+            .map(|(designator, _)| designator.into_string())
+            .collect()
+    }
+
+    /// Named fixes (navaids and designated points) within `radius` meters of the
+    /// given coordinates, as (distance, designator, latitude, longitude), nearest first.
+    ///
+    /// Only designators of one to five letters are considered, as only those may
+    /// be the base of a bearing/distance point in field 15.
+    fn named_fixes_within(&self, latitude: f64, longitude: f64, radius: f64) -> Vec<(f64, String, f64, f64)> {
+        let reference = Coor2D::geo(latitude, longitude);
+        // A degree of latitude spans more than 110 km: skip distant fixes before
+        // computing distances
+        let max_latitude_difference = radius / 110_000.;
+        let fixes = self
+            .points
+            .navaids()
+            .filter_map(|navaid| Some((navaid.name.clone()?, navaid.latitude, navaid.longitude)))
+            .chain(
+                self.points
+                    .designated_points()
+                    .map(|dp| (dp.designator.clone(), dp.latitude, dp.longitude)),
+            );
+        let mut fixes = fixes
+            .filter(|(name, lat, _)| {
+                (lat - latitude).abs() <= max_latitude_difference
+                    && (1..=5).contains(&name.len())
+                    && name.chars().all(|c| c.is_ascii_alphabetic())
+            })
+            .map(|(name, lat, lon)| {
+                let distance = self.distance_model.distance(&reference, &Coor2D::geo(lat, lon));
+                (distance, name, lat, lon)
+            })
+            .filter(|(distance, ..)| *distance <= radius)
+            .collect::<Vec<_>>();
+        fixes.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+        fixes
+    }
+
+    /// Resolve a field 15 bearing/distance point (e.g. `TOU090025`) to coordinates.
+    ///
+    /// Bearings are magnetic: the base point must be a navaid or an airport with
+    /// a published magnetic variation. When several of them share the name of the
+    /// base point, the one nearest to `near` (if any) is used. Returns `None` for
+    /// other kinds of points, and base points which are unknown or without a
+    /// magnetic variation.
+    pub fn resolve_bearing_distance(&self, point: &Point, near: Option<&ResolvedPoint>) -> Option<ResolvedPoint> {
+        let Point::BearingDistance {
+            point,
+            bearing,
+            distance,
+        } = point
+        else {
+            return None;
+        };
+        let (Point::Waypoint(name) | Point::Aerodrome(name)) = point.as_ref() else {
+            return None;
+        };
+        let mut candidates = ResolvedPoint::lookup(name, self)
+            .into_iter()
+            .filter_map(|candidate| Some((candidate.coor()?, candidate.magnetic_variation()?)));
+        let (base, variation) = match near.and_then(ResolvedPoint::coor) {
+            Some(near) => candidates.min_by(|(a, _), (b, _)| {
+                let model = self.distance_model;
+                model.distance(&near, a).total_cmp(&model.distance(&near, b))
+            })?,
+            None => candidates.next()?,
+        };
+        let bearing = geometry::magnetic_to_true(*bearing as f64, variation);
+        let (latitude, longitude) = geometry::forward(&base, bearing, *distance as f64 * NAUTICAL_MILE);
+        Some(ResolvedPoint::Coordinates { latitude, longitude })
+    }
+}
+
+/// Describe coordinates relative to the nearest named fix within
+/// [`DEFAULT_RELATIVE_RADIUS_NM`], see [`describe_relative_within`].
+pub fn describe_relative(point: &ResolvedPoint, db: &AirwayDatabase) -> Option<String> {
+    describe_relative_within(point, db, DEFAULT_RELATIVE_RADIUS_NM)
+}
+
+/// Describe coordinates in the field 15 bearing/distance form `FIXbbbddd`
+/// (e.g. `TOU090025`), relative to the nearest named fix within `radius_nm`
+/// nautical miles, for regenerated routes and human-readable summaries.
+///
+/// Bearings are magnetic, so only navaids and airports with a published
+/// magnetic variation are used as fixes, see
+/// [`AirwayDatabase::resolve_bearing_distance`]. Fixes whose designator
+/// resolves to other points (e.g. a designated point named after a navaid) are
+/// skipped. The bearing (magnetic, in degrees) and the distance (in nautical
+/// miles) from the fix are rounded, so that the description lies within a
+/// nautical mile of the point for fixes closer than about 50 NM. Points within
+/// half a nautical mile of the fix are described by its designator alone. Only
+/// [`ResolvedPoint::Coordinates`] are described, other points have a name.
+pub fn describe_relative_within(point: &ResolvedPoint, db: &AirwayDatabase, radius_nm: f64) -> Option<String> {
+    let ResolvedPoint::Coordinates { latitude, longitude } = point else {
+        return None;
+    };
+    let (name, lat, lon, variation) = db
+        .named_fixes_within(*latitude, *longitude, radius_nm * NAUTICAL_MILE)
+        .into_iter()
+        .find_map(|(_, name, lat, lon)| {
+            let fix = ResolvedPoint::Coordinates {
+                latitude: lat,
+                longitude: lon,
+            };
+            let variation = ResolvedPoint::lookup(&name, db)
+                .iter()
+                .filter(|candidate| db.collocated(candidate, &fix))
+                .find_map(ResolvedPoint::magnetic_variation)?;
+            Some((name, lat, lon, variation))
+        })?;
+    let (bearing, distance) = db
+        .distance_model
+        .inverse(&Coor2D::geo(lat, lon), &Coor2D::geo(*latitude, *longitude));
+    let distance = (distance / NAUTICAL_MILE).round() as u16;
+    if distance == 0 {
+        return Some(name);
+    }
+    if distance > 999 {
+        return None;
+    }
+    let bearing = geometry::true_to_magnetic(bearing, variation).round() as u16 % 360;
+    Some(format!("{name}{bearing:03}{distance:03}"))
+}
+
+/// Damerau-Levenshtein distance (optimal string alignment variant) between two strings.
+fn damerau_levenshtein(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Report where the requested level changes along a sequence of enriched segments.
+///
+/// Each entry contains the index of the first segment flown with the new level,
+/// together with the previous and the new level constraints. Segments without
+/// level information are skipped.
+pub fn level_changes(segments: &[ResolvedRouteSegment]) -> Vec<(usize, LevelConstraint, LevelConstraint)> {
+    let mut changes = Vec::new();
+    let mut current: Option<&LevelConstraint> = None;
+    for (idx, segment) in segments.iter().enumerate() {
+        let Some(level) = &segment.level else { continue };
+        if let Some(previous) = current {
+            if previous != level {
+                changes.push((idx, previous.clone(), level.clone()));
+            }
+        }
+        current = Some(level);
+    }
+    changes
+}
+
+/// Consecutive enriched segments flown along the same airway, or a single direct leg.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AirwayRun {
+    /// Name of the airway, `None` for a direct leg
+    pub name: Option<String>,
+    /// Points of the run in order, from its start to its end, intermediate waypoints included
+    pub waypoints: Vec<ResolvedPoint>,
+    /// Indices of the merged segments in the enriched route
+    pub segments: std::ops::Range<usize>,
+}
+
+/// Merge consecutive segments of the same airway into runs, for a compact leg list.
+///
+/// A segment extends the current run when it has the same airway name and starts
+/// where the run ends. Direct legs (without airway name) are never merged.
+pub fn merge_by_airway(segments: &[ResolvedRouteSegment]) -> Vec<AirwayRun> {
+    let mut runs: Vec<AirwayRun> = Vec::new();
+    for (idx, segment) in segments.iter().enumerate() {
+        if let Some(run) = runs.last_mut().filter(|run| {
+            run.name.is_some() && run.name == segment.name && run.waypoints.last() == Some(&segment.start)
+        }) {
+            run.waypoints.push(segment.end.clone());
+            run.segments.end = idx + 1;
+            continue;
+        }
+        runs.push(AirwayRun {
+            name: segment.name.clone(),
+            waypoints: vec![segment.start.clone(), segment.end.clone()],
+            segments: idx..idx + 1,
+        });
+    }
+    runs
+}
+
+fn score_hybrid(model: DistanceModel, a: &Coor2D, b: &Coor2D, x: &Coor2D) -> f64 {
+    // Ideally gap_ration is close to 1.0 and the bearing difference close to 0.0
+    let ab = model.inverse(a, b);
+    let ax = model.inverse(a, x);
+    let xb = model.inverse(x, b);
+
+    // Think about triangular inequality, we want x to be "between" a and b
+    let gap_ratio = (ax.1 + xb.1) / ab.1.max(1e-9);
+
+    let delta_a = (ax.0 - ab.0).abs().min(360.0 - (ax.0 - ab.0).abs());
+    let delta_b = (xb.0 - ab.0).abs().min(360.0 - (xb.0 - ab.0).abs());
+    let bearing_diff = (delta_a + delta_b) / 2.0; // Normalize to [0,1]
+
+    tracing::trace!(
+        target: "thrust::enrich",
+        "Scoring point: {} = {} + {}; bearing_diff = {:.3}, gap_ratio = {:.3}",
+        ab.1,
+        ax.1,
+        xb.1,
+        bearing_diff,
+        gap_ratio
+    );
+    // Combine the two metrics into a score
+    bearing_diff / 180. + (gap_ratio - 1.0).max(0.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::eurocontrol::aixm::airport_heliport::FacilityType;
+    use crate::data::eurocontrol::cache::SharedDatabase;
+
+    fn designated_point(identifier: &str, designator: &str, latitude: f64, longitude: f64) -> DesignatedPoint {
+        DesignatedPoint {
+            identifier: identifier.to_string(),
+            designator: designator.to_string(),
+            latitude,
+            longitude,
+            ..Default::default()
+        }
+    }
+
+    fn navaid(identifier: &str, name: &str, latitude: f64, longitude: f64) -> Navaid {
+        Navaid {
+            identifier: identifier.to_string(),
+            name: Some(name.to_string()),
+            latitude,
+            longitude,
+            ..Default::default()
+        }
+    }
+
+    fn route(identifier: &str, prefix: Option<&str>, second_letter: &str, number: &str) -> Route {
+        Route {
+            identifier: identifier.to_string(),
+            prefix: prefix.map(str::to_string),
+            second_letter: Some(second_letter.to_string()),
+            number: Some(number.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn route_segment(identifier: &str, route: &str, start: PointReference, end: PointReference) -> RouteSegment {
+        RouteSegment {
+            identifier: identifier.to_string(),
+            route_formed: Some(route.to_string()),
+            start,
+            end,
+            availability: Vec::new(),
+        }
+    }
+
+    /// A small database with a single airway UN1 going ABC (fix) - DEF - GHI,
+    /// and a navaid named ABC which is not on the airway.
+    fn fixture() -> AirwayDatabase {
+        let designated_points = [
+            designated_point("dp-abc", "ABC", 45.0, 1.0),
+            designated_point("dp-def", "DEF", 45.0, 2.0),
+            designated_point("dp-ghi", "GHI", 45.0, 3.0),
+        ];
+        let navaids = [navaid("nav-abc", "ABC", 45.0, 0.5)];
+        let routes = [route("rte-un1", Some("U"), "N", "1")];
+        let route_segments = [
+            route_segment(
+                "seg-1",
+                "rte-un1",
+                PointReference::DesignatedPoint("dp-abc".into()),
+                PointReference::DesignatedPoint("dp-def".into()),
+            ),
+            route_segment(
+                "seg-2",
+                "rte-un1",
+                PointReference::DesignatedPoint("dp-def".into()),
+                PointReference::DesignatedPoint("dp-ghi".into()),
+            ),
+        ];
+        let mut db = AirwayDatabase {
+            airports: HashMap::new(),
+            points: PointStore::Memory {
+                navaids: navaids.into_iter().map(|n| (n.identifier.clone().into(), n)).collect(),
+                designated_points: designated_points
+                    .into_iter()
+                    .map(|dp| (dp.identifier.clone().into(), dp))
+                    .collect(),
+            },
+            airways: OnceLock::from(Airways {
+                route_segments: route_segments
+                    .into_iter()
+                    .map(|s| (s.identifier.clone().into(), s))
+                    .collect(),
+                routes: routes.into_iter().map(|r| (r.identifier.clone().into(), r)).collect(),
+                segments_by_endpoints: HashMap::new(),
+            }),
+            airway_path: None,
+            airway_loader: Airways::load,
+            arrival_legs: HashMap::new(),
+            departure_legs: HashMap::new(),
+            standard_instrument_arrivals: HashMap::new(),
+            standard_instrument_departures: HashMap::new(),
+            stats: OnceLock::new(),
+            completions: OnceLock::new(),
+            segment_index: OnceLock::new(),
+            distance_model: DistanceModel::default(),
+            options: EnrichmentOptions::default(),
+            point_type_preference: DEFAULT_POINT_TYPE_PREFERENCE.to_vec(),
+            airac_cycle: None,
+            route_cache: None,
+            revision: 0,
+            overlay: None,
+        };
+        db.airways_mut().index_route_segments();
+        db
+    }
+
+    fn names(segments: &[ResolvedRouteSegment]) -> Vec<(String, String)> {
+        segments
+            .iter()
+            .map(|s| (format!("{}", s.start), format!("{}", s.end)))
+            .collect()
+    }
+
+    /// A compact view of the candidates of a route: point designators separated
+    /// by "|", airway names with the segment count of each candidate, or DCT.
+    fn summary(route: &CandidateRoute) -> Vec<String> {
+        let point = |point: &ResolvedPoint| match point {
+            ResolvedPoint::Navaid(navaid) => format!("navaid {}", navaid.name.as_deref().unwrap_or_default()),
+            ResolvedPoint::DesignatedPoint(dp) => dp.designator.clone(),
+            ResolvedPoint::AirportHeliport(airport) => airport.icao.clone(),
+            ResolvedPoint::Coordinates { latitude, longitude } => format!("{latitude},{longitude}"),
+            ResolvedPoint::None => String::new(),
+        };
+        route
+            .candidates
+            .iter()
+            .map(|candidate| match candidate {
+                EnrichedCandidates::Point((points, _, _, _)) => points.iter().map(point).collect::<Vec<_>>().join("|"),
+                EnrichedCandidates::PointCoords((pt, _, _, _)) => point(pt),
+                EnrichedCandidates::Airway((routes, name, _, _, _)) => {
+                    let counts = routes.iter().map(|r| r.segments.len().to_string()).collect::<Vec<_>>();
+                    format!("{name}:{}", counts.join(","))
+                }
+                EnrichedCandidates::Direct() => "DCT".to_string(),
+            })
+            .collect()
+    }
+
+    /// The fixture with another GHI designated point, far north of the airway.
+    fn fixture_with_duplicate_ghi() -> AirwayDatabase {
+        let mut db = fixture();
+        let dp = designated_point("dp-ghi-north", "GHI", 50.0, 3.0);
+        db.points
+            .designated_points_mut()
+            .insert(dp.identifier.clone().into(), dp);
+        db
+    }
+
+    fn latitudes(route: &CandidateRoute, i: usize) -> Vec<f64> {
+        match &route.candidates[i] {
+            EnrichedCandidates::Point((points, _, _, _)) => points
+                .iter()
+                .filter_map(|p| p.coor())
+                .map(|c| c.to_degrees()[1])
+                .collect(),
+            other => panic!("not a point candidate: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_resolve_candidates() {
+        let db = fixture();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 ABC UN1 GHI"));
+        // The navaid ABC is off UN1: the designated point ABC is considered as well
+        assert_eq!(summary(&route), ["navaid ABC|ABC", "UN1:2", "GHI"]);
+        assert_eq!(route.indices, [1, 2, 3]);
+        assert_eq!(route.tokens, [Some("ABC".to_string()), None, Some("GHI".to_string())]);
+        assert!(route.report.unresolved.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_candidates_unresolved() {
+        let db = fixture();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 XYZ DCT GHI"));
+        assert_eq!(summary(&route), ["", "DCT", "GHI"]);
+        assert_eq!(route.report.unresolved.len(), 1);
+        assert_eq!(
+            (
+                route.report.unresolved[0].index,
+                route.report.unresolved[0].token.as_str()
+            ),
+            (1, "XYZ")
+        );
+    }
+
+    #[test]
+    fn test_resolve_candidates_unknown_airway() {
+        let db = fixture();
+        let route = db.resolve_candidates(&Field15Parser::parse("DEF UZ9 GHI"));
+        assert_eq!(summary(&route), ["DEF", "DCT", "GHI"]);
+        assert!(route.report.missing_initial_group);
+        // Navaids and designated points are looked up together
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 ABC DCT GHI"));
+        assert_eq!(summary(&route), ["navaid ABC|ABC", "DCT", "GHI"]);
+
+        // With navaid priority, and without adjacent airway, the navaid ABC is not complemented
+        let mut db = fixture();
+        db.set_enrichment_options(EnrichmentOptions {
+            navaid_priority: true,
+            ..Default::default()
+        });
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 ABC DCT GHI"));
+        assert_eq!(summary(&route), ["navaid ABC", "DCT", "GHI"]);
+    }
+
+    #[test]
+    fn test_route_completeness() {
+        let db = fixture();
+        let completeness = db.route_completeness(Field15Parser::parse("N0450F350 ABC UN1 GHI DCT XYZ"));
+        assert_eq!((completeness.resolved, completeness.total), (3, 4));
+        assert_eq!(completeness.score, 0.75);
+        assert!(!completeness.is_complete());
+
+        // Unknown airways count as unresolved, direct legs are not counted
+        let completeness = db.route_completeness(Field15Parser::parse("N0450F350 DEF UZ9 GHI"));
+        assert_eq!((completeness.resolved, completeness.total), (2, 3));
+
+        let completeness = db.route_completeness(Field15Parser::parse("N0450F350 ABC UN1 GHI DCT DEF"));
+        assert_eq!(completeness, RouteCompleteness::new(4, 4));
+        assert!(completeness.is_complete());
+    }
+
+    #[test]
+    fn test_filter_airways_by_adjacent_points() {
+        let db = fixture();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 ABC UN1 GHI"));
+        let route = db.filter_airways_by_adjacent_points(route);
+        assert_eq!(summary(&route), ["navaid ABC|ABC", "UN1:2", "GHI"]);
+    }
+
+    #[test]
+    fn test_filter_airways_by_unresolved_points() {
+        let db = fixture();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 DEF UN1 XYZ"));
+        let route = db.filter_airways_by_adjacent_points(route);
+        assert_eq!(summary(&route), ["DEF", "DCT", ""]);
+    }
+
+    #[test]
+    fn test_filter_airways_with_substitution() {
+        let mut db = fixture();
+        let dp = designated_point("dp-jkl", "JKL", 45.0, 4.0);
+        db.points
+            .designated_points_mut()
+            .insert(dp.identifier.clone().into(), dp);
+        db.airways_mut()
+            .routes
+            .insert("rte-um5".into(), route("rte-um5", Some("U"), "M", "5"));
+        let segment = route_segment(
+            "seg-9",
+            "rte-um5",
+            PointReference::DesignatedPoint("dp-ghi".into()),
+            PointReference::DesignatedPoint("dp-jkl".into()),
+        );
+        db.airways_mut()
+            .route_segments
+            .insert(segment.identifier.clone().into(), segment);
+        db.airways_mut().index_route_segments();
+        let elements = Field15Parser::parse("N0450F350 DEF UM5 GHI");
+
+        let route = db.filter_airways_by_adjacent_points(db.resolve_candidates(&elements));
+        assert_eq!(summary(&route), ["DEF", "DCT", "GHI"]);
+        assert!(route.report.substitutions.is_empty());
+
+        db.set_airway_substitution(true);
+        let route = db.filter_airways_by_adjacent_points(db.resolve_candidates(&elements));
+        // The substitute is already trimmed to the bracketing points
+        assert_eq!(summary(&route), ["DEF", "UN1:1", "GHI"]);
+        assert_eq!(route.report.substitutions.len(), 1);
+        assert_eq!(route.report.substitutions[0].substitute, "UN1");
+    }
+
+    #[test]
+    fn test_filter_points_by_airways() {
+        let db = fixture();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 ABC UN1 GHI"));
+        let route = db.filter_points_by_airways(db.filter_airways_by_adjacent_points(route));
+        assert_eq!(summary(&route), ["ABC", "UN1:2", "GHI"]);
+    }
+
+    #[test]
+    fn test_filter_points_next_to_direct() {
+        let db = fixture_with_duplicate_ghi();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 ABC DCT GHI"));
+        let route = db.filter_points_by_airways(db.filter_airways_by_adjacent_points(route));
+        // Points next to direct legs are left for disambiguation
+        assert_eq!(summary(&route), ["navaid ABC|ABC", "DCT", "GHI|GHI"]);
+    }
+
+    #[test]
+    fn test_filter_points_off_airway() {
+        let db = fixture_with_duplicate_ghi();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 DEF UN1 GHI"));
+        assert_eq!(latitudes(&route, 2).len(), 2);
+        let route = db.filter_points_by_airways(db.filter_airways_by_adjacent_points(route));
+        assert_eq!(latitudes(&route, 2), [45.0]);
+    }
+
+    #[test]
+    fn test_trim_airways() {
+        let db = fixture();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 ABC UN1 DEF"));
+        let route = db.filter_points_by_airways(db.filter_airways_by_adjacent_points(route));
+        assert_eq!(summary(&route), ["ABC", "UN1:2", "DEF"]);
+        let route = db.trim_airways(route);
+        assert_eq!(summary(&route), ["ABC", "UN1:1", "DEF"]);
+    }
+
+    #[test]
+    fn test_trim_airways_reversed() {
+        let db = fixture();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 GHI UN1 ABC"));
+        let route = db.trim_airways(db.filter_points_by_airways(db.filter_airways_by_adjacent_points(route)));
+        assert_eq!(summary(&route), ["GHI", "UN1:2", "ABC"]);
+    }
+
+    #[test]
+    fn test_trim_airways_to_nothing() {
+        let db = fixture();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 DEF UN1 DEF"));
+        let route = db.trim_airways(db.filter_points_by_airways(db.filter_airways_by_adjacent_points(route)));
+        assert_eq!(summary(&route), ["DEF", "DCT", "DEF"]);
+    }
+
+    #[test]
+    fn test_disambiguate_with_last_known_point() {
+        let db = fixture_with_duplicate_ghi();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 DEF DCT GHI"));
+        let route = db.disambiguate(db.trim_airways(route));
+        assert_eq!(latitudes(&route, 2), [45.0]);
+    }
+
+    #[test]
+    fn test_disambiguate_without_last_known_point() {
+        let db = fixture_with_duplicate_ghi();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 GHI DCT DEF"));
+        let route = db.disambiguate(db.trim_airways(route));
+        // Only the next point is known: the candidates are kept as they are
+        assert_eq!(latitudes(&route, 0).len(), 2);
+    }
+
+    #[test]
+    fn test_disambiguate_repeated_point() {
+        let db = fixture_with_duplicate_ghi();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 DEF DCT GHI DCT ABC DCT GHI"));
+        let route = db.disambiguate(db.trim_airways(route));
+        assert_eq!(latitudes(&route, 2), [45.0]);
+        assert_eq!(latitudes(&route, 6), [45.0]);
+        assert!(route.report.inconsistencies.is_empty());
+    }
+
+    #[test]
+    fn test_assemble_segments() {
+        let db = fixture();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 ABC UN1 GHI"));
+        let route = db.filter_points_by_airways(db.filter_airways_by_adjacent_points(route));
+        let (segments, _) = db.assemble_segments(db.disambiguate(db.trim_airways(route)));
+        assert_eq!(segments.len(), 2);
+        assert!(segments.iter().all(|s| s.name.as_deref() == Some("UN1")));
+    }
+
+    #[test]
+    fn test_assemble_segments_repeated_point() {
+        let db = fixture();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 DEF DCT DEF DCT GHI"));
+        let (segments, _) = db.assemble_segments(route);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(names(&segments)[0].0, "DesignatedPoint(DEF: 45.000, 2.000)");
+    }
+
+    #[test]
+    fn test_assemble_segments_coordinates() {
+        let db = fixture();
+        let route = db.resolve_candidates(&Field15Parser::parse("N0450F350 DEF DCT 46N003E DCT GHI"));
+        let (segments, report) = db.assemble_segments(route);
+        assert_eq!(segments.len(), 2);
+        assert!(matches!(segments[0].end, ResolvedPoint::Coordinates { .. }));
+        assert_eq!(segments[1].start, segments[0].end);
+        assert_eq!(report.broken_segments, 0);
+    }
+
+    #[test]
+    fn test_enrich_route_level_block() {
+        let db = fixture();
+        let segments = db.enrich_route(Field15Parser::parse("N0450F310F350 ABC UN1 DEF/N0450F370 DCT GHI"));
+        assert_eq!(segments.len(), 2);
+        assert_eq!(
+            segments[0].level,
+            Some(LevelConstraint::Block {
+                lower: Altitude::FlightLevel(310),
+                upper: Altitude::FlightLevel(350)
+            })
+        );
+        let changes = level_changes(&segments);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].0, 1);
+        assert_eq!(changes[0].2, LevelConstraint::Level(Altitude::FlightLevel(370)));
+    }
+
+    #[test]
+    fn test_enrich_route_missing_initial_group() {
+        let db = fixture();
+        for route in ["ABC UN1 GHI", "N45F350 ABC UN1 GHI"] {
+            let (segments, report) = db.enrich_route_with_report(Field15Parser::parse(route));
+            assert_eq!(segments.len(), 2, "{route}");
+            assert!(segments.iter().all(|s| s.altitude.is_none() && s.speed.is_none()));
+            assert!(report.missing_initial_group, "{route}");
+            assert!(report.unresolved.is_empty(), "{route}");
+        }
+        let (_, report) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 ABC UN1 GHI"));
+        assert!(!report.missing_initial_group);
+    }
+
+    #[test]
+    fn test_enrich_route_coords() {
+        let db = fixture();
+        let coords = db.enrich_route_coords(Field15Parser::parse("N0450F350 ABC UN1 GHI DCT 46N003E"));
+        // ABC, DEF, GHI and the coordinates
+        assert_eq!(coords.len(), 4);
+        assert_eq!(coords[1], Coor2D::geo(45.0, 2.0));
+        assert_eq!(coords[3], Coor2D::geo(46.0, 3.0));
+    }
+
+    #[test]
+    fn test_enrich_route_vfr_portion() {
+        let db = fixture();
+        let segments = db.enrich_route(Field15Parser::parse("N0450F350 ABC UN1 DEF VFR DCT GHI"));
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1].level, Some(LevelConstraint::Vfr));
+        let changes = level_changes(&segments);
+        assert_eq!(
+            changes,
+            vec![(
+                1,
+                LevelConstraint::Level(Altitude::FlightLevel(350)),
+                LevelConstraint::Vfr
+            )]
+        );
+    }
+
+    static AIRWAY_LOADS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    fn counting_loader(path: &path::Path) -> Result<Airways, ThrustError> {
+        assert_eq!(path, path::Path::new("/nonexistent"));
+        AIRWAY_LOADS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Ok(fixture().airways.into_inner().unwrap())
+    }
+
+    #[test]
+    fn test_lazy_airways() {
+        let mut db = fixture();
+        db.airways = OnceLock::new();
+        db.airway_loader = counting_loader;
+        db.reload_dataset(path::Path::new("/nonexistent"), Dataset::RouteSegment)
+            .unwrap();
+
+        // Point lookups never touch the airways
+        assert_eq!(ResolvedPoint::lookup("DEF", &db).len(), 1);
+        assert_eq!(ResolvedPoint::lookup_navaids("ABC", &db).len(), 1);
+        assert_eq!(AIRWAY_LOADS.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        // Enrichment loads them transparently, only once
+        let segments = db.enrich_route(Field15Parser::parse("N0450F350 ABC UN1 GHI"));
+        assert_eq!(
+            names(&segments),
+            [
+                (
+                    "DesignatedPoint(ABC: 45.000, 1.000)",
+                    "DesignatedPoint(DEF: 45.000, 2.000)"
+                ),
+                (
+                    "DesignatedPoint(DEF: 45.000, 2.000)",
+                    "DesignatedPoint(GHI: 45.000, 3.000)"
+                ),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+        );
+        db.enrich_route(Field15Parser::parse("N0450F350 ABC UN1 DEF"));
+        assert_eq!(AIRWAY_LOADS.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_lazy_airways_error() {
+        let mut db = fixture();
+        db.airways = OnceLock::new();
+        db.reload_dataset(path::Path::new("/nonexistent"), Dataset::Route)
+            .unwrap();
+        assert!(db.load_airways().is_err());
+
+        // Enrichment finds no airway, and the failed load is not kept
+        let segments = db.enrich_route(Field15Parser::parse("N0450F350 ABC UN1 GHI"));
+        assert!(segments.iter().all(|segment| segment.name.as_deref() != Some("UN1")));
+        assert!(db.airways.get().is_none());
+        db.airway_loader = |_| Ok(fixture().airways.into_inner().unwrap());
+        assert!(db.load_airways().is_ok());
+        let segments = db.enrich_route(Field15Parser::parse("N0450F350 ABC UN1 GHI"));
+        assert!(segments.iter().all(|segment| segment.name.as_deref() == Some("UN1")));
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut db = fixture();
+        db.airways_mut()
+            .routes
+            .insert("rte-empty".into(), route("rte-empty", None, "L", "2"));
+        let stats = db.stats();
+        let records = |dataset: Dataset| {
+            stats
+                .datasets
+                .iter()
+                .find(|d| d.dataset == dataset)
+                .map(|d| d.records)
+                .unwrap()
+        };
+        assert_eq!(records(Dataset::Navaid), 1);
+        assert_eq!(records(Dataset::DesignatedPoint), 3);
+        assert_eq!(records(Dataset::RouteSegment), 2);
+        assert_eq!(records(Dataset::Route), 2);
+        assert_eq!(records(Dataset::ArrivalLeg), 0);
+        assert_eq!(stats.empty_routes, 1);
+        // The ABC navaid is not referenced by any segment
+        assert_eq!(stats.orphan_points, 1);
+        assert!(stats.estimated_bytes > 0);
+        assert!(std::ptr::eq(stats, db.stats()));
+    }
+
+    #[test]
+    fn test_airway_selection() {
+        let mut db = fixture();
+        // A second UN1 record goes the long way round, ABC - XYZ - WWW - GHI
+        for dp in [
+            designated_point("dp-xyz", "XYZ", 46.0, 2.0),
+            designated_point("dp-www", "WWW", 46.0, 2.5),
+        ] {
+            db.points
+                .designated_points_mut()
+                .insert(dp.identifier.clone().into(), dp);
+        }
+        db.airways_mut()
+            .routes
+            .insert("rte-un1b".into(), route("rte-un1b", Some("U"), "N", "1"));
+        let segments = [
+            ("seg-5", "dp-abc", "dp-xyz"),
+            ("seg-6", "dp-xyz", "dp-www"),
+            ("seg-7", "dp-www", "dp-ghi"),
+        ];
+        for (id, start, end) in segments {
+            let segment = route_segment(
+                id,
+                "rte-un1b",
+                PointReference::DesignatedPoint(start.into()),
+                PointReference::DesignatedPoint(end.into()),
+            );
+            db.airways_mut()
+                .route_segments
+                .insert(segment.identifier.clone().into(), segment);
+        }
+        db.airways_mut().index_route_segments();
+        let route = "N0450F350 ABC UN1 GHI";
+
+        // The first candidate in identifier order is the short one: the default takes the longest
+        let segments = db.enrich_route(Field15Parser::parse(route));
+        assert_eq!(segments.len(), 3);
+        assert!(names(&segments)[0].1.contains("XYZ"), "{:?}", names(&segments));
+
+        db.set_enrichment_options(EnrichmentOptions {
+            airway_selection: AirwaySelection::Nearest,
+            ..Default::default()
+        });
+        let segments = db.enrich_route(Field15Parser::parse(route));
+        assert_eq!(segments.len(), 2);
+        assert!(names(&segments)[0].1.contains("DEF"), "{:?}", names(&segments));
+    }
+
+    #[test]
+    fn test_complete() {
+        let mut db = fixture();
+        for dp in [
+            designated_point("dp-abcde", "ABCDE", 46.0, 1.0),
+            designated_point("dp-ab", "AB", 46.0, 2.0),
+        ] {
+            db.points
+                .designated_points_mut()
+                .insert(dp.identifier.clone().into(), dp);
+        }
+        db.airports.insert(
+            "ah-abcd".into(),
+            AirportHeliport {
+                identifier: "ah-abcd".to_string(),
+                icao: "ABCD".to_string(),
+                ..Default::default()
+            },
+        );
+
+        // Exact match first, then lexicographic order, then by kind for the same name
+        let entries = db.complete("abc", 10);
+        let names = entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                ("ABC", CompletionKind::Navaid),
+                ("ABC", CompletionKind::DesignatedPoint),
+                ("ABCD", CompletionKind::Airport),
+                ("ABCDE", CompletionKind::DesignatedPoint),
+            ]
+        );
+        assert_eq!((entries[0].latitude, entries[0].longitude), (Some(45.0), Some(0.5)));
+        assert_eq!(db.complete("ABC", 2).len(), 2);
+        assert!(db.complete("ABX", 10).is_empty());
+
+        let routes = db.complete("U", 10);
+        assert_eq!(routes.len(), 1);
+        assert_eq!(
+            (routes[0].name.as_str(), routes[0].kind),
+            ("UN1", CompletionKind::Route)
+        );
+        assert_eq!(routes[0].latitude, None);
+        let json = serde_json::to_string(&routes[0]).unwrap();
+        assert_eq!(json, r#"{"name":"UN1","kind":"route"}"#);
+    }
+
+    #[test]
+    fn test_route_cache_key() {
+        let mut db = fixture();
+        let key = db.route_cache_key("N0450F350 ABC UN1 GHI");
+        assert_eq!(db.route_cache_key("  n0450f350 ABC\tUN1   GHI "), key);
+        assert_ne!(db.route_cache_key("N0450F350 ABC UN1 DEF"), key);
+        // Keys do not depend on the build, e.g. to persist caches
+        assert_eq!(key, 0x61e1_51ce_029f_378a);
+
+        db.set_airac_cycle(Some("2508".to_string()));
+        assert_eq!(db.airac_cycle(), Some("2508"));
+        let cycle_2508 = db.route_cache_key("N0450F350 ABC UN1 GHI");
+        assert_eq!(cycle_2508, 0xec47_0504_4242_f4c3);
+        db.set_airac_cycle(Some("2509".to_string()));
+        assert_ne!(db.route_cache_key("N0450F350 ABC UN1 GHI"), cycle_2508);
+    }
+
+    #[test]
+    fn test_airac_cycle_from_path() {
+        let cycle = |path: &str| airac_cycle_from_path(path::Path::new(path));
+        assert_eq!(cycle("/data/aixm_2508").as_deref(), Some("2508"));
+        assert_eq!(cycle("/data/2508/aixm").as_deref(), Some("2508"));
+        assert_eq!(cycle("/data/eurocontrol-2601-v2").as_deref(), Some("2601"));
+        // Only valid AIRAC codes of four digits
+        assert_eq!(cycle("/data/aixm_2515"), None);
+        assert_eq!(cycle("/data/aixm_20250807"), None);
+        assert_eq!(cycle("/data/aixm"), None);
+
+        let mut db = fixture();
+        db.reload_dataset(path::Path::new("/nonexistent/aixm_2508"), Dataset::ArrivalLeg)
+            .unwrap();
+        assert_eq!(db.airac_cycle(), Some("2508"));
+    }
+
+    #[test]
+    fn test_with_cache() {
+        let db = fixture();
+        assert_eq!(db.cache_stats(), None);
+        let db = db.with_cache(10);
+        let (segments, report) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 ABC UN1 GHI"));
+        let (cached, cached_report) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 abc UN1 GHI"));
+        assert_eq!(names(&cached), names(&segments));
+        assert_eq!(cached_report.unresolved, report.unresolved);
+        let stats = db.cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+
+        // A block of levels is not mistaken for its lower level
+        db.enrich_route_with_report(Field15Parser::parse("N0450F350F390 ABC UN1 GHI"));
+        let stats = db.cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 2, 2));
+
+        // Changing a setting or the AIRAC cycle invalidates cached routes
+        let mut db = db;
+        db.set_airway_substitution(true);
+        assert_eq!(db.cache_stats().unwrap().entries, 0);
+        db.enrich_route_with_report(Field15Parser::parse("N0450F350 ABC UN1 GHI"));
+        db.set_airac_cycle(Some("2508".to_string()));
+        assert_eq!(db.cache_stats().unwrap().entries, 0);
+    }
+
+    #[test]
+    fn test_enrich_route_trace() {
+        let db = fixture_with_duplicate_ghi();
+        let trace = db.enrich_route_trace(Field15Parser::parse("N0450F350 ABC UN1 DEF DCT GHI"));
+        let stages = trace.stages.iter().map(|stage| stage.stage).collect::<Vec<_>>();
+        assert_eq!(
+            stages,
+            [
+                "resolve_candidates",
+                "filter_airways_by_adjacent_points",
+                "filter_points_by_airways",
+                "trim_airways",
+                "disambiguate"
+            ]
+        );
+        fn counts(stage: &TraceStage) -> Vec<(&str, usize)> {
+            stage.tokens.iter().map(|t| (t.token.as_str(), t.count)).collect()
+        }
+        assert_eq!(
+            counts(&trace.stages[0]),
+            [("ABC", 2), ("UN1", 1), ("DEF", 1), ("DCT", 0), ("GHI", 2)]
+        );
+        assert_eq!(counts(&trace.stages[2])[0], ("ABC", 1));
+        assert_eq!(trace.stages[3].tokens[1].candidates, ["UN1 (1 segments)"]);
+        assert_eq!(counts(&trace.stages[4])[4], ("GHI", 1));
+
+        // GHI is scored from DEF, the closest candidate is chosen
+        assert_eq!(trace.scores.len(), 1);
+        let scores = &trace.scores[0];
+        assert_eq!((scores.index, scores.token.as_str()), (5, "GHI"));
+        assert_eq!(scores.chosen.as_deref(), Some(scores.candidates[0].0.as_str()));
+        assert!(scores.candidates[0].0.contains("45.000"));
+        assert!(trace.to_string().contains("trim_airways\n"));
+    }
+
+    #[test]
+    fn test_enrich_route_trace_is_bounded() {
+        let mut db = fixture();
+        for i in 0..20 {
+            let dp = designated_point(&format!("dp-xyz-{i}"), "XYZ", 40.0 + i as f64, 1.0);
+            db.points
+                .designated_points_mut()
+                .insert(dp.identifier.clone().into(), dp);
+        }
+        let trace = db.enrich_route_trace(Field15Parser::parse("N0450F350 DEF DCT XYZ"));
+        let xyz = &trace.stages[0].tokens[2];
+        assert_eq!((xyz.count, xyz.candidates.len()), (20, TRACE_CANDIDATE_LIMIT));
+        assert_eq!(trace.scores[0].candidates.len(), TRACE_CANDIDATE_LIMIT);
+    }
+
+    #[test]
+    fn test_enrich_route_events() {
+        #[derive(Default)]
+        struct MemorySink(std::sync::Mutex<Vec<(String, EnrichmentEvent)>>);
+        impl EnrichmentSink for MemorySink {
+            fn record(&self, route: &str, events: &[EnrichmentEvent]) {
+                let mut recorded = self.0.lock().unwrap();
+                recorded.extend(events.iter().map(|event| (route.to_string(), event.clone())));
+            }
+        }
+
+        let mut db = fixture_with_duplicate_ghi();
+        let sink = Arc::new(MemorySink::default());
+        db.set_enrichment_options(EnrichmentOptions {
+            events: Some(sink.clone()),
+            ..Default::default()
+        });
+        // XYZ is unknown, so UN1 is flown direct and GHI is scored from ABC only
+        let route = "N0450F350 ABC DCT GHI UN1 XYZ";
+        db.enrich_route(Field15Parser::parse(route));
+        let events = std::mem::take(&mut *sink.0.lock().unwrap());
+        assert!(events.iter().all(|(recorded, _)| recorded == route));
+        let summary = events
+            .iter()
+            .map(|(_, e)| (e.stage, e.index, e.token.as_str(), e.outcome, e.candidates))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("resolve_candidates", 5, "XYZ", EventOutcome::Unresolved, 0),
+                ("filter_airways_by_adjacent_points", 4, "UN1", EventOutcome::Direct, 1),
+                ("disambiguate", 1, "ABC", EventOutcome::Undecided, 2),
+                ("disambiguate", 3, "GHI", EventOutcome::Chosen, 2),
+            ]
+        );
+        assert!(events[0].1.score.is_none());
+        assert!(events[3].1.score.is_some_and(f64::is_finite));
+
+        // The NDJSON sink writes one object per event
+        let path = std::env::temp_dir().join(format!("thrust-events-{}.ndjson", std::process::id()));
+        let sink = Arc::new(crate::data::eurocontrol::events::NdjsonSink::create(&path).unwrap());
+        db.set_enrichment_options(EnrichmentOptions {
+            events: Some(sink.clone()),
+            ..Default::default()
+        });
+        db.enrich_route(Field15Parser::parse(route));
+        sink.flush().unwrap();
+        let lines = std::fs::read_to_string(&path).unwrap();
+        let lines = lines
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["route"], route);
+        assert_eq!(lines[1]["outcome"], "direct");
+        assert_eq!(lines[2]["stage"], "disambiguate");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_enrich_route_overfly() {
+        let db = fixture();
+        let segments = db.enrich_route(Field15Parser::parse("N0450F350 ABC UN1 @DEF DCT @46N003E DCT GHI"));
+        assert_eq!(segments.len(), 3);
+        // The overflown fix still resolves, and the airway is not broken
+        assert_eq!(segments[0].name.as_deref(), Some("UN1"));
+        assert!(names(&segments)[0].1.contains("DEF"));
+        assert!(segments[0].overfly);
+        assert!(matches!(segments[1].end, ResolvedPoint::Coordinates { .. }));
+        assert!(segments[1].overfly);
+        assert!(!segments[2].overfly);
+        let json = serde_json::to_value(&segments).unwrap();
+        assert_eq!(json[0]["overfly"], true);
+        assert!(json[2].get("overfly").is_none());
+    }
+
+    #[test]
+    fn test_enrich_route_stay() {
+        let db = fixture();
+        let stays = |route: &str| {
+            db.enrich_route(Field15Parser::parse(route))
+                .into_iter()
+                .map(|segment| segment.stay)
+                .collect::<Vec<_>>()
+        };
+        // The STAY is kept on the segment ending at the point before it, along an airway or not
+        assert_eq!(stays("N0450F350 ABC UN1 DEF STAY1/0130 UN1 GHI"), [Some(90), None]);
+        assert_eq!(
+            stays("N0450F350 ABC DCT DEF DCT 46N003E STAY2/0045 DCT GHI"),
+            [None, Some(45), None]
+        );
+        // Without a duration, there is nothing to keep
+        assert_eq!(stays("N0450F350 ABC DCT DEF STAY1 DCT GHI"), [None, None]);
+
+        let segments = db.enrich_route(Field15Parser::parse("N0450F350 ABC UN1 DEF STAY1/0130 UN1 GHI"));
+        let json = serde_json::to_value(&segments).unwrap();
+        assert_eq!(json[0]["stay"], 90);
+        assert!(json[1].get("stay").is_none());
+    }
+
+    #[test]
+    fn test_enrich_route_with_fallback() {
+        let db = fixture();
+        let mut fallback = AirwayDatabase::empty();
+        let dp = designated_point("dp-xyz", "XYZ", 46.0, 3.0);
+        fallback
+            .points
+            .designated_points_mut()
+            .insert(dp.identifier.clone().into(), dp);
+
+        let elements = Field15Parser::parse("N0450F350 ABC UN1 GHI DCT XYZ DCT UVW");
+        let (segments, report) = db.enrich_route_with_fallback(elements.clone(), &fallback);
+        let sources = segments.iter().map(|(_, source)| *source).collect::<Vec<_>>();
+        assert_eq!(
+            sources,
+            [ResolvedBy::Primary, ResolvedBy::Primary, ResolvedBy::Fallback]
+        );
+        assert!(format!("{}", segments[2].0.end).contains("XYZ"));
+        // Only the identifier missing from both databases is unresolved
+        let unresolved = report.unresolved.iter().map(|t| t.token.as_str()).collect::<Vec<_>>();
+        assert_eq!(unresolved, ["UVW"]);
+
+        // Without fallback, XYZ is not resolved
+        let (segments, report) = db.enrich_route_with_report(elements);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(report.unresolved.len(), 2);
+    }
+
+    #[test]
+    fn test_nearest_segment() {
+        let mut db = fixture();
+        // UN2 goes from DEF north-east to JKL
+        let dp = designated_point("dp-jkl", "JKL", 46.0, 3.0);
+        db.points
+            .designated_points_mut()
+            .insert(dp.identifier.clone().into(), dp);
+        db.airways_mut()
+            .routes
+            .insert("rte-un2".into(), route("rte-un2", Some("U"), "N", "2"));
+        let segment = route_segment(
+            "seg-3",
+            "rte-un2",
+            PointReference::DesignatedPoint("dp-def".into()),
+            PointReference::DesignatedPoint("dp-jkl".into()),
+        );
+        db.airways_mut()
+            .route_segments
+            .insert(segment.identifier.clone().into(), segment);
+        db.airways_mut().index_route_segments();
+
+        let nearest = |latitude, longitude| {
+            let (segment, distance) = db.nearest_segment(latitude, longitude).unwrap();
+            (segment.identifier.clone(), (distance / 1000.).round())
+        };
+        assert_eq!(nearest(45.1, 1.5), ("seg-1".to_string(), 11.));
+        assert_eq!(nearest(44.9, 2.6), ("seg-2".to_string(), 11.));
+        assert_eq!(nearest(45.6, 2.4), ("seg-3".to_string(), 12.));
+        // Far from the network, past the end of UN1
+        assert_eq!(nearest(45.0, 10.0).0, "seg-2");
+        assert!(AirwayDatabase::empty().nearest_segment(45.0, 1.0).is_none());
+    }
+
+    #[test]
+    fn test_overlay() {
+        let overlay = OverlayData::from_json(
+            r#"{
+                "designated_points": [
+                    {"identifier": "notam-kolob", "designator": "KOLOB", "latitude": 45.5, "longitude": 3.5}
+                ],
+                "segments": [{"airway": "UN1", "start": "dp-ghi", "end": "notam-kolob"}]
+            }"#,
+        )
+        .unwrap();
+        let db: SharedDatabase = Arc::new(std::sync::RwLock::new(fixture().with_overlay(overlay)));
+        let route = || Field15Parser::parse("N0450F350 ABC UN1 KOLOB DCT DEF");
+
+        let (segments, report) = db.read().unwrap().enrich_route_with_report(route());
+        assert!(report.unresolved.is_empty());
+        let ends = names(&segments).into_iter().map(|(_, end)| end).collect::<Vec<_>>();
+        assert!(ends[2].contains("KOLOB") && ends[3].contains("DEF"), "{ends:?}");
+        let overlay = segments.iter().map(|s| s.overlay).collect::<Vec<_>>();
+        assert_eq!(overlay, [false, false, true, true]);
+        assert_eq!(segments[2].name.as_deref(), Some("UN1"));
+
+        // Without the overlay, KOLOB no longer resolves
+        db.write().unwrap().set_overlay(None);
+        let (segments, report) = db.read().unwrap().enrich_route_with_report(route());
+        let unresolved = report.unresolved.iter().map(|t| t.token.as_str()).collect::<Vec<_>>();
+        assert_eq!(unresolved, ["KOLOB"]);
+        assert!(segments.iter().all(|s| !s.overlay));
+    }
+
+    #[test]
+    fn test_overlay_extends_connected_candidates() {
+        let mut db = fixture();
+        for dp in [
+            designated_point("dp-xyz", "XYZ", 46.0, 2.0),
+            designated_point("dp-www", "WWW", 46.0, 2.5),
+        ] {
+            db.points
+                .designated_points_mut()
+                .insert(dp.identifier.clone().into(), dp);
+        }
+        db.airways_mut()
+            .routes
+            .insert("rte-un1b".into(), route("rte-un1b", Some("U"), "N", "1"));
+        let segment = route_segment(
+            "seg-5",
+            "rte-un1b",
+            PointReference::DesignatedPoint("dp-xyz".into()),
+            PointReference::DesignatedPoint("dp-www".into()),
+        );
+        db.airways_mut()
+            .route_segments
+            .insert(segment.identifier.clone().into(), segment);
+        db.airways_mut().index_route_segments();
+
+        // KOLOB extends the first airway through GHI, MNO through KOLOB, and PPP - QQQ
+        // is connected to neither airway
+        let overlay = OverlayData::from_json(
+            r#"{
+                "designated_points": [
+                    {"identifier": "notam-kolob", "designator": "KOLOB", "latitude": 45.5, "longitude": 3.5},
+                    {"identifier": "notam-mno", "designator": "MNO", "latitude": 45.5, "longitude": 4.0},
+                    {"identifier": "notam-ppp", "designator": "PPP", "latitude": 47.0, "longitude": 4.0},
+                    {"identifier": "notam-qqq", "designator": "QQQ", "latitude": 47.0, "longitude": 5.0}
+                ],
+                "segments": [
+                    {"identifier": "a-kolob-mno", "airway": "UN1", "start": "notam-kolob", "end": "notam-mno"},
+                    {"identifier": "b-ghi-kolob", "airway": "UN1", "start": "dp-ghi", "end": "notam-kolob"},
+                    {"identifier": "c-ppp-qqq", "airway": "UN1", "start": "notam-ppp", "end": "notam-qqq"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let db = db.with_overlay(overlay);
+        let sources = ResolvedRoute::lookup("UN1", &db)
+            .into_iter()
+            .map(|route| {
+                let mut sources = route
+                    .segments
+                    .into_iter()
+                    .filter_map(|segment| segment.source_segment)
+                    .collect::<Vec<_>>();
+                sources.sort();
+                sources
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sources,
+            [
+                vec!["a-kolob-mno", "b-ghi-kolob", "seg-1", "seg-2"],
+                vec!["seg-5"],
+                vec!["c-ppp-qqq"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_source_segment() {
+        let db = fixture();
+        let sources = |route: &str| {
+            db.enrich_route(Field15Parser::parse(route))
+                .into_iter()
+                .map(|segment| segment.source_segment)
+                .collect::<Vec<_>>()
+        };
+        let (seg_1, seg_2) = (Some("seg-1".to_string()), Some("seg-2".to_string()));
+        assert_eq!(sources("N0450F350 ABC UN1 GHI"), [seg_1.clone(), seg_2.clone()]);
+        // Reversed segments keep their source, direct segments have none
+        assert_eq!(sources("N0450F350 GHI UN1 ABC DCT 46N003E"), [seg_2, seg_1, None]);
+
+        let segments = db.enrich_route(Field15Parser::parse("N0450F350 ABC UN1 DEF DCT GHI"));
+        let json = serde_json::to_value(&segments).unwrap();
+        assert_eq!(json[0]["source_segment"], "seg-1");
+        assert!(json[1].get("source_segment").is_none());
+    }
+
+    #[test]
+    #[tracing_test::traced_test]
+    fn test_enrichment_is_quiet() {
+        let mut db = fixture_with_duplicate_ghi();
+        let segments = db.enrich_route(Field15Parser::parse("N0450F350 ABC UN1 GHI DCT 46N003E"));
+        assert_eq!(segments.len(), 3);
+
+        // Nor do VFR routes between heliports, without any airway
+        for (identifier, name, latitude, longitude) in
+            [("ah-issy", "ISSY", 48.83, 2.27), ("ah-neck", "NECKER", 48.85, 2.31)]
+        {
+            let heliport = AirportHeliport {
+                designator: Some(name.to_string()),
+                r#type: FacilityType::Heliport,
+                ..airport(identifier, "", latitude, longitude)
+            };
+            db.airports.insert(identifier.into(), heliport);
+        }
+        let mut vfr = designated_point("dp-novem", "NOVEM", 48.84, 2.29);
+        vfr.r#type = DesignatedPointType::from("VFR-RP");
+        db.points
+            .designated_points_mut()
+            .insert(vfr.identifier.clone().into(), vfr);
+        let segments = db.enrich_route(Field15Parser::parse("N0080VFR ISSY DCT NOVEM DCT NECKER"));
+        assert_eq!(segments.len(), 2);
+        // Choices between candidates are only reported at debug level and below
+        assert!(logs_contain("thrust::enrich"));
+        logs_assert(|lines: &[&str]| {
+            match lines
+                .iter()
+                .find(|line| [" INFO ", " WARN ", " ERROR "].iter().any(|level| line.contains(level)))
+            {
+                Some(line) => Err(format!("unexpected event: {line}")),
+                None => Ok(()),
+            }
+        });
+    }
+
+    #[test]
+    fn test_reversed_segments() {
+        let db = fixture();
+        let reversed = |route: &str| {
+            db.enrich_route(Field15Parser::parse(route))
+                .into_iter()
+                .map(|segment| segment.is_reversed)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(reversed("N0450F350 ABC UN1 GHI"), [false, false]);
+        assert_eq!(reversed("N0450F350 GHI UN1 ABC DCT 46N003E"), [true, true, false]);
+
+        let route = ResolvedRoute::from_db(&db.airways().routes["rte-un1"], &db);
+        let backward = route.between(&route.segments[1].end, &route.segments[0].start).unwrap();
+        assert!(backward.segments.iter().all(|segment| segment.is_reversed));
+        // Reversing a reversed sub-route restores the original direction
+        let forward = backward
+            .between(&route.segments[0].start, &route.segments[1].end)
+            .unwrap();
+        assert!(forward.segments.iter().all(|segment| !segment.is_reversed));
+
+        let segments = db.enrich_route(Field15Parser::parse("N0450F350 GHI UN1 DEF"));
+        let json = serde_json::to_value(&segments).unwrap();
+        assert_eq!(json[0]["is_reversed"], true);
+        let segments = db.enrich_route(Field15Parser::parse("N0450F350 DEF UN1 GHI"));
+        let json = serde_json::to_value(&segments).unwrap();
+        assert!(json[0].get("is_reversed").is_none());
+    }
+
+    #[test]
+    fn test_distance_by_region() {
+        let point = |latitude, longitude| ResolvedPoint::Coordinates { latitude, longitude };
+        let segment = ResolvedRouteSegment::direct;
+        let route = ResolvedRoute {
+            segments: vec![
+                segment(point(45., -2.), point(45., 4.)),
+                segment(point(45., 4.), point(45., 8.)),
+                segment(point(45., 8.), ResolvedPoint::None),
+            ],
+            name: "test".to_string(),
+        };
+        let rectangle = |west, east| vec![(40., west), (40., east), (50., east), (50., west)];
+        let regions = [
+            ("LFFF".to_string(), rectangle(0., 5.)),
+            ("LSAS".to_string(), rectangle(5., 12.)),
+            ("EGTT".to_string(), rectangle(-10., -5.)),
+        ];
+        let distances = route.distance_by_region(&regions);
+        assert_eq!(distances.len(), 2);
+
+        let model = DistanceModel::Ellipsoidal;
+        let coor = |(latitude, longitude)| point(latitude, longitude).coor().unwrap();
+        let distance = |a, b| model.distance(&coor(a), &coor(b));
+        let lfff = distance((45., 0.), (45., 4.)) + distance((45., 4.), (45., 5.));
+        let lsas = distance((45., 5.), (45., 8.));
+        assert!((distances["LFFF"] - lfff).abs() < 100., "{distances:?}");
+        assert!((distances["LSAS"] - lsas).abs() < 100., "{distances:?}");
+        // Distances inside all regions add up to the part of the route they cover
+        let total = distance((45., -2.), (45., 4.)) + distance((45., 4.), (45., 8.));
+        let outside = distance((45., -2.), (45., 0.));
+        assert!((distances.values().sum::<f64>() - (total - outside)).abs() < 100.);
+    }
+
+    #[test]
+    fn test_check_level_parity() {
+        let mut db = fixture();
+        // Odd levels eastbound (ABC to DEF), even levels westbound
+        let segment = db.airways_mut().route_segments.get_mut("seg-1").unwrap();
+        segment.availability = vec![
+            RouteAvailability {
+                direction: RouteDirection::Forward,
+                level_series: Some(LevelSeries::Odd),
+                ..Default::default()
+            },
+            RouteAvailability {
+                direction: RouteDirection::Backward,
+                level_series: Some(LevelSeries::Even),
+                ..Default::default()
+            },
+        ];
+
+        let eastbound = db.enrich_route(Field15Parser::parse("N0450F340 ABC UN1 GHI"));
+        let violations = db.check_level_parity(&eastbound, &Altitude::FlightLevel(340));
+        assert_eq!(
+            violations,
+            [ParityViolation {
+                index: 0,
+                segment: "seg-1".to_string(),
+                airway: Some("UN1".to_string()),
+                flight_level: 340,
+                required: LevelSeries::Odd,
+            }]
+        );
+        assert!(db
+            .check_level_parity(&eastbound, &Altitude::FlightLevel(350))
+            .is_empty());
+        // Above FL 410, odd levels step by 4000 ft
+        assert!(db
+            .check_level_parity(&eastbound, &Altitude::FlightLevel(450))
+            .is_empty());
+        assert_eq!(db.check_level_parity(&eastbound, &Altitude::FlightLevel(430)).len(), 1);
+
+        let westbound = db.enrich_route(Field15Parser::parse("N0450F340 GHI UN1 ABC"));
+        assert!(db
+            .check_level_parity(&westbound, &Altitude::FlightLevel(340))
+            .is_empty());
+        let violations = db.check_level_parity(&westbound, &Altitude::FlightLevel(350));
+        assert_eq!(violations.len(), 1);
+        assert_eq!((violations[0].index, violations[0].required), (1, LevelSeries::Even));
+        // Metric levels are not checked
+        assert!(db
+            .check_level_parity(&westbound, &Altitude::MetricLevel(1070))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_point_index_equivalence() {
+        let memory = fixture_with_duplicate_ghi();
+        let mut buffer = Vec::new();
+        memory.write_points(&mut buffer).unwrap();
+        let mut mapped = fixture();
+        mapped.points = PointStore::Mapped(PointIndex::from_vec(buffer).unwrap());
+        assert_same_points(&memory, mapped);
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn test_point_archive_equivalence() {
+        let memory = fixture_with_duplicate_ghi();
+        let path = std::env::temp_dir().join(format!("thrust-archive-{}.rkyv", std::process::id()));
+        memory.save_archive(&path).unwrap();
+        let mut archived = fixture();
+        archived.points = PointStore::Archived(PointArchive::open(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert_same_points(&memory, archived);
+
+        assert!(PointArchive::from_vec(b"not an archive".to_vec()).is_err());
+    }
+
+    /// Check that a database with points out of memory resolves points as `memory`.
+    fn assert_same_points(memory: &AirwayDatabase, mut mapped: AirwayDatabase) {
+        let sorted = |points: Vec<ResolvedPoint>| {
+            let mut points = points.iter().map(|p| format!("{p:?}")).collect::<Vec<_>>();
+            points.sort();
+            points
+        };
+        for name in ["ABC", "abc", "GHI", "DEF", "XYZ", ""] {
+            assert_eq!(
+                sorted(ResolvedPoint::lookup(name, memory)),
+                sorted(ResolvedPoint::lookup(name, &mapped)),
+                "{name}"
+            );
+        }
+        assert_eq!(
+            sorted(memory.all_points().collect()),
+            sorted(mapped.all_points().collect())
+        );
+        for reference in [
+            PointReference::Navaid("nav-abc".into()),
+            PointReference::DesignatedPoint("dp-ghi-north".into()),
+            PointReference::DesignatedPoint("dp-missing".into()),
+        ] {
+            assert_eq!(
+                format!("{:?}", ResolvedPoint::from_db(&reference, memory)),
+                format!("{:?}", ResolvedPoint::from_db(&reference, &mapped))
+            );
+        }
+        let route = "N0450F350 ABC UN1 GHI DCT GHJ";
+        let (memory_segments, memory_report) = memory.enrich_route_with_report(Field15Parser::parse(route));
+        let (mapped_segments, mapped_report) = mapped.enrich_route_with_report(Field15Parser::parse(route));
+        assert_eq!(format!("{memory_segments:?}"), format!("{mapped_segments:?}"));
+        assert_eq!(memory_report.unresolved, mapped_report.unresolved);
+        assert_eq!(memory.complete("G", 10), mapped.complete("G", 10));
+        assert_eq!(memory.stats().orphan_points, mapped.stats().orphan_points);
+
+        // Mutating a mapped database copies its points into memory
+        let dp = designated_point("dp-xyz", "XYZ", 46.0, 3.0);
+        mapped
+            .points
+            .designated_points_mut()
+            .insert(dp.identifier.clone().into(), dp);
+        assert!(matches!(mapped.points, PointStore::Memory { .. }));
+        assert_eq!(ResolvedPoint::lookup("XYZ", &mapped).len(), 1);
+        assert_eq!(mapped.all_points().count(), memory.all_points().count() + 1);
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_to_parquet() {
+        use polars::prelude::*;
+
+        let db = fixture();
+        let dir = std::env::temp_dir().join(format!("thrust-parquet-{}", std::process::id()));
+        db.to_parquet(&dir).unwrap();
+        let read = |name: &str| {
+            ParquetReader::new(std::fs::File::open(dir.join(name)).unwrap())
+                .finish()
+                .unwrap()
+        };
+        assert_eq!(read("navaids.parquet").height(), 1);
+        assert_eq!(read("designated_points.parquet").height(), 3);
+        assert_eq!(read("routes.parquet").height(), 1);
+        let segments = read("route_segments.parquet");
+        assert_eq!(segments.height(), 2);
+        assert_eq!(
+            segments.column("start_identifier").unwrap().str().unwrap().get(0),
+            Some("dp-abc")
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_route_from_fixes() {
+        let db = fixture_with_duplicate_ghi();
+        let route = db.route_from_fixes(&["DEF", "ghi", "ABC"]);
+        assert_eq!(route.name, "DEF ghi ABC");
+        assert_eq!(route.segments.len(), 2);
+        assert!(route.segments.iter().all(|s| s.name.is_none()));
+        // The GHI closest to DEF, and the ABC closest to GHI
+        assert_eq!(route.segments[0].end.coor(), Some(Coor2D::geo(45.0, 3.0)));
+        assert_eq!(route.segments[1].start, route.segments[0].end);
+        assert!(matches!(route.segments[1].end, ResolvedPoint::DesignatedPoint(_)));
+
+        // Unknown fixes are skipped
+        let route = db.route_from_fixes(&["DEF", "XYZ", "GHI"]);
+        assert_eq!(route.segments.len(), 1);
+        assert!(db.route_from_fixes(&["DEF"]).segments.is_empty());
+    }
+
+    #[test]
+    fn test_all_points() {
+        let db = fixture();
+        let points = db.all_points().collect::<Vec<_>>();
+        assert_eq!(
+            points.len(),
+            db.points.navaid_count() + db.points.designated_point_count()
+        );
+        let navaids = points
+            .iter()
+            .filter(|point| matches!(point, ResolvedPoint::Navaid(_)))
+            .count();
+        assert_eq!(navaids, db.points.navaid_count());
+        assert!(points.iter().all(|point| point.coor().is_some()));
+    }
+
+    #[test]
+    fn test_route_report() {
+        let mut db = fixture();
+        // UN2 goes DEF - ABC, and GHI - JKL with JKL missing from the database
+        db.airways_mut()
+            .routes
+            .insert("rte-un2".into(), route("rte-un2", Some("U"), "N", "2"));
+        let segments = [("seg-3", "dp-ghi", "dp-jkl"), ("seg-4", "dp-def", "dp-abc")];
+        for (id, start, end) in segments {
+            let segment = route_segment(
+                id,
+                "rte-un2",
+                PointReference::DesignatedPoint(start.into()),
+                PointReference::DesignatedPoint(end.into()),
+            );
+            db.airways_mut()
+                .route_segments
+                .insert(segment.identifier.clone().into(), segment);
+        }
+        db.airways_mut().index_route_segments();
+
+        let report = db.route_report().collect::<Vec<_>>();
+        assert_eq!(report.len(), 2);
+        let (un1, un2) = (&report[0], &report[1]);
+        assert_eq!((un1.designator.as_str(), un1.segments), ("UN1", 2));
+        assert_eq!(un1.unresolved_segments, 0);
+        assert!(un1.continuous);
+        assert!((un1.length - 157_700.).abs() < 500., "{}", un1.length);
+
+        assert_eq!((un2.designator.as_str(), un2.segments), ("UN2", 2));
+        assert_eq!(un2.unresolved_segments, 1);
+        assert!(un2.continuous);
+        assert!((un2.length - 78_850.).abs() < 500., "{}", un2.length);
+
+        // Add a segment which does not connect to the rest of UN2
+        let segment = route_segment(
+            "seg-5",
+            "rte-un2",
+            PointReference::DesignatedPoint("dp-ghi".into()),
+            PointReference::Navaid("nav-abc".into()),
+        );
+        db.airways_mut()
+            .route_segments
+            .insert(segment.identifier.clone().into(), segment);
+        db.airways_mut().index_route_segments();
+        let un2 = db.route_report().nth(1).unwrap();
+        assert_eq!(un2.segments, 3);
+        assert!(!un2.continuous);
+    }
+
+    #[test]
+    fn test_segment_between() {
+        let db = fixture();
+        let abc = PointReference::DesignatedPoint("dp-abc".into());
+        let def = PointReference::DesignatedPoint("dp-def".into());
+        let ghi = PointReference::DesignatedPoint("dp-ghi".into());
+
+        let segment = db.segment_between(&abc, &def).unwrap();
+        assert_eq!(segment.identifier, "seg-1");
+        assert_eq!(segment.route_formed.as_deref(), Some("rte-un1"));
+        let reverse = db.segment_between(&ghi, &def).unwrap();
+        assert_eq!(reverse.identifier, "seg-2");
+        assert!(db.segment_between(&abc, &ghi).is_none());
+        assert!(db
+            .segment_between(&PointReference::Navaid("dp-abc".into()), &def)
+            .is_none());
+    }
+
+    #[test]
+    fn test_route_segments_index() {
+        let db = fixture();
+        assert_eq!(db.airways().routes["rte-un1"].segments, vec!["seg-1", "seg-2"]);
+        let route = ResolvedRoute::from_db(&db.airways().routes["rte-un1"], &db);
+        assert_eq!(route.segments.len(), 2);
+    }
+
+    #[test]
+    fn test_route_lookup_sloppy_designator() {
+        let mut db = fixture();
+        let mut sloppy = Route {
+            identifier: "rte-l2".to_string(),
+            second_letter: Some("L2".to_string()),
+            ..Default::default()
+        };
+        assert!(sloppy.normalize());
+        db.airways_mut().routes.insert(sloppy.identifier.clone().into(), sloppy);
+
+        assert_eq!(ResolvedRoute::lookup("UN1", &db).len(), 1);
+        let routes = ResolvedRoute::lookup("L2", &db);
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].name, "L2");
+
+        // Names which cannot be designators yield no candidate, and never panic
+        for name in ["Ü1", "UÜ1", "U", "L", "", "UN1Ü", "L🛫"] {
+            assert!(ResolvedRoute::lookup(name, &db).is_empty(), "{name:?}");
+        }
+    }
+
+    #[test]
+    fn test_route_lookup_incomplete_designator() {
+        let mut db = fixture();
+        // As read from a record without designatorNumber
+        let mut incomplete = Route {
+            identifier: "rte-un".to_string(),
+            prefix: Some("U".to_string()),
+            second_letter: Some("N".to_string()),
+            ..Default::default()
+        };
+        assert!(!incomplete.normalize());
+        db.airways_mut()
+            .routes
+            .insert(incomplete.identifier.clone().into(), incomplete);
+        let dp = designated_point("dp-jkl", "JKL", 45.0, 4.0);
+        db.points
+            .designated_points_mut()
+            .insert(dp.identifier.clone().into(), dp);
+        db.airways_mut()
+            .routes
+            .insert("rte-um5".into(), route("rte-um5", Some("U"), "M", "5"));
+        for (id, route, start, end) in [
+            ("seg-un", "rte-un", "dp-def", "dp-ghi"),
+            ("seg-9", "rte-um5", "dp-ghi", "dp-jkl"),
+        ] {
+            let segment = route_segment(
+                id,
+                route,
+                PointReference::DesignatedPoint(start.into()),
+                PointReference::DesignatedPoint(end.into()),
+            );
+            db.airways_mut()
+                .route_segments
+                .insert(segment.identifier.clone().into(), segment);
+        }
+        db.airways_mut().index_route_segments();
+
+        for name in ["UN", "N", ""] {
+            assert!(ResolvedRoute::lookup(name, &db).is_empty(), "{name:?}");
+        }
+        let routes = ResolvedRoute::lookup("UN1", &db);
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].segments.len(), 2);
+        let completions = db.complete("U", 10);
+        assert_eq!(
+            completions.iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>(),
+            ["UM5", "UN1"]
+        );
+
+        // Nor is it offered as a substitute for an airway not joining the bracketing points
+        db.set_airway_substitution(true);
+        let route =
+            db.filter_airways_by_adjacent_points(db.resolve_candidates(&Field15Parser::parse("N0450F350 DEF UM5 GHI")));
+        assert_eq!(route.report.substitutions.len(), 1);
+        assert_eq!(route.report.substitutions[0].substitute, "UN1");
+    }
+
+    #[test]
+    fn test_damerau_levenshtein() {
+        assert_eq!(damerau_levenshtein("NATOR", "NATOR"), 0);
+        assert_eq!(damerau_levenshtein("NATTOR", "NATOR"), 1);
+        assert_eq!(damerau_levenshtein("NAOTR", "NATOR"), 1);
+        assert_eq!(damerau_levenshtein("LACOU", "LAKOS"), 2);
+    }
+
+    #[test]
+    fn test_suggest() {
+        let mut db = fixture();
+        for dp in [
+            designated_point("dp-nator", "NATOR", 45.0, 4.0),
+            designated_point("dp-natro", "NATRO", 45.0, 5.0),
+            designated_point("dp-bator", "BATOR", 10.0, 5.0),
+        ] {
+            db.points
+                .designated_points_mut()
+                .insert(dp.identifier.clone().into(), dp);
+        }
+        assert_eq!(db.suggest("NATTOR", None, 3), vec!["NATOR", "BATOR", "NATRO"]);
+        // BATOR is too far from the reference point
+        assert_eq!(db.suggest("NATTOR", Some((45.0, 3.0)), 3), vec!["NATOR", "NATRO"]);
+
+        let (_, report) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 GHI DCT NATTOR"));
+        assert_eq!(report.unresolved.len(), 1);
+        assert_eq!(report.unresolved[0].token, "NATTOR");
+        assert_eq!(report.unresolved[0].index, 3);
+        assert_eq!(
+            report.unresolved[0].suggestions.first().map(String::as_str),
+            Some("NATOR")
+        );
+    }
+
+    #[test]
+    fn test_lookup_navaids_and_designated_points() {
+        let mut db = fixture();
+        assert!(matches!(
+            ResolvedPoint::lookup("ABC", &db).as_slice(),
+            [ResolvedPoint::Navaid(_), ResolvedPoint::DesignatedPoint(_)]
+        ));
+        db.set_enrichment_options(EnrichmentOptions {
+            navaid_priority: true,
+            ..Default::default()
+        });
+        assert!(matches!(
+            ResolvedPoint::lookup("ABC", &db).as_slice(),
+            [ResolvedPoint::Navaid(_)]
+        ));
+    }
+
+    #[test]
+    fn test_designated_point_named_like_distant_navaid() {
+        let mut db = fixture();
+        let kolob = navaid("nav-kolob", "KOLOB", 10.0, -60.0);
+        db.points.navaids_mut().insert(kolob.identifier.clone().into(), kolob);
+        let kolob = designated_point("dp-kolob", "KOLOB", 45.2, 2.5);
+        db.points
+            .designated_points_mut()
+            .insert(kolob.identifier.clone().into(), kolob);
+
+        // The designated point next to the route is chosen over the distant navaid
+        let route = Field15Parser::parse("N0450F350 DEF DCT KOLOB DCT GHI");
+        let segments = db.enrich_route(route.clone());
+        assert_eq!(segments.len(), 2);
+        assert!(matches!(&segments[0].end, ResolvedPoint::DesignatedPoint(dp) if dp.identifier == "dp-kolob"));
+
+        db.set_enrichment_options(EnrichmentOptions {
+            navaid_priority: true,
+            ..Default::default()
+        });
+        let segments = db.enrich_route(route);
+        assert!(matches!(&segments[0].end, ResolvedPoint::Navaid(navaid) if navaid.identifier == "nav-kolob"));
+    }
+
+    #[test]
+    fn test_merge_by_airway() {
+        let mut db = fixture();
+        for (id, designator, longitude) in [("dp-jkl", "JKL", 4.0), ("dp-mno", "MNO", 5.0)] {
+            let dp = designated_point(id, designator, 45.0, longitude);
+            db.points
+                .designated_points_mut()
+                .insert(dp.identifier.clone().into(), dp);
+        }
+        let point = |id: &str| ResolvedPoint::from_db(&PointReference::DesignatedPoint(id.into()), &db);
+        let segment = |start: &str, end: &str, name: Option<&str>| ResolvedRouteSegment {
+            name: name.map(str::to_string),
+            ..ResolvedRouteSegment::direct(point(start), point(end))
+        };
+        let ids = ["dp-abc", "dp-def", "dp-ghi", "dp-jkl", "dp-mno"];
+        let mut segments = ids
+            .windows(2)
+            .map(|pair| segment(pair[0], pair[1], Some("UL607")))
+            .collect::<Vec<_>>();
+        let runs = merge_by_airway(&segments);
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].name.as_deref(), Some("UL607"));
+        assert_eq!(runs[0].waypoints, ids.map(point));
+        assert_eq!(runs[0].segments, 0..4);
+
+        // Direct legs are kept apart, and so are discontinuous segments of an airway
+        segments.push(segment("dp-mno", "dp-abc", None));
+        segments.push(segment("dp-abc", "dp-ghi", None));
+        segments.push(segment("dp-def", "dp-ghi", Some("UL607")));
+        let runs = merge_by_airway(&segments);
+        let summary = runs
+            .iter()
+            .map(|run| (run.name.as_deref(), run.waypoints.len(), run.segments.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                (Some("UL607"), 5, 0..4),
+                (None, 2, 4..5),
+                (None, 2, 5..6),
+                (Some("UL607"), 2, 6..7)
+            ]
+        );
+        assert!(merge_by_airway(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_emit_gaps() {
+        use crate::data::eurocontrol::response::{RouteFootprint, SegmentJson};
+
+        let mut db = fixture();
+        let route = || Field15Parser::parse("N0450F350 DEF DCT XYZ DCT GHI");
+
+        // By default, the unresolved point is bridged by a plain direct segment
+        let (segments, report) = db.enrich_route_with_report(route());
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].name, None);
+        assert!(segments[0].is_complete() && !segments[0].is_gap());
+        assert_eq!(report.gaps.len(), 1);
+        let gap = &report.gaps[0];
+        assert_eq!(
+            (gap.index, &gap.start, &gap.end),
+            (0, &segments[0].start, &segments[0].end)
+        );
+        assert!((gap.distance - 78_800.).abs() < 500., "{}", gap.distance);
+        let direct = RouteFootprint::from_segments(&segments);
+
+        db.set_enrichment_options(EnrichmentOptions {
+            emit_gaps: true,
+            ..Default::default()
+        });
+        let (segments, report) = db.enrich_route_with_report(route());
+        assert_eq!(
+            names(&segments),
+            names(&db.enrich_route(Field15Parser::parse("N0450F350 DEF GHI")))
+        );
+        assert_eq!(segments[0].name.as_deref(), Some(GAP));
+        assert!(segments[0].is_gap() && !segments[0].is_complete());
+        assert_eq!(report.gaps.len(), 1);
+        assert!(SegmentJson::from(&segments[0]).gap);
+        let footprint = RouteFootprint::from_segments(&segments);
+        assert!(footprint.airways.is_empty());
+        assert_eq!(footprint.direct_distance + footprint.airway_distance, 0.);
+        // The gap is no longer a direct segment, but the total distance is the same
+        let total =
+            |footprint: &RouteFootprint| footprint.direct_distance + footprint.airway_distance + footprint.gap_distance;
+        assert_eq!((direct.gap_distance, footprint.direct_distance), (0., 0.));
+        assert_eq!(total(&footprint), total(&direct));
+        assert_eq!(footprint.efficiency, direct.efficiency);
+
+        // An airway starting away from the previous point is bridged as well
+        db.set_enrichment_options(EnrichmentOptions {
+            emit_gaps: true,
+            keep_partial_airways: true,
+            ..Default::default()
+        });
+        let (segments, report) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 46N001E DCT XYZ UN1 GHI"));
+        let kinds = segments.iter().map(|s| s.name.as_deref()).collect::<Vec<_>>();
+        assert_eq!(kinds, [Some(GAP), Some("UN1"), Some("UN1")]);
+        assert_eq!(segments[0].end, segments[1].start);
+        assert_eq!(report.gaps.len(), 1);
+        assert!(
+            (report.gaps[0].distance - 111_000.).abs() < 1_000.,
+            "{}",
+            report.gaps[0].distance
+        );
+
+        let footprint = RouteFootprint::from_segments(&segments);
+        assert!((footprint.gap_distance - report.gaps[0].distance).abs() < 1e-6);
+
+        // A continuous route has no gap
+        let (segments, report) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 DEF DCT GHI"));
+        assert!(report.gaps.is_empty() && segments.iter().all(|s| !s.is_gap()));
+    }
+
+    #[test]
+    fn test_named_fixes() {
+        let db = fixture();
+        let point = |id: &str| ResolvedPoint::from_db(&PointReference::DesignatedPoint(id.into()), &db);
+        let segment = ResolvedRouteSegment::direct;
+        let coordinates = ResolvedPoint::Coordinates {
+            latitude: 46.,
+            longitude: 2.5,
+        };
+        let route = ResolvedRoute {
+            segments: vec![
+                segment(
+                    ResolvedPoint::Navaid(db.points.navaid("nav-abc").unwrap().into_owned()),
+                    point("dp-def"),
+                ),
+                segment(point("dp-def"), coordinates.clone()),
+                segment(coordinates, point("dp-ghi")),
+            ],
+            name: "test".to_string(),
+        };
+        assert_eq!(
+            route.named_fixes(),
+            vec![
+                ("ABC".to_string(), 45., 0.5),
+                ("DEF".to_string(), 45., 2.),
+                ("GHI".to_string(), 45., 3.)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enrich_route_repeated_fix_resolves_identically() {
+        let mut db = fixture();
+        for dp in [
+            designated_point("dp-xyz-1", "XYZ", 45., 2.5),
+            designated_point("dp-xyz-2", "XYZ", 45., 3.4),
+            designated_point("dp-jkl", "JKL", 45., 3.2),
+        ] {
+            db.points
+                .designated_points_mut()
+                .insert(dp.identifier.clone().into(), dp);
+        }
+        let elements = Field15Parser::parse("N0450F350 DEF DCT XYZ DCT GHI/N0450F370 JKL DCT XYZ");
+        assert!(matches!(&elements[3], Field15Element::Point(Point::Waypoint(name)) if name == "XYZ"));
+        assert!(matches!(&elements[9], Field15Element::Point(Point::Waypoint(name)) if name == "XYZ"));
+
+        let (segments, report) = db.enrich_route_with_report(elements);
+        let xyz = segments
+            .iter()
+            .flat_map(|s| [&s.start, &s.end])
+            .filter_map(|p| match p {
+                ResolvedPoint::DesignatedPoint(dp) if dp.designator == "XYZ" => Some(dp.identifier.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        // Both occurrences keep the candidate between DEF and GHI, even though the
+        // other one is closer to JKL
+        assert_eq!(xyz, vec!["dp-xyz-1", "dp-xyz-1", "dp-xyz-1"]);
+        assert!(report.inconsistencies.is_empty());
+    }
+
+    #[test]
+    fn test_enrich_route_airway_substitution() {
+        let mut db = fixture();
+        let jkl = designated_point("dp-jkl", "JKL", 46.0, 2.0);
+        db.points
+            .designated_points_mut()
+            .insert(jkl.identifier.clone().into(), jkl);
+        db.airways_mut()
+            .routes
+            .insert("rte-um2".into(), route("rte-um2", Some("U"), "M", "2"));
+        let segment = route_segment(
+            "seg-3",
+            "rte-um2",
+            PointReference::DesignatedPoint("dp-def".into()),
+            PointReference::DesignatedPoint("dp-jkl".into()),
+        );
+        db.airways_mut()
+            .route_segments
+            .insert(segment.identifier.clone().into(), segment);
+        db.airways_mut().index_route_segments();
+
+        // UM2 does not reach GHI: fall back to a direct leg by default...
+        let route = "N0450F350 DEF UM2 GHI";
+        let (segments, report) = db.enrich_route_with_report(Field15Parser::parse(route));
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].name, None);
+        assert!(report.substitutions.is_empty());
+
+        // ... or follow UN1 which connects DEF and GHI
+        db.set_airway_substitution(true);
+        let (segments, report) = db.enrich_route_with_report(Field15Parser::parse(route));
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].name.as_deref(), Some("UN1"));
+        assert!(names(&segments)[0].1.starts_with("DesignatedPoint(GHI"));
+        assert_eq!(
+            report.substitutions,
+            vec![AirwaySubstitution {
+                index: 2,
+                filed: "UM2".to_string(),
+                substitute: "UN1".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_partial_airway() {
+        let mut db = fixture();
+        // UN1 is extended east to JKL, further from DEF than ABC
+        let dp = designated_point("dp-jkl", "JKL", 45.0, 5.0);
+        db.points
+            .designated_points_mut()
+            .insert(dp.identifier.clone().into(), dp);
+        let segment = route_segment(
+            "seg-3",
+            "rte-un1",
+            PointReference::DesignatedPoint("dp-ghi".into()),
+            PointReference::DesignatedPoint("dp-jkl".into()),
+        );
+        db.airways_mut()
+            .route_segments
+            .insert(segment.identifier.clone().into(), segment);
+        db.airways_mut().index_route_segments();
+        let designators = |segments: &[ResolvedRouteSegment]| {
+            segments
+                .iter()
+                .filter(|s| s.name.as_deref() == Some("UN1"))
+                .map(|s| match (&s.start, &s.end) {
+                    (ResolvedPoint::DesignatedPoint(a), ResolvedPoint::DesignatedPoint(b)) => {
+                        format!("{}-{}", a.designator, b.designator)
+                    }
+                    _ => panic!("{s:?}"),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // By default, the airway is flown direct
+        let route = Field15Parser::parse("N0450F350 ABC UN1 XYZ DCT 46N006E");
+        let (segments, report) = db.enrich_route_with_report(route.clone());
+        assert!(designators(&segments).is_empty());
+        assert!(report.partial_airways.is_empty());
+
+        db.set_enrichment_options(EnrichmentOptions {
+            keep_partial_airways: true,
+            ..Default::default()
+        });
+        // Only the entry fix is known: the airway is kept from that fix onward
+        let (segments, report) = db.enrich_route_with_report(route);
+        assert_eq!(designators(&segments), ["ABC-DEF", "DEF-GHI", "GHI-JKL"]);
+        assert_eq!(segments.len(), 4);
+        assert_eq!(report.unresolved.len(), 1);
+        assert_eq!(
+            report.partial_airways,
+            [PartialAirway {
+                index: 2,
+                airway: "UN1".to_string(),
+                entry: true
+            }]
+        );
+
+        // From the middle of the airway, towards the nearer end
+        let (segments, _) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 DEF UN1 XYZ"));
+        assert_eq!(designators(&segments), ["DEF-ABC"]);
+
+        // Only the exit fix is known: the airway is kept from the nearer end to that fix
+        let (segments, report) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 XYZ UN1 GHI"));
+        assert_eq!(designators(&segments), ["JKL-GHI"]);
+        assert!(!report.partial_airways[0].entry);
+    }
+
+    #[test]
+    fn test_dangling_segment_is_skipped() {
+        let mut db = fixture();
+        // A segment of UN1 towards a point withdrawn from the database
+        let segment = route_segment(
+            "seg-3",
+            "rte-un1",
+            PointReference::DesignatedPoint("dp-ghi".into()),
+            PointReference::DesignatedPoint("dp-withdrawn".into()),
+        );
+        db.airways_mut()
+            .route_segments
+            .insert(segment.identifier.clone().into(), segment);
+        db.airways_mut().index_route_segments();
+        assert_eq!(db.stats().dangling_segments, 1);
+
+        let route = ResolvedRoute::from_db(&db.airways().routes["rte-un1"], &db);
+        assert_eq!(route.segments.len(), 3);
+        assert_eq!(route.segments.iter().filter(|s| !s.is_complete()).count(), 1);
+        assert!(!route.contains(&ResolvedPoint::None));
+        assert!(route.between(&ResolvedPoint::None, &route.segments[0].start).is_none());
+
+        let (segments, report) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 ABC UN1 GHI"));
+        assert_eq!(segments.len(), 2);
+        assert!(segments.iter().all(|s| s.is_complete()));
+
+        // Without a point to trim to, the airway is followed to its end
+        let (segments, report_end) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 ABC UN1 46N004E"));
+        assert!(segments.iter().all(|s| s.is_complete()));
+        assert!(segments
+            .iter()
+            .flat_map(|s| [&s.start, &s.end])
+            .all(|p| p.coor().is_some()));
+        assert_eq!(report.broken_segments, 0);
+        assert_eq!(report_end.broken_segments, 1);
+    }
+
+    #[test]
+    fn test_boundary_airway_repaired() {
+        let db = fixture();
+        let starts_with = |point: &ResolvedPoint, name: &str| point.to_string().starts_with(name);
+
+        // A leading airway keeps the segment towards its fix, away from the rest of the route
+        let (segments, report) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 UN1 DEF DCT 46N004E"));
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].name.as_deref(), Some("UN1"));
+        assert!(starts_with(&segments[0].start, "DesignatedPoint(ABC"));
+        assert!(starts_with(&segments[0].end, "DesignatedPoint(DEF"));
+        assert_eq!(
+            report.boundary_airways,
+            [BoundaryAirway {
+                index: 1,
+                airway: "UN1".to_string(),
+                repaired: true
+            }]
+        );
+
+        // A trailing airway keeps the segment leaving its fix
+        let (segments, report) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 46N000E DCT DEF UN1"));
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1].name.as_deref(), Some("UN1"));
+        assert!(starts_with(&segments[1].start, "DesignatedPoint(DEF"));
+        assert!(starts_with(&segments[1].end, "DesignatedPoint(GHI"));
+        assert_eq!(report.boundary_airways.len(), 1);
+        assert_eq!(report.boundary_airways[0].index, 4);
+        assert!(report.boundary_airways[0].repaired);
+
+        // Airways between two points are left alone
+        let (_, report) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 ABC UN1 GHI"));
+        assert!(report.boundary_airways.is_empty());
+    }
+
+    #[test]
+    fn test_boundary_airway_unrepairable() {
+        let db = fixture();
+        // The adjacent point is not on the airway: fly direct rather than along the whole airway
+        let (segments, report) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 UN1 46N004E DCT GHI"));
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].name, None);
+        assert!(segments[0].end.to_string().starts_with("DesignatedPoint(GHI"));
+        assert_eq!(
+            report.boundary_airways,
+            [BoundaryAirway {
+                index: 1,
+                airway: "UN1".to_string(),
+                repaired: false
+            }]
+        );
+
+        // A lone airway
+        let (segments, report) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 UN1"));
+        assert!(segments.is_empty());
+        assert!(!report.boundary_airways[0].repaired);
+    }
+
+    #[test]
+    fn test_enrich_batch() {
+        let db = fixture();
+        let batch = vec![
+            (42, "N0450F350 ABC UN1 GHI".to_string()),
+            (7, "N0450F350 XYZ DCT QRS".to_string()),
+            (13, "N0450F350 DEF DCT GHI".to_string()),
+            (1, "".to_string()),
+        ];
+        let results = db.enrich_batch(batch);
+        assert_eq!(results.iter().map(|(k, _)| *k).collect::<Vec<_>>(), vec![42, 7, 13, 1]);
+        assert_eq!(results[0].1.as_ref().unwrap().0.len(), 2);
+        match &results[1].1 {
+            Err(EnrichmentError::Unresolved(tokens)) => {
+                assert_eq!(
+                    tokens.iter().map(|t| t.token.as_str()).collect::<Vec<_>>(),
+                    ["XYZ", "QRS"]
+                );
+            }
+            other => panic!("unexpected result {other:?}"),
+        }
+        let (segments, report) = results[2].1.as_ref().unwrap();
+        assert_eq!(names(segments)[0].0, "DesignatedPoint(DEF: 45.000, 2.000)");
+        assert!(report.unresolved.is_empty());
+        assert!(matches!(results[3].1, Err(EnrichmentError::Empty)));
+    }
+
+    #[test]
+    fn test_lookup_prefers_icao_designated_points() {
+        let mut db = fixture();
+        db.points.designated_points_mut().get_mut("dp-ghi").unwrap().r#type = DesignatedPointType::Icao;
+        // A generated point colliding with GHI, closer to the previous fix
+        let mut coord = designated_point("dp-ghi-coord", "GHI", 45.0, 2.2);
+        coord.r#type = DesignatedPointType::Coord;
+        db.points
+            .designated_points_mut()
+            .insert(coord.identifier.clone().into(), coord);
+        let route = "N0450F350 ABC DCT DEF DCT GHI";
+
+        assert_eq!(ResolvedPoint::lookup("GHI", &db).len(), 1);
+        let segments = db.enrich_route(Field15Parser::parse(route));
+        assert!(matches!(&segments[1].end, ResolvedPoint::DesignatedPoint(dp) if dp.identifier == "dp-ghi"));
+
+        // Without preference, the COORD duplicate wins the geometric tie-break
+        db.set_point_type_preference(vec![]);
+        assert_eq!(ResolvedPoint::lookup("GHI", &db).len(), 2);
+        let segments = db.enrich_route(Field15Parser::parse(route));
+        assert!(matches!(&segments[1].end, ResolvedPoint::DesignatedPoint(dp) if dp.identifier == "dp-ghi-coord"));
+    }
+
+    #[test]
+    fn test_nav_log() {
+        let mut vor = navaid("nav-tou", "TOU", 45.0, 1.5);
+        vor.r#type = "VOR_DME".to_string();
+        vor.frequency = Some(117.7);
+        vor.call_sign = Some("TBN".to_string());
+        let segment = ResolvedRouteSegment::direct;
+        let route = ResolvedRoute {
+            segments: vec![
+                segment(
+                    ResolvedPoint::DesignatedPoint(designated_point("dp-abc", "ABC", 45.0, 1.0)),
+                    ResolvedPoint::Navaid(vor.clone()),
+                ),
+                segment(
+                    ResolvedPoint::Navaid(vor),
+                    ResolvedPoint::DesignatedPoint(designated_point("dp-def", "DEF", 45.0, 2.0)),
+                ),
+            ],
+            name: "UN1".to_string(),
+        };
+        let log = route.nav_log();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].name, "TOU");
+        assert_eq!(log[0].frequency, Some(117.7));
+        assert_eq!(log[0].call_sign.as_deref(), Some("TBN"));
+        // Heading east, about 39.4 km along the parallel at 45°N
+        assert!((log[0].bearing.unwrap() - 90.).abs() < 0.5);
+        assert!((log[0].distance.unwrap() - 39_400.).abs() < 200.);
+    }
+
+    fn airport(identifier: &str, icao: &str, latitude: f64, longitude: f64) -> AirportHeliport {
+        AirportHeliport {
+            identifier: identifier.to_string(),
+            icao: icao.to_string(),
+            latitude,
+            longitude,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_enrich_route_mid_route_airport() {
+        let mut db = fixture_with_duplicate_ghi();
+        let lfxx = airport("ah-lfxx", "LFXX", 49.5, 3.0);
+        db.airports.insert(lfxx.identifier.clone().into(), lfxx);
+        assert!(matches!(
+            ResolvedPoint::lookup("lfxx", &db).as_slice(),
+            [ResolvedPoint::AirportHeliport(airport)] if airport.icao == "LFXX"
+        ));
+        // Fixes take precedence over airports
+        assert!(matches!(
+            ResolvedPoint::lookup("ABC", &db).as_slice(),
+            [ResolvedPoint::Navaid(_), ResolvedPoint::DesignatedPoint(_)]
+        ));
+
+        // A refuelling stop is a point of the route, and anchors the choice of GHI
+        let (segments, report) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 ABC DCT LFXX DCT GHI"));
+        assert!(report.unresolved.is_empty());
+        assert_eq!(segments.len(), 2);
+        assert!(matches!(&segments[0].end, ResolvedPoint::AirportHeliport(airport) if airport.icao == "LFXX"));
+        assert_eq!(segments[1].end.coor().map(|c| c.to_degrees()[1]), Some(50.0));
+    }
+
+    #[test]
+    fn test_enrich_route_zzzz() {
+        let mut db = fixture();
+        let zzzz = airport("ah-zzzz", "ZZZZ", 45.0, 1.5);
+        db.airports.insert(zzzz.identifier.clone().into(), zzzz);
+        assert!(ResolvedPoint::lookup("ZZZZ", &db).is_empty());
+
+        let (segments, report) = db.enrich_route_with_report(Field15Parser::parse("N0450F350 ABC DCT ZZZZ DCT GHI"));
+        assert_eq!(segments.len(), 1);
+        assert_eq!(report.unresolved.len(), 1);
+        let token = &report.unresolved[0];
+        assert_eq!((token.index, token.token.as_str()), (3, "ZZZZ"));
+        assert!(token.suggestions.is_empty());
+        assert!(token.hint.as_deref().is_some_and(|hint| hint.contains("field 18")));
+    }
+
+    #[test]
+    fn test_bearing_distance_is_magnetic() {
+        let mut db = fixture();
+        // ICAO Doc 4444, Appendix 2: DUB180040 is 40 NM on the 180° magnetic radial of VOR DUB
+        let dub = Navaid {
+            magnetic_variation: Some(-3.),
+            ..navaid("nav-dub", "DUB", 53.5, -6.3)
+        };
+        db.points.navaids_mut().insert("nav-dub".into(), dub);
+        let elements = Field15Parser::parse("N0450F350 DUB180040");
+        let Some(Field15Element::Point(point)) = elements.last() else {
+            panic!("{elements:?}");
+        };
+        let resolved = db.resolve_bearing_distance(point, None).unwrap();
+        let (bearing, distance) =
+            DistanceModel::Ellipsoidal.inverse(&Coor2D::geo(53.5, -6.3), &resolved.coor().unwrap());
+        // With a variation of 3°W, the true bearing is 177°
+        assert!((bearing.rem_euclid(360.) - 177.).abs() < 1e-6, "{bearing}");
+        assert!((distance - 40. * NAUTICAL_MILE).abs() < 1., "{distance}");
+        assert_eq!(describe_relative(&resolved, &db).as_deref(), Some("DUB180040"));
+
+        // Without a published variation, the bearing cannot be converted
+        db.points.navaids_mut().get_mut("nav-dub").unwrap().magnetic_variation = None;
+        assert!(db.resolve_bearing_distance(point, None).is_none());
+        assert_eq!(describe_relative(&resolved, &db), None);
+    }
+
+    #[test]
+    fn test_describe_relative_round_trip() {
+        let mut db = fixture();
+        // Bearings are magnetic: only navaids with a magnetic variation are used as fixes
+        let navaids = db.points.navaids_mut();
+        navaids.get_mut("nav-abc").unwrap().magnetic_variation = Some(1.);
+        for (identifier, name, longitude, variation) in [("nav-def", "DEF", 2., 2.), ("nav-ghi", "GHI", 3., -1.5)] {
+            let navaid = Navaid {
+                magnetic_variation: Some(variation),
+                ..navaid(identifier, name, 45., longitude)
+            };
+            navaids.insert(identifier.into(), navaid);
+        }
+        let coordinates = |latitude, longitude| ResolvedPoint::Coordinates { latitude, longitude };
+        for (latitude, longitude) in [(45.3, 2.2), (44.6, 3.5), (45.05, 1.9), (44.7, 1.6), (45.4, 0.8)] {
+            let point = coordinates(latitude, longitude);
+            let description = describe_relative(&point, &db).unwrap();
+            let elements = Field15Parser::parse(&format!("N0450F350 {description}"));
+            let Some(Field15Element::Point(parsed)) = elements.last() else {
+                panic!("{description}: {elements:?}");
+            };
+            let resolved = db.resolve_bearing_distance(parsed, Some(&point)).unwrap();
+            let distance = DistanceModel::Ellipsoidal.distance(&point.coor().unwrap(), &resolved.coor().unwrap());
+            assert!(distance < NAUTICAL_MILE, "{description}: {distance} m");
+        }
+
+        let description = describe_relative(&coordinates(45.3, 2.2), &db).unwrap();
+        assert!(
+            description.starts_with("DEF0") && description.len() == 9,
+            "{description}"
+        );
+        assert_eq!(
+            describe_relative(&coordinates(45.0, 2.001), &db).as_deref(),
+            Some("DEF")
+        );
+        assert_eq!(describe_relative(&coordinates(50.0, 2.0), &db), None);
+        assert_eq!(describe_relative_within(&coordinates(45.3, 2.2), &db, 10.), None);
+        let named = ResolvedPoint::from_db(&PointReference::DesignatedPoint("dp-def".into()), &db);
+        assert_eq!(describe_relative(&named, &db), None);
+
+        // Bearing/distance points are resolved in enrichment, or reported if their base is unknown
+        let route = format!("N0450F350 ABC DCT {description} DCT GHI");
+        let segments = db.enrich_route(Field15Parser::parse(&route));
+        assert_eq!(segments.len(), 2);
+        let end = segments[0].end.coor().unwrap();
+        assert!(DistanceModel::Ellipsoidal.distance(&end, &Coor2D::geo(45.3, 2.2)) < NAUTICAL_MILE);
+        let (segments, report) =
+            db.enrich_route_with_report(Field15Parser::parse("N0450F350 ABC DCT XYZ090010 DCT GHI"));
+        assert_eq!(segments.len(), 1);
+        assert_eq!(report.unresolved.len(), 1);
+        assert_eq!(report.unresolved[0].token, "XYZ090010");
+    }
+
+    #[test]
+    fn test_inline_coordinates_endpoint() {
+        let mut db = fixture();
+        let end = PointReference::Coordinates(45.0, 4.0);
+        assert!(matches!(
+            ResolvedPoint::from_db(&end, &db),
+            ResolvedPoint::Coordinates { latitude, longitude } if (latitude, longitude) == (45.0, 4.0)
+        ));
+        let segment = route_segment(
+            "seg-3",
+            "rte-un1",
+            PointReference::DesignatedPoint("dp-ghi".into()),
+            end,
+        );
+        let airways = db.airways_mut();
+        airways.route_segments.insert("seg-3".into(), segment);
+        airways.index_route_segments();
+
+        let route = ResolvedRoute::lookup("UN1", &db).remove(0);
+        assert_eq!(route.segments.len(), 3);
+        assert!(route.segments.iter().all(|s| s.is_complete()));
+        assert!(route.contains(&ResolvedPoint::Coordinates {
+            latitude: 45.0,
+            longitude: 4.0
+        }));
+        assert_eq!(db.stats().dangling_segments, 0);
+    }
+
+    #[test]
+    fn test_revisited_points() {
+        let db = fixture();
+        let point = |id: &str| ResolvedPoint::from_db(&PointReference::DesignatedPoint(id.into()), &db);
+        let segment = ResolvedRouteSegment::direct;
+        // ABC DEF GHI DEF ABC: the route loops back through DEF to ABC
+        let route = ResolvedRoute {
+            segments: vec![
+                segment(point("dp-abc"), point("dp-def")),
+                segment(point("dp-def"), point("dp-ghi")),
+                segment(point("dp-ghi"), point("dp-def")),
+                segment(point("dp-def"), point("dp-abc")),
+            ],
+            name: "loop".to_string(),
+        };
+        assert_eq!(route.revisited_points(), vec![point("dp-abc"), point("dp-def")]);
+
+        // Shared endpoints of consecutive segments are not revisits
+        let route = ResolvedRoute::lookup("UN1", &db).remove(0);
+        assert!(route.revisited_points().is_empty());
+    }
+
+    #[test]
+    fn test_fix_progress() {
+        let db = fixture();
+        let point = |id: &str| ResolvedPoint::from_db(&PointReference::DesignatedPoint(id.into()), &db);
+        let segment = ResolvedRouteSegment::direct;
+        let end = ResolvedPoint::Coordinates {
+            latitude: 45.0,
+            longitude: 4.0,
+        };
+        // Three legs of one degree of longitude along the same parallel
+        let route = ResolvedRoute {
+            segments: vec![
+                segment(point("dp-abc"), point("dp-def")),
+                segment(point("dp-def"), point("dp-ghi")),
+                segment(point("dp-ghi"), end.clone()),
+            ],
+            name: "test".to_string(),
+        };
+        let progress = |p: &ResolvedPoint| route.fix_progress(p).unwrap();
+        assert_eq!(progress(&point("dp-abc")), 0.);
+        assert!((progress(&point("dp-def")) - 1. / 3.).abs() < 1e-3);
+        assert!((progress(&point("dp-ghi")) - 2. / 3.).abs() < 1e-3);
+        assert_eq!(progress(&end), 1.);
+        let elsewhere = ResolvedPoint::Navaid(db.points.navaid("nav-abc").unwrap().into_owned());
+        assert_eq!(route.fix_progress(&elsewhere), None);
+    }
+
+    #[test]
+    fn test_closest_point() {
+        let db = fixture();
+        let route = ResolvedRoute::lookup("UN1", &db).remove(0);
+        let index = |name: &str| {
+            route
+                .segments
+                .iter()
+                .position(|s| format!("{}", s.start).contains(name))
+                .unwrap()
+        };
+
+        // North of the DEF-GHI leg: the foot lies on the leg, slightly north of
+        // the parallel since the geodesic bulges towards the pole
+        let (lat, lon, i) = route.closest_point(45.2, 2.5).unwrap();
+        assert_eq!(i, index("DEF"));
+        assert!((lon - 2.5).abs() < 1e-3, "{lon}");
+        assert!(lat > 45. && lat < 45.01, "{lat}");
+        let foot = Coor2D::geo(lat, lon);
+        let (bearing, _) = DistanceModel::Ellipsoidal.inverse(&foot, &Coor2D::geo(45.2, 2.5));
+        assert!(bearing.abs() < 0.1, "{bearing}");
+
+        // Beyond the end of the route, the foot is clamped to the last fix
+        let (lat, lon, i) = route.closest_point(45.0, 4.0).unwrap();
+        assert_eq!(i, index("DEF"));
+        assert!((lat - 45.).abs() < 1e-9 && (lon - 3.).abs() < 1e-9);
+
+        // Further away, the perpendicular departs from the meridian since the
+        // course along the geodesic is not exactly east
+        let (_, lon, i) = route.closest_point(44.5, 1.25).unwrap();
+        assert_eq!(i, index("ABC"));
+        assert!((lon - 1.25).abs() < 1e-2, "{lon}");
+
+        assert!(ResolvedRoute {
+            segments: vec![],
+            name: "UN1".to_string()
+        }
+        .closest_point(45., 2.)
+        .is_none());
+    }
+
+    #[test]
+    fn test_distance_to_go() {
+        let db = fixture();
+        let route = ResolvedRoute::lookup("UN1", &db).remove(0);
+        let total = route.distance_to_go_nm(45.0, 0.0).unwrap();
+        // Two legs of about 78.8 km (42.5 NM) each along the parallel
+        assert!((total - 85.1).abs() < 0.5, "{total}");
+
+        let half = route.distance_to_go_nm(45.0, 2.0).unwrap();
+        assert!((half - total / 2.).abs() < 1e-6, "{half} {total}");
+        // Geodesics are not parallel to the 45th parallel, so the foot of the
+        // perpendicular from further north is slightly past DEF
+        let abeam = route.distance_to_go_nm(45.3, 2.0).unwrap();
+        assert!((abeam - total / 2.).abs() < 0.2, "{abeam} {total}");
+        assert!(route.distance_to_go_nm(45.0, 5.0).unwrap().abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_enrich_route_merge_split_airway() {
+        let mut db = fixture();
+        let jkl = designated_point("dp-jkl", "JKL", 45.0, 4.0);
+        db.points
+            .designated_points_mut()
+            .insert(jkl.identifier.clone().into(), jkl);
+        // UN2 is published as two records, ABC-DEF-GHI and DEF-GHI-JKL
+        for id in ["rte-un2-a", "rte-un2-b"] {
+            db.airways_mut()
+                .routes
+                .insert(id.into(), route(id, Some("U"), "N", "2"));
+        }
+        let dp = |id: &str| PointReference::DesignatedPoint(id.into());
+        for (id, record, start, end) in [
+            ("seg-a1", "rte-un2-a", "dp-abc", "dp-def"),
+            ("seg-a2", "rte-un2-a", "dp-def", "dp-ghi"),
+            ("seg-b1", "rte-un2-b", "dp-def", "dp-ghi"),
+            ("seg-b2", "rte-un2-b", "dp-ghi", "dp-jkl"),
+        ] {
+            let segment = route_segment(id, record, dp(start), dp(end));
+            db.airways_mut()
+                .route_segments
+                .insert(segment.identifier.clone().into(), segment);
+        }
+        db.airways_mut().index_route_segments();
+        let route = "N0450F350 ABC UN2 JKL";
+
+        // Neither record covers ABC to JKL: fall back to a direct leg
+        let segments = db.enrich_route(Field15Parser::parse(route));
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].name, None);
+
+        db.set_enrichment_options(EnrichmentOptions {
+            merge_split_airways: true,
+            ..Default::default()
+        });
+        let segments = db.enrich_route(Field15Parser::parse(route));
+        assert_eq!(segments.len(), 3);
+        assert!(segments.iter().all(|s| s.name.as_deref() == Some("UN2")));
+        let ends = segments
+            .iter()
+            .map(|s| match &s.end {
+                ResolvedPoint::DesignatedPoint(dp) => dp.designator.as_str(),
+                _ => "",
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(ends, ["DEF", "GHI", "JKL"]);
+
+        // The segment listed in both records is only kept once
+        let merged = ResolvedRoute::merge(ResolvedRoute::lookup("UN2", &db));
+        assert_eq!(merged.segments.len(), 3);
+    }
+
+    #[test]
+    fn test_partition_direct_vs_airway() {
+        let db = fixture();
+        let route = ResolvedRoute {
+            segments: db.enrich_route(Field15Parser::parse("N0450F350 DEF UN1 GHI DCT ABC")),
+            name: String::new(),
+        };
+        let (direct, airway) = route.partition_direct_vs_airway();
+        assert_eq!(airway.len(), 1);
+        assert_eq!(airway[0].name.as_deref(), Some("UN1"));
+        assert!(names(&airway)[0].1.starts_with("DesignatedPoint(GHI"));
+        assert_eq!(direct.len(), 1);
+        assert!(names(&direct)[0].0.starts_with("DesignatedPoint(GHI"));
+    }
+
+    #[test]
+    fn test_enrich_route_collocated_exit_fix() {
+        let mut db = fixture();
+        // A navaid a couple of meters away from GHI, the exit fix of UN1
+        let navaid = navaid("nav-ghv", "GHV", 45.00002, 3.0);
+        db.points.navaids_mut().insert(navaid.identifier.clone().into(), navaid);
+        let segments = db.enrich_route(Field15Parser::parse("N0450F350 ABC UN1 GHI DCT GHV"));
+        assert_eq!(segments.len(), 2);
+        assert!(segments.iter().all(|s| s.name.as_deref() == Some("UN1")));
+
+        // Further points are connected from the airway exit fix
+        let segments = db.enrich_route(Field15Parser::parse("N0450F350 ABC UN1 GHI DCT GHV DCT DEF"));
+        assert_eq!(segments.len(), 3);
+        assert!(names(&segments)[2].0.starts_with("DesignatedPoint(GHI"));
+    }
+
+    #[test]
+    fn test_enrich_route_collocated_fix_on_airway() {
+        let db = fixture();
+        let segments = db.enrich_route(Field15Parser::parse("N0450F350 ABC UN1 GHI"));
+        assert_eq!(segments.len(), 2);
+        assert!(matches!(&segments[0].start, ResolvedPoint::DesignatedPoint(dp) if dp.identifier == "dp-abc"));
+        assert!(segments.iter().all(|s| s.name.as_deref() == Some("UN1")));
+        let names = names(&segments);
+        assert!(names[1].1.starts_with("DesignatedPoint(GHI"));
+    }
+
+    #[test]
+    fn test_procedure_leg_rows() {
+        use crate::data::eurocontrol::response::procedure_leg_rows;
+
+        let mut db = fixture();
+        let lfxx = airport("ah-lfxx", "LFXX", 45.0, 0.0);
+        db.airports.insert(lfxx.identifier.clone().into(), lfxx);
+        let sid = StandardInstrumentDeparture {
+            identifier: "sid-abc1a".to_string(),
+            designator: "ABC1A".to_string(),
+            airport_heliport: Some("ah-lfxx".to_string()),
+            ..Default::default()
+        };
+        db.standard_instrument_departures
+            .insert(sid.identifier.clone().into(), sid);
+        let legs = [
+            (
+                "leg-3",
+                PointReference::DesignatedPoint("dp-def".into()),
+                PointReference::DesignatedPoint("dp-ghi".into()),
+            ),
+            (
+                "leg-1",
+                PointReference::AirportHeliport("ah-lfxx".into()),
+                PointReference::Navaid("nav-abc".into()),
+            ),
+            (
+                "leg-2",
+                PointReference::Navaid("nav-abc".into()),
+                PointReference::DesignatedPoint("dp-def".into()),
+            ),
+        ];
+        for (identifier, start, end) in legs {
+            let leg = DepartureLeg {
+                identifier: identifier.to_string(),
+                departure: Some("sid-abc1a".to_string()),
+                start,
+                end,
+                ..Default::default()
+            };
+            db.departure_legs.insert(leg.identifier.clone().into(), leg);
+        }
+
+        let rows = procedure_leg_rows(&db.sid_procedures());
+        assert_eq!(rows.len(), 3);
+        assert!(rows
+            .iter()
+            .all(|r| r.procedure == "ABC1A" && r.airport.as_deref() == Some("LFXX")));
+        assert_eq!(rows.iter().map(|r| r.leg_index).collect::<Vec<_>>(), [0, 1, 2]);
+        let points = rows
+            .iter()
+            .map(|r| {
+                (
+                    r.start.as_deref(),
+                    r.start_kind.as_str(),
+                    r.end.as_deref(),
+                    r.end_kind.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            points,
+            [
+                (Some("LFXX"), "AirportHeliport", Some("ABC"), "Navaid"),
+                (Some("ABC"), "Navaid", Some("DEF"), "DesignatedPoint"),
+                (Some("DEF"), "DesignatedPoint", Some("GHI"), "DesignatedPoint"),
+            ]
+        );
+        assert_eq!((rows[0].start_latitude, rows[0].start_longitude), (45.0, 0.0));
+        assert_eq!((rows[2].end_latitude, rows[2].end_longitude), (45.0, 3.0));
+    }
+
+    #[test]
+    fn test_fix_table() {
+        let mut db = fixture();
+        // About 200 m north of the ABC navaid, and a far away namesake
+        let abc = designated_point("dp-abc-vor", " abc", 45.0018, 0.5);
+        db.points
+            .designated_points_mut()
+            .insert(abc.identifier.clone().into(), abc);
+        let lfbo = airport("ahp-lfbo", "LFBO", 43.6, 1.4);
+        db.airports.insert(lfbo.identifier.clone().into(), lfbo);
+
+        let fixes = db.fix_table(NAUTICAL_MILE);
+        let summary = fixes
+            .iter()
+            .map(|fix| {
+                (
+                    fix.designator.as_str(),
+                    fix.kind.as_str(),
+                    fix.identifier.as_str(),
+                    fix.sources.len(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("ABC", "Navaid", "nav-abc", 2),
+                ("ABC", "DesignatedPoint", "dp-abc", 1),
+                ("DEF", "DesignatedPoint", "dp-def", 1),
+                ("GHI", "DesignatedPoint", "dp-ghi", 1),
+                ("LFBO", "AirportHeliport", "ahp-lfbo", 1),
+            ]
+        );
+        assert_eq!((fixes[0].latitude, fixes[0].longitude), (45.0, 0.5));
+        assert_eq!(
+            fixes[0].sources,
+            [
+                FixSource::new("Navaid", "nav-abc"),
+                FixSource::new("DesignatedPoint", "dp-abc-vor"),
+            ]
+        );
+
+        // Records 200 m apart are kept apart by a smaller radius
+        let fixes = db.fix_table(100.);
+        assert_eq!(fixes.len(), 6);
+        assert!(fixes.iter().all(|fix| fix.sources.len() == 1));
+    }
+
+    #[test]
+    fn test_check_coordinate_swaps() {
+        let swapped_fixture = || {
+            let mut db = fixture();
+            let swp = designated_point("dp-swp", "SWP", 2.5, 45.0);
+            db.points
+                .designated_points_mut()
+                .insert(swp.identifier.clone().into(), swp);
+            let segment = route_segment(
+                "seg-3",
+                "rte-un1",
+                PointReference::DesignatedPoint("dp-def".into()),
+                PointReference::DesignatedPoint("dp-swp".into()),
+            );
+            db.airways_mut()
+                .route_segments
+                .insert(segment.identifier.clone().into(), segment);
+            db.airways_mut().index_route_segments();
+            db
+        };
+
+        let mut db = swapped_fixture();
+        let report = db.check_coordinate_swaps(&CoordinateSwapOptions::default());
+        assert_eq!(
+            report.points,
+            [SuspectedSwap {
+                identifier: "dp-swp".to_string(),
+                name: Some("SWP".to_string()),
+                latitude: 2.5,
+                longitude: 45.0,
+                fixed: false,
+            }]
+        );
+        assert_eq!(report.segments.len(), 1);
+        assert_eq!(report.segments[0].identifier, "seg-3");
+        assert_eq!(report.segments[0].end.as_deref(), Some("dp-swp"));
+        assert!(report.segments[0].length > 3_000_000.);
+        assert_eq!(db.points.designated_point("dp-swp").unwrap().latitude, 2.5);
+
+        let mut db = swapped_fixture();
+        let options = CoordinateSwapOptions {
+            fix_suspected_swaps: true,
+            ..Default::default()
+        };
+        let report = db.check_coordinate_swaps(&options);
+        assert_eq!(report.points.len(), 1);
+        assert!(report.points[0].fixed);
+        assert!(report.segments.is_empty());
+        let swp = db.points.designated_point("dp-swp").unwrap();
+        assert_eq!((swp.latitude, swp.longitude), (45.0, 2.5));
+
+        // Once fixed, nothing is left to report
+        assert_eq!(db.check_coordinate_swaps(&options), CoordinateSwapReport::default());
+    }
+
+    #[test]
+    fn test_reconstruct_route() {
+        use crate::data::eurocontrol::response::PointJson;
+
+        let mut db = fixture_with_duplicate_ghi();
+        for dp in [
+            designated_point("dp-jkl", "JKL", 45.0, 4.0),
+            designated_point("dp-mno", "MNO", 46.0, 5.0),
+        ] {
+            db.points
+                .designated_points_mut()
+                .insert(dp.identifier.clone().into(), dp);
+        }
+        let un871 = route("rte-un871", Some("U"), "N", "871");
+        let segment = route_segment(
+            "seg-3",
+            "rte-un871",
+            PointReference::DesignatedPoint("dp-ghi".into()),
+            PointReference::DesignatedPoint("dp-jkl".into()),
+        );
+        db.airways_mut().routes.insert(un871.identifier.clone().into(), un871);
+        db.airways_mut()
+            .route_segments
+            .insert(segment.identifier.clone().into(), segment);
+        db.airways_mut().index_route_segments();
+
+        let segments = db.reconstruct_route(&["DEF", "ghi", "JKL", "MNO"]);
+        let legs = segments
+            .iter()
+            .map(|s| {
+                (
+                    PointJson::from(&s.start).name.unwrap(),
+                    PointJson::from(&s.end).name.unwrap(),
+                    s.name.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            legs,
+            [
+                ("DEF".to_string(), "GHI".to_string(), Some("UN1")),
+                ("GHI".to_string(), "JKL".to_string(), Some("UN871")),
+                // No airway joins JKL and MNO
+                ("JKL".to_string(), "MNO".to_string(), None),
+            ]
+        );
+        // GHI is settled by its neighbours, not the point 5° north
+        let ghi = ResolvedPoint::from_db(&PointReference::DesignatedPoint("dp-ghi".into()), &db);
+        assert_eq!(segments[1].start, ghi);
+        assert_eq!(segments[1].source_segment.as_deref(), Some("seg-3"));
+        assert_eq!(segments[2].source_segment, None);
+    }
 }
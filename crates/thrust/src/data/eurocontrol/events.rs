@@ -0,0 +1,182 @@
+//! Structured events describing the decisions taken while enriching routes.
+//!
+//! An [`EnrichmentSink`] set in [`EnrichmentOptions::events`] receives, for each
+//! enriched route, one [`EnrichmentEvent`] per notable decision: an unresolved
+//! identifier, an airway replaced or flown direct, an ambiguous point settled by
+//! its score, etc. [`NdjsonSink`] writes them as JSON lines for offline analysis
+//! of why flight plans fail to resolve.
+//!
+//! [`EnrichmentOptions::events`]: crate::data::eurocontrol::database::EnrichmentOptions::events
+
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use crate::error::ThrustError;
+
+/// The outcome of a decision taken while enriching a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventOutcome {
+    /// No point or airway found for the identifier
+    Unresolved,
+    /// Found in the fallback database only
+    Fallback,
+    /// The filed airway was replaced by another airway connecting the same points
+    Substituted,
+    /// The airway was replaced by a direct leg
+    Direct,
+    /// The airway was kept from its single resolved point to its nearer end
+    Partial,
+    /// The airway filed at the boundary of the route was reduced to the segment
+    /// adjacent to its point
+    Repaired,
+    /// A point was chosen among several candidates
+    Chosen,
+    /// A repeated point was chosen differently than at its first occurrence
+    Inconsistent,
+    /// No point could be chosen among several candidates for lack of reference points
+    Undecided,
+}
+
+/// A notable decision taken on an element of a field 15 route.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EnrichmentEvent {
+    /// Index of the element in the parsed field 15 route
+    pub index: usize,
+    /// The element as filed, e.g. a waypoint identifier or an airway designator
+    pub token: String,
+    /// The enrichment stage, as named in an `EnrichmentTrace`
+    pub stage: &'static str,
+    pub outcome: EventOutcome,
+    /// Number of candidates considered
+    pub candidates: usize,
+    /// Score of the chosen candidate (lower is better), for ambiguous points
+    pub score: Option<f64>,
+}
+
+impl EnrichmentEvent {
+    pub(crate) fn new(
+        stage: &'static str,
+        index: usize,
+        token: impl Into<String>,
+        outcome: EventOutcome,
+        candidates: usize,
+    ) -> Self {
+        EnrichmentEvent {
+            index,
+            token: token.into(),
+            stage,
+            outcome,
+            candidates,
+            score: None,
+        }
+    }
+}
+
+/// A destination for the events of enriched routes.
+///
+/// Events are recorded once per route, after its candidates are narrowed down,
+/// possibly from several threads at once. Routes served from the route cache
+/// are not enriched again and record no event.
+pub trait EnrichmentSink: Send + Sync {
+    /// Record the events of a route, given as its field 15 elements separated by spaces.
+    fn record(&self, route: &str, events: &[EnrichmentEvent]);
+}
+
+impl std::fmt::Debug for dyn EnrichmentSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EnrichmentSink")
+    }
+}
+
+/// Write events as newline-delimited JSON, one object per event with the route
+/// it belongs to, e.g.
+///
+/// ```json
+/// {"route":"N0450F350 ABC UN1 GHI","index":2,"token":"UN1","stage":"trim_airways","outcome":"direct","candidates":1,"score":null}
+/// ```
+///
+/// The events of a route are written together. Write errors are logged and
+/// otherwise ignored, so that they do not interrupt enrichment.
+pub struct NdjsonSink {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+#[derive(Serialize)]
+struct RouteEvent<'a> {
+    route: &'a str,
+    #[serde(flatten)]
+    event: &'a EnrichmentEvent,
+}
+
+impl NdjsonSink {
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        NdjsonSink {
+            writer: Mutex::new(Box::new(writer)),
+        }
+    }
+
+    /// Write events to a file, truncated if it exists.
+    pub fn create(path: &Path) -> Result<Self, ThrustError> {
+        let file = std::fs::File::create(path)?;
+        Ok(NdjsonSink::new(std::io::BufWriter::new(file)))
+    }
+
+    /// Flush buffered events to the underlying writer.
+    pub fn flush(&self) -> Result<(), ThrustError> {
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl EnrichmentSink for NdjsonSink {
+    fn record(&self, route: &str, events: &[EnrichmentEvent]) {
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        for event in events {
+            let written = serde_json::to_writer(&mut *writer, &RouteEvent { route, event })
+                .map_err(ThrustError::from)
+                .and_then(|_| Ok(writer.write_all(b"\n")?));
+            if let Err(e) = written {
+                tracing::error!(target: "thrust::enrich", "Failed to write enrichment event: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+impl Drop for NdjsonSink {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            tracing::error!(target: "thrust::enrich", "Failed to flush enrichment events: {}", e);
+        }
+    }
+}
+
+/// The events of a route being enriched, only collected when a sink is set.
+#[derive(Debug, Default)]
+pub(crate) struct EventLog(Option<Vec<EnrichmentEvent>>);
+
+impl EventLog {
+    pub(crate) fn new(enabled: bool) -> Self {
+        EventLog(enabled.then(Vec::new))
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Record an event, built only if events are collected.
+    pub(crate) fn push(&mut self, event: impl FnOnce() -> EnrichmentEvent) {
+        if let Some(events) = &mut self.0 {
+            events.push(event());
+        }
+    }
+
+    pub(crate) fn take(&mut self) -> Vec<EnrichmentEvent> {
+        self.0.take().unwrap_or_default()
+    }
+}
@@ -0,0 +1,728 @@
+//! Geometry of terminal procedure legs.
+//!
+//! Legs of SID and STAR procedures are not all straight lines: radius-to-fix (RF)
+//! and arc (AF) legs follow a circle around a published centre. This module
+//! derives the shape of a leg from its ARINC 424 path terminator and samples it
+//! as a polyline with the ellipsoidal forward problem.
+//!
+//! It also projects points onto geodesic segments, which is the basis for
+//! comparing an enriched route with a reference polyline (e.g. a radar track),
+//! and clips enriched routes to a bounding box.
+
+use geodesy::prelude::*;
+use serde::Serialize;
+
+use crate::data::eurocontrol::database::{ResolvedLeg, ResolvedPoint, ResolvedRouteSegment};
+use crate::distance::{DistanceModel, EARTH_RADIUS, WGS84};
+
+/// The direction of a turn along an arc leg.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TurnDirection {
+    #[serde(rename = "left")]
+    Left,
+    #[serde(rename = "right")]
+    Right,
+}
+
+impl TurnDirection {
+    /// Parse an AIXM turn direction ("LEFT" or "RIGHT").
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "LEFT" => Some(TurnDirection::Left),
+            "RIGHT" => Some(TurnDirection::Right),
+            _ => None,
+        }
+    }
+}
+
+/// The shape of a procedure leg between its start and end points.
+///
+/// # Variants
+/// - `Straight`: Geodesic between both points (TF legs and anything unsupported)
+/// - `Arc`: Circle of `radius` meters around `center` (latitude, longitude)
+/// - `CourseToFix`: Track flown on a published course into the end fix (CF legs)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LegGeometry {
+    Straight,
+    Arc {
+        center: (f64, f64),
+        radius: f64,
+        direction: TurnDirection,
+    },
+    CourseToFix {
+        course: f64,
+    },
+}
+
+impl LegGeometry {
+    /// Derive the geometry of a leg from its parsed ARINC 424 fields.
+    ///
+    /// Arc legs fall back to a straight line when the centre or the turn
+    /// direction is missing; the radius is measured from the centre to `end`.
+    pub fn from_arinc(
+        leg_type: Option<&str>,
+        course: Option<f64>,
+        turn_direction: Option<&str>,
+        arc_centre: &ResolvedPoint,
+        end: &ResolvedPoint,
+    ) -> Self {
+        match leg_type.map(str::trim) {
+            Some("RF") | Some("AF") => {
+                let direction = turn_direction.and_then(TurnDirection::parse);
+                match (direction, arc_centre.coor(), end.coor()) {
+                    (Some(direction), Some(c), Some(e)) => LegGeometry::Arc {
+                        center: (c[1].to_degrees(), c[0].to_degrees()),
+                        radius: WGS84.distance(&c, &e),
+                        direction,
+                    },
+                    _ => LegGeometry::Straight,
+                }
+            }
+            Some("CF") => match course {
+                Some(course) => LegGeometry::CourseToFix { course },
+                None => LegGeometry::Straight,
+            },
+            _ => LegGeometry::Straight,
+        }
+    }
+}
+
+/// Sample a leg as a polyline of (latitude, longitude) pairs.
+///
+/// Consecutive samples are at most `spacing_m` meters apart; the first and last
+/// samples are the start and end points of the leg. Straight and course-to-fix
+/// legs follow the geodesic between both points, arc legs follow the circle
+/// around their centre in the published turn direction. Legs with an unresolved
+/// endpoint produce no samples.
+pub fn sample(leg: &ResolvedLeg, spacing_m: f64) -> Vec<(f64, f64)> {
+    let (Some(start), Some(end)) = (leg.segment.start.coor(), leg.segment.end.coor()) else {
+        return Vec::new();
+    };
+    let spacing = spacing_m.max(1.);
+
+    let mut samples = match leg.geometry {
+        LegGeometry::Straight | LegGeometry::CourseToFix { .. } => {
+            let (bearing, distance) = DistanceModel::Ellipsoidal.inverse(&start, &end);
+            let n = (distance / spacing).ceil().max(1.) as usize;
+            (0..n)
+                .map(|i| forward(&start, bearing, distance * i as f64 / n as f64))
+                .collect::<Vec<_>>()
+        }
+        LegGeometry::Arc { center, direction, .. } => {
+            let c = Coor2D::geo(center.0, center.1);
+            let (az1, r1) = DistanceModel::Ellipsoidal.inverse(&c, &start);
+            let (az2, r2) = DistanceModel::Ellipsoidal.inverse(&c, &end);
+            let sweep = match direction {
+                TurnDirection::Right => (az2 - az1).rem_euclid(360.),
+                TurnDirection::Left => -(az1 - az2).rem_euclid(360.),
+            };
+            let length = sweep.abs().to_radians() * (r1 + r2) / 2.;
+            let n = (length / spacing).ceil().max(1.) as usize;
+            (0..n)
+                .map(|i| {
+                    let t = i as f64 / n as f64;
+                    forward(&c, az1 + sweep * t, r1 + (r2 - r1) * t)
+                })
+                .collect::<Vec<_>>()
+        }
+    };
+    samples.push((end[1].to_degrees(), end[0].to_degrees()));
+    samples
+}
+
+/// Project `point` onto the geodesic segment from `start` to `end`.
+///
+/// Returns the foot of the perpendicular, clamped to the segment endpoints, and
+/// its distance in meters to `point`. Along-track and cross-track distances use
+/// a spherical approximation, which is well below a meter for legs of a few
+/// hundred kilometers.
+pub(crate) fn project(start: &Coor2D, end: &Coor2D, point: &Coor2D) -> (Coor2D, f64) {
+    let (course, length) = DistanceModel::Ellipsoidal.inverse(start, end);
+    let (bearing, distance) = DistanceModel::Ellipsoidal.inverse(start, point);
+    let (d, angle) = (distance / EARTH_RADIUS, (bearing - course).to_radians());
+    let cross_track = (d.sin() * angle.sin()).asin();
+    let along_track = (d.cos() / cross_track.cos()).clamp(-1., 1.).acos() * EARTH_RADIUS;
+    let along_track = (along_track * angle.cos().signum()).clamp(0., length);
+    let foot = WGS84.geodesic_fwd(start, course.to_radians(), along_track);
+    let foot = Coor2D::raw(foot[0], foot[1]);
+    (foot, DistanceModel::Ellipsoidal.distance(&foot, point))
+}
+
+/// Deviation between an enriched route and a reference polyline.
+///
+/// Distances are in meters. Reference points are measured against the enriched
+/// polyline and vice versa; the Hausdorff distance is the largest of both maxima.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GeometryDeviation {
+    /// Largest distance of a reference point to the enriched route
+    pub max_reference: f64,
+    /// Mean distance of the reference points to the enriched route
+    pub mean_reference: f64,
+    /// Index of the reference point furthest from the enriched route
+    pub worst_reference: Option<usize>,
+    /// Largest distance of a point of the enriched route to the reference
+    pub max_enriched: f64,
+    /// Mean distance of the points of the enriched route to the reference
+    pub mean_enriched: f64,
+    /// Index of the point of the enriched route furthest from the reference
+    pub worst_enriched: Option<usize>,
+    /// Hausdorff distance between both polylines
+    pub hausdorff: f64,
+}
+
+/// Compare the geometry of enriched segments with a reference polyline of
+/// (latitude, longitude) pairs.
+///
+/// Points of the enriched route are the endpoints of the segments, in order;
+/// segments with an unresolved endpoint are skipped. Indices in the result
+/// refer to these points and to `reference` respectively.
+pub fn compare_geometry(segments: &[ResolvedRouteSegment], reference: &[(f64, f64)]) -> GeometryDeviation {
+    let mut enriched: Vec<Coor2D> = Vec::new();
+    for (start, end) in segments.iter().filter_map(|s| Some((s.start.coor()?, s.end.coor()?))) {
+        for point in [start, end] {
+            if enriched.last() != Some(&point) {
+                enriched.push(point);
+            }
+        }
+    }
+    let reference = reference
+        .iter()
+        .map(|(lat, lon)| Coor2D::geo(*lat, *lon))
+        .collect::<Vec<_>>();
+
+    let (max_reference, mean_reference, worst_reference) = deviation(&reference, &enriched);
+    let (max_enriched, mean_enriched, worst_enriched) = deviation(&enriched, &reference);
+    GeometryDeviation {
+        max_reference,
+        mean_reference,
+        worst_reference,
+        max_enriched,
+        mean_enriched,
+        worst_enriched,
+        hausdorff: max_reference.max(max_enriched),
+    }
+}
+
+/// Maximum and mean distance of `points` to the `polyline`, with the index of the worst point.
+fn deviation(points: &[Coor2D], polyline: &[Coor2D]) -> (f64, f64, Option<usize>) {
+    if points.is_empty() || polyline.is_empty() {
+        return (0., 0., None);
+    }
+    let distances = points
+        .iter()
+        .map(|point| match polyline {
+            [single] => DistanceModel::Ellipsoidal.distance(single, point),
+            _ => polyline
+                .windows(2)
+                .map(|leg| project(&leg[0], &leg[1], point).1)
+                .fold(f64::INFINITY, f64::min),
+        })
+        .collect::<Vec<_>>();
+    let (worst, max) = distances
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(i, d)| (i, *d))
+        .unwrap();
+    (max, distances.iter().sum::<f64>() / distances.len() as f64, Some(worst))
+}
+
+/// A box of latitudes and longitudes, in degrees.
+///
+/// A box whose western bound lies east of its eastern bound spans the antimeridian,
+/// e.g. `west: 170., east: -170.` covers 20 degrees of longitude.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, serde::Deserialize)]
+pub struct BoundingBox {
+    pub south: f64,
+    pub west: f64,
+    pub north: f64,
+    pub east: f64,
+}
+
+impl BoundingBox {
+    /// Whether the box contains a point, boundaries included.
+    pub fn contains(&self, latitude: f64, longitude: f64) -> bool {
+        let longitude = wrap_longitude(longitude);
+        let within_longitudes = if self.west <= self.east {
+            self.west <= longitude && longitude <= self.east
+        } else {
+            self.west <= longitude || longitude <= self.east
+        };
+        self.south <= latitude && latitude <= self.north && within_longitudes
+    }
+}
+
+/// Clip enriched segments to the portions inside a bounding box.
+///
+/// Segments follow the geodesic between their endpoints. Cut points, where a
+/// segment enters or exits the box, become [`ResolvedPoint::Coordinates`]
+/// endpoints; names and constraints are kept. A segment crossing the box
+/// produces one segment per portion inside, and segments entirely outside the
+/// box, or with an unresolved endpoint, are dropped.
+pub fn clip_to_bbox(segments: &[ResolvedRouteSegment], bbox: &BoundingBox) -> Vec<ResolvedRouteSegment> {
+    let mut clipped = Vec::new();
+    for segment in segments {
+        let (Some(start), Some(end)) = (segment.start.coor(), segment.end.coor()) else {
+            continue;
+        };
+        let (bearing, length) = DistanceModel::Ellipsoidal.inverse(&start, &end);
+        let at = |distance: f64| forward(&start, bearing, distance);
+
+        // Longitude is monotonic along a geodesic, latitude has at most one extremum:
+        // bracket each crossing of the box boundaries between samples, then bisect.
+        let n = (length / CLIP_SAMPLING).ceil().max(CLIP_MIN_SAMPLES) as usize;
+        let samples = (0..=n).map(|i| length * i as f64 / n as f64).collect::<Vec<_>>();
+        let mut cuts = vec![0., length];
+        let boundaries = [
+            (bbox.south, true),
+            (bbox.north, true),
+            (bbox.west, false),
+            (bbox.east, false),
+        ];
+        for (value, is_latitude) in boundaries {
+            let f = |distance: f64| {
+                let (lat, lon) = at(distance);
+                if is_latitude {
+                    lat - value
+                } else {
+                    wrap_longitude(lon - value)
+                }
+            };
+            for pair in samples.windows(2) {
+                let (mut a, mut b) = (pair[0], pair[1]);
+                let (fa, fb) = (f(a), f(b));
+                if fa == 0. {
+                    cuts.push(a);
+                }
+                if fa * fb >= 0. {
+                    continue;
+                }
+                for _ in 0..CLIP_ITERATIONS {
+                    let mid = (a + b) / 2.;
+                    if f(mid) * fa > 0. {
+                        a = mid;
+                    } else {
+                        b = mid;
+                    }
+                }
+                let cut = (a + b) / 2.;
+                // Discard the discontinuity of wrapped longitudes, opposite to the boundary
+                if f(cut).abs() < 1e-6 {
+                    cuts.push(cut);
+                }
+            }
+        }
+        cuts.sort_by(f64::total_cmp);
+        cuts.dedup_by(|a, b| (*a - *b).abs() < CLIP_TOLERANCE);
+
+        // Keep the portions between consecutive cuts whose middle is inside the box
+        let mut portions: Vec<(f64, f64)> = Vec::new();
+        for pair in cuts.windows(2) {
+            let (lat, lon) = at((pair[0] + pair[1]) / 2.);
+            if !bbox.contains(lat, lon) {
+                continue;
+            }
+            match portions.last_mut() {
+                Some(last) if (last.1 - pair[0]).abs() < CLIP_TOLERANCE => last.1 = pair[1],
+                _ => portions.push((pair[0], pair[1])),
+            }
+        }
+        if length == 0. && bbox.contains(start[1].to_degrees(), start[0].to_degrees()) {
+            portions.push((0., 0.));
+        }
+        for (a, b) in portions {
+            let point = |distance: f64, endpoint: &ResolvedPoint, at_endpoint: bool| {
+                if at_endpoint {
+                    endpoint.clone()
+                } else {
+                    let (latitude, longitude) = at(distance);
+                    ResolvedPoint::Coordinates { latitude, longitude }
+                }
+            };
+            let at_end = (length - b).abs() < CLIP_TOLERANCE;
+            clipped.push(ResolvedRouteSegment {
+                start: point(a, &segment.start, a < CLIP_TOLERANCE),
+                end: point(b, &segment.end, at_end),
+                overfly: segment.overfly && at_end,
+                stay: segment.stay.filter(|_| at_end),
+                ..segment.clone()
+            });
+        }
+    }
+    clipped
+}
+
+/// Whether a point lies inside a polygon of (latitude, longitude) vertices, in degrees.
+///
+/// Edges are straight lines in latitude and longitude, as polygons are usually
+/// published, and the polygon may be closed or not. Points on the boundary may
+/// be counted either way.
+pub fn point_in_polygon(polygon: &[(f64, f64)], latitude: f64, longitude: f64) -> bool {
+    let mut inside = false;
+    let mut previous = match polygon.last() {
+        Some(last) => *last,
+        None => return false,
+    };
+    for &(lat, lon) in polygon {
+        let (prev_lat, prev_lon) = previous;
+        if (lat > latitude) != (prev_lat > latitude)
+            && longitude < lon + (latitude - lat) / (prev_lat - lat) * (prev_lon - lon)
+        {
+            inside = !inside;
+        }
+        previous = (lat, lon);
+    }
+    inside
+}
+
+/// Distance flown inside a polygon along enriched segments, in meters.
+///
+/// Segments follow the geodesic between their endpoints, and the polygon is
+/// tested with [`point_in_polygon`]. Segments with an unresolved endpoint are
+/// ignored.
+pub fn distance_in_polygon(segments: &[ResolvedRouteSegment], polygon: &[(f64, f64)]) -> f64 {
+    let mut distance = 0.;
+    for segment in segments {
+        let (Some(start), Some(end)) = (segment.start.coor(), segment.end.coor()) else {
+            continue;
+        };
+        let (bearing, length) = DistanceModel::Ellipsoidal.inverse(&start, &end);
+        let inside = |distance: f64| {
+            let (lat, lon) = forward(&start, bearing, distance);
+            point_in_polygon(polygon, lat, lon)
+        };
+        distance += polygon_portions(length, inside)
+            .into_iter()
+            .map(|(a, b)| b - a)
+            .sum::<f64>();
+    }
+    distance
+}
+
+/// A region crossed by a route, with the points where the route enters and exits it.
+#[derive(Debug, Clone, PartialEq, Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RegionCrossing {
+    /// Name of the region, e.g. a FIR designator
+    pub name: String,
+    /// Entry point as (latitude, longitude), the start of the route if it begins inside
+    pub entry: (f64, f64),
+    /// Exit point as (latitude, longitude), the end of the route if it ends inside
+    pub exit: (f64, f64),
+}
+
+/// Regions crossed by enriched segments, in the order they are entered.
+///
+/// Regions are named polygons of (latitude, longitude) vertices, tested with
+/// [`point_in_polygon`] along the geodesic of each segment. A region entered
+/// several times is listed once per visit. Segments with an unresolved endpoint
+/// are ignored.
+pub fn region_crossings(
+    segments: &[ResolvedRouteSegment],
+    regions: &[(String, Vec<(f64, f64)>)],
+) -> Vec<RegionCrossing> {
+    // Crossings with their start and end along the route, in meters
+    let mut crossings: Vec<(f64, f64, RegionCrossing)> = Vec::new();
+    let mut offset = 0.;
+    for segment in segments {
+        let (Some(start), Some(end)) = (segment.start.coor(), segment.end.coor()) else {
+            continue;
+        };
+        let (bearing, length) = DistanceModel::Ellipsoidal.inverse(&start, &end);
+        for (name, polygon) in regions {
+            let inside = |distance: f64| {
+                let (lat, lon) = forward(&start, bearing, distance);
+                point_in_polygon(polygon, lat, lon)
+            };
+            for (a, b) in polygon_portions(length, inside) {
+                let exit = forward(&start, bearing, b);
+                // Portions continuing a crossing through the shared endpoint of two segments
+                let previous = crossings
+                    .iter_mut()
+                    .find(|(_, to, crossing)| crossing.name == *name && (offset + a - to).abs() < CLIP_TOLERANCE);
+                match previous {
+                    Some((_, to, crossing)) => {
+                        *to = offset + b;
+                        crossing.exit = exit;
+                    }
+                    None => crossings.push((
+                        offset + a,
+                        offset + b,
+                        RegionCrossing {
+                            name: name.clone(),
+                            entry: forward(&start, bearing, a),
+                            exit,
+                        },
+                    )),
+                }
+            }
+        }
+        offset += length;
+    }
+    crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+    crossings.into_iter().map(|(_, _, crossing)| crossing).collect()
+}
+
+/// The portions of a geodesic of `length` meters inside a polygon, as
+/// (start, end) distances along it, where `inside` tests the point at a distance.
+fn polygon_portions(length: f64, inside: impl Fn(f64) -> bool) -> Vec<(f64, f64)> {
+    // Bracket each change of side between samples, then bisect
+    let n = (length / REGION_SAMPLING).ceil().max(CLIP_MIN_SAMPLES) as usize;
+    let samples = (0..=n).map(|i| length * i as f64 / n as f64).collect::<Vec<_>>();
+    let mut cuts = vec![0., length];
+    for pair in samples.windows(2) {
+        let (mut a, mut b) = (pair[0], pair[1]);
+        let side = inside(a);
+        if side == inside(b) {
+            continue;
+        }
+        for _ in 0..CLIP_ITERATIONS {
+            let mid = (a + b) / 2.;
+            if inside(mid) == side {
+                a = mid;
+            } else {
+                b = mid;
+            }
+        }
+        cuts.push((a + b) / 2.);
+    }
+    cuts.sort_by(f64::total_cmp);
+    cuts.windows(2)
+        .filter(|pair| pair[1] - pair[0] > CLIP_TOLERANCE && inside((pair[0] + pair[1]) / 2.))
+        .map(|pair| (pair[0], pair[1]))
+        .collect()
+}
+
+/// Maximum distance between samples when bracketing the crossings of a polygon, in meters.
+const REGION_SAMPLING: f64 = 10_000.;
+
+/// Maximum distance between samples when bracketing the crossings of a box, in meters.
+const CLIP_SAMPLING: f64 = 50_000.;
+const CLIP_MIN_SAMPLES: f64 = 16.;
+const CLIP_ITERATIONS: usize = 60;
+/// Cuts closer than this distance along a segment are merged, in meters.
+const CLIP_TOLERANCE: f64 = 1e-3;
+
+/// A longitude (or longitude difference) wrapped to [-180, 180), in degrees.
+fn wrap_longitude(longitude: f64) -> f64 {
+    (longitude + 180.).rem_euclid(360.) - 180.
+}
+
+/// Convert a magnetic bearing into a true bearing, in degrees within [0, 360),
+/// with the magnetic variation in degrees, positive East.
+pub fn magnetic_to_true(magnetic: f64, variation: f64) -> f64 {
+    (magnetic + variation).rem_euclid(360.)
+}
+
+/// Convert a true bearing into a magnetic bearing, in degrees within [0, 360),
+/// with the magnetic variation in degrees, positive East.
+pub fn true_to_magnetic(bearing: f64, variation: f64) -> f64 {
+    (bearing - variation).rem_euclid(360.)
+}
+
+/// Destination (latitude, longitude) from `from` along `bearing` degrees over `distance` meters.
+pub(crate) fn forward(from: &Coor2D, bearing: f64, distance: f64) -> (f64, f64) {
+    let dest = WGS84.geodesic_fwd(from, bearing.to_radians(), distance);
+    (dest[1].to_degrees(), dest[0].to_degrees())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coordinates(latitude: f64, longitude: f64) -> ResolvedPoint {
+        ResolvedPoint::Coordinates { latitude, longitude }
+    }
+
+    #[test]
+    fn test_rf_leg_sample_constant_radius() {
+        // Quarter circle of ~10 km around the centre, from north to east
+        let centre = Coor2D::geo(43.6, 1.4);
+        let north = forward(&centre, 0., 10_000.);
+        let east = forward(&centre, 90., 10_000.);
+        let end = coordinates(east.0, east.1);
+        let geometry = LegGeometry::from_arinc(Some("RF"), None, Some("RIGHT"), &coordinates(43.6, 1.4), &end);
+        let leg = ResolvedLeg {
+            segment: ResolvedRouteSegment::direct(coordinates(north.0, north.1), end),
+            geometry,
+            start_role: None,
+            end_role: None,
+        };
+
+        let samples = sample(&leg, 500.);
+        assert!(samples.len() > 30);
+        for (lat, lon) in &samples {
+            let (bearing, d) = DistanceModel::Ellipsoidal.inverse(&centre, &Coor2D::geo(*lat, *lon));
+            assert!((d - 10_000.).abs() < 1., "{d}");
+            // Turning right from north to east stays in the north-east quadrant
+            assert!((-1e-6..=90. + 1e-6).contains(&bearing), "{bearing}");
+        }
+
+        let straight = LegGeometry::from_arinc(Some("TF"), None, None, &ResolvedPoint::None, &leg.segment.end);
+        assert_eq!(straight, LegGeometry::Straight);
+        let left = LegGeometry::from_arinc(
+            Some("RF"),
+            None,
+            Some("LEFT"),
+            &coordinates(43.6, 1.4),
+            &leg.segment.end,
+        );
+        let samples = sample(&ResolvedLeg { geometry: left, ..leg }, 500.);
+        // Turning left goes the long way round, through the south-west
+        assert!(samples.iter().any(|(lat, lon)| *lat < 43.6 && *lon < 1.4));
+    }
+
+    #[test]
+    fn test_compare_geometry() {
+        let segment = |start: (f64, f64), end: (f64, f64)| {
+            ResolvedRouteSegment::direct(coordinates(start.0, start.1), coordinates(end.0, end.1))
+        };
+        let segments = [segment((45.0, 1.0), (45.0, 2.0)), segment((45.0, 2.0), (45.0, 3.0))];
+        // The reference goes 5 NM north of the middle fix
+        let offset = forward(&Coor2D::geo(45.0, 2.0), 0., 5. * 1852.);
+        let reference = [(45.0, 1.0), offset, (45.0, 3.0)];
+
+        let deviation = compare_geometry(&segments, &reference);
+        assert_eq!(deviation.worst_reference, Some(1));
+        assert!((deviation.max_reference - 9260.).abs() < 10., "{deviation:?}");
+        assert!((deviation.mean_reference - 9260. / 3.).abs() < 10., "{deviation:?}");
+        // The middle fix is slightly closer to the slanted reference legs
+        assert_eq!(deviation.worst_enriched, Some(1));
+        assert!(deviation.max_enriched < deviation.max_reference);
+        assert!(deviation.max_enriched > 9000., "{deviation:?}");
+        assert_eq!(deviation.hausdorff, deviation.max_reference);
+
+        let deviation = compare_geometry(&segments, &[(45.0, 1.0), (45.0, 2.0), (45.0, 3.0)]);
+        assert!(deviation.hausdorff < 1., "{deviation:?}");
+        assert_eq!(compare_geometry(&segments, &[]).worst_reference, None);
+    }
+
+    fn clip_segment(start: (f64, f64), end: (f64, f64), name: &str) -> ResolvedRouteSegment {
+        ResolvedRouteSegment {
+            name: Some(name.to_string()),
+            overfly: true,
+            ..ResolvedRouteSegment::direct(coordinates(start.0, start.1), coordinates(end.0, end.1))
+        }
+    }
+
+    fn assert_close(point: &ResolvedPoint, latitude: f64, longitude: f64) {
+        let ResolvedPoint::Coordinates {
+            latitude: lat,
+            longitude: lon,
+        } = point
+        else {
+            panic!("{point} is not a cut point");
+        };
+        assert!((lat - latitude).abs() < 1e-6, "{lat} != {latitude}");
+        assert!(wrap_longitude(lon - longitude).abs() < 1e-6, "{lon} != {longitude}");
+    }
+
+    #[test]
+    fn test_clip_to_bbox() {
+        let bbox = BoundingBox {
+            south: 42.,
+            west: 0.,
+            north: 48.,
+            east: 10.,
+        };
+        let segments = [
+            // Along a meridian, entering and exiting the box
+            clip_segment((40., 5.), (50., 5.), "A"),
+            // Along a parallel, outside the box
+            clip_segment((40., -10.), (40., 10.), "B"),
+            // Entering the box only
+            clip_segment((45., -5.), (45., 5.), "C"),
+            // Inside the box
+            clip_segment((44., 2.), (46., 8.), "D"),
+        ];
+        let clipped = clip_to_bbox(&segments, &bbox);
+        let names = clipped.iter().map(|s| s.name.as_deref().unwrap()).collect::<Vec<_>>();
+        assert_eq!(names, ["A", "C", "D"]);
+
+        assert_close(&clipped[0].start, 42., 5.);
+        assert_close(&clipped[0].end, 48., 5.);
+        assert!(!clipped[0].overfly);
+        // The cut point lies on the western boundary, on the original geodesic
+        let ResolvedPoint::Coordinates { longitude, .. } = clipped[1].start else {
+            panic!("{} is not a cut point", clipped[1].start);
+        };
+        assert!(longitude.abs() < 1e-6);
+        let (start, end) = (Coor2D::geo(45., -5.), Coor2D::geo(45., 5.));
+        assert!(project(&start, &end, &clipped[1].start.coor().unwrap()).1 < 0.1);
+        assert_eq!(clipped[1].end, segments[2].end);
+        assert!(clipped[1].overfly);
+        assert_eq!(clipped[2].start, segments[3].start);
+        assert_eq!(clipped[2].end, segments[3].end);
+    }
+
+    #[test]
+    fn test_clip_to_bbox_antimeridian() {
+        let bbox = BoundingBox {
+            south: 50.,
+            west: 175.,
+            north: 60.,
+            east: -175.,
+        };
+        assert!(bbox.contains(55., 180.) && bbox.contains(55., -178.) && !bbox.contains(55., 170.));
+        let segments = [
+            clip_segment((55., 170.), (55., -170.), "A"),
+            clip_segment((55., -170.), (55., 170.), "B"),
+            clip_segment((55., 10.), (55., 20.), "C"),
+        ];
+        let clipped = clip_to_bbox(&segments, &bbox);
+        assert_eq!(clipped.len(), 2);
+        for (segment, (start, end)) in clipped.iter().zip([(175., -175.), (-175., 175.)]) {
+            let [ResolvedPoint::Coordinates { longitude: a, .. }, ResolvedPoint::Coordinates { longitude: b, .. }] =
+                [&segment.start, &segment.end]
+            else {
+                panic!("{segment:?} has no cut points");
+            };
+            assert!(wrap_longitude(a - start).abs() < 1e-6, "{a} != {start}");
+            assert!(wrap_longitude(b - end).abs() < 1e-6, "{b} != {end}");
+            // The geodesic bulges poleward of the parallel, symmetrically
+            let (lat_a, lat_b) = (segment.start.coor().unwrap()[1], segment.end.coor().unwrap()[1]);
+            assert!((lat_a - lat_b).abs() < 1e-9 && lat_a.to_degrees() > 55.);
+        }
+    }
+
+    #[test]
+    fn test_region_crossings() {
+        let rectangle = |west, east| vec![(40., west), (40., east), (50., east), (50., west)];
+        let regions = [
+            ("WEST".to_string(), rectangle(0., 2.)),
+            ("EAST".to_string(), rectangle(2., 4.)),
+            (
+                "NORTH".to_string(),
+                rectangle(-10., 10.)
+                    .into_iter()
+                    .map(|(lat, lon)| (lat + 20., lon))
+                    .collect(),
+            ),
+        ];
+        let segments = [
+            clip_segment((45., -1.), (45., 1.), "A"),
+            // Crossing the boundary between both regions halfway
+            clip_segment((45., 1.), (45., 3.), "B"),
+            clip_segment((45., 3.), (45., 5.), "C"),
+            // Back into the eastern region
+            clip_segment((45., 5.), (45., 3.), "D"),
+        ];
+        let crossings = region_crossings(&segments, &regions);
+        let names = crossings.iter().map(|c| c.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["WEST", "EAST", "EAST"]);
+
+        let assert_at = |(lat, lon): (f64, f64), longitude: f64| {
+            // Geodesics bulge slightly north of the parallel
+            assert!(lat > 45. - 1e-9 && lat < 45.02, "{lat}");
+            assert!((lon - longitude).abs() < 1e-6, "{lon} != {longitude}");
+        };
+        assert_at(crossings[0].entry, 0.);
+        assert_at(crossings[0].exit, 2.);
+        assert_at(crossings[1].entry, 2.);
+        assert_at(crossings[1].exit, 4.);
+        assert_at(crossings[2].entry, 4.);
+        // The route ends inside the region
+        assert_at(crossings[2].exit, 3.);
+    }
+}
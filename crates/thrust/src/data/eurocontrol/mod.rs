@@ -1,5 +1,14 @@
 //! EUROCONTROL data parsers.
 
 pub mod aixm;
+#[cfg(feature = "rkyv")]
+pub mod archive;
+pub mod cache;
 pub mod database;
 pub mod ddr;
+pub mod events;
+pub mod geometry;
+pub mod overlay;
+pub mod points;
+pub mod response;
+mod spatial;
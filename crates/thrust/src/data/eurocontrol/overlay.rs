@@ -0,0 +1,313 @@
+//! Supplementary fixes and airways overlaying an [`AirwayDatabase`].
+//!
+//! Operators sometimes need points missing from the AIXM files, e.g. temporary
+//! waypoints published by NOTAM. Rather than rebuilding the datasets, an
+//! [`OverlayData`] lists extra navaids, designated points and airway segments,
+//! usually read from a JSON or TOML file:
+//!
+//! ```toml
+//! [[designated_points]]
+//! identifier = "notam-kolob"
+//! designator = "KOLOB"
+//! latitude = 45.5
+//! longitude = 3.5
+//!
+//! [[segments]]
+//! airway = "UN1"
+//! start = "dp-ghi"
+//! end = "notam-kolob"
+//! ```
+//!
+//! Overlay points take precedence over the database points of the same name or
+//! identifier, and overlay segments extend the airways of the same designator
+//! sharing one of their endpoints (or form a new airway). Segments resolved
+//! through the overlay are flagged with [`ResolvedRouteSegment::overlay`].
+//!
+//! An overlay is set with [`AirwayDatabase::with_overlay`], and replaced or
+//! removed at runtime with [`AirwayDatabase::set_overlay`], e.g. through the write
+//! lock of a [`SharedDatabase`](super::cache::SharedDatabase).
+//!
+//! [`AirwayDatabase`]: crate::data::eurocontrol::database::AirwayDatabase
+//! [`AirwayDatabase::with_overlay`]: crate::data::eurocontrol::database::AirwayDatabase::with_overlay
+//! [`AirwayDatabase::set_overlay`]: crate::data::eurocontrol::database::AirwayDatabase::set_overlay
+//! [`ResolvedRouteSegment::overlay`]: crate::data::eurocontrol::database::ResolvedRouteSegment::overlay
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::eurocontrol::aixm::designated_point::DesignatedPoint;
+use crate::data::eurocontrol::aixm::identifier::GmlId;
+use crate::data::eurocontrol::aixm::navaid::Navaid;
+use crate::data::eurocontrol::aixm::route::{Route, RouteDesignator};
+use crate::data::eurocontrol::aixm::route_segment::{PointReference, RouteSegment};
+use crate::data::eurocontrol::database::{ResolvedPoint, ResolvedRouteSegment};
+use crate::data::eurocontrol::points::PointStore;
+use crate::error::ThrustError;
+
+/// Extra navaids, designated points and airway segments, as described in the
+/// [module documentation](self).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct OverlayData {
+    #[serde(default)]
+    pub navaids: Vec<OverlayNavaid>,
+    #[serde(default)]
+    pub designated_points: Vec<OverlayPoint>,
+    #[serde(default)]
+    pub segments: Vec<OverlaySegment>,
+}
+
+/// A navaid of an overlay.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct OverlayNavaid {
+    /// Identifier of the navaid, replacing the database navaid with the same identifier
+    pub identifier: String,
+    /// Name of the navaid, as filed in routes
+    pub name: String,
+    /// Latitude in decimal degrees
+    pub latitude: f64,
+    /// Longitude in decimal degrees
+    pub longitude: f64,
+    /// Type of navaid (e.g. VOR, NDB)
+    #[serde(default, rename = "type")]
+    pub r#type: String,
+}
+
+/// A designated point of an overlay.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct OverlayPoint {
+    /// Identifier of the point, replacing the database point with the same identifier
+    pub identifier: String,
+    /// Designator of the point, as filed in routes
+    pub designator: String,
+    /// Latitude in decimal degrees
+    pub latitude: f64,
+    /// Longitude in decimal degrees
+    pub longitude: f64,
+}
+
+/// An airway segment of an overlay, joining two navaids or designated points.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct OverlaySegment {
+    /// Identifier of the segment, `overlay-{airway}-{start}-{end}` by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    /// Designator of the airway (e.g. "UN1")
+    pub airway: String,
+    /// Identifier of the start navaid or designated point, in the overlay or in the database
+    pub start: String,
+    /// Identifier of the end navaid or designated point, in the overlay or in the database
+    pub end: String,
+}
+
+impl OverlayData {
+    /// Read an overlay from a JSON string.
+    pub fn from_json(text: &str) -> Result<Self, ThrustError> {
+        Ok(serde_json::from_str(text)?)
+    }
+
+    /// Read an overlay from a TOML string.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(text: &str) -> Result<Self, ThrustError> {
+        toml::from_str(text).map_err(|e| ThrustError::InvalidData(e.to_string()))
+    }
+
+    /// Read an overlay from a TOML file if its extension is `.toml` (with the `toml`
+    /// feature), from a JSON file otherwise.
+    pub fn from_file(path: &Path) -> Result<Self, ThrustError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ThrustError::FileNotFound(format!("{}: {}", path.display(), e)))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            #[cfg(feature = "toml")]
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Self::from_toml(&text),
+            #[cfg(not(feature = "toml"))]
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Err(ThrustError::InvalidData(format!(
+                "{}: TOML overlays need the `toml` feature",
+                path.display()
+            ))),
+            _ => Self::from_json(&text),
+        }
+    }
+}
+
+/// The records of an [`OverlayData`], indexed like those of the database.
+#[derive(Debug, Default)]
+pub(crate) struct Overlay {
+    pub(crate) points: PointStore,
+    pub(crate) routes: HashMap<GmlId, Route>,
+    pub(crate) route_segments: HashMap<GmlId, RouteSegment>,
+}
+
+impl Overlay {
+    /// Index the records of an overlay.
+    ///
+    /// Segment endpoints are looked up among the overlay points first, then among
+    /// the `database` points. Segments with an unknown endpoint or an invalid airway
+    /// designator are skipped.
+    pub(crate) fn new(data: OverlayData, database: &PointStore) -> Self {
+        let mut overlay = Overlay::default();
+        *overlay.points.navaids_mut() = data
+            .navaids
+            .into_iter()
+            .map(|navaid| {
+                let navaid = Navaid {
+                    identifier: navaid.identifier,
+                    latitude: navaid.latitude,
+                    longitude: navaid.longitude,
+                    name: Some(navaid.name),
+                    r#type: navaid.r#type,
+                    ..Default::default()
+                };
+                (navaid.identifier.clone().into(), navaid)
+            })
+            .collect();
+        *overlay.points.designated_points_mut() = data
+            .designated_points
+            .into_iter()
+            .map(|dp| {
+                let dp = DesignatedPoint {
+                    identifier: dp.identifier,
+                    latitude: dp.latitude,
+                    longitude: dp.longitude,
+                    designator: dp.designator,
+                    ..Default::default()
+                };
+                (dp.identifier.clone().into(), dp)
+            })
+            .collect();
+
+        for segment in data.segments {
+            let reference = |identifier: &str| {
+                for points in [&overlay.points, database] {
+                    if points.contains_navaid(identifier) {
+                        return Some(PointReference::Navaid(identifier.into()));
+                    }
+                    if points.contains_designated_point(identifier) {
+                        return Some(PointReference::DesignatedPoint(identifier.into()));
+                    }
+                }
+                None
+            };
+            let (Some(start), Some(end)) = (reference(&segment.start), reference(&segment.end)) else {
+                tracing::warn!(
+                    target: "thrust::aixm",
+                    "Skipping overlay segment {} - {}: unknown endpoint",
+                    segment.start,
+                    segment.end
+                );
+                continue;
+            };
+            let airway = segment.airway.trim().to_uppercase();
+            let Some(designator) = RouteDesignator::parse(&airway) else {
+                tracing::warn!(target: "thrust::aixm", "Invalid route designator '{}' in overlay", airway);
+                continue;
+            };
+            let route_id = format!("overlay-{airway}");
+            let identifier = segment
+                .identifier
+                .unwrap_or_else(|| format!("overlay-{airway}-{}-{}", segment.start, segment.end));
+            let route = overlay.routes.entry(route_id.clone().into()).or_insert_with(|| Route {
+                identifier: route_id.clone(),
+                prefix: designator.prefix,
+                second_letter: Some(designator.second_letter),
+                number: Some(designator.number),
+                multiple_identifier: designator.multiple_identifier,
+                segments: Vec::new(),
+            });
+            route.segments.push(identifier.clone());
+            overlay.route_segments.insert(
+                identifier.clone().into(),
+                RouteSegment {
+                    identifier,
+                    route_formed: Some(route_id),
+                    start,
+                    end,
+                    ..Default::default()
+                },
+            );
+        }
+        for route in overlay.routes.values_mut() {
+            route.segments.sort();
+            route.segments.dedup();
+        }
+        overlay
+    }
+
+    /// Whether a point is an overlay navaid or designated point.
+    pub(crate) fn contains_point(&self, point: &ResolvedPoint) -> bool {
+        match point {
+            ResolvedPoint::Navaid(navaid) => self.points.contains_navaid(&navaid.identifier),
+            ResolvedPoint::DesignatedPoint(dp) => self.points.contains_designated_point(&dp.identifier),
+            _ => false,
+        }
+    }
+
+    /// Whether a segment comes from the overlay, or has an overlay endpoint.
+    pub(crate) fn contains_segment(&self, segment: &ResolvedRouteSegment) -> bool {
+        self.contains_point(&segment.start)
+            || self.contains_point(&segment.end)
+            || segment
+                .source_segment
+                .as_deref()
+                .is_some_and(|id| self.route_segments.contains_key(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay_from_json() {
+        let data = OverlayData::from_json(
+            r#"{
+                "navaids": [{"identifier": "nav-tmp", "name": "TMP", "latitude": 45.0, "longitude": 4.0}],
+                "designated_points": [{"identifier": "dp-kolob", "designator": "KOLOB", "latitude": 45.5, "longitude": 4.5}],
+                "segments": [
+                    {"airway": "UN1", "start": "nav-tmp", "end": "dp-kolob"},
+                    {"airway": "UN1", "start": "dp-kolob", "end": "dp-abc"},
+                    {"airway": "UN1", "start": "dp-kolob", "end": "dp-unknown"},
+                    {"airway": "NOT AN AIRWAY", "start": "nav-tmp", "end": "dp-kolob"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(data.navaids[0].r#type, "");
+
+        let mut database = PointStore::default();
+        let dp = DesignatedPoint {
+            identifier: "dp-abc".to_string(),
+            designator: "ABC".to_string(),
+            ..Default::default()
+        };
+        database
+            .designated_points_mut()
+            .insert(dp.identifier.clone().into(), dp);
+
+        let overlay = Overlay::new(data, &database);
+        assert_eq!(overlay.points.navaids_named("tmp").len(), 1);
+        assert_eq!(overlay.points.designated_points_named("KOLOB").len(), 1);
+        // Segments with an unknown endpoint or an invalid designator are skipped
+        let route = &overlay.routes["overlay-UN1"];
+        assert_eq!(
+            route.segments,
+            ["overlay-UN1-dp-kolob-dp-abc", "overlay-UN1-nav-tmp-dp-kolob"]
+        );
+        let segment = &overlay.route_segments["overlay-UN1-dp-kolob-dp-abc"];
+        assert_eq!(segment.end, PointReference::DesignatedPoint("dp-abc".into()));
+        assert_eq!(overlay.route_segments.len(), 2);
+
+        assert!(OverlayData::from_json(r#"{"navaids": [{"identifier": "nav-tmp"}]}"#).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn test_overlay_from_toml() {
+        assert_eq!(OverlayData::from_toml("").unwrap(), OverlayData::default());
+    }
+}
@@ -0,0 +1,650 @@
+//! Storage of the navaids and designated points of an [`AirwayDatabase`].
+//!
+//! Points parsed from AIXM files are kept in hash maps. Since parsing them takes
+//! most of the loading time, they can also be written once to a point index with
+//! [`AirwayDatabase::write_point_index`], then memory-mapped by
+//! [`AirwayDatabase::new_with_point_index`]: records are only materialised when
+//! they are looked up.
+//!
+//! # Point index format
+//!
+//! All integers and floats are little-endian.
+//! - a 16-byte header: the magic `THRPTS03`, then the number of navaids and the
+//!   number of designated points (`u32`);
+//! - fixed-size records for navaids sorted by uppercase name, then for designated
+//!   points sorted by uppercase designator (see [`RECORD_SIZE`]);
+//! - the record indices of navaids, then of designated points, sorted by identifier (`u32`);
+//! - the string table, UTF-8 strings referenced by records as `(offset, length)` pairs
+//!   of `u32`, where a length of `u32::MAX` stands for a missing value.
+//!
+//! A record holds the latitude, longitude, frequency and magnetic variation
+//! (`f64`, NaN when missing), then five string references: the identifier, the name of a navaid or designator
+//! of a designated point, then the type, description and call sign of a navaid, or
+//! the name and type of a designated point (and a missing value).
+//!
+//! [`AirwayDatabase`]: crate::data::eurocontrol::database::AirwayDatabase
+//! [`AirwayDatabase::write_point_index`]: crate::data::eurocontrol::database::AirwayDatabase::write_point_index
+//! [`AirwayDatabase::new_with_point_index`]: crate::data::eurocontrol::database::AirwayDatabase::new_with_point_index
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use crate::data::eurocontrol::aixm::designated_point::{DesignatedPoint, DesignatedPointType};
+use crate::data::eurocontrol::aixm::identifier::GmlId;
+use crate::data::eurocontrol::aixm::navaid::Navaid;
+#[cfg(feature = "rkyv")]
+use crate::data::eurocontrol::archive::PointArchive;
+use crate::error::ThrustError;
+
+const MAGIC: &[u8; 8] = b"THRPTS03";
+const HEADER_SIZE: usize = 16;
+/// Size of a point record in the index, in bytes.
+pub const RECORD_SIZE: usize = 72;
+const MISSING: u32 = u32::MAX;
+
+/// The navaids and designated points of a database, in memory or memory-mapped.
+#[derive(Debug)]
+pub(crate) enum PointStore {
+    Memory {
+        navaids: HashMap<GmlId, Navaid>,
+        designated_points: HashMap<GmlId, DesignatedPoint>,
+    },
+    Mapped(PointIndex),
+    #[cfg(feature = "rkyv")]
+    Archived(PointArchive),
+}
+
+impl Default for PointStore {
+    fn default() -> Self {
+        PointStore::Memory {
+            navaids: HashMap::new(),
+            designated_points: HashMap::new(),
+        }
+    }
+}
+
+impl PointStore {
+    pub(crate) fn navaid_count(&self) -> usize {
+        match self {
+            PointStore::Memory { navaids, .. } => navaids.len(),
+            PointStore::Mapped(index) => index.navaids,
+            #[cfg(feature = "rkyv")]
+            PointStore::Archived(archive) => archive.navaid_count(),
+        }
+    }
+
+    pub(crate) fn designated_point_count(&self) -> usize {
+        match self {
+            PointStore::Memory { designated_points, .. } => designated_points.len(),
+            PointStore::Mapped(index) => index.designated_points,
+            #[cfg(feature = "rkyv")]
+            PointStore::Archived(archive) => archive.designated_point_count(),
+        }
+    }
+
+    pub(crate) fn navaid(&self, identifier: &str) -> Option<Cow<'_, Navaid>> {
+        match self {
+            PointStore::Memory { navaids, .. } => navaids.get(identifier).map(Cow::Borrowed),
+            PointStore::Mapped(index) => index
+                .find_identifier(PointKind::Navaid, identifier)
+                .map(|i| Cow::Owned(index.navaid(i))),
+            #[cfg(feature = "rkyv")]
+            PointStore::Archived(archive) => archive
+                .find_navaid(identifier)
+                .map(|i| Cow::Owned(archive.navaid_at(i))),
+        }
+    }
+
+    pub(crate) fn designated_point(&self, identifier: &str) -> Option<Cow<'_, DesignatedPoint>> {
+        match self {
+            PointStore::Memory { designated_points, .. } => designated_points.get(identifier).map(Cow::Borrowed),
+            PointStore::Mapped(index) => index
+                .find_identifier(PointKind::DesignatedPoint, identifier)
+                .map(|i| Cow::Owned(index.designated_point(i))),
+            #[cfg(feature = "rkyv")]
+            PointStore::Archived(archive) => archive
+                .find_designated_point(identifier)
+                .map(|i| Cow::Owned(archive.designated_point_at(i))),
+        }
+    }
+
+    pub(crate) fn contains_navaid(&self, identifier: &str) -> bool {
+        match self {
+            PointStore::Memory { navaids, .. } => navaids.contains_key(identifier),
+            PointStore::Mapped(index) => index.find_identifier(PointKind::Navaid, identifier).is_some(),
+            #[cfg(feature = "rkyv")]
+            PointStore::Archived(archive) => archive.find_navaid(identifier).is_some(),
+        }
+    }
+
+    pub(crate) fn contains_designated_point(&self, identifier: &str) -> bool {
+        match self {
+            PointStore::Memory { designated_points, .. } => designated_points.contains_key(identifier),
+            PointStore::Mapped(index) => index.find_identifier(PointKind::DesignatedPoint, identifier).is_some(),
+            #[cfg(feature = "rkyv")]
+            PointStore::Archived(archive) => archive.find_designated_point(identifier).is_some(),
+        }
+    }
+
+    /// All navaids, in no particular order.
+    pub(crate) fn navaids(&self) -> Box<dyn Iterator<Item = Cow<'_, Navaid>> + '_> {
+        match self {
+            PointStore::Memory { navaids, .. } => Box::new(navaids.values().map(Cow::Borrowed)),
+            PointStore::Mapped(index) => Box::new((0..index.navaids).map(|i| Cow::Owned(index.navaid(i)))),
+            #[cfg(feature = "rkyv")]
+            PointStore::Archived(archive) => {
+                Box::new((0..archive.navaid_count()).map(|i| Cow::Owned(archive.navaid_at(i))))
+            }
+        }
+    }
+
+    /// All designated points, in no particular order.
+    pub(crate) fn designated_points(&self) -> Box<dyn Iterator<Item = Cow<'_, DesignatedPoint>> + '_> {
+        match self {
+            PointStore::Memory { designated_points, .. } => Box::new(designated_points.values().map(Cow::Borrowed)),
+            PointStore::Mapped(index) => Box::new(
+                (index.navaids..index.navaids + index.designated_points).map(|i| Cow::Owned(index.designated_point(i))),
+            ),
+            #[cfg(feature = "rkyv")]
+            PointStore::Archived(archive) => {
+                Box::new((0..archive.designated_point_count()).map(|i| Cow::Owned(archive.designated_point_at(i))))
+            }
+        }
+    }
+
+    /// The navaids named `name` (case-insensitive, ignoring surrounding spaces in names).
+    pub(crate) fn navaids_named(&self, name: &str) -> Vec<Navaid> {
+        match self {
+            PointStore::Memory { navaids, .. } => navaids
+                .values()
+                .filter(|n| {
+                    n.name
+                        .as_deref()
+                        .is_some_and(|n_name| n_name.trim().eq_ignore_ascii_case(name))
+                })
+                .cloned()
+                .collect(),
+            PointStore::Mapped(index) => index
+                .find_key(PointKind::Navaid, name)
+                .map(|i| index.navaid(i))
+                .collect(),
+            #[cfg(feature = "rkyv")]
+            PointStore::Archived(archive) => archive.navaids_named(name),
+        }
+    }
+
+    /// The designated points with designator `name` (case-insensitive, ignoring
+    /// surrounding spaces in designators).
+    pub(crate) fn designated_points_named(&self, name: &str) -> Vec<DesignatedPoint> {
+        match self {
+            PointStore::Memory { designated_points, .. } => designated_points
+                .values()
+                .filter(|dp| dp.designator.trim().eq_ignore_ascii_case(name))
+                .cloned()
+                .collect(),
+            PointStore::Mapped(index) => index
+                .find_key(PointKind::DesignatedPoint, name)
+                .map(|i| index.designated_point(i))
+                .collect(),
+            #[cfg(feature = "rkyv")]
+            PointStore::Archived(archive) => archive.designated_points_named(name),
+        }
+    }
+
+    /// Mutable access to the navaids, copying them into memory if they are mapped.
+    pub(crate) fn navaids_mut(&mut self) -> &mut HashMap<GmlId, Navaid> {
+        match self.materialise() {
+            PointStore::Memory { navaids, .. } => navaids,
+            _ => unreachable!("points are in memory"),
+        }
+    }
+
+    /// Mutable access to the designated points, copying them into memory if they are mapped.
+    pub(crate) fn designated_points_mut(&mut self) -> &mut HashMap<GmlId, DesignatedPoint> {
+        match self.materialise() {
+            PointStore::Memory { designated_points, .. } => designated_points,
+            _ => unreachable!("points are in memory"),
+        }
+    }
+
+    fn materialise(&mut self) -> &mut Self {
+        if !matches!(self, PointStore::Memory { .. }) {
+            let navaids = self
+                .navaids()
+                .map(|n| (n.identifier.clone().into(), n.into_owned()))
+                .collect();
+            let designated_points = self
+                .designated_points()
+                .map(|dp| (dp.identifier.clone().into(), dp.into_owned()))
+                .collect();
+            *self = PointStore::Memory {
+                navaids,
+                designated_points,
+            };
+        }
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PointKind {
+    Navaid,
+    DesignatedPoint,
+}
+
+/// The bytes of a point index, read in memory or memory-mapped.
+#[derive(Debug)]
+enum IndexBytes {
+    Owned(Vec<u8>),
+    Mapped(memmap2::Mmap),
+}
+
+impl std::ops::Deref for IndexBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            IndexBytes::Owned(bytes) => bytes,
+            IndexBytes::Mapped(mmap) => mmap,
+        }
+    }
+}
+
+/// A point index, as described in the [module documentation](self).
+#[derive(Debug)]
+pub struct PointIndex {
+    bytes: IndexBytes,
+    navaids: usize,
+    designated_points: usize,
+}
+
+impl PointIndex {
+    /// Memory-map a point index file.
+    ///
+    /// The file must not be modified while it is mapped.
+    pub fn open(path: &Path) -> Result<Self, ThrustError> {
+        let file = File::open(path)?;
+        // SAFETY: the index is only read, and the file is not expected to change
+        // while the database is in use.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        Self::from_bytes(IndexBytes::Mapped(mmap))
+    }
+
+    /// Read a point index from a byte buffer.
+    pub fn from_vec(bytes: Vec<u8>) -> Result<Self, ThrustError> {
+        Self::from_bytes(IndexBytes::Owned(bytes))
+    }
+
+    fn from_bytes(bytes: IndexBytes) -> Result<Self, ThrustError> {
+        let invalid = |msg: &str| ThrustError::InvalidData(format!("Invalid point index: {msg}"));
+        if bytes.len() < HEADER_SIZE || &bytes[..8] != MAGIC {
+            return Err(invalid("bad header"));
+        }
+        let navaids = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+        let designated_points = u32::from_le_bytes(bytes[12..16].try_into().unwrap()) as usize;
+        let index = PointIndex {
+            bytes,
+            navaids,
+            designated_points,
+        };
+        if index.bytes.len() < index.strings_start() {
+            return Err(invalid("truncated records"));
+        }
+        // Lookups by identifier are binary searches: the order must be within bounds and sorted
+        let ordered = |kind| {
+            let range = index.range(kind);
+            let order = range
+                .clone()
+                .map(|k| index.u32_at(index.order_start() + k * 4) as usize)
+                .collect::<Vec<_>>();
+            order.iter().all(|i| range.contains(i))
+                && order.windows(2).all(|w| index.string(w[0], 0) <= index.string(w[1], 0))
+        };
+        if !ordered(PointKind::Navaid) || !ordered(PointKind::DesignatedPoint) {
+            return Err(invalid("bad identifier order"));
+        }
+        Ok(index)
+    }
+
+    /// Write the navaids and designated points to a point index.
+    pub fn write<'a, W: Write>(
+        navaids: impl Iterator<Item = &'a Navaid>,
+        designated_points: impl Iterator<Item = &'a DesignatedPoint>,
+        writer: &mut W,
+    ) -> Result<(), ThrustError> {
+        let mut navaids = navaids.collect::<Vec<_>>();
+        navaids.sort_by_cached_key(|n| n.name.as_deref().map(|name| name.trim().to_ascii_uppercase()));
+        let mut designated_points = designated_points.collect::<Vec<_>>();
+        designated_points.sort_by_cached_key(|dp| dp.designator.trim().to_ascii_uppercase());
+
+        let too_large = || ThrustError::InvalidData("Too many points for a point index".to_string());
+        let mut strings: Vec<u8> = Vec::new();
+        let mut string = |value: Option<&str>| -> Result<[u8; 8], ThrustError> {
+            let (offset, length) = match value {
+                Some(value) => {
+                    let offset = u32::try_from(strings.len()).map_err(|_| too_large())?;
+                    strings.extend_from_slice(value.as_bytes());
+                    (offset, u32::try_from(value.len()).map_err(|_| too_large())?)
+                }
+                None => (0, MISSING),
+            };
+            let mut bytes = [0u8; 8];
+            bytes[..4].copy_from_slice(&offset.to_le_bytes());
+            bytes[4..].copy_from_slice(&length.to_le_bytes());
+            Ok(bytes)
+        };
+
+        let mut records = Vec::with_capacity((navaids.len() + designated_points.len()) * RECORD_SIZE);
+        let mut record = |coords: [f64; 4], refs: [[u8; 8]; 5]| {
+            for value in coords {
+                records.extend_from_slice(&value.to_le_bytes());
+            }
+            for reference in refs {
+                records.extend_from_slice(&reference);
+            }
+        };
+        for navaid in &navaids {
+            let refs = [
+                string(Some(&navaid.identifier))?,
+                string(navaid.name.as_deref())?,
+                string(Some(&navaid.r#type))?,
+                string(navaid.description.as_deref())?,
+                string(navaid.call_sign.as_deref())?,
+            ];
+            let frequency = navaid.frequency.unwrap_or(f64::NAN);
+            let variation = navaid.magnetic_variation.unwrap_or(f64::NAN);
+            record([navaid.latitude, navaid.longitude, frequency, variation], refs);
+        }
+        for dp in &designated_points {
+            let refs = [
+                string(Some(&dp.identifier))?,
+                string(Some(&dp.designator))?,
+                string(dp.name.as_deref())?,
+                string(Some(&dp.r#type.to_string()))?,
+                string(None)?,
+            ];
+            record([dp.latitude, dp.longitude, f64::NAN, f64::NAN], refs);
+        }
+
+        let order = |identifiers: Vec<&str>, offset: usize| {
+            let mut order = (0..identifiers.len()).collect::<Vec<_>>();
+            order.sort_by_key(|&i| identifiers[i]);
+            order.into_iter().map(move |i| (i + offset) as u32)
+        };
+        let navaid_order = order(navaids.iter().map(|n| n.identifier.as_str()).collect(), 0);
+        let dp_order = order(
+            designated_points.iter().map(|dp| dp.identifier.as_str()).collect(),
+            navaids.len(),
+        );
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&u32::try_from(navaids.len()).map_err(|_| too_large())?.to_le_bytes())?;
+        writer.write_all(
+            &u32::try_from(designated_points.len())
+                .map_err(|_| too_large())?
+                .to_le_bytes(),
+        )?;
+        writer.write_all(&records)?;
+        for i in navaid_order.chain(dp_order) {
+            writer.write_all(&i.to_le_bytes())?;
+        }
+        writer.write_all(&strings)?;
+        Ok(())
+    }
+
+    fn records(&self) -> usize {
+        self.navaids + self.designated_points
+    }
+
+    fn order_start(&self) -> usize {
+        HEADER_SIZE + self.records() * RECORD_SIZE
+    }
+
+    fn strings_start(&self) -> usize {
+        self.order_start() + self.records() * 4
+    }
+
+    fn f64_at(&self, offset: usize) -> f64 {
+        f64::from_le_bytes(self.bytes[offset..offset + 8].try_into().unwrap())
+    }
+
+    fn u32_at(&self, offset: usize) -> u32 {
+        u32::from_le_bytes(self.bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    /// The `field`-th string of record `i`; malformed references read as missing.
+    fn string(&self, i: usize, field: usize) -> Option<&str> {
+        let offset = HEADER_SIZE + i * RECORD_SIZE + 32 + field * 8;
+        let (start, length) = (self.u32_at(offset), self.u32_at(offset + 4));
+        if length == MISSING {
+            return None;
+        }
+        let start = self.strings_start() + start as usize;
+        let bytes = self.bytes.get(start..start + length as usize)?;
+        std::str::from_utf8(bytes).ok()
+    }
+
+    fn navaid(&self, i: usize) -> Navaid {
+        let offset = HEADER_SIZE + i * RECORD_SIZE;
+        let frequency = self.f64_at(offset + 16);
+        let variation = self.f64_at(offset + 24);
+        Navaid {
+            identifier: self.string(i, 0).unwrap_or_default().to_string(),
+            latitude: self.f64_at(offset),
+            longitude: self.f64_at(offset + 8),
+            name: self.string(i, 1).map(str::to_string),
+            r#type: self.string(i, 2).unwrap_or_default().to_string(),
+            description: self.string(i, 3).map(str::to_string),
+            frequency: (!frequency.is_nan()).then_some(frequency),
+            call_sign: self.string(i, 4).map(str::to_string),
+            magnetic_variation: (!variation.is_nan()).then_some(variation),
+        }
+    }
+
+    fn designated_point(&self, i: usize) -> DesignatedPoint {
+        let offset = HEADER_SIZE + i * RECORD_SIZE;
+        DesignatedPoint {
+            identifier: self.string(i, 0).unwrap_or_default().to_string(),
+            latitude: self.f64_at(offset),
+            longitude: self.f64_at(offset + 8),
+            designator: self.string(i, 1).unwrap_or_default().to_string(),
+            name: self.string(i, 2).map(str::to_string),
+            r#type: DesignatedPointType::from(self.string(i, 3).unwrap_or_default()),
+        }
+    }
+
+    fn range(&self, kind: PointKind) -> std::ops::Range<usize> {
+        match kind {
+            PointKind::Navaid => 0..self.navaids,
+            PointKind::DesignatedPoint => self.navaids..self.records(),
+        }
+    }
+
+    /// The records of `kind` whose name or designator matches `name`, by binary search.
+    fn find_key(&self, kind: PointKind, name: &str) -> impl Iterator<Item = usize> + '_ {
+        let name = name.to_ascii_uppercase();
+        let key = move |i: usize| self.string(i, 1).map(|key| key.trim().to_ascii_uppercase());
+        let range = self.range(kind);
+        let start = partition_point(range.clone(), |i| key(i).as_deref() < Some(name.as_str()));
+        (start..range.end).take_while(move |&i| key(i).as_deref() == Some(name.as_str()))
+    }
+
+    /// The record of `kind` with the given identifier, by binary search.
+    fn find_identifier(&self, kind: PointKind, identifier: &str) -> Option<usize> {
+        let range = self.range(kind);
+        let record = |k: usize| self.u32_at(self.order_start() + k * 4) as usize;
+        let k = partition_point(range.clone(), |k| self.string(record(k), 0) < Some(identifier));
+        let i = record(range.contains(&k).then_some(k)?);
+        (self.string(i, 0) == Some(identifier)).then_some(i)
+    }
+}
+
+/// The first index of `range` for which `pred` is false, `pred` being true then false.
+fn partition_point(range: std::ops::Range<usize>, pred: impl Fn(usize) -> bool) -> usize {
+    let (mut low, mut high) = (range.start, range.end);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn navaid(identifier: &str, name: Option<&str>, frequency: Option<f64>) -> Navaid {
+        Navaid {
+            identifier: identifier.to_string(),
+            latitude: 43.5,
+            longitude: 1.25,
+            name: name.map(str::to_string),
+            r#type: "VOR_DME".to_string(),
+            description: None,
+            frequency,
+            call_sign: None,
+            magnetic_variation: None,
+        }
+    }
+
+    fn designated_point(identifier: &str, designator: &str, r#type: DesignatedPointType) -> DesignatedPoint {
+        DesignatedPoint {
+            identifier: identifier.to_string(),
+            latitude: -12.5,
+            longitude: 130.,
+            designator: designator.to_string(),
+            name: None,
+            r#type,
+        }
+    }
+
+    fn points() -> (Vec<Navaid>, Vec<DesignatedPoint>) {
+        let tou = Navaid {
+            call_sign: Some("TBN".to_string()),
+            magnetic_variation: Some(1.5),
+            ..navaid("n-3", Some("TOU"), Some(117.7))
+        };
+        let navaids = [
+            tou,
+            navaid("n-1", Some(" tou "), None),
+            navaid("n-2", None, None),
+            navaid("n-4", Some("FJR"), Some(114.65)),
+        ];
+        let designated_points = [
+            designated_point("d-2", "LACOU", DesignatedPointType::Icao),
+            designated_point("d-1", "LACOU", DesignatedPointType::Other("ODD".to_string())),
+            designated_point("d-3", "ÉTÉ", DesignatedPointType::Terminal),
+        ];
+        (navaids.to_vec(), designated_points.to_vec())
+    }
+
+    /// The same points, in memory and in a point index.
+    fn stores() -> (PointStore, PointStore) {
+        let (navaids, designated_points) = points();
+        let mut bytes = Vec::new();
+        PointIndex::write(navaids.iter(), designated_points.iter(), &mut bytes).unwrap();
+        let memory = PointStore::Memory {
+            navaids: navaids.into_iter().map(|n| (n.identifier.clone().into(), n)).collect(),
+            designated_points: designated_points
+                .into_iter()
+                .map(|dp| (dp.identifier.clone().into(), dp))
+                .collect(),
+        };
+        (memory, PointStore::Mapped(PointIndex::from_vec(bytes).unwrap()))
+    }
+
+    fn sorted<T: std::fmt::Debug>(items: impl IntoIterator<Item = T>) -> Vec<String> {
+        let mut items = items.into_iter().map(|item| format!("{item:?}")).collect::<Vec<_>>();
+        items.sort();
+        items
+    }
+
+    #[test]
+    fn test_point_store_equivalence() {
+        let (memory, mapped) = stores();
+        assert_equivalent(&memory, &mapped);
+        assert_eq!(mapped.navaids_named("tou").len(), 2);
+        assert_eq!(mapped.navaid("n-3").unwrap().frequency, Some(117.7));
+        assert_eq!(mapped.navaid("n-3").unwrap().magnetic_variation, Some(1.5));
+        assert_eq!(mapped.navaid("n-3").unwrap().call_sign.as_deref(), Some("TBN"));
+        assert_eq!(mapped.navaid("n-4").unwrap().call_sign, None);
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn test_point_archive_store_equivalence() {
+        let (memory, _) = stores();
+        let (navaids, designated_points) = points();
+        let mut bytes = Vec::new();
+        PointArchive::write(navaids.iter(), designated_points.iter(), &mut bytes).unwrap();
+        let archived = PointStore::Archived(PointArchive::from_vec(bytes.clone()).unwrap());
+        assert_equivalent(&memory, &archived);
+        assert_eq!(archived.navaids_named("tou").len(), 2);
+
+        assert!(PointArchive::from_vec(bytes[..bytes.len() / 2].to_vec()).is_err());
+    }
+
+    fn assert_equivalent(memory: &PointStore, mapped: &PointStore) {
+        assert_eq!(memory.navaid_count(), mapped.navaid_count());
+        assert_eq!(memory.designated_point_count(), mapped.designated_point_count());
+        assert_eq!(sorted(memory.navaids()), sorted(mapped.navaids()));
+        assert_eq!(sorted(memory.designated_points()), sorted(mapped.designated_points()));
+        for name in ["TOU", "tou", " TOU", "FJR", "", "XYZ"] {
+            assert_eq!(
+                sorted(memory.navaids_named(name)),
+                sorted(mapped.navaids_named(name)),
+                "{name}"
+            );
+        }
+        for name in ["LACOU", "lacou", "ÉTÉ", "LAC"] {
+            assert_eq!(
+                sorted(memory.designated_points_named(name)),
+                sorted(mapped.designated_points_named(name)),
+                "{name}"
+            );
+        }
+        for id in ["n-1", "n-2", "n-4", "n-5", "d-1", ""] {
+            assert_eq!(format!("{:?}", memory.navaid(id)), format!("{:?}", mapped.navaid(id)));
+            assert_eq!(memory.contains_navaid(id), mapped.contains_navaid(id));
+        }
+        for id in ["d-1", "d-2", "d-3", "d-0", "n-1"] {
+            assert_eq!(
+                format!("{:?}", memory.designated_point(id)),
+                format!("{:?}", mapped.designated_point(id))
+            );
+            assert_eq!(
+                memory.contains_designated_point(id),
+                mapped.contains_designated_point(id)
+            );
+        }
+    }
+
+    #[test]
+    fn test_point_index_rejects_malformed_files() {
+        assert!(PointIndex::from_vec(b"NOTANIDX\0\0\0\0\0\0\0\0".to_vec()).is_err());
+        let mut bytes = Vec::new();
+        PointIndex::write(
+            [navaid("n-1", Some("TOU"), None), navaid("n-2", Some("FJR"), None)].iter(),
+            std::iter::empty(),
+            &mut bytes,
+        )
+        .unwrap();
+        assert!(PointIndex::from_vec(bytes[..HEADER_SIZE + RECORD_SIZE].to_vec()).is_err());
+        assert!(PointIndex::from_vec(bytes.clone()).is_ok());
+        // An identifier order which is not sorted
+        let order = HEADER_SIZE + 2 * RECORD_SIZE;
+        let mut unsorted = bytes.clone();
+        unsorted[order..order + 8].rotate_left(4);
+        assert!(PointIndex::from_vec(unsorted).is_err());
+        // An identifier order pointing outside of the navaid records
+        bytes[order..order + 4].copy_from_slice(&7u32.to_le_bytes());
+        assert!(PointIndex::from_vec(bytes).is_err());
+    }
+}
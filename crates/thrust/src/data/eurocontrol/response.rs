@@ -0,0 +1,1112 @@
+//! Canonical JSON representation of resolved routes.
+//!
+//! These types define the stable shape returned by the route resolution server
+//! and the Python bindings, so that clients do not depend on the internal
+//! structure of [`ResolvedPoint`] and [`ResolvedRouteSegment`].
+//!
+//! Values keep their full precision. The number of decimals of coordinates and
+//! the unit of distances are chosen with [`OutputOptions`] when writing them out,
+//! through [`OutputOptions::export`], whatever the output format.
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::eurocontrol::aixm::route_segment::RouteDirection;
+use crate::data::eurocontrol::database::{
+    order_route_segments, AirwayDatabase, EnrichmentReport, FixRecord, ResolvedPoint, ResolvedProcedure, ResolvedRoute,
+    ResolvedRouteSegment, UnresolvedToken,
+};
+use crate::data::eurocontrol::geometry::{region_crossings, RegionCrossing};
+use crate::data::field15::{Altitude, LevelConstraint, Speed};
+use crate::distance::{DistanceModel, NAUTICAL_MILE};
+use crate::error::ThrustError;
+
+/// Default number of decimals of coordinates in responses (about 0.1 m).
+pub const DEFAULT_PRECISION: u32 = 6;
+
+/// Maximum number of decimals of coordinates in responses, beyond the precision of `f64`.
+const MAX_PRECISION: u8 = 15;
+
+/// Units of distances in responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DistanceUnit {
+    #[default]
+    Meters,
+    NauticalMiles,
+}
+
+impl DistanceUnit {
+    /// Parse a unit name ("m" or "nm", and their long forms), case insensitive.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "m" | "meters" | "metres" => Some(DistanceUnit::Meters),
+            "nm" | "nmi" | "nautical_miles" => Some(DistanceUnit::NauticalMiles),
+            _ => None,
+        }
+    }
+
+    /// Convert a distance in meters to this unit.
+    pub fn convert(&self, meters: f64) -> f64 {
+        match self {
+            DistanceUnit::Meters => meters,
+            DistanceUnit::NauticalMiles => meters / NAUTICAL_MILE,
+        }
+    }
+}
+
+/// How coordinates and distances are written in responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputOptions {
+    /// Number of decimals of latitudes and longitudes
+    pub coord_decimals: u8,
+    /// Unit of lengths and distances
+    pub distance_unit: DistanceUnit,
+}
+
+impl Default for OutputOptions {
+    fn default() -> Self {
+        OutputOptions {
+            coord_decimals: DEFAULT_PRECISION as u8,
+            distance_unit: DistanceUnit::default(),
+        }
+    }
+}
+
+impl OutputOptions {
+    /// Options from the text of query parameters or command line flags, e.g.
+    /// `precision=4` and `units=nm`. Missing values keep their default.
+    pub fn parse(precision: Option<&str>, units: Option<&str>) -> Result<Self, ThrustError> {
+        let mut options = OutputOptions::default();
+        if let Some(precision) = precision {
+            options.coord_decimals = precision
+                .trim()
+                .parse()
+                .ok()
+                .filter(|decimals| *decimals <= MAX_PRECISION)
+                .ok_or_else(|| ThrustError::InvalidData(format!("precision must be between 0 and {MAX_PRECISION}")))?;
+        }
+        if let Some(units) = units {
+            options.distance_unit = DistanceUnit::parse(units)
+                .ok_or_else(|| ThrustError::InvalidData("units must be m or nm".to_string()))?;
+        }
+        Ok(options)
+    }
+
+    /// Round a latitude or longitude to the number of decimals of the options.
+    pub fn round(&self, coordinate: f64) -> f64 {
+        let factor = 10f64.powi(self.coord_decimals as i32);
+        (coordinate * factor).round() / factor
+    }
+
+    /// Convert a distance in meters to the unit of the options.
+    pub fn distance(&self, meters: f64) -> f64 {
+        self.distance_unit.convert(meters)
+    }
+
+    /// Wrap a value so that it serializes according to these options.
+    ///
+    /// The value itself is left untouched: coordinates are rounded, and
+    /// distances converted, on a copy made while serializing.
+    pub fn export<'a, T: Export>(&self, value: &'a T) -> Exported<'a, T> {
+        Exported { value, options: *self }
+    }
+}
+
+/// Values whose coordinates and distances follow [`OutputOptions`] when exported.
+pub trait Export: Serialize {
+    /// A copy of the value, as written with `options`.
+    fn to_output(&self, options: &OutputOptions) -> Self;
+}
+
+/// A value serialized according to [`OutputOptions`], see [`OutputOptions::export`].
+#[derive(Debug, Clone, Copy)]
+pub struct Exported<'a, T> {
+    value: &'a T,
+    options: OutputOptions,
+}
+
+impl<T: Export> Serialize for Exported<'_, T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.to_output(&self.options).serialize(serializer)
+    }
+}
+
+impl<T: Export> Export for Vec<T> {
+    fn to_output(&self, options: &OutputOptions) -> Self {
+        self.iter().map(|value| value.to_output(options)).collect()
+    }
+}
+
+/// A point of a resolved route, as serialized in JSON responses.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PointJson {
+    /// Latitude in decimal degrees
+    pub latitude: f64,
+    /// Longitude in decimal degrees
+    pub longitude: f64,
+    /// Published identifier of the point (ICAO code, navaid or fix designator)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// A segment of a resolved route, as serialized in JSON responses.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SegmentJson {
+    pub start: PointJson,
+    pub end: PointJson,
+    /// Name of the airway or procedure, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub altitude: Option<Altitude>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<Speed>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<LevelConstraint>,
+    /// The end point must be overflown
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub overfly: bool,
+    /// Minutes spent at the end point, filed with a STAY group
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stay: Option<u16>,
+    /// Identifier of the AIXM route segment this segment comes from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_segment: Option<String>,
+    /// The AIXM route segment is traversed from its end to its start
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_reversed: bool,
+    /// The segment comes from the overlay of the database, or has an overlay endpoint
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub overlay: bool,
+    /// The segment bridges a discontinuity of the route, e.g. over an unresolved
+    /// point, and should be drawn distinctly
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub gap: bool,
+}
+
+/// A route resolution request, e.g. one line of a JSON-lines batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RouteRequest {
+    /// Caller-defined identifier, echoed in the response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<serde_json::Value>,
+    /// The field 15 route string
+    pub route: String,
+}
+
+/// The response to a route resolution request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RouteResponse {
+    /// Identifier of the request, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<serde_json::Value>,
+    /// The original field 15 route string
+    pub route: String,
+    pub segments: Vec<SegmentJson>,
+    /// Identifiers which could not be resolved, with suggestions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unresolved: Vec<UnresolvedToken>,
+    /// The route does not begin with a valid speed/level group
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub missing_initial_group: bool,
+    /// Summary of the airways, fixes and regions of the route, if requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub footprint: Option<RouteFootprint>,
+}
+
+/// What a resolved route goes through, summarized in a single record per flight.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RouteFootprint {
+    /// Distinct names of airways (and procedures) flown, in order of first use
+    pub airways: Vec<String>,
+    /// Distinct designators of the navaids and designated points, in order of first visit
+    pub fixes: Vec<String>,
+    /// Length of direct segments, in meters unless exported in another unit
+    pub direct_distance: f64,
+    /// Length of segments along airways and procedures, in meters unless exported in another unit
+    pub airway_distance: f64,
+    /// Length of the discontinuities of the route, in meters unless exported in another unit
+    #[serde(default)]
+    pub gap_distance: f64,
+    /// Regions (e.g. FIRs) crossed, in order, when regions are given
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub regions: Vec<RegionCrossing>,
+    /// Horizontal efficiency, from the first to the last point of the route
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub efficiency: Option<RouteEfficiency>,
+}
+
+/// The horizontal efficiency of a route: its length compared with the great
+/// circle distance between its departure and arrival.
+///
+/// Fields are left empty when they cannot be computed: the filed distance when a
+/// segment has an unresolved endpoint, the great circle distance when the
+/// departure or arrival is unresolved, and the ratio when they coincide.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RouteEfficiency {
+    /// Length of the route, in meters unless exported in another unit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filed_distance: Option<f64>,
+    /// Great circle distance from departure to arrival, in meters unless exported in another unit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub great_circle_distance: Option<f64>,
+    /// Filed distance over great circle distance, 1 for a direct route
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ratio: Option<f64>,
+    /// Filed distance in excess of the great circle distance, in meters unless exported in another unit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excess_distance: Option<f64>,
+}
+
+/// JSON Schema of [`RouteResponse`], derived from the type definitions.
+#[cfg(feature = "schema")]
+pub fn response_schema() -> serde_json::Value {
+    schemars::schema_for!(RouteResponse).to_value()
+}
+
+/// JSON Schema of [`RouteRequest`], derived from the type definitions.
+#[cfg(feature = "schema")]
+pub fn request_schema() -> serde_json::Value {
+    schemars::schema_for!(RouteRequest).to_value()
+}
+
+/// One segment of an airway, as a flat table row.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AirwaySegmentRow {
+    /// Designator of the airway
+    pub airway: String,
+    /// Name of the start point
+    pub start: Option<String>,
+    pub start_latitude: f64,
+    pub start_longitude: f64,
+    /// Name of the end point
+    pub end: Option<String>,
+    pub end_latitude: f64,
+    pub end_longitude: f64,
+    /// Length of the segment, in meters unless exported in another unit
+    pub length: f64,
+    /// Direction of flight allowed along the segment, from the start to the end point
+    pub direction: Option<RouteDirection>,
+    /// Lowest limit of the segment over all directions, e.g. "FL245" or "GND"
+    pub lower_limit: Option<String>,
+    /// Highest limit of the segment over all directions, e.g. "FL660" or "UNL"
+    pub upper_limit: Option<String>,
+}
+
+/// One leg of a SID or STAR, as a flat table row.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcedureLegRow {
+    /// ICAO code of the airport of the procedure
+    pub airport: Option<String>,
+    /// Designator of the procedure
+    pub procedure: String,
+    /// Position of the leg in the procedure, from 0
+    pub leg_index: usize,
+    /// Name of the start point
+    pub start: Option<String>,
+    /// Kind of the start point, e.g. "DesignatedPoint"
+    pub start_kind: String,
+    pub start_latitude: f64,
+    pub start_longitude: f64,
+    /// Name of the end point
+    pub end: Option<String>,
+    /// Kind of the end point, e.g. "Navaid"
+    pub end_kind: String,
+    pub end_latitude: f64,
+    pub end_longitude: f64,
+}
+
+/// Formats of tabular responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableFormat {
+    Csv,
+    #[default]
+    Json,
+}
+
+impl TableFormat {
+    /// Parse a format name ("csv" or "json"), case insensitive.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "csv" => Some(TableFormat::Csv),
+            "json" => Some(TableFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// The MIME type of the rendered table.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            TableFormat::Csv => "text/csv",
+            TableFormat::Json => "application/json",
+        }
+    }
+
+    /// Render rows as a CSV table with a header line, or as a JSON array.
+    pub fn render<T: Export>(&self, rows: &[T], options: &OutputOptions) -> Result<String, ThrustError> {
+        let rows = rows.iter().map(|row| options.export(row));
+        match self {
+            TableFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(Vec::new());
+                for row in rows {
+                    writer.serialize(row)?;
+                }
+                let bytes = writer.into_inner().map_err(|e| ThrustError::Io(e.to_string()))?;
+                Ok(String::from_utf8(bytes).map_err(|e| ThrustError::InvalidData(e.to_string()))?)
+            }
+            TableFormat::Json => Ok(serde_json::to_string(&rows.collect::<Vec<_>>())?),
+        }
+    }
+}
+
+/// The footprint of a route, as a flat table row.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FootprintRow {
+    /// The field 15 route string
+    pub route: String,
+    /// Airways flown, separated by spaces
+    pub airways: String,
+    /// Fixes visited, separated by spaces
+    pub fixes: String,
+    pub direct_distance: f64,
+    pub airway_distance: f64,
+    pub gap_distance: f64,
+    /// Regions crossed, separated by spaces
+    pub regions: String,
+    pub filed_distance: Option<f64>,
+    pub great_circle_distance: Option<f64>,
+    /// Filed distance over great circle distance
+    pub efficiency: Option<f64>,
+    pub excess_distance: Option<f64>,
+}
+
+/// The segments of all airways named `name`, one row per segment.
+///
+/// Segments follow each other along the airway, from one end to the other,
+/// and incomplete segments (with an endpoint missing from the database) are
+/// skipped. An unknown airway yields no rows.
+pub fn airway_segment_rows(db: &AirwayDatabase, name: &str) -> Vec<AirwaySegmentRow> {
+    let model = db.distance_model();
+    ResolvedRoute::lookup(name, db)
+        .into_iter()
+        .flat_map(|route| {
+            let airway = route.name;
+            order_route_segments(route.segments)
+                .into_iter()
+                .filter_map(move |segment| {
+                    let length = model.distance(&segment.start.coor()?, &segment.end.coor()?);
+                    let start = PointJson::from(&segment.start);
+                    let end = PointJson::from(&segment.end);
+                    let source = segment.source_segment.as_deref().and_then(|id| db.route_segment(id));
+                    Some(AirwaySegmentRow {
+                        airway: airway.clone(),
+                        start: start.name,
+                        start_latitude: start.latitude,
+                        start_longitude: start.longitude,
+                        end: end.name,
+                        end_latitude: end.latitude,
+                        end_longitude: end.longitude,
+                        length,
+                        direction: source.and_then(|source| source.direction()),
+                        lower_limit: source
+                            .and_then(|source| source.lower_limit())
+                            .map(|limit| limit.to_string()),
+                        upper_limit: source
+                            .and_then(|source| source.upper_limit())
+                            .map(|limit| limit.to_string()),
+                    })
+                })
+        })
+        .collect()
+}
+
+/// The legs of SID or STAR procedures, one row per leg in flight order.
+pub fn procedure_leg_rows(procedures: &[ResolvedProcedure]) -> Vec<ProcedureLegRow> {
+    fn kind(point: &ResolvedPoint) -> &'static str {
+        match point {
+            ResolvedPoint::AirportHeliport(_) => "AirportHeliport",
+            ResolvedPoint::Navaid(_) => "Navaid",
+            ResolvedPoint::DesignatedPoint(_) => "DesignatedPoint",
+            ResolvedPoint::Coordinates { .. } => "Coordinates",
+            ResolvedPoint::None => "None",
+        }
+    }
+
+    procedures
+        .iter()
+        .flat_map(|procedure| {
+            procedure.legs.iter().enumerate().map(|(leg_index, leg)| {
+                let start = PointJson::from(&leg.segment.start);
+                let end = PointJson::from(&leg.segment.end);
+                ProcedureLegRow {
+                    airport: procedure.airport.clone(),
+                    procedure: procedure.name.clone(),
+                    leg_index,
+                    start: start.name,
+                    start_kind: kind(&leg.segment.start).to_string(),
+                    start_latitude: start.latitude,
+                    start_longitude: start.longitude,
+                    end: end.name,
+                    end_kind: kind(&leg.segment.end).to_string(),
+                    end_latitude: end.latitude,
+                    end_longitude: end.longitude,
+                }
+            })
+        })
+        .collect()
+}
+
+/// The legs of procedures as a polars DataFrame, with one column per field of
+/// [`ProcedureLegRow`].
+#[cfg(feature = "polars")]
+pub fn procedure_legs_dataframe(rows: &[ProcedureLegRow]) -> Result<polars::prelude::DataFrame, ThrustError> {
+    use polars::prelude::*;
+
+    Ok(df!(
+        "airport" => rows.iter().map(|r| r.airport.as_deref()).collect::<Vec<_>>(),
+        "procedure" => rows.iter().map(|r| r.procedure.as_str()).collect::<Vec<_>>(),
+        "leg_index" => rows.iter().map(|r| r.leg_index as u32).collect::<Vec<_>>(),
+        "start" => rows.iter().map(|r| r.start.as_deref()).collect::<Vec<_>>(),
+        "start_kind" => rows.iter().map(|r| r.start_kind.as_str()).collect::<Vec<_>>(),
+        "start_latitude" => rows.iter().map(|r| r.start_latitude).collect::<Vec<_>>(),
+        "start_longitude" => rows.iter().map(|r| r.start_longitude).collect::<Vec<_>>(),
+        "end" => rows.iter().map(|r| r.end.as_deref()).collect::<Vec<_>>(),
+        "end_kind" => rows.iter().map(|r| r.end_kind.as_str()).collect::<Vec<_>>(),
+        "end_latitude" => rows.iter().map(|r| r.end_latitude).collect::<Vec<_>>(),
+        "end_longitude" => rows.iter().map(|r| r.end_longitude).collect::<Vec<_>>(),
+    )?)
+}
+
+/// The [fix table](AirwayDatabase::fix_table) as a polars DataFrame, with one
+/// column per field of [`FixRecord`]. Sources are written as a comma-separated
+/// list of `kind:identifier`, with their count in `source_count`.
+#[cfg(feature = "polars")]
+pub fn fix_table_dataframe(fixes: &[FixRecord]) -> Result<polars::prelude::DataFrame, ThrustError> {
+    use polars::prelude::*;
+
+    let sources = |fix: &FixRecord| {
+        fix.sources
+            .iter()
+            .map(|source| format!("{}:{}", source.kind, source.identifier))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    Ok(df!(
+        "designator" => fixes.iter().map(|f| f.designator.as_str()).collect::<Vec<_>>(),
+        "kind" => fixes.iter().map(|f| f.kind.as_str()).collect::<Vec<_>>(),
+        "identifier" => fixes.iter().map(|f| f.identifier.as_str()).collect::<Vec<_>>(),
+        "latitude" => fixes.iter().map(|f| f.latitude).collect::<Vec<_>>(),
+        "longitude" => fixes.iter().map(|f| f.longitude).collect::<Vec<_>>(),
+        "sources" => fixes.iter().map(sources).collect::<Vec<_>>(),
+        "source_count" => fixes.iter().map(|f| f.sources.len() as u32).collect::<Vec<_>>(),
+    )?)
+}
+
+impl From<&ResolvedPoint> for PointJson {
+    fn from(point: &ResolvedPoint) -> Self {
+        match point {
+            ResolvedPoint::AirportHeliport(airport) => PointJson {
+                latitude: airport.latitude,
+                longitude: airport.longitude,
+                name: Some(airport.code().to_string()),
+            },
+            ResolvedPoint::Navaid(navaid) => PointJson {
+                latitude: navaid.latitude,
+                longitude: navaid.longitude,
+                name: navaid.name.clone(),
+            },
+            ResolvedPoint::DesignatedPoint(dp) => PointJson {
+                latitude: dp.latitude,
+                longitude: dp.longitude,
+                name: Some(dp.designator.clone()),
+            },
+            ResolvedPoint::Coordinates { latitude, longitude } => PointJson {
+                latitude: *latitude,
+                longitude: *longitude,
+                name: None,
+            },
+            ResolvedPoint::None => PointJson {
+                latitude: 0.0,
+                longitude: 0.0,
+                name: None,
+            },
+        }
+    }
+}
+
+impl From<&ResolvedRouteSegment> for SegmentJson {
+    fn from(segment: &ResolvedRouteSegment) -> Self {
+        SegmentJson {
+            start: (&segment.start).into(),
+            end: (&segment.end).into(),
+            name: segment.name.clone(),
+            altitude: segment.altitude.clone(),
+            speed: segment.speed.clone(),
+            level: segment.level.clone(),
+            overfly: segment.overfly,
+            stay: segment.stay,
+            source_segment: segment.source_segment.clone(),
+            is_reversed: segment.is_reversed,
+            overlay: segment.overlay,
+            gap: segment.is_gap(),
+        }
+    }
+}
+
+impl RouteFootprint {
+    /// Summarize enriched segments, without regions.
+    ///
+    /// Named segments count as airway distance and unnamed ones as direct
+    /// distance. Gap segments, and discontinuities between consecutive segments
+    /// when gaps are not emitted, count as gap distance: the sum of the three does
+    /// not depend on [`EnrichmentOptions::emit_gaps`]. Segments with an unresolved
+    /// endpoint are left out.
+    ///
+    /// [`EnrichmentOptions::emit_gaps`]: crate::data::eurocontrol::database::EnrichmentOptions::emit_gaps
+    pub fn from_segments(segments: &[ResolvedRouteSegment]) -> Self {
+        let mut footprint = RouteFootprint {
+            airways: Vec::new(),
+            fixes: Vec::new(),
+            direct_distance: 0.,
+            airway_distance: 0.,
+            gap_distance: discontinuities(segments).fold(0., |total, length| total + length),
+            regions: Vec::new(),
+            efficiency: None,
+        };
+        for segment in segments {
+            if let Some(name) = segment.name.as_ref().filter(|_| !segment.is_gap()) {
+                if !footprint.airways.contains(name) {
+                    footprint.airways.push(name.clone());
+                }
+            }
+            for point in [&segment.start, &segment.end] {
+                let fix = match point {
+                    ResolvedPoint::Navaid(navaid) => navaid.name.as_ref(),
+                    ResolvedPoint::DesignatedPoint(dp) => Some(&dp.designator),
+                    _ => None,
+                };
+                if let Some(fix) = fix.filter(|fix| !footprint.fixes.contains(fix)) {
+                    footprint.fixes.push(fix.clone());
+                }
+            }
+            if let (Some(start), Some(end)) = (segment.start.coor(), segment.end.coor()) {
+                let length = DistanceModel::Ellipsoidal.distance(&start, &end);
+                match segment.name {
+                    _ if segment.is_gap() => footprint.gap_distance += length,
+                    Some(_) => footprint.airway_distance += length,
+                    None => footprint.direct_distance += length,
+                }
+            }
+        }
+        if let (Some(first), Some(last)) = (segments.first(), segments.last()) {
+            footprint.efficiency = Some(efficiency(segments, &first.start, &last.end));
+        }
+        footprint
+    }
+
+    /// Summarize enriched segments, with the regions they cross, see
+    /// [`region_crossings`](crate::data::eurocontrol::geometry::region_crossings).
+    pub fn from_segments_and_regions(segments: &[ResolvedRouteSegment], regions: &[(String, Vec<(f64, f64)>)]) -> Self {
+        RouteFootprint {
+            regions: region_crossings(segments, regions),
+            ..RouteFootprint::from_segments(segments)
+        }
+    }
+
+    /// The footprint as a table row for `route`.
+    pub fn to_row(&self, route: &str) -> FootprintRow {
+        let efficiency = self.efficiency.as_ref();
+        FootprintRow {
+            route: route.to_string(),
+            airways: self.airways.join(" "),
+            fixes: self.fixes.join(" "),
+            direct_distance: self.direct_distance,
+            airway_distance: self.airway_distance,
+            gap_distance: self.gap_distance,
+            regions: self
+                .regions
+                .iter()
+                .map(|region| region.name.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+            filed_distance: efficiency.and_then(|e| e.filed_distance),
+            great_circle_distance: efficiency.and_then(|e| e.great_circle_distance),
+            efficiency: efficiency.and_then(|e| e.ratio),
+            excess_distance: efficiency.and_then(|e| e.excess_distance),
+        }
+    }
+}
+
+/// The lengths of the discontinuities between consecutive segments, where a
+/// segment does not start at the end of the previous one, with ellipsoidal distances.
+fn discontinuities(segments: &[ResolvedRouteSegment]) -> impl Iterator<Item = f64> + '_ {
+    segments
+        .windows(2)
+        .filter(|pair| pair[0].end != pair[1].start)
+        .filter_map(|pair| Some(DistanceModel::Ellipsoidal.distance(&pair[0].end.coor()?, &pair[1].start.coor()?)))
+}
+
+/// The horizontal efficiency of enriched segments flown from `adep` to `ades`,
+/// with ellipsoidal distances.
+///
+/// Discontinuities between segments count in the filed distance, whether they
+/// are bridged by gap segments or not.
+pub fn efficiency(segments: &[ResolvedRouteSegment], adep: &ResolvedPoint, ades: &ResolvedPoint) -> RouteEfficiency {
+    let model = DistanceModel::Ellipsoidal;
+    let filed_distance = segments
+        .iter()
+        .map(|segment| Some(model.distance(&segment.start.coor()?, &segment.end.coor()?)))
+        .sum::<Option<f64>>()
+        .filter(|_| !segments.is_empty())
+        .map(|distance| discontinuities(segments).fold(distance, |total, length| total + length));
+    let great_circle_distance = adep
+        .coor()
+        .zip(ades.coor())
+        .map(|(adep, ades)| model.distance(&adep, &ades));
+    let (ratio, excess_distance) = match (filed_distance, great_circle_distance) {
+        (Some(filed), Some(gc)) => ((gc > 0.).then(|| filed / gc), Some(filed - gc)),
+        _ => (None, None),
+    };
+    RouteEfficiency {
+        filed_distance,
+        great_circle_distance,
+        ratio,
+        excess_distance,
+    }
+}
+
+impl RouteResponse {
+    /// Build the response for a route string and its enriched segments.
+    ///
+    /// Coordinates keep their full precision: use [`OutputOptions::export`] to
+    /// round them when serializing.
+    pub fn new(route: &str, segments: &[ResolvedRouteSegment]) -> Self {
+        RouteResponse {
+            id: None,
+            route: route.to_string(),
+            segments: segments.iter().map(SegmentJson::from).collect(),
+            unresolved: Vec::new(),
+            missing_initial_group: false,
+            footprint: None,
+        }
+    }
+
+    /// Attach the diagnostics of the enrichment to the response.
+    pub fn with_report(mut self, report: &EnrichmentReport) -> Self {
+        self.unresolved = report.unresolved.clone();
+        self.missing_initial_group = report.missing_initial_group;
+        self
+    }
+
+    /// Attach the footprint of the route to the response.
+    pub fn with_footprint(mut self, footprint: RouteFootprint) -> Self {
+        self.footprint = Some(footprint);
+        self
+    }
+
+    /// Echo the identifier of the request in the response.
+    pub fn with_id(mut self, id: Option<serde_json::Value>) -> Self {
+        self.id = id;
+        self
+    }
+}
+
+impl Export for PointJson {
+    fn to_output(&self, options: &OutputOptions) -> Self {
+        PointJson {
+            latitude: options.round(self.latitude),
+            longitude: options.round(self.longitude),
+            name: self.name.clone(),
+        }
+    }
+}
+
+impl Export for SegmentJson {
+    fn to_output(&self, options: &OutputOptions) -> Self {
+        SegmentJson {
+            start: self.start.to_output(options),
+            end: self.end.to_output(options),
+            ..self.clone()
+        }
+    }
+}
+
+impl Export for RouteResponse {
+    fn to_output(&self, options: &OutputOptions) -> Self {
+        RouteResponse {
+            segments: self.segments.to_output(options),
+            footprint: self.footprint.as_ref().map(|footprint| footprint.to_output(options)),
+            ..self.clone()
+        }
+    }
+}
+
+impl Export for RouteFootprint {
+    fn to_output(&self, options: &OutputOptions) -> Self {
+        let round = |(latitude, longitude): (f64, f64)| (options.round(latitude), options.round(longitude));
+        RouteFootprint {
+            direct_distance: options.distance(self.direct_distance),
+            airway_distance: options.distance(self.airway_distance),
+            gap_distance: options.distance(self.gap_distance),
+            regions: self
+                .regions
+                .iter()
+                .map(|region| RegionCrossing {
+                    entry: round(region.entry),
+                    exit: round(region.exit),
+                    ..region.clone()
+                })
+                .collect(),
+            efficiency: self.efficiency.as_ref().map(|efficiency| efficiency.to_output(options)),
+            ..self.clone()
+        }
+    }
+}
+
+impl Export for RouteEfficiency {
+    fn to_output(&self, options: &OutputOptions) -> Self {
+        RouteEfficiency {
+            filed_distance: self.filed_distance.map(|d| options.distance(d)),
+            great_circle_distance: self.great_circle_distance.map(|d| options.distance(d)),
+            excess_distance: self.excess_distance.map(|d| options.distance(d)),
+            ..self.clone()
+        }
+    }
+}
+
+impl Export for FootprintRow {
+    fn to_output(&self, options: &OutputOptions) -> Self {
+        FootprintRow {
+            direct_distance: options.distance(self.direct_distance),
+            airway_distance: options.distance(self.airway_distance),
+            gap_distance: options.distance(self.gap_distance),
+            filed_distance: self.filed_distance.map(|d| options.distance(d)),
+            great_circle_distance: self.great_circle_distance.map(|d| options.distance(d)),
+            excess_distance: self.excess_distance.map(|d| options.distance(d)),
+            ..self.clone()
+        }
+    }
+}
+
+impl Export for ProcedureLegRow {
+    fn to_output(&self, options: &OutputOptions) -> Self {
+        ProcedureLegRow {
+            start_latitude: options.round(self.start_latitude),
+            start_longitude: options.round(self.start_longitude),
+            end_latitude: options.round(self.end_latitude),
+            end_longitude: options.round(self.end_longitude),
+            ..self.clone()
+        }
+    }
+}
+
+impl Export for FixRecord {
+    fn to_output(&self, options: &OutputOptions) -> Self {
+        FixRecord {
+            latitude: options.round(self.latitude),
+            longitude: options.round(self.longitude),
+            ..self.clone()
+        }
+    }
+}
+
+impl Export for AirwaySegmentRow {
+    fn to_output(&self, options: &OutputOptions) -> Self {
+        AirwaySegmentRow {
+            start_latitude: options.round(self.start_latitude),
+            start_longitude: options.round(self.start_longitude),
+            end_latitude: options.round(self.end_latitude),
+            end_longitude: options.round(self.end_longitude),
+            length: options.distance(self.length),
+            ..self.clone()
+        }
+    }
+}
+
+impl Export for ResolvedPoint {
+    fn to_output(&self, options: &OutputOptions) -> Self {
+        let mut point = self.clone();
+        let (latitude, longitude) = match &mut point {
+            ResolvedPoint::AirportHeliport(airport) => (&mut airport.latitude, &mut airport.longitude),
+            ResolvedPoint::Navaid(navaid) => (&mut navaid.latitude, &mut navaid.longitude),
+            ResolvedPoint::DesignatedPoint(dp) => (&mut dp.latitude, &mut dp.longitude),
+            ResolvedPoint::Coordinates { latitude, longitude } => (latitude, longitude),
+            ResolvedPoint::None => return point,
+        };
+        *latitude = options.round(*latitude);
+        *longitude = options.round(*longitude);
+        point
+    }
+}
+
+impl Export for ResolvedRouteSegment {
+    fn to_output(&self, options: &OutputOptions) -> Self {
+        ResolvedRouteSegment {
+            start: self.start.to_output(options),
+            end: self.end.to_output(options),
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::eurocontrol::aixm::designated_point::DesignatedPoint;
+    use crate::data::eurocontrol::aixm::navaid::Navaid;
+
+    #[test]
+    fn test_route_response_keys() {
+        let segments = vec![
+            ResolvedRouteSegment {
+                start: ResolvedPoint::Navaid(Navaid {
+                    identifier: "nav-abc".to_string(),
+                    name: Some("ABC".to_string()),
+                    latitude: 45.0,
+                    longitude: 0.5,
+                    ..Default::default()
+                }),
+                end: ResolvedPoint::DesignatedPoint(DesignatedPoint {
+                    identifier: "dp-def".to_string(),
+                    designator: "DEF".to_string(),
+                    latitude: 45.0,
+                    longitude: 2.0,
+                    ..Default::default()
+                }),
+                name: Some("UN1".to_string()),
+                altitude: Some(Altitude::FlightLevel(350)),
+                speed: Some(Speed::Knots(450)),
+                level: Some(LevelConstraint::Level(Altitude::FlightLevel(350))),
+                overfly: false,
+                stay: None,
+                source_segment: None,
+                is_reversed: false,
+                overlay: false,
+            },
+            ResolvedRouteSegment {
+                start: ResolvedPoint::Coordinates {
+                    latitude: 45.0,
+                    longitude: 2.0,
+                },
+                end: ResolvedPoint::Coordinates {
+                    latitude: 46.0,
+                    longitude: 3.0,
+                },
+                name: None,
+                altitude: None,
+                speed: None,
+                level: None,
+                overfly: false,
+                stay: None,
+                source_segment: None,
+                is_reversed: false,
+                overlay: false,
+            },
+        ];
+        let response = RouteResponse::new("N0450F350 ABC UN1 DEF DCT 46N003E", &segments);
+        let json = serde_json::to_value(&response).unwrap();
+
+        let keys = |value: &serde_json::Value| value.as_object().unwrap().keys().cloned().collect::<Vec<_>>();
+        assert_eq!(keys(&json), vec!["route", "segments"]);
+        let segments = json["segments"].as_array().unwrap();
+        assert_eq!(
+            keys(&segments[0]),
+            vec!["altitude", "end", "level", "name", "speed", "start"]
+        );
+        assert_eq!(keys(&segments[0]["start"]), vec!["latitude", "longitude", "name"]);
+        assert_eq!(keys(&segments[1]), vec!["end", "start"]);
+        assert_eq!(keys(&segments[1]["end"]), vec!["latitude", "longitude"]);
+        assert_eq!(
+            segments[0].to_string(),
+            r#"{"altitude":{"FL":350},"end":{"latitude":45.0,"longitude":2.0,"name":"DEF"},"level":{"level":{"FL":350}},"name":"UN1","speed":{"kts":450},"start":{"latitude":45.0,"longitude":0.5,"name":"ABC"}}"#
+        );
+    }
+
+    #[test]
+    fn test_route_response_precision() {
+        let segments = vec![ResolvedRouteSegment::direct(
+            ResolvedPoint::Coordinates {
+                latitude: 43.629_075_123_456,
+                longitude: 1.363_817_345_678,
+            },
+            ResolvedPoint::Coordinates {
+                latitude: -0.1 - 0.2,
+                longitude: 2.0,
+            },
+        )];
+        let decimals = |value: &serde_json::Value| {
+            let text = value.to_string();
+            text.split_once('.').map_or(0, |(_, decimals)| decimals.len())
+        };
+        let response = RouteResponse::new("", &segments);
+        for precision in [4, 6, 7] {
+            let options = OutputOptions::parse(Some(&precision.to_string()), None).unwrap();
+            let json = serde_json::to_value(options.export(&response)).unwrap();
+            for key in ["latitude", "longitude"] {
+                let value = &json["segments"][0]["start"][key];
+                assert_eq!(decimals(value), precision, "{value}");
+            }
+            assert_eq!(json["segments"][0]["end"]["latitude"], -0.3);
+        }
+        let json = serde_json::to_value(OutputOptions::default().export(&response)).unwrap();
+        assert_eq!(json["segments"][0]["start"]["latitude"], 43.629075);
+        // The response itself keeps full precision
+        assert_eq!(response.segments[0].start.latitude, 43.629_075_123_456);
+        let json = serde_json::to_value(OutputOptions::default().export(&segments)).unwrap();
+        assert_eq!(json[0]["start"]["longitude"], 1.363817);
+        assert!(OutputOptions::parse(Some("16"), None).is_err());
+        assert!(OutputOptions::parse(Some("four"), None).is_err());
+    }
+
+    #[test]
+    fn test_route_efficiency() {
+        let point = |latitude: f64, longitude: f64| ResolvedPoint::Coordinates { latitude, longitude };
+        let segment = ResolvedRouteSegment::direct;
+        // A dog leg north of the great circle, 12% longer than it
+        let (adep, ades) = (point(45., 0.), point(45., 2.));
+        let segments = [
+            segment(adep.clone(), point(45.3622, 1.)),
+            segment(point(45.3622, 1.), ades.clone()),
+        ];
+        let result = efficiency(&segments, &adep, &ades);
+        let (filed, gc) = (result.filed_distance.unwrap(), result.great_circle_distance.unwrap());
+        assert!((gc - 157_400.).abs() < 500., "{result:?}");
+        assert!((result.ratio.unwrap() - 1.12).abs() < 1e-3, "{result:?}");
+        assert!((result.excess_distance.unwrap() - 0.12 * gc).abs() < 200., "{result:?}");
+        assert_eq!(filed - gc, result.excess_distance.unwrap());
+
+        let footprint = RouteFootprint::from_segments(&segments);
+        assert_eq!(footprint.efficiency.as_ref(), Some(&result));
+        let row = footprint
+            .to_row("")
+            .to_output(&OutputOptions::parse(None, Some("nm")).unwrap());
+        assert!((row.great_circle_distance.unwrap() - gc / NAUTICAL_MILE).abs() < 1e-6);
+        assert_eq!(row.efficiency, result.ratio);
+
+        // Unresolved endpoints and zero distances
+        let unresolved = efficiency(&segments, &adep, &ResolvedPoint::None);
+        assert_eq!(unresolved.filed_distance, Some(filed));
+        assert_eq!((unresolved.great_circle_distance, unresolved.ratio), (None, None));
+        let broken = [segment(adep.clone(), ResolvedPoint::None)];
+        let broken = efficiency(&broken, &adep, &ades);
+        assert_eq!((broken.filed_distance, broken.excess_distance), (None, None));
+        let round_trip = [segment(adep.clone(), ades.clone()), segment(ades.clone(), adep.clone())];
+        let round_trip = efficiency(&round_trip, &adep, &adep);
+        assert_eq!((round_trip.great_circle_distance, round_trip.ratio), (Some(0.), None));
+        assert_eq!(round_trip.excess_distance, round_trip.filed_distance);
+        assert_eq!(efficiency(&[], &adep, &ades).filed_distance, None);
+    }
+
+    #[test]
+    fn test_route_footprint() {
+        let fix = |designator: &str, longitude: f64| {
+            ResolvedPoint::DesignatedPoint(DesignatedPoint {
+                identifier: format!("dp-{}", designator.to_lowercase()),
+                designator: designator.to_string(),
+                latitude: 45.0,
+                longitude,
+                ..Default::default()
+            })
+        };
+        let coordinates = |longitude: f64| ResolvedPoint::Coordinates {
+            latitude: 45.0,
+            longitude,
+        };
+        let segment = |start: ResolvedPoint, end: ResolvedPoint, name: Option<&str>| ResolvedRouteSegment {
+            name: name.map(str::to_string),
+            ..ResolvedRouteSegment::direct(start, end)
+        };
+        // 45N000E DCT ABC UN1 GHI UN1 DEF DCT 45N004E, flying back along UN1
+        let segments = vec![
+            segment(coordinates(0.), fix("ABC", 1.), None),
+            segment(fix("ABC", 1.), fix("DEF", 2.), Some("UN1")),
+            segment(fix("DEF", 2.), fix("GHI", 3.), Some("UN1")),
+            segment(fix("GHI", 3.), fix("DEF", 2.), Some("UN1")),
+            segment(fix("DEF", 2.), coordinates(4.), None),
+            segment(coordinates(4.), ResolvedPoint::None, None),
+        ];
+        let footprint = RouteFootprint::from_segments(&segments);
+        assert_eq!(footprint.airways, ["UN1"]);
+        assert_eq!(footprint.fixes, ["ABC", "DEF", "GHI"]);
+        assert!(footprint.regions.is_empty());
+        let degree =
+            DistanceModel::Ellipsoidal.distance(&coordinates(0.).coor().unwrap(), &fix("ABC", 1.).coor().unwrap());
+        assert!((footprint.airway_distance - 3. * degree).abs() < 10., "{footprint:?}");
+        assert!((footprint.direct_distance - 3. * degree).abs() < 10., "{footprint:?}");
+        assert_eq!(footprint.gap_distance, 0.);
+
+        // Without gap segments, a discontinuity between two segments counts as a gap
+        let mut discontinuous = segments.clone();
+        discontinuous.remove(2);
+        let gapped = RouteFootprint::from_segments(&discontinuous);
+        assert!((gapped.airway_distance - 2. * degree).abs() < 10., "{gapped:?}");
+        assert!((gapped.gap_distance - degree).abs() < 10., "{gapped:?}");
+
+        // Two adjacent FIRs, with a common boundary at 2.5E
+        let rectangle = |west, east| vec![(40., west), (40., east), (50., east), (50., west)];
+        let regions = [
+            ("LFBB".to_string(), rectangle(-1., 2.5)),
+            ("LFMM".to_string(), rectangle(2.5, 5.)),
+        ];
+        let footprint = RouteFootprint::from_segments_and_regions(&segments, &regions);
+        let names = footprint.regions.iter().map(|r| r.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["LFBB", "LFMM", "LFBB", "LFMM"]);
+        let boundary =
+            |(latitude, longitude): (f64, f64)| (longitude - 2.5).abs() < 1e-6 && (latitude - 45.).abs() < 0.01;
+        assert_eq!(footprint.regions[0].entry, (45., 0.));
+        assert!(boundary(footprint.regions[0].exit));
+        assert!(boundary(footprint.regions[1].entry) && boundary(footprint.regions[1].exit));
+        assert!(boundary(footprint.regions[2].entry) && boundary(footprint.regions[2].exit));
+        assert!(boundary(footprint.regions[3].entry));
+        assert!((footprint.regions[3].exit.1 - 4.).abs() < 1e-6);
+
+        let response = RouteResponse::new("", &segments).with_footprint(footprint.clone());
+        let options = OutputOptions::parse(Some("2"), Some("nm")).unwrap();
+        let json = serde_json::to_value(options.export(&response)).unwrap();
+        assert_eq!(json["footprint"]["regions"][1]["entry"], serde_json::json!([45.0, 2.5]));
+        assert_eq!(
+            json["footprint"]["airway_distance"],
+            footprint.airway_distance / NAUTICAL_MILE
+        );
+
+        let row = footprint.to_row("45N000E DCT ABC UN1 GHI UN1 DEF DCT 45N004E");
+        assert_eq!(row.fixes, "ABC DEF GHI");
+        assert_eq!(row.regions, "LFBB LFMM LFBB LFMM");
+    }
+
+    #[test]
+    fn test_distance_unit() {
+        let options = OutputOptions::parse(None, Some("NM")).unwrap();
+        assert_eq!(options.coord_decimals, DEFAULT_PRECISION as u8);
+        assert_eq!(options.distance_unit, DistanceUnit::NauticalMiles);
+        assert_eq!(options.distance(18_520.), 10.);
+        assert_eq!(OutputOptions::default().distance(18_520.), 18_520.);
+        assert_eq!(DistanceUnit::parse("metres"), Some(DistanceUnit::Meters));
+        assert!(OutputOptions::parse(None, Some("km")).is_err());
+
+        let row = AirwaySegmentRow {
+            airway: "UN1".to_string(),
+            start: Some("ABC".to_string()),
+            start_latitude: 45.123_456_789,
+            start_longitude: 1.0,
+            end: Some("DEF".to_string()),
+            end_latitude: 45.0,
+            end_longitude: 2.0,
+            length: 92_600.,
+            direction: Some(RouteDirection::Forward),
+            lower_limit: Some("FL245".to_string()),
+            upper_limit: None,
+        };
+        let csv = TableFormat::Csv.render(std::slice::from_ref(&row), &options).unwrap();
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("UN1,ABC,45.123457,1.0,DEF,45.0,2.0,50.0,FORWARD,FL245,")
+        );
+        assert_eq!(row.length, 92_600.);
+    }
+}
@@ -0,0 +1,170 @@
+//! A grid index of route segments, for nearest segment queries.
+//!
+//! Conformance monitoring asks "which airway am I on?" for many positions, and
+//! scanning every segment of the network for each of them is too slow. The index
+//! registers each segment in the cells of a regular latitude/longitude grid crossed
+//! by its geodesic, then searches the cells in rings of growing size around a
+//! position, until no cell left can hold a segment closer than the best one found.
+//!
+//! Distances are those of [`project`](super::geometry::project): to the foot of
+//! the perpendicular, clamped to the segment endpoints.
+
+use std::collections::{HashMap, HashSet};
+
+use geodesy::prelude::*;
+
+use crate::data::eurocontrol::aixm::identifier::GmlId;
+use crate::data::eurocontrol::geometry;
+use crate::distance::{DistanceModel, WGS84};
+
+/// Size of the grid cells, in degrees.
+const CELL: f64 = 1.;
+const LAT_CELLS: i32 = (180. / CELL) as i32;
+const LON_CELLS: i32 = (360. / CELL) as i32;
+
+/// Largest distance between two consecutive points sampled along a segment, in meters.
+const SAMPLE_SPACING: f64 = 10_000.;
+
+/// Smallest radius of curvature of the WGS84 ellipsoid (along meridians, at the
+/// equator), so that distances derived from angles are never overestimated.
+const MIN_RADIUS: f64 = 6_335_439.;
+
+/// Route segments by grid cell, as described in the [module documentation](self).
+#[derive(Debug, Default)]
+pub(crate) struct SegmentIndex {
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    /// Identifier and endpoints of each segment
+    segments: Vec<(GmlId, Coor2D, Coor2D)>,
+}
+
+impl SegmentIndex {
+    /// Index segments given by their identifier and endpoints (as `Coor2D::geo`).
+    pub(crate) fn new(segments: impl IntoIterator<Item = (GmlId, Coor2D, Coor2D)>) -> Self {
+        let mut index = SegmentIndex {
+            segments: segments.into_iter().collect(),
+            ..Default::default()
+        };
+        for (i, (_, start, end)) in index.segments.iter().enumerate() {
+            let (course, length) = DistanceModel::Ellipsoidal.inverse(start, end);
+            let n = (length / SAMPLE_SPACING).ceil().max(1.) as usize;
+            let mut last = None;
+            for k in 0..=n {
+                let sample = WGS84.geodesic_fwd(start, course.to_radians(), length * k as f64 / n as f64);
+                let cell = cell(&Coor2D::raw(sample[0], sample[1]));
+                if last.replace(cell) != Some(cell) {
+                    index.cells.entry(cell).or_default().push(i);
+                }
+            }
+        }
+        for segments in index.cells.values_mut() {
+            segments.dedup();
+        }
+        index
+    }
+
+    /// The identifier of the segment nearest to a position, and its distance in meters.
+    pub(crate) fn nearest(&self, latitude: f64, longitude: f64) -> Option<(&GmlId, f64)> {
+        let point = Coor2D::geo(latitude, longitude);
+        let (row, col) = cell(&point);
+        let mut best: Option<(usize, f64)> = None;
+        let mut seen = HashSet::new();
+        for k in 0..=LAT_CELLS.max(LON_CELLS / 2) {
+            if best.is_some_and(|(_, distance)| distance <= lower_bound(latitude, k)) {
+                break;
+            }
+            for cell in ring(row, col, k) {
+                for &i in self.cells.get(&cell).into_iter().flatten() {
+                    if !seen.insert(i) {
+                        continue;
+                    }
+                    let (_, start, end) = &self.segments[i];
+                    let (_, distance) = geometry::project(start, end, &point);
+                    if best.is_none_or(|(_, best)| distance < best) {
+                        best = Some((i, distance));
+                    }
+                }
+            }
+        }
+        best.map(|(i, distance)| (&self.segments[i].0, distance))
+    }
+}
+
+/// The (row, column) of the cell holding a point, as `Coor2D::geo`.
+fn cell(coor: &Coor2D) -> (i32, i32) {
+    let (longitude, latitude) = (coor[0].to_degrees(), coor[1].to_degrees());
+    let row = (((latitude + 90.) / CELL).floor() as i32).clamp(0, LAT_CELLS - 1);
+    let col = (((longitude + 180.) / CELL).floor() as i32).rem_euclid(LON_CELLS);
+    (row, col)
+}
+
+/// The cells at `k` cells from (`row`, `col`) in either direction, wrapping around
+/// the antimeridian.
+fn ring(row: i32, col: i32, k: i32) -> impl Iterator<Item = (i32, i32)> {
+    let edges = if k == 0 { vec![0] } else { vec![-k, k] };
+    let rows = edges
+        .clone()
+        .into_iter()
+        .flat_map(move |dr| (-k..=k).map(move |dc| (dr, dc)));
+    let cols = (1 - k..k).flat_map(move |dr| edges.clone().into_iter().map(move |dc| (dr, dc)));
+    rows.chain(cols)
+        .map(move |(dr, dc)| (row + dr, (col + dc).rem_euclid(LON_CELLS)))
+        .filter(|(r, _)| (0..LAT_CELLS).contains(r))
+}
+
+/// A lower bound of the distance in meters from a position at `latitude` to the
+/// segments not registered in the first `k` rings around it.
+///
+/// A point of such a segment lies in ring `k - 1` at least, since consecutive
+/// samples of a segment are in the same or adjacent cells (except within a few
+/// degrees of the poles, where cells are narrower than the sample spacing), so it
+/// differs from the position by more than `k - 2` cells in latitude or in longitude.
+fn lower_bound(latitude: f64, k: i32) -> f64 {
+    let angle = (f64::from(k - 2) * CELL).to_radians();
+    if angle <= 0. {
+        return 0.;
+    }
+    let max_latitude = (latitude.abs() + f64::from(k + 1) * CELL).min(90.).to_radians();
+    let across = 2. * (max_latitude.cos() * (angle.min(std::f64::consts::PI) / 2.).sin()).asin();
+    angle.min(across) * MIN_RADIUS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_segment_index() {
+        let segment = |id: &str, (lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64)| {
+            (GmlId::from(id), Coor2D::geo(lat1, lon1), Coor2D::geo(lat2, lon2))
+        };
+        let segments = vec![
+            segment("a", (45., 1.), (45., 2.)),
+            segment("b", (45., 2.), (47., 6.)),
+            segment("c", (60., -30.), (55., -10.)),
+            segment("d", (-10., 179.5), (-12., -178.)),
+            segment("e", (20., 100.), (21., 100.)),
+        ];
+        let index = SegmentIndex::new(segments.clone());
+        let positions = [
+            (45.1, 1.5),
+            (46.2, 3.),
+            (58., -20.),
+            (-11., 180.),
+            (-11.5, -179.),
+            (50., 60.),
+            (-80., 0.),
+            (89.9, 10.),
+        ];
+        for (latitude, longitude) in positions {
+            let point = Coor2D::geo(latitude, longitude);
+            let expected = segments
+                .iter()
+                .map(|(id, start, end)| (id, geometry::project(start, end, &point).1))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            let (id, distance) = index.nearest(latitude, longitude).unwrap();
+            assert_eq!((id, distance), expected, "{latitude}, {longitude}");
+        }
+        assert!(SegmentIndex::default().nearest(45., 1.).is_none());
+    }
+}
@@ -74,6 +74,19 @@ pub enum Field15Element {
     Connector(Connector),
     /// A modifier that changes speed, altitude, or other parameters
     Modifier(Modifier),
+    /// Diagnostic emitted first when the route does not begin with a speed/level group
+    MissingInitialGroup {
+        /// The malformed group found in place of a valid one, if any
+        #[serde(rename = "missing_initial_group")]
+        malformed: Option<String>,
+    },
+    /// Marker emitted before a point filed with an overfly prefix (e.g. `@LACOU`),
+    /// so that the point itself still parses and resolves as any other point
+    Overfly {
+        /// The point as filed, with its prefix
+        #[serde(rename = "overfly")]
+        token: String,
+    },
 }
 
 /// A point in the route (waypoint, coordinate, or navaid)
@@ -192,6 +205,7 @@ pub struct Modifier {
 
 /// Speed representation
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Speed {
     /// Knots (N followed by 4 digits)
     #[serde(rename = "kts")]
@@ -206,6 +220,7 @@ pub enum Speed {
 
 /// Altitude representation
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Altitude {
     /// Flight level (F followed by 3 digits)
     #[serde(rename = "FL")]
@@ -224,12 +239,93 @@ pub enum Altitude {
     Vfr,
 }
 
+/// Requested level for a portion of the route
+///
+/// A level group is either a single level, a block between two levels
+/// (e.g. `F310F350` for a cruise climb), or VFR.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum LevelConstraint {
+    /// A single requested level
+    #[serde(rename = "level")]
+    Level(Altitude),
+    /// A block of levels, from `lower` to `upper`
+    #[serde(rename = "block")]
+    Block { lower: Altitude, upper: Altitude },
+    /// VFR flight, no specific level
+    #[serde(rename = "VFR")]
+    Vfr,
+}
+
+impl Altitude {
+    /// Convert the altitude to feet, if it is not VFR
+    pub fn feet(&self) -> Option<f64> {
+        const FEET_PER_METER: f64 = 3.28084;
+        match self {
+            Altitude::FlightLevel(fl) => Some(*fl as f64 * 100.0),
+            Altitude::MetricLevel(s) => Some(*s as f64 * 10.0 * FEET_PER_METER),
+            Altitude::Altitude(a) => Some(*a as f64 * 100.0),
+            Altitude::MetricAltitude(m) => Some(*m as f64 * 10.0 * FEET_PER_METER),
+            Altitude::Vfr => None,
+        }
+    }
+}
+
+impl From<Altitude> for LevelConstraint {
+    fn from(altitude: Altitude) -> Self {
+        match altitude {
+            Altitude::Vfr => LevelConstraint::Vfr,
+            altitude => LevelConstraint::Level(altitude),
+        }
+    }
+}
+
+impl LevelConstraint {
+    /// The lowest altitude of the constraint, if it is not VFR
+    pub fn lower(&self) -> Option<&Altitude> {
+        match self {
+            LevelConstraint::Level(altitude) => Some(altitude),
+            LevelConstraint::Block { lower, .. } => Some(lower),
+            LevelConstraint::Vfr => None,
+        }
+    }
+
+    /// The highest altitude of the constraint, if it is not VFR
+    pub fn upper(&self) -> Option<&Altitude> {
+        match self {
+            LevelConstraint::Level(altitude) => Some(altitude),
+            LevelConstraint::Block { upper, .. } => Some(upper),
+            LevelConstraint::Vfr => None,
+        }
+    }
+}
+
+impl Modifier {
+    /// The level constraint expressed by this modifier, if any
+    pub fn level(&self) -> Option<LevelConstraint> {
+        match (&self.altitude, &self.altitude_cruise_to) {
+            (Some(Altitude::Vfr), _) => Some(LevelConstraint::Vfr),
+            (Some(lower), Some(upper)) => Some(LevelConstraint::Block {
+                lower: lower.clone(),
+                upper: upper.clone(),
+            }),
+            (Some(altitude), None) => Some(LevelConstraint::Level(altitude.clone())),
+            (None, _) => None,
+        }
+    }
+}
+
 impl fmt::Display for Field15Element {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Field15Element::Point(p) => write!(f, "Point({})", p),
             Field15Element::Connector(c) => write!(f, "Connector({})", c),
             Field15Element::Modifier(m) => write!(f, "Modifier({})", m),
+            Field15Element::MissingInitialGroup { malformed: None } => write!(f, "MissingInitialGroup"),
+            Field15Element::MissingInitialGroup { malformed: Some(token) } => {
+                write!(f, "MissingInitialGroup({})", token)
+            }
+            Field15Element::Overfly { token } => write!(f, "Overfly({})", token),
         }
     }
 }
@@ -279,15 +375,22 @@ impl fmt::Display for Connector {
 
 impl fmt::Display for Modifier {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match (&self.speed, &self.altitude, self.cruise_climb) {
-            (Some(s), Some(a), true) => write!(f, "{}{}PLUS", s, a),
-            (Some(s), Some(a), false) => write!(f, "{}{}", s, a),
-            (Some(s), None, true) => write!(f, "{}PLUS", s),
-            (Some(s), None, false) => write!(f, "{}", s),
-            (None, Some(a), true) => write!(f, "{}PLUS", a),
-            (None, Some(a), false) => write!(f, "{}", a),
-            (None, None, _) => write!(f, ""),
+        if self.speed.is_none() && self.altitude.is_none() {
+            return Ok(());
+        }
+        if let Some(s) = &self.speed {
+            write!(f, "{}", s)?;
+        }
+        if let Some(a) = &self.altitude {
+            write!(f, "{}", a)?;
+        }
+        if let Some(a) = &self.altitude_cruise_to {
+            write!(f, "{}", a)?;
         }
+        if self.cruise_climb {
+            write!(f, "PLUS")?;
+        }
+        Ok(())
     }
 }
 
@@ -313,6 +416,16 @@ impl fmt::Display for Altitude {
     }
 }
 
+impl fmt::Display for LevelConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelConstraint::Level(a) => write!(f, "{}", a),
+            LevelConstraint::Block { lower, upper } => write!(f, "{}{}", lower, upper),
+            LevelConstraint::Vfr => write!(f, "VFR"),
+        }
+    }
+}
+
 /// A parser for ICAO Field 15 route strings
 pub struct Field15Parser;
 
@@ -321,14 +434,40 @@ impl Field15Parser {
     ///
     /// The parser treats forward slash (/) as both whitespace and a token separator,
     /// similar to the reference Python implementation's tokenization approach.
+    /// A slash followed by a speed/level group introduces a change of speed or level
+    /// at the previous point; between two points or airways, as in older formats
+    /// (e.g. `GODOS/UL607/ABNEG`), it only separates them.
+    ///
+    /// Routes should begin with a speed/level group: when they do not, the first
+    /// element is a [`Field15Element::MissingInitialGroup`] diagnostic, and a
+    /// malformed group (e.g. `N45F350`) is skipped instead of parsed as a point.
     pub fn parse(route: &str) -> Vec<Field15Element> {
         let mut elements = Vec::new();
         let tokens = Self::tokenize(route);
         let mut i = 0;
         let mut first_point_parsed = false;
 
+        if let Some(&first) = tokens.first() {
+            if Self::parse_modifier(first).is_none() {
+                let malformed = Self::is_malformed_group(first).then(|| first.to_string());
+                if malformed.is_some() {
+                    i += 1;
+                }
+                elements.push(Field15Element::MissingInitialGroup { malformed });
+            }
+        }
+
         while i < tokens.len() {
-            let token = tokens[i];
+            let mut token = tokens[i];
+
+            // Strip an overfly prefix: the marker is inserted before the point once parsed
+            let overfly = match token.strip_prefix('@') {
+                Some(fix) if !fix.is_empty() => {
+                    token = fix;
+                    Some((tokens[i].to_string(), elements.len()))
+                }
+                _ => None,
+            };
 
             // Handle truncate indicator 'T' - must be last token
             if token == "T" {
@@ -339,9 +478,14 @@ impl Field15Parser {
                 break;
             }
 
-            // Handle forward slash - it signals a speed/altitude change is coming
+            // Handle forward slash - it signals a speed/altitude change is coming, or
+            // only separates two points or airways. A malformed speed/level group after
+            // a slash is dropped rather than parsed as a point or an airway.
             if token == "/" {
-                i += 1;
+                let malformed = tokens
+                    .get(i + 1)
+                    .is_some_and(|next| Self::parse_modifier(next).is_none() && Self::is_malformed_group(next));
+                i += if malformed { 2 } else { 1 };
                 continue;
             }
 
@@ -440,6 +584,11 @@ impl Field15Parser {
                 first_point_parsed = true;
             }
 
+            if let Some((token, index)) = overfly {
+                if matches!(elements.get(index), Some(Field15Element::Point(_))) {
+                    elements.insert(index, Field15Element::Overfly { token });
+                }
+            }
             i += 1;
         }
 
@@ -566,6 +715,16 @@ impl Field15Parser {
         }
     }
 
+    /// Check if a token looks like a speed/level group without being a valid one:
+    /// a speed indicator followed by digits, then a level indicator.
+    fn is_malformed_group(token: &str) -> bool {
+        let Some(rest) = token.strip_prefix(['N', 'K', 'M']) else {
+            return false;
+        };
+        let level = rest.trim_start_matches(|c: char| c.is_ascii_digit());
+        level.len() < rest.len() && level.starts_with(['F', 'A', 'S', 'M'])
+    }
+
     /// Parse speed component
     fn parse_speed(s: &str) -> Option<Speed> {
         if s.len() < 4 {
@@ -983,6 +1142,30 @@ mod tests {
         assert_eq!(modifiers.len(), 2);
     }
 
+    #[test]
+    fn test_slash_separators() {
+        let elements = Field15Parser::parse("N0450F350 GODOS/UL607/ABNEG/N0460F370/UL607/LASAT/N046F37 DCT ROXOG");
+        let n0460f370 = Modifier {
+            speed: Some(Speed::Knots(460)),
+            altitude: Some(Altitude::FlightLevel(370)),
+            altitude_cruise_to: None,
+            cruise_climb: false,
+        };
+        assert_eq!(
+            elements[1..],
+            [
+                Field15Element::Point(Point::Waypoint("GODOS".to_string())),
+                Field15Element::Connector(Connector::Airway("UL607".to_string())),
+                Field15Element::Point(Point::Waypoint("ABNEG".to_string())),
+                Field15Element::Modifier(n0460f370),
+                Field15Element::Connector(Connector::Airway("UL607".to_string())),
+                Field15Element::Point(Point::Waypoint("LASAT".to_string())),
+                Field15Element::Connector(Connector::Direct),
+                Field15Element::Point(Point::Waypoint("ROXOG".to_string())),
+            ]
+        );
+    }
+
     #[test]
     fn test_coordinate_validation() {
         assert!(Field15Parser::is_coordinate("5020N"));
@@ -1624,4 +1807,89 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_level_constraint() {
+        let elements = Field15Parser::parse("N0450F310F350 LACOU/N0100VFR");
+        let levels = elements
+            .iter()
+            .filter_map(|e| match e {
+                Field15Element::Modifier(m) => m.level(),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            levels,
+            vec![
+                LevelConstraint::Block {
+                    lower: Altitude::FlightLevel(310),
+                    upper: Altitude::FlightLevel(350)
+                },
+                LevelConstraint::Vfr
+            ]
+        );
+        assert_eq!(levels[0].to_string(), "F310F350");
+        assert_eq!(elements[0].to_string(), "Modifier(N0450F310F350)");
+        assert_eq!(levels[0].upper().and_then(Altitude::feet), Some(35000.0));
+        assert_eq!(
+            serde_json::to_string(&levels).unwrap(),
+            r#"[{"block":{"lower":{"FL":310},"upper":{"FL":350}}},"VFR"]"#
+        );
+    }
+
+    #[test]
+    fn test_missing_initial_group() {
+        let elements = Field15Parser::parse("LACOU UM184 CNA");
+        assert_eq!(elements[0], Field15Element::MissingInitialGroup { malformed: None });
+        assert_eq!(elements[1], Field15Element::Point(Point::Waypoint("LACOU".to_string())));
+        assert_eq!(
+            serde_json::to_string(&elements[0]).unwrap(),
+            r#"{"missing_initial_group":null}"#
+        );
+
+        let elements = Field15Parser::parse("N45F350 LACOU UM184 CNA");
+        assert_eq!(
+            elements[0],
+            Field15Element::MissingInitialGroup {
+                malformed: Some("N45F350".to_string())
+            }
+        );
+        // The malformed group is not mistaken for a point
+        assert_eq!(elements[1], Field15Element::Point(Point::Waypoint("LACOU".to_string())));
+        assert_eq!(elements.len(), 4);
+
+        let elements = Field15Parser::parse("N0450F350 LACOU UM184 CNA");
+        assert!(!elements
+            .iter()
+            .any(|e| matches!(e, Field15Element::MissingInitialGroup { .. })));
+    }
+
+    #[test]
+    fn test_overfly_prefix() {
+        let elements = Field15Parser::parse("N0450F350 LACOU DCT @CNA DCT @4530N00130E UM184 PPN");
+        assert_eq!(
+            elements[3],
+            Field15Element::Overfly {
+                token: "@CNA".to_string()
+            }
+        );
+        // The point after DCT is still parsed as a point
+        assert_eq!(elements[4], Field15Element::Point(Point::Waypoint("CNA".to_string())));
+        assert_eq!(
+            elements[6],
+            Field15Element::Overfly {
+                token: "@4530N00130E".to_string()
+            }
+        );
+        assert_eq!(elements[7], Field15Element::Point(Point::Coordinates((45.5, 1.5))));
+        assert_eq!(
+            elements[8],
+            Field15Element::Connector(Connector::Airway("UM184".to_string()))
+        );
+        assert_eq!(serde_json::to_string(&elements[3]).unwrap(), r#"{"overfly":"@CNA"}"#);
+
+        // A lone prefix is not a point
+        let elements = Field15Parser::parse("N0450F350 LACOU DCT @ CNA");
+        assert!(!elements.iter().any(|e| matches!(e, Field15Element::Overfly { .. })));
+    }
 }
@@ -0,0 +1,84 @@
+//! Distance and bearing computations on the Earth surface.
+//!
+//! Ellipsoidal computations on the WGS84 ellipsoid are accurate but slower;
+//! a spherical (haversine) approximation is often enough for bulk processing.
+
+use geodesy::prelude::*;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+/// The WGS84 ellipsoid.
+pub static WGS84: Lazy<Ellipsoid> = Lazy::new(|| Ellipsoid::named("WGS84").unwrap());
+
+/// Mean Earth radius in meters, used for spherical computations.
+pub const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// One nautical mile in meters.
+pub const NAUTICAL_MILE: f64 = 1852.;
+
+/// The model of the Earth used for distance and bearing computations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DistanceModel {
+    /// Geodesics on the WGS84 ellipsoid
+    #[default]
+    #[serde(rename = "ellipsoidal")]
+    Ellipsoidal,
+    /// Great circles on a sphere (haversine formula)
+    #[serde(rename = "spherical")]
+    Spherical,
+}
+
+impl DistanceModel {
+    /// Distance in meters between two points (as returned by `Coor2D::geo`).
+    pub fn distance(&self, a: &Coor2D, b: &Coor2D) -> f64 {
+        match self {
+            DistanceModel::Ellipsoidal => WGS84.distance(a, b),
+            DistanceModel::Spherical => haversine(a, b),
+        }
+    }
+
+    /// Initial bearing in degrees (clockwise from north, in `[-180, 180]`)
+    /// and distance in meters from `a` to `b`.
+    pub fn inverse(&self, a: &Coor2D, b: &Coor2D) -> (f64, f64) {
+        match self {
+            DistanceModel::Ellipsoidal => {
+                let inv = WGS84.geodesic_inv(a, b).to_degrees();
+                (inv[0], inv[2])
+            }
+            DistanceModel::Spherical => {
+                let (lon1, lat1) = a.xy();
+                let (lon2, lat2) = b.xy();
+                let dlon = lon2 - lon1;
+                let y = dlon.sin() * lat2.cos();
+                let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+                (y.atan2(x).to_degrees(), haversine(a, b))
+            }
+        }
+    }
+}
+
+/// Great circle distance in meters with the haversine formula.
+fn haversine(a: &Coor2D, b: &Coor2D) -> f64 {
+    let (lon1, lat1) = a.xy();
+    let (lon2, lat2) = b.xy();
+    let h = ((lat2 - lat1) / 2.).sin().powi(2) + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.).sin().powi(2);
+    2. * EARTH_RADIUS * h.sqrt().min(1.).asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spherical_agrees_with_ellipsoidal() {
+        // Paris Charles de Gaulle to Frankfurt
+        let a = Coor2D::geo(49.0097, 2.5479);
+        let b = Coor2D::geo(50.0379, 8.5622);
+        let (bearing_e, distance_e) = DistanceModel::Ellipsoidal.inverse(&a, &b);
+        let (bearing_s, distance_s) = DistanceModel::Spherical.inverse(&a, &b);
+        assert!((distance_e - distance_s).abs() / distance_e < 0.005);
+        assert!((bearing_e - bearing_s).abs() < 0.5);
+        assert_eq!(DistanceModel::Spherical.distance(&a, &b), distance_s);
+        assert_eq!(DistanceModel::default(), DistanceModel::Ellipsoidal);
+    }
+}
@@ -139,6 +139,13 @@ impl From<reqwest::Error> for ThrustError {
     }
 }
 
+#[cfg(feature = "polars")]
+impl From<polars::prelude::PolarsError> for ThrustError {
+    fn from(e: polars::prelude::PolarsError) -> Self {
+        Self::InvalidData(e.to_string())
+    }
+}
+
 impl From<&str> for ThrustError {
     fn from(e: &str) -> Self {
         Self::Other(e.to_string())
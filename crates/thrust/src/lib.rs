@@ -1,11 +1,17 @@
 //! Thrust core functionalities.
 //!
 
+#[cfg(feature = "toml")]
+pub mod config;
 pub mod data;
+pub mod distance;
 pub mod error;
 pub mod intervals;
 
 #[cfg(feature = "polars")]
 pub mod kalman;
 
+#[cfg(feature = "rest")]
+pub mod serve;
+
 pub use error::ThrustError;
@@ -0,0 +1,353 @@
+//! HTTP endpoints for route resolution, behind the `rest` feature.
+//!
+//! [`routes`] returns an [`axum::Router`] which can be served on its own (as in
+//! the `field15_serve` example) or nested into an existing application:
+//!
+//! ```no_run
+//! use std::sync::{Arc, RwLock};
+//! use thrust::data::eurocontrol::database::AirwayDatabase;
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let database = Arc::new(RwLock::new(AirwayDatabase::new("path/to/aixm".as_ref())?));
+//! let app = axum::Router::new().nest("/thrust", thrust::serve::routes(database));
+//! let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await?;
+//! axum::serve(listener, app).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The endpoints are:
+//! - `POST /resolve` with `{"route": "..."}`, for a [`RouteResponse`], with the
+//!   candidates of each enrichment stage if `?debug=trace`, and the summary of the
+//!   route if `?footprint=true`;
+//! - `POST /resolve_stream` with `{"route": "..."}`, for the segments as
+//!   `application/x-ndjson`, one per line, sent once the route is enriched;
+//! - `POST /resolve_batch` with a list of `{"id": ..., "route": "..."}`, for a
+//!   list of [`RouteResponse`], or of `{"id": ..., "route": ..., "error": ...}`
+//!   for the routes which cannot be enriched, in the same order;
+//! - `GET /stats`, for database and cache statistics;
+//! - `POST /reload`, to reload the database and clear the cache, only if the
+//!   dataset directory is known (see [`ServeState::with_dataset`]);
+//! - `GET /airway/{name}/segments?format=csv|json`, for the segments of an airway;
+//! - `GET /complete?q=NA&limit=10`, for designators starting with a prefix;
+//! - `GET /schema`, for the JSON Schema of `/resolve` responses, with the `schema` feature.
+//!
+//! `precision` and `units` query parameters apply to all responses with
+//! coordinates or distances, see [`OutputOptions::parse`]. CORS and other
+//! middleware are left to the application. Enrichment and other work taking
+//! the database lock run on the blocking thread pool of the runtime.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::{Json, Path as UrlPath, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::Router;
+use serde::{Deserialize, Serialize};
+
+use crate::data::eurocontrol::cache::{CacheStats, CachedEnricher, SharedDatabase};
+use crate::data::eurocontrol::database::{DatabaseStats, EnrichmentTrace};
+use crate::data::eurocontrol::response::{
+    airway_segment_rows, Export, Exported, OutputOptions, RouteFootprint, RouteRequest, RouteResponse, TableFormat,
+};
+use crate::data::field15::Field15Parser;
+
+/// What the endpoints share: the database behind a route cache, and the
+/// directory it was loaded from.
+pub struct ServeState {
+    enricher: CachedEnricher,
+    dataset: Option<PathBuf>,
+}
+
+impl ServeState {
+    pub fn new(enricher: CachedEnricher) -> Self {
+        ServeState {
+            enricher,
+            dataset: None,
+        }
+    }
+
+    /// Enable `POST /reload`, reloading the database from this directory.
+    pub fn with_dataset(mut self, path: impl Into<PathBuf>) -> Self {
+        self.dataset = Some(path.into());
+        self
+    }
+}
+
+impl From<SharedDatabase> for ServeState {
+    fn from(database: SharedDatabase) -> Self {
+        ServeState::new(CachedEnricher::new(database))
+    }
+}
+
+impl From<CachedEnricher> for ServeState {
+    fn from(enricher: CachedEnricher) -> Self {
+        ServeState::new(enricher)
+    }
+}
+
+/// The endpoints listed in the [module documentation](self).
+pub fn routes(state: impl Into<ServeState>) -> Router {
+    let state = state.into();
+    let app = Router::new()
+        .route("/resolve", post(resolve_route))
+        .route("/resolve_stream", post(resolve_stream))
+        .route("/resolve_batch", post(resolve_batch))
+        .route("/stats", get(stats))
+        .route("/airway/{name}/segments", get(airway_segments))
+        .route("/complete", get(complete));
+    let app = match state.dataset {
+        Some(_) => app.route("/reload", post(reload)),
+        None => app,
+    };
+    #[cfg(feature = "schema")]
+    let app = app.route("/schema", get(schema));
+    app.with_state(Arc::new(state))
+}
+
+#[derive(Debug, Deserialize)]
+struct ResolveQuery {
+    debug: Option<String>,
+    precision: Option<String>,
+    units: Option<String>,
+    /// With `?footprint=true`, summarize the airways, fixes and efficiency of the route
+    footprint: Option<bool>,
+}
+
+/// A response with the candidates of each enrichment stage, with `?debug=trace`.
+#[derive(Debug, Serialize)]
+struct TracedResponse<'a> {
+    #[serde(flatten)]
+    response: Exported<'a, RouteResponse>,
+    trace: EnrichmentTrace,
+}
+
+/// A route of `/resolve_batch` which could not be enriched.
+#[derive(Debug, Serialize)]
+struct BatchError {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<serde_json::Value>,
+    route: String,
+    error: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum BatchEntry {
+    Resolved(RouteResponse),
+    Failed(BatchError),
+}
+
+#[derive(Debug, Deserialize)]
+struct OutputQuery {
+    precision: Option<String>,
+    units: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TableQuery {
+    format: Option<String>,
+    precision: Option<String>,
+    units: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompleteQuery {
+    q: String,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct ServerStats {
+    #[serde(flatten)]
+    database: DatabaseStats,
+    cache: CacheStats,
+}
+
+type SharedState = State<Arc<ServeState>>;
+
+/// Run CPU-bound work, or work waiting for the database lock, on the blocking
+/// thread pool so that it does not stall the other requests.
+async fn blocking<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> Result<T, Response> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())
+}
+
+async fn resolve_route(
+    State(state): SharedState,
+    Query(query): Query<ResolveQuery>,
+    Json(payload): Json<RouteRequest>,
+) -> Response {
+    let options = match OutputOptions::parse(query.precision.as_deref(), query.units.as_deref()) {
+        Ok(options) => options,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    let trace = match query.debug.as_deref() {
+        None => false,
+        Some("trace") => true,
+        Some(_) => return (StatusCode::BAD_REQUEST, "debug must be trace").into_response(),
+    };
+    tracing::debug!(target: "thrust::serve", "Received route to resolve: {}", payload.route);
+    let response = blocking(move || {
+        let (enriched, report) = state.enricher.enrich_route_with_report(&payload.route);
+        let mut response = RouteResponse::new(&payload.route, &enriched)
+            .with_report(&report)
+            .with_id(payload.id);
+        if query.footprint == Some(true) {
+            response = response.with_footprint(RouteFootprint::from_segments(&enriched));
+        }
+        if !trace {
+            return (StatusCode::OK, Json(options.export(&response))).into_response();
+        }
+        let elements = Field15Parser::parse(&payload.route);
+        let trace = state.enricher.database().read().unwrap().enrich_route_trace(elements);
+        let response = options.export(&response);
+        (StatusCode::OK, Json(TracedResponse { response, trace })).into_response()
+    });
+    response.await.unwrap_or_else(|error| error)
+}
+
+/// The segments of a route as newline-delimited JSON (`application/x-ndjson`),
+/// one segment per line.
+///
+/// Enrichment narrows down the candidates of the whole route before assembling
+/// segments, since the choice of a point depends on the points after it, so the
+/// lines arrive once enrichment finishes. Diagnostics such as unresolved
+/// identifiers are only returned by `/resolve`.
+async fn resolve_stream(
+    State(state): SharedState,
+    Query(query): Query<OutputQuery>,
+    Json(payload): Json<RouteRequest>,
+) -> Response {
+    let options = match OutputOptions::parse(query.precision.as_deref(), query.units.as_deref()) {
+        Ok(options) => options,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    tracing::debug!(target: "thrust::serve", "Received route to stream: {}", payload.route);
+    let response = blocking(move || {
+        let (enriched, _) = state.enricher.enrich_route_with_report(&payload.route);
+        RouteResponse::new(&payload.route, &enriched)
+    });
+    let response = match response.await {
+        Ok(response) => response,
+        Err(error) => return error,
+    };
+    let lines = response
+        .segments
+        .into_iter()
+        .map(move |segment| serde_json::to_string(&options.export(&segment)).map(|line| line + "\n"));
+    let body = Body::from_stream(futures_util::stream::iter(lines));
+    (StatusCode::OK, [(header::CONTENT_TYPE, "application/x-ndjson")], body).into_response()
+}
+
+/// Enrich routes in parallel with [`AirwayDatabase::enrich_batch`], bypassing the
+/// route cache.
+///
+/// [`AirwayDatabase::enrich_batch`]: crate::data::eurocontrol::database::AirwayDatabase::enrich_batch
+async fn resolve_batch(
+    State(state): SharedState,
+    Query(query): Query<OutputQuery>,
+    Json(payload): Json<Vec<RouteRequest>>,
+) -> Response {
+    let options = match OutputOptions::parse(query.precision.as_deref(), query.units.as_deref()) {
+        Ok(options) => options,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    tracing::debug!(target: "thrust::serve", "Received {} routes to resolve", payload.len());
+    let entries = blocking(move || {
+        let routes = payload
+            .into_iter()
+            .map(|request| ((request.id, request.route.clone()), request.route));
+        let database = state.enricher.database().read().unwrap();
+        database
+            .enrich_batch(routes)
+            .into_iter()
+            .map(|((id, route), result)| match result {
+                Ok((segments, report)) => {
+                    let response = RouteResponse::new(&route, &segments).with_report(&report).with_id(id);
+                    BatchEntry::Resolved(response.to_output(&options))
+                }
+                Err(e) => BatchEntry::Failed(BatchError {
+                    id,
+                    route,
+                    error: e.to_string(),
+                }),
+            })
+            .collect::<Vec<_>>()
+    });
+    match entries.await {
+        Ok(entries) => (StatusCode::OK, Json(entries)).into_response(),
+        Err(error) => error,
+    }
+}
+
+async fn stats(State(state): SharedState) -> Response {
+    let stats = blocking(move || ServerStats {
+        database: state.enricher.database().read().unwrap().stats().clone(),
+        cache: state.enricher.stats(),
+    });
+    match stats.await {
+        Ok(stats) => (StatusCode::OK, Json(stats)).into_response(),
+        Err(error) => error,
+    }
+}
+
+async fn reload(State(state): SharedState) -> Response {
+    let Some(path) = state.dataset.clone() else {
+        return (StatusCode::NOT_FOUND, "No dataset to reload").into_response();
+    };
+    let reloaded = blocking(move || state.enricher.reload(&path));
+    match reloaded.await {
+        Ok(Ok(())) => (StatusCode::OK, "Database reloaded").into_response(),
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(error) => error,
+    }
+}
+
+async fn airway_segments(
+    State(state): SharedState,
+    UrlPath(name): UrlPath<String>,
+    Query(query): Query<TableQuery>,
+) -> Response {
+    let format = match query.format.as_deref().map(TableFormat::parse) {
+        None => TableFormat::default(),
+        Some(Some(format)) => format,
+        Some(None) => return (StatusCode::BAD_REQUEST, "format must be csv or json").into_response(),
+    };
+    let options = match OutputOptions::parse(query.precision.as_deref(), query.units.as_deref()) {
+        Ok(options) => options,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    let response = blocking(move || {
+        let rows = airway_segment_rows(&state.enricher.database().read().unwrap(), &name.to_uppercase());
+        if rows.is_empty() {
+            return (StatusCode::NOT_FOUND, format!("Unknown airway {name}")).into_response();
+        }
+        match format.render(&rows, &options) {
+            Ok(body) => (StatusCode::OK, [(header::CONTENT_TYPE, format.content_type())], body).into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    });
+    response.await.unwrap_or_else(|error| error)
+}
+
+async fn complete(State(state): SharedState, Query(query): Query<CompleteQuery>) -> Response {
+    let entries = blocking(move || {
+        let database = state.enricher.database().read().unwrap();
+        database.complete(&query.q, query.limit.unwrap_or(10))
+    });
+    match entries.await {
+        Ok(entries) => (StatusCode::OK, Json(entries)).into_response(),
+        Err(error) => error,
+    }
+}
+
+#[cfg(feature = "schema")]
+async fn schema() -> Response {
+    use crate::data::eurocontrol::response::response_schema;
+    (StatusCode::OK, Json(response_schema())).into_response()
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::{temp_folder, write_baseline, DESIGNATED_POINTS, EMPTY};
+use thrust::data::eurocontrol::database::AirwayDatabase;
+use thrust::data::eurocontrol::response::{airway_segment_rows, AirwaySegmentRow, OutputOptions, TableFormat};
+
+const ROUTES: &str = r#"<message:AIXMBasicMessage>
+  <aixm:Route gml:id="rte-un1">
+    <gml:identifier codeSpace="urn:uuid:">rte-un1</gml:identifier>
+    <aixm:designatorPrefix>U</aixm:designatorPrefix>
+    <aixm:designatorSecondLetter>N</aixm:designatorSecondLetter>
+    <aixm:designatorNumber>1</aixm:designatorNumber>
+  </aixm:Route>
+</message:AIXMBasicMessage>"#;
+
+// Identifiers are deliberately not in the order of the airway
+const ROUTE_SEGMENTS: &str = r#"<message:AIXMBasicMessage>
+  <aixm:RouteSegment gml:id="seg-1">
+    <gml:identifier codeSpace="urn:uuid:">seg-1</gml:identifier>
+    <aixm:routeFormed xlink:href="urn:uuid:rte-un1"/>
+    <aixm:start><aixm:EnRouteSegmentPoint><aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-def"/></aixm:EnRouteSegmentPoint></aixm:start>
+    <aixm:end><aixm:EnRouteSegmentPoint><aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-ghi"/></aixm:EnRouteSegmentPoint></aixm:end>
+  </aixm:RouteSegment>
+  <aixm:RouteSegment gml:id="seg-2">
+    <gml:identifier codeSpace="urn:uuid:">seg-2</gml:identifier>
+    <aixm:routeFormed xlink:href="urn:uuid:rte-un1"/>
+    <aixm:start><aixm:EnRouteSegmentPoint><aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-abc"/></aixm:EnRouteSegmentPoint></aixm:start>
+    <aixm:end><aixm:EnRouteSegmentPoint><aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-def"/></aixm:EnRouteSegmentPoint></aixm:end>
+    <aixm:availability>
+      <aixm:RouteAvailability>
+        <aixm:direction>FORWARD</aixm:direction>
+        <aixm:levels>
+          <aixm:AirspaceLayer>
+            <aixm:upperLimit uom="FL">660</aixm:upperLimit>
+            <aixm:lowerLimit uom="FL">245</aixm:lowerLimit>
+          </aixm:AirspaceLayer>
+        </aixm:levels>
+      </aixm:RouteAvailability>
+    </aixm:availability>
+  </aixm:RouteSegment>
+</message:AIXMBasicMessage>"#;
+
+fn database() -> AirwayDatabase {
+    let folder = temp_folder("airway");
+    write_baseline(&folder, "DesignatedPoint", DESIGNATED_POINTS);
+    write_baseline(&folder, "Route", ROUTES);
+    write_baseline(&folder, "RouteSegment", ROUTE_SEGMENTS);
+    for name in ["AirportHeliport", "Navaid"] {
+        write_baseline(&folder, name, EMPTY);
+    }
+    let db = AirwayDatabase::new(&folder);
+    std::fs::remove_dir_all(&folder).unwrap();
+    db.unwrap()
+}
+
+#[test]
+fn test_airway_segments_table() {
+    let db = database();
+    let rows = airway_segment_rows(&db, "UN1");
+    let names = rows
+        .iter()
+        .map(|row| (row.start.as_deref().unwrap(), row.end.as_deref().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(names, [("ABC", "DEF"), ("DEF", "GHI")]);
+    assert!(rows.iter().all(|row| (row.length - 78_800.).abs() < 500.));
+
+    let format = TableFormat::parse("csv").unwrap();
+    assert_eq!(format.content_type(), "text/csv");
+    let csv = format.render(&rows, &OutputOptions::default()).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some(
+            "airway,start,start_latitude,start_longitude,end,end_latitude,end_longitude,length,\
+            direction,lower_limit,upper_limit"
+        )
+    );
+    let line = lines.next().unwrap();
+    assert!(line.starts_with("UN1,ABC,45.0,1.0,DEF,45.0,2.0,"));
+    assert!(line.ends_with(",FORWARD,FL245,FL660"));
+    // Segments without availability have no limits
+    assert!(lines.next().unwrap().ends_with(",,,"));
+    assert_eq!(lines.count(), 0);
+
+    let json = TableFormat::Json.render(&rows, &OutputOptions::default()).unwrap();
+    let parsed: Vec<AirwaySegmentRow> = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, rows);
+
+    // Unknown airways yield no rows, answered with a 404 by the server
+    assert!(airway_segment_rows(&db, "UN999").is_empty());
+    assert_eq!(TableFormat::parse("xml"), None);
+}
@@ -0,0 +1,44 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// Three designated points ABC, DEF and GHI along the 45th parallel.
+pub const DESIGNATED_POINTS: &str = r#"<message:AIXMBasicMessage>
+  <aixm:DesignatedPoint gml:id="dp-abc">
+    <gml:identifier codeSpace="urn:uuid:">dp-abc</gml:identifier>
+    <aixm:designator>ABC</aixm:designator>
+    <aixm:Point><gml:pos>45.0 1.0</gml:pos></aixm:Point>
+  </aixm:DesignatedPoint>
+  <aixm:DesignatedPoint gml:id="dp-def">
+    <gml:identifier codeSpace="urn:uuid:">dp-def</gml:identifier>
+    <aixm:designator>DEF</aixm:designator>
+    <aixm:Point><gml:pos>45.0 2.0</gml:pos></aixm:Point>
+  </aixm:DesignatedPoint>
+  <aixm:DesignatedPoint gml:id="dp-ghi">
+    <gml:identifier codeSpace="urn:uuid:">dp-ghi</gml:identifier>
+    <aixm:designator>GHI</aixm:designator>
+    <aixm:Point><gml:pos>45.0 3.0</gml:pos></aixm:Point>
+  </aixm:DesignatedPoint>
+</message:AIXMBasicMessage>"#;
+
+pub const EMPTY: &str = "<message:AIXMBasicMessage></message:AIXMBasicMessage>";
+
+/// Write an AIXM baseline archive `{name}.BASELINE.zip` with a single entry.
+pub fn write_baseline(folder: &Path, name: &str, content: &str) {
+    let mut writer = ZipWriter::new(File::create(folder.join(format!("{name}.BASELINE.zip"))).unwrap());
+    writer
+        .start_file(format!("{name}.BASELINE"), SimpleFileOptions::default())
+        .unwrap();
+    writer.write_all(content.as_bytes()).unwrap();
+    writer.finish().unwrap();
+}
+
+/// A fresh temporary folder, unique to the test process and `label`.
+pub fn temp_folder(label: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!("thrust-{label}-{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    folder
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::{temp_folder, write_baseline, DESIGNATED_POINTS, EMPTY};
+use thrust::data::eurocontrol::database::AirwayDatabase;
+use thrust::data::eurocontrol::response::{RouteRequest, RouteResponse};
+
+#[test]
+fn test_jsonl_request_echoes_id() {
+    let folder = temp_folder("batch");
+    write_baseline(&folder, "DesignatedPoint", DESIGNATED_POINTS);
+    for name in ["AirportHeliport", "Navaid", "Route", "RouteSegment"] {
+        write_baseline(&folder, name, EMPTY);
+    }
+    let db = AirwayDatabase::new(&folder);
+    std::fs::remove_dir_all(&folder).unwrap();
+    let db = db.unwrap();
+
+    let lines = [
+        r#"{"id": "AFR123-1", "route": "N0450F350 ABC DCT DEF DCT XYZ"}"#,
+        r#"{"id": "AFR123-2", "route": "N0450F350 XYZ"}"#,
+    ];
+    let requests = lines.map(|line| serde_json::from_str::<RouteRequest>(line).unwrap());
+    let mut results = db.enrich_batch(requests.map(|request| ((request.id, request.route.clone()), request.route)));
+    let ((id, _), unresolved) = results.pop().unwrap();
+    assert_eq!(id, Some("AFR123-2".into()));
+    assert_eq!(
+        unresolved.unwrap_err().to_string(),
+        "no segment resolved (unresolved: XYZ)"
+    );
+
+    let ((id, route), enriched) = results.pop().unwrap();
+    let (segments, report) = enriched.unwrap();
+    let response = RouteResponse::new(&route, &segments).with_report(&report).with_id(id);
+
+    let json = serde_json::to_value(&response).unwrap();
+    assert_eq!(json["id"], "AFR123-1");
+    assert_eq!(json["route"], "N0450F350 ABC DCT DEF DCT XYZ");
+    assert_eq!(json["segments"].as_array().unwrap().len(), 1);
+    assert_eq!(json["segments"][0]["start"]["name"], "ABC");
+    assert_eq!(json["unresolved"][0]["token"], "XYZ");
+
+    // Requests without an identifier produce responses without the key
+    let request: RouteRequest = serde_json::from_str(r#"{"route": "ABC DCT DEF"}"#).unwrap();
+    let response = RouteResponse::new(&request.route, &[]).with_id(request.id);
+    assert!(serde_json::to_value(&response).unwrap().get("id").is_none());
+}
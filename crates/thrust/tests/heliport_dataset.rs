@@ -0,0 +1,130 @@
+mod common;
+
+use common::{temp_folder, write_baseline, DESIGNATED_POINTS, EMPTY};
+use thrust::data::eurocontrol::database::{AirwayDatabase, EnrichmentOptions, ResolvedPoint};
+use thrust::data::field15::Field15Parser;
+
+/// Two heliports without ICAO code, as published for rotorcraft operations.
+const HELIPORTS: &str = r#"<message:AIXMBasicMessage>
+  <aixm:AirportHeliport gml:id="ah-issy">
+    <gml:identifier codeSpace="urn:uuid:">ah-issy</gml:identifier>
+    <aixm:timeSlice>
+      <aixm:AirportHeliportTimeSlice>
+        <aixm:designator>LF7522</aixm:designator>
+        <aixm:name>ISSY</aixm:name>
+        <aixm:type>HP</aixm:type>
+        <aixm:ARP><aixm:ElevatedPoint><gml:pos>48.833 2.273</gml:pos></aixm:ElevatedPoint></aixm:ARP>
+      </aixm:AirportHeliportTimeSlice>
+    </aixm:timeSlice>
+  </aixm:AirportHeliport>
+  <aixm:AirportHeliport gml:id="ah-necker">
+    <gml:identifier codeSpace="urn:uuid:">ah-necker</gml:identifier>
+    <aixm:timeSlice>
+      <aixm:AirportHeliportTimeSlice>
+        <aixm:designator>LF7524</aixm:designator>
+        <aixm:name>NECKER</aixm:name>
+        <aixm:type>HP</aixm:type>
+        <aixm:ARP><aixm:ElevatedPoint><gml:pos>48.846 2.316</gml:pos></aixm:ElevatedPoint></aixm:ARP>
+      </aixm:AirportHeliportTimeSlice>
+    </aixm:timeSlice>
+  </aixm:AirportHeliport>
+</message:AIXMBasicMessage>"#;
+
+/// VFR reporting points between the heliports, and an ICAO point far away.
+const REPORTING_POINTS: &str = r#"<message:AIXMBasicMessage>
+  <aixm:DesignatedPoint gml:id="dp-novem">
+    <gml:identifier codeSpace="urn:uuid:">dp-novem</gml:identifier>
+    <aixm:designator>NOVEM</aixm:designator>
+    <aixm:type>OTHER:VFR_REP</aixm:type>
+    <aixm:Point><gml:pos>48.838 2.285</gml:pos></aixm:Point>
+  </aixm:DesignatedPoint>
+  <aixm:DesignatedPoint gml:id="dp-siera">
+    <gml:identifier codeSpace="urn:uuid:">dp-siera</gml:identifier>
+    <aixm:designator>SIERA</aixm:designator>
+    <aixm:type>OTHER:VFR_REP</aixm:type>
+    <aixm:Point><gml:pos>48.842 2.301</gml:pos></aixm:Point>
+  </aixm:DesignatedPoint>
+  <aixm:DesignatedPoint gml:id="dp-siera-icao">
+    <gml:identifier codeSpace="urn:uuid:">dp-siera-icao</gml:identifier>
+    <aixm:designator>SIERA</aixm:designator>
+    <aixm:type>ICAO</aixm:type>
+    <aixm:Point><gml:pos>43.5 5.5</gml:pos></aixm:Point>
+  </aixm:DesignatedPoint>
+</message:AIXMBasicMessage>"#;
+
+fn database() -> AirwayDatabase {
+    let folder = temp_folder("heliport");
+    write_baseline(&folder, "AirportHeliport", HELIPORTS);
+    // Along with the few IFR points of the other tests
+    write_baseline(
+        &folder,
+        "DesignatedPoint",
+        &format!("{REPORTING_POINTS}{DESIGNATED_POINTS}"),
+    );
+    for name in ["Navaid", "Route", "RouteSegment"] {
+        write_baseline(&folder, name, EMPTY);
+    }
+    let db = AirwayDatabase::new(&folder);
+    std::fs::remove_dir_all(&folder).unwrap();
+    db.unwrap()
+}
+
+fn names(points: &[ResolvedPoint]) -> Vec<String> {
+    points
+        .iter()
+        .map(|point| match point {
+            ResolvedPoint::AirportHeliport(airport) => airport.code().to_string(),
+            ResolvedPoint::DesignatedPoint(dp) => format!("{} ({})", dp.designator, dp.r#type),
+            other => other.to_string(),
+        })
+        .collect()
+}
+
+#[test]
+fn test_heliport_lookup() {
+    let db = database();
+    // Heliports without ICAO code resolve by designator, but not by their free text name
+    for name in ["LF7522", "lf7522"] {
+        assert_eq!(names(&ResolvedPoint::lookup(name, &db)), ["LF7522"], "{name}");
+    }
+    assert!(ResolvedPoint::lookup("LF7523", &db).is_empty());
+    assert!(ResolvedPoint::lookup("ISSY", &db).is_empty());
+
+    // VFR reporting points are kept along with the ICAO point of the same name
+    let mut siera = names(&ResolvedPoint::lookup("SIERA", &db));
+    siera.sort();
+    assert_eq!(siera, ["SIERA (ICAO)", "SIERA (OTHER:VFR_REP)"]);
+    assert_eq!(names(&ResolvedPoint::lookup("ABC", &db)), ["ABC ()"]);
+}
+
+#[test]
+fn test_enrich_vfr_route_between_heliports() {
+    let mut db = database();
+    // SIERA is the reporting point near the heliports, not the distant ICAO point
+    let route = Field15Parser::parse("N0080VFR NOVEM DCT SIERA DCT LF7524");
+    let (segments, report) = db.enrich_route_with_report(route.clone());
+    let points = segments
+        .iter()
+        .map(|segment| segment.start.clone())
+        .chain(segments.last().map(|segment| segment.end.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names(&points),
+        ["NOVEM (OTHER:VFR_REP)", "SIERA (OTHER:VFR_REP)", "LF7524"]
+    );
+    assert!(segments.iter().all(|segment| segment.name.is_none()));
+    assert!(report.completeness.is_complete());
+    assert!(report.unresolved.is_empty() && report.inconsistencies.is_empty());
+    assert!(report.substitutions.is_empty() && report.boundary_airways.is_empty());
+    assert!(report.partial_airways.is_empty() && !report.missing_initial_group);
+
+    // Excluding VFR reporting points, e.g. for IFR traffic
+    db.set_enrichment_options(EnrichmentOptions {
+        exclude_vfr_reporting_points: true,
+        ..Default::default()
+    });
+    assert!(ResolvedPoint::lookup("NOVEM", &db).is_empty());
+    let (_, report) = db.enrich_route_with_report(route);
+    assert_eq!(report.unresolved.len(), 1);
+    assert_eq!(report.unresolved[0].token, "NOVEM");
+}
@@ -0,0 +1,125 @@
+//! Compare the routes enriched by the library with those returned by the
+//! Python bindings, `thrust.field15.AiracDatabase.enrich_route` and `enrich_routes`.
+//!
+//! The bindings must be built beforehand in a virtual environment, e.g. with
+//! `maturin develop` in the `python` folder, and `THRUST_PYTHON` set to the
+//! interpreter of that environment, then the test run with `cargo test --test
+//! python_parity -- --ignored`.
+
+mod common;
+
+use std::env;
+use std::process::Command;
+
+use common::{temp_folder, write_baseline, DESIGNATED_POINTS, EMPTY};
+use serde_json::Value;
+use thrust::data::eurocontrol::database::AirwayDatabase;
+use thrust::data::eurocontrol::response::SegmentJson;
+use thrust::data::field15::Field15Parser;
+
+const AIRPORT_HELIPORT: &str = r#"<message:AIXMBasicMessage>
+  <message:hasMember>
+    <aixm:AirportHeliport gml:id="ah-lfbo">
+      <gml:identifier codeSpace="urn:uuid:">ah-lfbo</gml:identifier>
+      <aixm:timeSlice>
+        <aixm:AirportHeliportTimeSlice>
+          <aixm:name>TOULOUSE BLAGNAC</aixm:name>
+          <aixm:locationIndicatorICAO>LFBO</aixm:locationIndicatorICAO>
+          <aixm:ARP>
+            <aixm:ElevatedPoint><gml:pos>43.635 1.368</gml:pos></aixm:ElevatedPoint>
+          </aixm:ARP>
+        </aixm:AirportHeliportTimeSlice>
+      </aixm:timeSlice>
+    </aixm:AirportHeliport>
+  </message:hasMember>
+</message:AIXMBasicMessage>"#;
+
+const ROUTES: &str = r#"<message:AIXMBasicMessage>
+  <aixm:Route gml:id="rte-un1">
+    <gml:identifier codeSpace="urn:uuid:">rte-un1</gml:identifier>
+    <aixm:designatorPrefix>U</aixm:designatorPrefix>
+    <aixm:designatorSecondLetter>N</aixm:designatorSecondLetter>
+    <aixm:designatorNumber>1</aixm:designatorNumber>
+  </aixm:Route>
+</message:AIXMBasicMessage>"#;
+
+const ROUTE_SEGMENTS: &str = r#"<message:AIXMBasicMessage>
+  <aixm:RouteSegment gml:id="seg-1">
+    <gml:identifier codeSpace="urn:uuid:">seg-1</gml:identifier>
+    <aixm:routeFormed xlink:href="urn:uuid:rte-un1"/>
+    <aixm:start><aixm:EnRouteSegmentPoint><aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-abc"/></aixm:EnRouteSegmentPoint></aixm:start>
+    <aixm:end><aixm:EnRouteSegmentPoint><aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-def"/></aixm:EnRouteSegmentPoint></aixm:end>
+  </aixm:RouteSegment>
+  <aixm:RouteSegment gml:id="seg-2">
+    <gml:identifier codeSpace="urn:uuid:">seg-2</gml:identifier>
+    <aixm:routeFormed xlink:href="urn:uuid:rte-un1"/>
+    <aixm:start><aixm:EnRouteSegmentPoint><aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-def"/></aixm:EnRouteSegmentPoint></aixm:start>
+    <aixm:end><aixm:EnRouteSegmentPoint><aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-ghi"/></aixm:EnRouteSegmentPoint></aixm:end>
+  </aixm:RouteSegment>
+</message:AIXMBasicMessage>"#;
+
+const FIXTURE_ROUTES: &[&str] = &[
+    "N0450F350 ABC UN1 GHI",
+    "N0450F350 ABC DCT GHI",
+    "N0450F350 ABC UN1 DEF DCT 4530N00200E DCT GHI",
+    "N0450F350 LFBO DCT ABC UN1 GHI",
+    "N0450F350 ABC DCT XYZ DCT GHI",
+    "N0450F350 ABC UN1 DEF/N0460F370 DCT GHI",
+    "N0450F350 ABC UN1 DEF STAY1/0130 UN1 GHI",
+];
+
+const ENRICH_SCRIPT: &str = r#"
+import json, sys
+from thrust.field15 import AiracDatabase
+
+db = AiracDatabase(sys.argv[1])
+routes = json.loads(sys.argv[2])
+enriched = [[s.to_dict() for s in db.enrich_route(r)] for r in routes]
+batch = db.enrich_routes(routes)
+assert enriched == [[s.to_dict() for s in segments] for segments, _ in batch]
+assert all(error is None for _, error in batch)
+[(segments, error)] = db.enrich_routes(["N0450F350 XYZ DCT UVW"])
+assert segments == [] and "XYZ" in error and "UVW" in error, error
+print(json.dumps(enriched))
+"#;
+
+#[test]
+#[ignore = "needs the Python bindings, see THRUST_PYTHON"]
+fn python_bindings_enrich_routes_as_the_library() {
+    let python = env::var("THRUST_PYTHON").expect("THRUST_PYTHON must be set to run this test");
+
+    let folder = temp_folder("python-parity");
+    write_baseline(&folder, "AirportHeliport", AIRPORT_HELIPORT);
+    write_baseline(&folder, "DesignatedPoint", DESIGNATED_POINTS);
+    write_baseline(&folder, "Navaid", EMPTY);
+    write_baseline(&folder, "Route", ROUTES);
+    write_baseline(&folder, "RouteSegment", ROUTE_SEGMENTS);
+
+    let db = AirwayDatabase::new(&folder).unwrap();
+    let output = Command::new(&python)
+        .arg("-c")
+        .arg(ENRICH_SCRIPT)
+        .arg(&folder)
+        .arg(serde_json::to_string(FIXTURE_ROUTES).unwrap())
+        .output()
+        .expect("unable to run THRUST_PYTHON");
+    std::fs::remove_dir_all(&folder).unwrap();
+    assert!(
+        output.status.success(),
+        "Python enrichment failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Every field of the segments must agree between both front-ends
+    let from_python: Vec<Vec<Value>> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(from_python.len(), FIXTURE_ROUTES.len());
+    for (route, python_segments) in FIXTURE_ROUTES.iter().zip(from_python) {
+        let rust_segments = db
+            .enrich_route(Field15Parser::parse(route))
+            .iter()
+            .map(|segment| serde_json::to_value(SegmentJson::from(segment)).unwrap())
+            .collect::<Vec<_>>();
+        assert!(!rust_segments.is_empty(), "{route}: no segment");
+        assert_eq!(rust_segments, python_segments, "{route}");
+    }
+}
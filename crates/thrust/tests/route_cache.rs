@@ -0,0 +1,56 @@
+mod common;
+
+use std::sync::{Arc, RwLock};
+
+use common::{temp_folder, write_baseline, DESIGNATED_POINTS, EMPTY};
+use thrust::data::eurocontrol::cache::CachedEnricher;
+use thrust::data::eurocontrol::database::AirwayDatabase;
+use thrust::distance::DistanceModel;
+
+#[test]
+fn test_cached_enricher() {
+    let folder = temp_folder("route-cache");
+    write_baseline(&folder, "DesignatedPoint", DESIGNATED_POINTS);
+    for name in ["AirportHeliport", "Navaid", "Route", "RouteSegment"] {
+        write_baseline(&folder, name, EMPTY);
+    }
+    let db = AirwayDatabase::new(&folder).unwrap();
+    let enricher = CachedEnricher::new(Arc::new(RwLock::new(db)));
+
+    let (segments, _) = enricher.enrich_route_with_report("N0450F350 ABC DCT DEF");
+    assert_eq!(segments.len(), 1);
+    let stats = enricher.stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (0, 1, 1));
+
+    // The same route, up to whitespace and case, is served from the cache
+    let (cached, _) = enricher.enrich_route_with_report("n0450f350  ABC DCT   DEF");
+    assert_eq!(cached.len(), 1);
+    assert_eq!(format!("{}", cached[0].end), format!("{}", segments[0].end));
+    assert_eq!((enricher.stats().hits, enricher.stats().misses), (1, 1));
+
+    // Reloading the database clears the cache
+    let reload = enricher.reload(&folder);
+    std::fs::remove_dir_all(&folder).unwrap();
+    reload.unwrap();
+    assert_eq!(enricher.stats().entries, 0);
+    enricher.enrich_route_with_report("N0450F350 ABC DCT DEF");
+    assert_eq!((enricher.stats().hits, enricher.stats().misses), (1, 2));
+
+    // Routes are cached for the AIRAC cycle of the database
+    enricher
+        .database()
+        .write()
+        .unwrap()
+        .set_airac_cycle(Some("2508".to_string()));
+    enricher.enrich_route_with_report("N0450F350 ABC DCT DEF");
+    assert_eq!((enricher.stats().hits, enricher.stats().misses), (1, 3));
+
+    // So are they for the settings of the database, even when changed through its lock
+    enricher
+        .database()
+        .write()
+        .unwrap()
+        .set_distance_model(DistanceModel::Spherical);
+    enricher.enrich_route_with_report("N0450F350 ABC DCT DEF");
+    assert_eq!((enricher.stats().hits, enricher.stats().misses), (1, 4));
+}
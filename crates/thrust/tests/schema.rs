@@ -0,0 +1,57 @@
+#![cfg(feature = "schema")]
+
+mod common;
+
+use common::{temp_folder, write_baseline, DESIGNATED_POINTS, EMPTY};
+use thrust::data::eurocontrol::database::{AirwayDatabase, ResolvedRouteSegment};
+use thrust::data::eurocontrol::response::{request_schema, response_schema, RouteRequest, RouteResponse};
+use thrust::data::field15::Field15Parser;
+
+#[test]
+fn test_response_validates_against_schema() {
+    let folder = temp_folder("schema");
+    write_baseline(&folder, "DesignatedPoint", DESIGNATED_POINTS);
+    for name in ["AirportHeliport", "Navaid", "Route", "RouteSegment"] {
+        write_baseline(&folder, name, EMPTY);
+    }
+    let db = AirwayDatabase::new(&folder);
+    std::fs::remove_dir_all(&folder).unwrap();
+    let db = db.unwrap();
+
+    let request = RouteRequest {
+        id: Some(serde_json::json!(42)),
+        route: "N0450F350 ABC DCT DEF/M079F370F390 DCT GHI DCT XYZ".to_string(),
+    };
+    let request_json = serde_json::to_value(&request).unwrap();
+    assert!(jsonschema::validator_for(&request_schema())
+        .unwrap()
+        .is_valid(&request_json));
+
+    let (segments, report) = db.enrich_route_with_report(Field15Parser::parse(&request.route));
+    assert_eq!(segments.len(), 2);
+    let response = RouteResponse::new(&request.route, &segments)
+        .with_report(&report)
+        .with_id(request.id);
+    let response_json = serde_json::to_value(&response).unwrap();
+    let validator = jsonschema::validator_for(&response_schema()).unwrap();
+    let errors = validator
+        .iter_errors(&response_json)
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>();
+    assert!(errors.is_empty(), "{errors:?}");
+
+    // The schema is strict enough to reject malformed responses
+    let mut broken = response_json.clone();
+    broken["segments"][0]["start"]["latitude"] = serde_json::json!("45.0");
+    assert!(!validator.is_valid(&broken));
+
+    // Raw enriched segments, as printed by field15_resolve
+    let schema = schemars::schema_for!(Vec<ResolvedRouteSegment>).to_value();
+    let segments_json = serde_json::to_value(&segments).unwrap();
+    let errors = jsonschema::validator_for(&schema)
+        .unwrap()
+        .iter_errors(&segments_json)
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>();
+    assert!(errors.is_empty(), "{errors:?}");
+}
@@ -0,0 +1,187 @@
+#![cfg(feature = "rest")]
+
+mod common;
+
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use common::{temp_folder, write_baseline, DESIGNATED_POINTS, EMPTY};
+use serde_json::{json, Value};
+use thrust::data::eurocontrol::cache::CachedEnricher;
+use thrust::data::eurocontrol::database::AirwayDatabase;
+use thrust::data::eurocontrol::response::SegmentJson;
+use thrust::serve::{routes, ServeState};
+use tower::ServiceExt;
+
+const ROUTES: &str = r#"<message:AIXMBasicMessage>
+  <aixm:Route gml:id="rte-un1">
+    <gml:identifier codeSpace="urn:uuid:">rte-un1</gml:identifier>
+    <aixm:designatorPrefix>U</aixm:designatorPrefix>
+    <aixm:designatorSecondLetter>N</aixm:designatorSecondLetter>
+    <aixm:designatorNumber>1</aixm:designatorNumber>
+  </aixm:Route>
+</message:AIXMBasicMessage>"#;
+
+const ROUTE_SEGMENTS: &str = r#"<message:AIXMBasicMessage>
+  <aixm:RouteSegment gml:id="seg-1">
+    <gml:identifier codeSpace="urn:uuid:">seg-1</gml:identifier>
+    <aixm:routeFormed xlink:href="urn:uuid:rte-un1"/>
+    <aixm:start><aixm:EnRouteSegmentPoint><aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-abc"/></aixm:EnRouteSegmentPoint></aixm:start>
+    <aixm:end><aixm:EnRouteSegmentPoint><aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-def"/></aixm:EnRouteSegmentPoint></aixm:end>
+  </aixm:RouteSegment>
+  <aixm:RouteSegment gml:id="seg-2">
+    <gml:identifier codeSpace="urn:uuid:">seg-2</gml:identifier>
+    <aixm:routeFormed xlink:href="urn:uuid:rte-un1"/>
+    <aixm:start><aixm:EnRouteSegmentPoint><aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-def"/></aixm:EnRouteSegmentPoint></aixm:start>
+    <aixm:end><aixm:EnRouteSegmentPoint><aixm:pointChoice_fixDesignatedPoint xlink:href="urn:uuid:dp-ghi"/></aixm:EnRouteSegmentPoint></aixm:end>
+  </aixm:RouteSegment>
+</message:AIXMBasicMessage>"#;
+
+fn write_dataset(folder: &Path) {
+    write_baseline(folder, "DesignatedPoint", DESIGNATED_POINTS);
+    write_baseline(folder, "Route", ROUTES);
+    write_baseline(folder, "RouteSegment", ROUTE_SEGMENTS);
+    for name in ["AirportHeliport", "Navaid"] {
+        write_baseline(folder, name, EMPTY);
+    }
+}
+
+fn app(label: &str) -> Router {
+    let folder = temp_folder(label);
+    write_dataset(&folder);
+    let database = AirwayDatabase::new(&folder);
+    std::fs::remove_dir_all(&folder).unwrap();
+    routes(Arc::new(RwLock::new(database.unwrap())))
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, String) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+fn post(uri: &str, body: Value) -> Request<Body> {
+    Request::post(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn get(uri: &str) -> Request<Body> {
+    Request::get(uri).body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn test_resolve() {
+    let app = app("serve-resolve");
+    let request = json!({"id": 42, "route": "N0450F350 ABC UN1 GHI"});
+    let (status, body) = send(&app, post("/resolve?footprint=true", request.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    let response: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(response["id"], 42);
+    let segments = response["segments"].as_array().unwrap();
+    assert_eq!(segments.len(), 2);
+    assert!(segments.iter().all(|segment| segment["name"] == "UN1"));
+    assert!(response["footprint"].is_object());
+    assert!(response.get("trace").is_none());
+
+    let (status, body) = send(&app, post("/resolve?debug=trace", request.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(serde_json::from_str::<Value>(&body).unwrap()["trace"].is_object());
+
+    for uri in ["/resolve?debug=verbose", "/resolve?units=furlongs"] {
+        let (status, _) = send(&app, post(uri, request.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+    }
+
+    // The cache serves the route after the first request (invalid queries are rejected beforehand)
+    let (status, body) = send(&app, get("/stats")).await;
+    assert_eq!(status, StatusCode::OK);
+    let stats: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(stats["cache"]["misses"], 1);
+    assert_eq!(stats["cache"]["hits"], 1);
+    assert!(stats["datasets"].is_array());
+}
+
+#[tokio::test]
+async fn test_resolve_stream() {
+    let app = app("serve-stream");
+    let request = json!({"route": "N0450F350 ABC UN1 GHI"});
+    let response = app.clone().oneshot(post("/resolve_stream", request)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/x-ndjson");
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let segments = String::from_utf8(body.to_vec())
+        .unwrap()
+        .lines()
+        .map(serde_json::from_str::<SegmentJson>)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(segments.len(), 2);
+    assert!(segments.iter().all(|segment| segment.name.as_deref() == Some("UN1")));
+}
+
+#[tokio::test]
+async fn test_resolve_batch() {
+    let app = app("serve-batch");
+    let requests = json!([
+        {"id": 1, "route": "N0450F350 ABC UN1 GHI"},
+        {"id": "two", "route": ""},
+        {"route": "N0450F350 ABC DCT GHI"},
+    ]);
+    let (status, body) = send(&app, post("/resolve_batch?precision=2", requests)).await;
+    assert_eq!(status, StatusCode::OK);
+    let responses: Vec<Value> = serde_json::from_str(&body).unwrap();
+    assert_eq!(responses.len(), 3);
+    assert_eq!(responses[0]["id"], 1);
+    assert_eq!(responses[0]["segments"].as_array().unwrap().len(), 2);
+    assert_eq!(responses[0]["segments"][0]["start"]["latitude"], 45.0);
+    assert_eq!(responses[1]["id"], "two");
+    assert_eq!(responses[1]["error"], "empty route");
+    assert!(responses[2].get("id").is_none());
+    assert_eq!(responses[2]["segments"].as_array().unwrap().len(), 1);
+
+    let (status, _) = send(&app, post("/resolve_batch?units=furlongs", json!([]))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_lookup_endpoints() {
+    let app = app("serve-lookup");
+    let (status, body) = send(&app, get("/airway/un1/segments?format=csv")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.lines().count(), 3);
+    let (status, _) = send(&app, get("/airway/UN1/segments?format=xml")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, get("/airway/UN999/segments")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = send(&app, get("/complete?q=AB")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("ABC"));
+
+    // Without a dataset directory, the database cannot be reloaded
+    let (status, _) = send(&app, post("/reload", json!({}))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_reload() {
+    let folder = temp_folder("serve-reload");
+    write_dataset(&folder);
+    let database = Arc::new(RwLock::new(AirwayDatabase::new(&folder).unwrap()));
+    let app = routes(ServeState::new(CachedEnricher::new(database)).with_dataset(&folder));
+    let (status, _) = send(&app, post("/resolve", json!({"route": "N0450F350 ABC UN1 GHI"}))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(&app, post("/reload", json!({}))).await;
+    std::fs::remove_dir_all(&folder).unwrap();
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = send(&app, get("/stats")).await;
+    let stats: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(stats["cache"]["entries"], 0);
+}
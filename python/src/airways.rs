@@ -70,8 +70,8 @@ impl AirwayRecord {
 fn point_to_record(point: &ResolvedPoint) -> Option<AirwayPointRecord> {
     match point {
         ResolvedPoint::AirportHeliport(airport) => Some(AirwayPointRecord {
-            code: normalize_point_code(&airport.icao),
-            raw_code: Some(airport.icao.clone()),
+            code: normalize_point_code(airport.code()),
+            raw_code: Some(airport.code().to_string()),
             kind: "airport".to_string(),
             latitude: airport.latitude,
             longitude: airport.longitude,
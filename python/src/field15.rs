@@ -1,6 +1,44 @@
-use pyo3::{exceptions::PyOSError, prelude::*, types::PyDict};
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde::Serialize;
+use serde_json::Value;
 use thrust::data::eurocontrol::database::{AirwayDatabase, ResolvedPoint, ResolvedRouteSegment};
-use thrust::data::field15::Field15Parser;
+use thrust::data::eurocontrol::response::{PointJson, SegmentJson};
+use thrust::data::field15::{Altitude, Field15Parser, LevelConstraint, Speed};
+
+/// Convert a JSON value into the equivalent Python object.
+fn json_to_py(py: Python<'_>, value: &Value) -> PyResult<Py<PyAny>> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_pyobject(py)?.to_owned().into_any().unbind(),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into_pyobject(py)?.into_any().unbind(),
+            None => n.as_f64().unwrap_or(f64::NAN).into_pyobject(py)?.into_any().unbind(),
+        },
+        Value::String(s) => s.into_pyobject(py)?.into_any().unbind(),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(json_to_py(py, item)?)?;
+            }
+            list.into_any().unbind()
+        }
+        Value::Object(map) => {
+            let d = PyDict::new(py);
+            for (key, item) in map {
+                d.set_item(key, json_to_py(py, item)?)?;
+            }
+            d.into_any().unbind()
+        }
+    })
+}
+
+/// Convert a serializable value into the equivalent Python object.
+fn to_py(py: Python<'_>, value: &impl Serialize) -> PyResult<Py<PyAny>> {
+    let value = serde_json::to_value(value).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    json_to_py(py, &value)
+}
 
 #[pyclass]
 pub struct AiracDatabase {
@@ -30,54 +68,60 @@ impl Point {
     }
 
     fn to_dict(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
-        let d = PyDict::new(py);
-        d.set_item("latitude", self.latitude)?;
-        d.set_item("longitude", self.longitude)?;
-        if let Some(name) = &self.name {
-            d.set_item("name", name)?;
+        to_py(py, &PointJson::from(self))
+    }
+}
+
+impl From<&Point> for PointJson {
+    fn from(point: &Point) -> Self {
+        PointJson {
+            latitude: point.latitude,
+            longitude: point.longitude,
+            name: point.name.clone(),
+        }
+    }
+}
+
+impl From<PointJson> for Point {
+    fn from(point: PointJson) -> Self {
+        Self {
+            latitude: point.latitude,
+            longitude: point.longitude,
+            name: point.name,
         }
-        Ok(d.into())
     }
 }
 
 impl From<ResolvedPoint> for Point {
     fn from(point: ResolvedPoint) -> Self {
-        match point {
-            ResolvedPoint::AirportHeliport(airport) => Self {
-                latitude: airport.latitude,
-                longitude: airport.longitude,
-                name: Some(airport.icao),
-            },
-            ResolvedPoint::Navaid(navaid) => Self {
-                latitude: navaid.latitude,
-                longitude: navaid.longitude,
-                name: navaid.name,
-            },
-            ResolvedPoint::DesignatedPoint(designated_point) => Self {
-                latitude: designated_point.latitude,
-                longitude: designated_point.longitude,
-                name: Some(designated_point.designator),
-            },
-            ResolvedPoint::Coordinates { latitude, longitude } => Self {
-                latitude,
-                longitude,
-                name: None,
-            },
-            ResolvedPoint::None => Self {
-                latitude: 0.0,
-                longitude: 0.0,
-                name: None,
-            },
-        }
+        Self::from(PointJson::from(&point))
     }
 }
 
-#[pyclass(get_all)]
+#[pyclass]
 #[derive(Debug, Clone)]
 pub struct Segment {
+    #[pyo3(get)]
     start: Point,
+    #[pyo3(get)]
     end: Point,
+    #[pyo3(get)]
     name: Option<String>,
+    altitude: Option<Altitude>,
+    speed: Option<Speed>,
+    level: Option<LevelConstraint>,
+    #[pyo3(get)]
+    overfly: bool,
+    #[pyo3(get)]
+    stay: Option<u16>,
+    #[pyo3(get)]
+    source_segment: Option<String>,
+    #[pyo3(get)]
+    is_reversed: bool,
+    #[pyo3(get)]
+    overlay: bool,
+    #[pyo3(get)]
+    gap: bool,
 }
 
 #[pymethods]
@@ -94,23 +138,64 @@ impl Segment {
         )
     }
 
+    /// The altitude filed for the segment, as in `to_dict`
+    #[getter]
+    fn altitude(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        to_py(py, &self.altitude)
+    }
+
+    /// The speed filed for the segment, as in `to_dict`
+    #[getter]
+    fn speed(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        to_py(py, &self.speed)
+    }
+
+    /// The level constraint of the segment, as in `to_dict`
+    #[getter]
+    fn level(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        to_py(py, &self.level)
+    }
+
     fn to_dict(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
-        let d = PyDict::new(py);
-        d.set_item("start", self.start.to_dict(py)?)?;
-        d.set_item("end", self.end.to_dict(py)?)?;
-        if let Some(name) = &self.name {
-            d.set_item("name", name)?;
+        to_py(py, &SegmentJson::from(self))
+    }
+}
+
+impl From<&Segment> for SegmentJson {
+    fn from(segment: &Segment) -> Self {
+        SegmentJson {
+            start: PointJson::from(&segment.start),
+            end: PointJson::from(&segment.end),
+            name: segment.name.clone(),
+            altitude: segment.altitude.clone(),
+            speed: segment.speed.clone(),
+            level: segment.level.clone(),
+            overfly: segment.overfly,
+            stay: segment.stay,
+            source_segment: segment.source_segment.clone(),
+            is_reversed: segment.is_reversed,
+            overlay: segment.overlay,
+            gap: segment.gap,
         }
-        Ok(d.into())
     }
 }
 
 impl From<ResolvedRouteSegment> for Segment {
     fn from(segment: ResolvedRouteSegment) -> Self {
+        let gap = segment.is_gap();
         Self {
             start: Point::from(segment.start),
             end: Point::from(segment.end),
             name: segment.name,
+            altitude: segment.altitude,
+            speed: segment.speed,
+            level: segment.level,
+            overfly: segment.overfly,
+            stay: segment.stay,
+            source_segment: segment.source_segment,
+            is_reversed: segment.is_reversed,
+            overlay: segment.overlay,
+            gap,
         }
     }
 }
@@ -124,11 +209,58 @@ impl AiracDatabase {
         Ok(Self { database })
     }
 
+    /// Load the database quickly, with airways parsed on first use and, if an
+    /// `index` written by `write_point_index` is given, memory-mapped points.
+    #[staticmethod]
+    #[pyo3(signature = (path, index=None))]
+    fn load(path: String, index: Option<String>) -> PyResult<Self> {
+        let path = std::path::Path::new(&path);
+        let database = match index {
+            Some(index) => AirwayDatabase::new_with_point_index(path, std::path::Path::new(&index)),
+            None => AirwayDatabase::new_lazy(path),
+        }
+        .map_err(|e| PyOSError::new_err(e.to_string()))?;
+        Ok(Self { database })
+    }
+
+    /// Load the airways deferred by `load` now, raising the errors instead of logging them.
+    fn load_airways(&self) -> PyResult<()> {
+        self.database
+            .load_airways()
+            .map_err(|e| PyOSError::new_err(e.to_string()))
+    }
+
+    /// Write the navaids and designated points to a point index file, for `load`.
+    fn write_point_index(&self, path: String) -> PyResult<()> {
+        self.database
+            .write_point_index(std::path::Path::new(&path))
+            .map_err(|e| PyOSError::new_err(e.to_string()))
+    }
+
+    /// Record counts and estimated memory footprint of the database.
+    fn info(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        to_py(py, self.database.stats())
+    }
+
     fn enrich_route(&self, route: String) -> Vec<Segment> {
         let elements = Field15Parser::parse(&route);
         let enriched = self.database.enrich_route(elements);
         enriched.into_iter().map(Segment::from).collect()
     }
+
+    /// Enrich several routes in parallel, without holding the GIL, as a list of
+    /// `(segments, error)` pairs. A route which cannot be enriched yields no
+    /// segment and the error, e.g. the identifiers which could not be resolved.
+    fn enrich_routes(&self, py: Python<'_>, routes: Vec<String>) -> Vec<(Vec<Segment>, Option<String>)> {
+        let results = py.detach(|| self.database.enrich_batch(routes.into_iter().map(|route| ((), route))));
+        results
+            .into_iter()
+            .map(|(_, result)| match result {
+                Ok((segments, _)) => (segments.into_iter().map(Segment::from).collect(), None),
+                Err(e) => (Vec::new(), Some(e.to_string())),
+            })
+            .collect()
+    }
 }
 
 pub fn init(py: Python<'_>) -> PyResult<Bound<'_, PyModule>> {
@@ -11,9 +11,24 @@ class Segment:
     start: Point
     end: Point
     name: str | None
+    altitude: Any
+    speed: Any
+    level: Any
+    overfly: bool
+    stay: int | None
+    source_segment: str | None
+    is_reversed: bool
+    overlay: bool
+    gap: bool
 
     def to_dict(self) -> dict[str, Any]: ...
 
 class AiracDatabase:
     def __init__(self, path: str) -> None: ...
+    @staticmethod
+    def load(path: str, index: str | None = None) -> AiracDatabase: ...
+    def load_airways(self) -> None: ...
+    def write_point_index(self, path: str) -> None: ...
+    def info(self) -> dict[str, Any]: ...
     def enrich_route(self, route: str) -> list[Segment]: ...
+    def enrich_routes(self, routes: list[str]) -> list[tuple[list[Segment], str | None]]: ...